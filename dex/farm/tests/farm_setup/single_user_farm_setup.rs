@@ -7,7 +7,8 @@ use multiversx_sc::storage::mappers::StorageTokenWrapper;
 use multiversx_sc::types::{Address, EsdtLocalRole, ManagedAddress, MultiValueEncoded};
 use multiversx_sc_scenario::whitebox_legacy::{TxContextStack, TxTokenTransfer};
 use multiversx_sc_scenario::{
-    managed_address, managed_biguint, managed_token_id, rust_biguint, whitebox_legacy::*, DebugApi,
+    managed_address, managed_biguint, managed_token_id, managed_token_id_wrapped, rust_biguint,
+    whitebox_legacy::*, DebugApi,
 };
 
 type RustBigUint = num_bigint::BigUint;
@@ -75,8 +76,8 @@ where
         // init pair contract
         blockchain_wrapper
             .execute_tx(&owner_addr, &pair_wrapper, &rust_zero, |sc| {
-                let first_token_id = managed_token_id!(WEGLD_TOKEN_ID);
-                let second_token_id = managed_token_id!(MEX_TOKEN_ID);
+                let first_token_id = managed_token_id_wrapped!(WEGLD_TOKEN_ID);
+                let second_token_id = managed_token_id_wrapped!(MEX_TOKEN_ID);
                 let router_address = managed_address!(&owner_addr);
                 let router_owner_address = managed_address!(&owner_addr);
                 let total_fee_percent = 300u64;
@@ -224,6 +225,7 @@ where
                 let MultiValue3 { 0: payments } = sc.add_liquidity(
                     managed_biguint!(first_token_min),
                     managed_biguint!(second_token_min),
+                    OptionalValue::None,
                 );
 
                 assert_eq!(payments.0.token_identifier, managed_token_id!(LP_TOKEN_ID));
@@ -351,8 +351,8 @@ pub mod fuzz_data_tests {
 
         blockchain_wrapper
             .execute_tx(owner_addr, &pair_wrapper, &rust_zero, |sc| {
-                let first_token_id = managed_token_id!(first_token);
-                let second_token_id = managed_token_id!(second_token);
+                let first_token_id = managed_token_id_wrapped!(first_token);
+                let second_token_id = managed_token_id_wrapped!(second_token);
                 let router_address = managed_address!(owner_addr);
                 let router_owner_address = managed_address!(owner_addr);
                 let total_fee_percent = TOTAL_FEE_PERCENT;
@@ -6,7 +6,8 @@ pub mod fuzz_pair_test {
     multiversx_sc::derive_imports!();
 
     use multiversx_sc_scenario::{
-        managed_biguint, managed_token_id, rust_biguint, whitebox_legacy::TxTokenTransfer, DebugApi,
+        managed_biguint, managed_token_id_wrapped, rust_biguint, whitebox_legacy::TxTokenTransfer,
+        DebugApi,
     };
 
     use rand::prelude::*;
@@ -92,6 +93,7 @@ pub mod fuzz_pair_test {
                 sc.add_liquidity(
                     managed_biguint!(first_token_min),
                     managed_biguint!(second_token_min),
+                    OptionalValue::None,
                 );
             },
         );
@@ -196,6 +198,7 @@ pub mod fuzz_pair_test {
                 sc.remove_liquidity(
                     managed_biguint!(first_token_min),
                     managed_biguint!(second_token_min),
+                    OptionalValue::None,
                 );
             },
         );
@@ -288,8 +291,9 @@ pub mod fuzz_pair_test {
                 &rust_biguint!(payment_amount),
                 |sc| {
                     sc.swap_tokens_fixed_input(
-                        managed_token_id!(desired_token_id),
+                        managed_token_id_wrapped!(desired_token_id),
                         managed_biguint!(desired_amount_min),
+                        OptionalValue::None,
                     );
                 },
             );
@@ -336,8 +340,9 @@ pub mod fuzz_pair_test {
                 &rust_biguint!(payment_amount),
                 |sc| {
                     sc.swap_tokens_fixed_output(
-                        managed_token_id!(desired_token_id),
+                        managed_token_id_wrapped!(desired_token_id),
                         managed_biguint!(desired_amount_min),
+                        OptionalValue::None,
                     );
                 },
             );
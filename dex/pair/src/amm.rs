@@ -1,13 +1,16 @@
 multiversx_sc::imports!();
 multiversx_sc::derive_imports!();
 
-use crate::config::MAX_PERCENTAGE;
+use crate::config::{CurveType, MAX_PERCENTAGE};
+use crate::errors::ERROR_NOT_ENOUGH_RESERVE;
 
 use super::config;
+use super::stable_swap;
 
 #[multiversx_sc::module]
 pub trait AmmModule:
     config::ConfigModule
+    + stable_swap::StableSwapModule
     + token_send::TokenSendModule
     + permissions_module::PermissionsModule
     + pausable::PausableModule
@@ -17,7 +20,14 @@ pub trait AmmModule:
         first_token_amount: &BigUint,
         second_token_amount: &BigUint,
     ) -> BigUint {
-        first_token_amount * second_token_amount
+        match self.curve_type().get() {
+            CurveType::ConstantProduct => first_token_amount * second_token_amount,
+            CurveType::StableSwap => self.compute_stable_swap_invariant(
+                first_token_amount,
+                second_token_amount,
+                self.get_amp_factor_precise(),
+            ),
+        }
     }
 
     fn quote(
@@ -35,6 +45,10 @@ pub trait AmmModule:
         reserve_in: &BigUint,
         reserve_out: &BigUint,
     ) -> BigUint {
+        if self.curve_type().get() == CurveType::StableSwap {
+            return self.get_stable_swap_amount_out(amount_in, reserve_in, reserve_out);
+        }
+
         let numerator = amount_in * reserve_out;
         let denominator = reserve_in + amount_in;
 
@@ -46,8 +60,14 @@ pub trait AmmModule:
         amount_in: &BigUint,
         reserve_in: &BigUint,
         reserve_out: &BigUint,
+        total_fee_percent: u64,
     ) -> BigUint {
-        let amount_in_with_fee = amount_in * (MAX_PERCENTAGE - self.total_fee_percent().get());
+        let amount_in_with_fee = amount_in * (MAX_PERCENTAGE - total_fee_percent);
+        if self.curve_type().get() == CurveType::StableSwap {
+            let amount_in_after_fee = amount_in_with_fee / MAX_PERCENTAGE;
+            return self.get_stable_swap_amount_out(&amount_in_after_fee, reserve_in, reserve_out);
+        }
+
         let numerator = &amount_in_with_fee * reserve_out;
         let denominator = (reserve_in * MAX_PERCENTAGE) + amount_in_with_fee;
 
@@ -59,14 +79,89 @@ pub trait AmmModule:
         amount_out: &BigUint,
         reserve_in: &BigUint,
         reserve_out: &BigUint,
+        total_fee_percent: u64,
     ) -> BigUint {
+        let fee_multiplier = MAX_PERCENTAGE - total_fee_percent;
+        if self.curve_type().get() == CurveType::StableSwap {
+            let amount_in_after_fee =
+                self.get_stable_swap_amount_in(amount_out, reserve_in, reserve_out);
+            return (amount_in_after_fee * MAX_PERCENTAGE / fee_multiplier) + 1u64;
+        }
+
         let numerator = reserve_in * amount_out * MAX_PERCENTAGE;
-        let denominator =
-            (reserve_out - amount_out) * (MAX_PERCENTAGE - self.total_fee_percent().get());
+        let denominator = (reserve_out - amount_out) * fee_multiplier;
 
         (numerator / denominator) + 1u64
     }
 
+    /// The part of `amount_in` to swap so that the swap output and the rest of the input
+    /// match the reserves ratio after the swap. Computed for the constant product formula,
+    /// so on the StableSwap curve it is only an approximation.
+    fn get_single_token_swap_amount(
+        &self,
+        amount_in: &BigUint,
+        reserve_in: &BigUint,
+        total_fee_percent: u64,
+    ) -> BigUint {
+        // s = (sqrt((R * (2 - f))^2 + 4 * (1 - f) * R * a) - R * (2 - f)) / (2 * (1 - f))
+        let fee_multiplier = MAX_PERCENTAGE - total_fee_percent;
+        let reserve_term = reserve_in * (2 * MAX_PERCENTAGE - total_fee_percent);
+        let discriminant = &reserve_term * &reserve_term
+            + reserve_in * amount_in * (4 * fee_multiplier * MAX_PERCENTAGE);
+
+        (discriminant.sqrt() - reserve_term) / (2 * fee_multiplier)
+    }
+
+    fn get_stable_swap_amount_out(
+        &self,
+        amount_in: &BigUint,
+        reserve_in: &BigUint,
+        reserve_out: &BigUint,
+    ) -> BigUint {
+        let amp_factor = self.get_amp_factor_precise();
+        let invariant = self.compute_stable_swap_invariant(reserve_in, reserve_out, amp_factor);
+        if invariant == 0u64 {
+            return BigUint::zero();
+        }
+
+        let new_reserve_in = reserve_in + amount_in;
+        let new_reserve_out =
+            self.compute_stable_swap_reserve(&new_reserve_in, &invariant, amp_factor);
+
+        // round down in favor of the pool
+        let min_reserve_out = new_reserve_out + 1u64;
+        if reserve_out > &min_reserve_out {
+            reserve_out - &min_reserve_out
+        } else {
+            BigUint::zero()
+        }
+    }
+
+    fn get_stable_swap_amount_in(
+        &self,
+        amount_out: &BigUint,
+        reserve_in: &BigUint,
+        reserve_out: &BigUint,
+    ) -> BigUint {
+        let amp_factor = self.get_amp_factor_precise();
+        let invariant = self.compute_stable_swap_invariant(reserve_in, reserve_out, amp_factor);
+        require!(
+            invariant > 0u64 && amount_out < reserve_out,
+            ERROR_NOT_ENOUGH_RESERVE
+        );
+
+        let new_reserve_out = reserve_out - amount_out;
+        let new_reserve_in =
+            self.compute_stable_swap_reserve(&new_reserve_out, &invariant, amp_factor);
+
+        // round up in favor of the pool
+        if &new_reserve_in > reserve_in {
+            new_reserve_in - reserve_in + 1u64
+        } else {
+            BigUint::from(1u64)
+        }
+    }
+
     fn get_special_fee_from_input(&self, amount_in: &BigUint) -> BigUint {
         amount_in * self.special_fee_percent().get() / MAX_PERCENTAGE
     }
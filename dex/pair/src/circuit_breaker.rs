@@ -0,0 +1,73 @@
+multiversx_sc::imports!();
+
+use crate::config::{self, MAX_PERCENTAGE};
+use crate::errors::*;
+use crate::{amm, safe_price, safe_price_view, stable_swap};
+
+#[multiversx_sc::module]
+pub trait CircuitBreakerModule:
+    safe_price_view::SafePriceViewModule
+    + safe_price::SafePriceModule
+    + config::ConfigModule
+    + token_send::TokenSendModule
+    + amm::AmmModule
+    + stable_swap::StableSwapModule
+    + permissions_module::PermissionsModule
+    + pausable::PausableModule
+{
+    /// Sets the maximum deviation, in MAX_PERCENTAGE units, between the spot price
+    /// after a swap or a liquidity add and the pair's safe price.
+    /// A value of 0 disables the check.
+    #[endpoint(setMaxPriceDeviation)]
+    fn set_max_price_deviation(&self, max_price_deviation: u64) {
+        self.require_caller_has_owner_or_admin_permissions();
+        require!(max_price_deviation <= MAX_PERCENTAGE, ERROR_BAD_PERCENTS);
+
+        self.max_price_deviation().set(max_price_deviation);
+    }
+
+    /// The reserves are compared to the safe price of the default offset.
+    /// An action leaving the price beyond the limit is rejected, unless it strictly brings
+    /// the price closer to the safe price without crossing it, so that the price can recover.
+    fn require_price_deviation_within_limit(
+        &self,
+        first_token_reserve_before: &BigUint,
+        second_token_reserve_before: &BigUint,
+        first_token_reserve: &BigUint,
+        second_token_reserve: &BigUint,
+    ) {
+        let max_price_deviation = self.max_price_deviation().get();
+        if max_price_deviation == 0 || self.price_observations().is_empty() {
+            return;
+        }
+
+        let pair_address = self.blockchain().get_sc_address();
+        let current_round = self.blockchain().get_block_round();
+        let default_offset_rounds = self.get_default_offset_rounds(&pair_address, current_round);
+        let (price_deviation, is_price_above_average) = self
+            .get_price_deviation_and_side_from_average(
+                first_token_reserve,
+                second_token_reserve,
+                default_offset_rounds,
+            );
+        if price_deviation <= max_price_deviation {
+            return;
+        }
+
+        let (price_deviation_before, was_price_above_average) = self
+            .get_price_deviation_and_side_from_average(
+                first_token_reserve_before,
+                second_token_reserve_before,
+                default_offset_rounds,
+            );
+        require!(
+            price_deviation < price_deviation_before
+                && is_price_above_average == was_price_above_average,
+            ERROR_PRICE_DEVIATION_TOO_HIGH
+        );
+    }
+
+    #[view(getMaxPriceDeviation)]
+    #[storage_mapper("max_price_deviation")]
+    fn max_price_deviation(&self) -> SingleValueMapper<u64>;
+}
@@ -7,6 +7,20 @@ use super::errors::*;
 
 pub const MAX_PERCENTAGE: u64 = 100_000;
 pub const MAX_FEE_PERCENTAGE: u64 = 5_000;
+pub const MIN_FEE_CHANGE_DELAY_BLOCKS: u64 = 14_400;
+
+#[derive(TypeAbi, TopEncode, TopDecode, PartialEq, Eq, Clone, Copy, Debug)]
+pub enum CurveType {
+    ConstantProduct,
+    StableSwap,
+}
+
+#[derive(TypeAbi, TopEncode, TopDecode, PartialEq, Eq, Clone, Debug)]
+pub struct PendingFeeChange {
+    pub total_fee_percent: u64,
+    pub special_fee_percent: u64,
+    pub effective_block: u64,
+}
 
 #[multiversx_sc::module]
 pub trait ConfigModule:
@@ -18,12 +32,97 @@ pub trait ConfigModule:
         self.state().set(State::PartialActive);
     }
 
+    /// Changes the fees instantly. Only possible while swaps are not enabled,
+    /// otherwise the change has to be announced through `announceFeePercents`.
     #[endpoint(setFeePercents)]
     fn set_fee_percent(&self, total_fee_percent: u64, special_fee_percent: u64) {
         self.require_caller_has_owner_or_admin_permissions();
+        require!(
+            self.state().get() != State::Active,
+            ERROR_FEE_CHANGE_NOT_ANNOUNCED
+        );
+
         self.set_fee_percents(total_fee_percent, special_fee_percent);
     }
 
+    /// Schedules a fee change, which can be applied through `applyFeePercents`
+    /// once the fee change delay has passed. Replaces the previously announced change, if any.
+    #[endpoint(announceFeePercents)]
+    fn announce_fee_percents(&self, total_fee_percent: u64, special_fee_percent: u64) {
+        self.require_caller_has_owner_or_admin_permissions();
+        require!(
+            total_fee_percent >= special_fee_percent && total_fee_percent <= MAX_FEE_PERCENTAGE,
+            ERROR_BAD_PERCENTS
+        );
+
+        let effective_block = self.blockchain().get_block_nonce() + self.get_fee_change_delay();
+        let pending_fee_change = PendingFeeChange {
+            total_fee_percent,
+            special_fee_percent,
+            effective_block,
+        };
+        self.fee_change_announced_event(&pending_fee_change);
+        self.pending_fee_change().set(pending_fee_change);
+    }
+
+    /// Applies the announced fee change. Callable by anyone once it is effective.
+    #[endpoint(applyFeePercents)]
+    fn apply_fee_percents(&self) {
+        let pending_fee_change_mapper = self.pending_fee_change();
+        require!(
+            !pending_fee_change_mapper.is_empty(),
+            ERROR_NO_PENDING_FEE_CHANGE
+        );
+
+        let pending_fee_change = pending_fee_change_mapper.take();
+        require!(
+            self.blockchain().get_block_nonce() >= pending_fee_change.effective_block,
+            ERROR_FEE_CHANGE_NOT_EFFECTIVE
+        );
+
+        self.set_fee_percents(
+            pending_fee_change.total_fee_percent,
+            pending_fee_change.special_fee_percent,
+        );
+        self.fee_change_applied_event(&pending_fee_change);
+    }
+
+    #[endpoint(cancelFeePercentsChange)]
+    fn cancel_fee_percents_change(&self) {
+        self.require_caller_has_owner_or_admin_permissions();
+
+        let pending_fee_change_mapper = self.pending_fee_change();
+        require!(
+            !pending_fee_change_mapper.is_empty(),
+            ERROR_NO_PENDING_FEE_CHANGE
+        );
+
+        let pending_fee_change = pending_fee_change_mapper.take();
+        self.fee_change_cancelled_event(&pending_fee_change);
+    }
+
+    #[endpoint(setFeeChangeDelay)]
+    fn set_fee_change_delay(&self, delay_blocks: u64) {
+        self.require_caller_has_owner_permissions();
+        require!(
+            delay_blocks >= MIN_FEE_CHANGE_DELAY_BLOCKS,
+            ERROR_FEE_CHANGE_DELAY_TOO_SHORT
+        );
+
+        self.fee_change_delay().set(delay_blocks);
+    }
+
+    /// The number of blocks between the announcement and the application of a fee change.
+    #[view(getFeeChangeDelay)]
+    fn get_fee_change_delay(&self) -> u64 {
+        let fee_change_delay_mapper = self.fee_change_delay();
+        if fee_change_delay_mapper.is_empty() {
+            return MIN_FEE_CHANGE_DELAY_BLOCKS;
+        }
+
+        fee_change_delay_mapper.get()
+    }
+
     fn set_fee_percents(&self, total_fee_percent: u64, special_fee_percent: u64) {
         require!(
             total_fee_percent >= special_fee_percent && total_fee_percent <= MAX_FEE_PERCENTAGE,
@@ -46,6 +145,10 @@ pub trait ConfigModule:
     #[storage_mapper("special_fee_percent")]
     fn special_fee_percent(&self) -> SingleValueMapper<u64>;
 
+    #[view(getCurveType)]
+    #[storage_mapper("curve_type")]
+    fn curve_type(&self) -> SingleValueMapper<CurveType>;
+
     #[view(getRouterManagedAddress)]
     #[storage_mapper("router_address")]
     fn router_address(&self) -> SingleValueMapper<ManagedAddress>;
@@ -55,11 +158,11 @@ pub trait ConfigModule:
 
     #[view(getFirstTokenId)]
     #[storage_mapper("first_token_id")]
-    fn first_token_id(&self) -> SingleValueMapper<TokenIdentifier>;
+    fn first_token_id(&self) -> SingleValueMapper<EgldOrEsdtTokenIdentifier>;
 
     #[view(getSecondTokenId)]
     #[storage_mapper("second_token_id")]
-    fn second_token_id(&self) -> SingleValueMapper<TokenIdentifier>;
+    fn second_token_id(&self) -> SingleValueMapper<EgldOrEsdtTokenIdentifier>;
 
     #[view(getTotalSupply)]
     #[storage_mapper("lp_token_supply")]
@@ -71,5 +174,21 @@ pub trait ConfigModule:
 
     #[view(getReserve)]
     #[storage_mapper("reserve")]
-    fn pair_reserve(&self, token_id: &TokenIdentifier) -> SingleValueMapper<BigUint>;
+    fn pair_reserve(&self, token_id: &EgldOrEsdtTokenIdentifier) -> SingleValueMapper<BigUint>;
+
+    #[view(getPendingFeeChange)]
+    #[storage_mapper("pending_fee_change")]
+    fn pending_fee_change(&self) -> SingleValueMapper<PendingFeeChange>;
+
+    #[storage_mapper("fee_change_delay")]
+    fn fee_change_delay(&self) -> SingleValueMapper<u64>;
+
+    #[event("fee_change_announced")]
+    fn fee_change_announced_event(&self, pending_fee_change: &PendingFeeChange);
+
+    #[event("fee_change_applied")]
+    fn fee_change_applied_event(&self, fee_change: &PendingFeeChange);
+
+    #[event("fee_change_cancelled")]
+    fn fee_change_cancelled_event(&self, pending_fee_change: &PendingFeeChange);
 }
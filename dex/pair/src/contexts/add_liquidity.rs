@@ -2,8 +2,8 @@ multiversx_sc::imports!();
 multiversx_sc::derive_imports!();
 
 pub struct AddLiquidityContext<M: ManagedTypeApi> {
-    pub first_payment: EsdtTokenPayment<M>,
-    pub second_payment: EsdtTokenPayment<M>,
+    pub first_payment: EgldOrEsdtTokenPayment<M>,
+    pub second_payment: EgldOrEsdtTokenPayment<M>,
     pub first_token_amount_min: BigUint<M>,
     pub second_token_amount_min: BigUint<M>,
     pub first_token_optimal_amount: BigUint<M>,
@@ -13,8 +13,8 @@ pub struct AddLiquidityContext<M: ManagedTypeApi> {
 
 impl<M: ManagedTypeApi> AddLiquidityContext<M> {
     pub fn new(
-        first_payment: EsdtTokenPayment<M>,
-        second_payment: EsdtTokenPayment<M>,
+        first_payment: EgldOrEsdtTokenPayment<M>,
+        second_payment: EgldOrEsdtTokenPayment<M>,
         first_token_amount_min: BigUint<M>,
         second_token_amount_min: BigUint<M>,
     ) -> Self {
@@ -16,8 +16,8 @@ where
     sc_ref: &'a C,
     pub contract_state: State,
     pub lp_token_id: TokenIdentifier<C::Api>,
-    pub first_token_id: TokenIdentifier<C::Api>,
-    pub second_token_id: TokenIdentifier<C::Api>,
+    pub first_token_id: EgldOrEsdtTokenIdentifier<C::Api>,
+    pub second_token_id: EgldOrEsdtTokenIdentifier<C::Api>,
     pub first_token_reserve: BigUint<C::Api>,
     pub second_token_reserve: BigUint<C::Api>,
     pub lp_token_supply: BigUint<C::Api>,
@@ -47,8 +47,8 @@ where
 
     pub fn get_swap_tokens_order(
         &self,
-        first_token_id: &TokenIdentifier<C::Api>,
-        second_token_id: &TokenIdentifier<C::Api>,
+        first_token_id: &EgldOrEsdtTokenIdentifier<C::Api>,
+        second_token_id: &EgldOrEsdtTokenIdentifier<C::Api>,
     ) -> SwapTokensOrder {
         if first_token_id == &self.first_token_id && second_token_id == &self.second_token_id {
             SwapTokensOrder::PoolOrder
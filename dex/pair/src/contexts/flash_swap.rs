@@ -0,0 +1,33 @@
+multiversx_sc::imports!();
+multiversx_sc::derive_imports!();
+
+use super::base::SwapTokensOrder;
+
+pub struct FlashSwapContext<M: ManagedTypeApi> {
+    pub output_token_id: EgldOrEsdtTokenIdentifier<M>,
+    pub output_token_amount: BigUint<M>,
+    pub swap_tokens_order: SwapTokensOrder,
+
+    pub first_token_amount_in: BigUint<M>,
+    pub second_token_amount_in: BigUint<M>,
+    pub first_token_fee_amount: BigUint<M>,
+    pub second_token_fee_amount: BigUint<M>,
+}
+
+impl<M: ManagedTypeApi> FlashSwapContext<M> {
+    pub fn new(
+        output_token_id: EgldOrEsdtTokenIdentifier<M>,
+        output_token_amount: BigUint<M>,
+        swap_tokens_order: SwapTokensOrder,
+    ) -> Self {
+        FlashSwapContext {
+            output_token_id,
+            output_token_amount,
+            swap_tokens_order,
+            first_token_amount_in: BigUint::zero(),
+            second_token_amount_in: BigUint::zero(),
+            first_token_fee_amount: BigUint::zero(),
+            second_token_fee_amount: BigUint::zero(),
+        }
+    }
+}
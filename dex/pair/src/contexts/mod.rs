@@ -1,5 +1,6 @@
 pub mod add_liquidity;
 pub mod base;
+pub mod flash_swap;
 pub mod output_builder;
 pub mod remove_liquidity;
 pub mod swap;
@@ -29,12 +29,12 @@ pub trait OutputBuilderModule:
                 0,
                 add_liq_context.liq_added.clone(),
             ),
-            EsdtTokenPayment::new(
+            EgldOrEsdtTokenPayment::new(
                 storage_cache.first_token_id.clone(),
                 0,
                 add_liq_context.first_token_optimal_amount.clone(),
             ),
-            EsdtTokenPayment::new(
+            EgldOrEsdtTokenPayment::new(
                 storage_cache.second_token_id.clone(),
                 0,
                 add_liq_context.second_token_optimal_amount.clone(),
@@ -47,21 +47,20 @@ pub trait OutputBuilderModule:
         &self,
         storage_cache: &StorageCache<Self>,
         add_liq_context: &AddLiquidityContext<Self::Api>,
-    ) -> ManagedVec<EsdtTokenPayment<Self::Api>> {
-        let mut payments: ManagedVec<EsdtTokenPayment<Self::Api>> = ManagedVec::new();
-
-        payments.push(EsdtTokenPayment::new(
-            storage_cache.first_token_id.clone(),
-            0,
-            &add_liq_context.first_payment.amount - &add_liq_context.first_token_optimal_amount,
-        ));
-        payments.push(EsdtTokenPayment::new(
-            storage_cache.second_token_id.clone(),
-            0,
-            &add_liq_context.second_payment.amount - &add_liq_context.second_token_optimal_amount,
-        ));
-
-        payments
+    ) -> [EgldOrEsdtTokenPayment<Self::Api>; 2] {
+        [
+            EgldOrEsdtTokenPayment::new(
+                storage_cache.first_token_id.clone(),
+                0,
+                &add_liq_context.first_payment.amount - &add_liq_context.first_token_optimal_amount,
+            ),
+            EgldOrEsdtTokenPayment::new(
+                storage_cache.second_token_id.clone(),
+                0,
+                &add_liq_context.second_payment.amount
+                    - &add_liq_context.second_token_optimal_amount,
+            ),
+        ]
     }
 
     fn build_add_liq_results(
@@ -75,12 +74,12 @@ pub trait OutputBuilderModule:
                 0,
                 add_liq_context.liq_added.clone(),
             ),
-            EsdtTokenPayment::new(
+            EgldOrEsdtTokenPayment::new(
                 storage_cache.first_token_id.clone(),
                 0,
                 add_liq_context.first_token_optimal_amount.clone(),
             ),
-            EsdtTokenPayment::new(
+            EgldOrEsdtTokenPayment::new(
                 storage_cache.second_token_id.clone(),
                 0,
                 add_liq_context.second_token_optimal_amount.clone(),
@@ -93,72 +92,85 @@ pub trait OutputBuilderModule:
         &self,
         storage_cache: &StorageCache<Self>,
         remove_liq_context: &RemoveLiquidityContext<Self::Api>,
-    ) -> ManagedVec<EsdtTokenPayment<Self::Api>> {
-        let mut payments = ManagedVec::new();
-
-        payments.push(EsdtTokenPayment::new(
-            storage_cache.first_token_id.clone(),
-            0,
-            remove_liq_context.first_token_amount_removed.clone(),
-        ));
-        payments.push(EsdtTokenPayment::new(
-            storage_cache.second_token_id.clone(),
-            0,
-            remove_liq_context.second_token_amount_removed.clone(),
-        ));
-
-        payments
+    ) -> [EgldOrEsdtTokenPayment<Self::Api>; 2] {
+        [
+            EgldOrEsdtTokenPayment::new(
+                storage_cache.first_token_id.clone(),
+                0,
+                remove_liq_context.first_token_amount_removed.clone(),
+            ),
+            EgldOrEsdtTokenPayment::new(
+                storage_cache.second_token_id.clone(),
+                0,
+                remove_liq_context.second_token_amount_removed.clone(),
+            ),
+        ]
     }
 
     fn build_remove_liq_results(
         &self,
-        output_payments: ManagedVec<EsdtTokenPayment<Self::Api>>,
+        output_payments: [EgldOrEsdtTokenPayment<Self::Api>; 2],
     ) -> RemoveLiquidityResultType<Self::Api> {
-        (output_payments.get(0), output_payments.get(1)).into()
+        let [first_payment, second_payment] = output_payments;
+        (first_payment, second_payment).into()
     }
 
     fn build_swap_output_payments(
         &self,
         swap_context: &SwapContext<Self::Api>,
-    ) -> ManagedVec<EsdtTokenPayment<Self::Api>> {
-        let mut payments = ManagedVec::new();
-
-        if self.should_generate_locked_asset() {
-            let locked_asset = self.lock_tokens(
+    ) -> [EgldOrEsdtTokenPayment<Self::Api>; 2] {
+        let output_payment = if self.should_generate_locked_asset() {
+            self.lock_tokens(
                 swap_context.output_token_id.clone(),
                 swap_context.final_output_amount.clone(),
-            );
-            payments.push(locked_asset);
+            )
         } else {
-            payments.push(EsdtTokenPayment::new(
+            EgldOrEsdtTokenPayment::new(
                 swap_context.output_token_id.clone(),
                 0,
                 swap_context.final_output_amount.clone(),
-            ));
-        }
+            )
+        };
 
         let extra_amount = &swap_context.input_token_amount - &swap_context.final_input_amount;
-        payments.push(EsdtTokenPayment::new(
-            swap_context.input_token_id.clone(),
-            0,
-            extra_amount,
-        ));
+        let extra_payment =
+            EgldOrEsdtTokenPayment::new(swap_context.input_token_id.clone(), 0, extra_amount);
 
-        payments
+        [output_payment, extra_payment]
     }
 
     #[inline]
     fn build_swap_fixed_input_results(
         &self,
-        output_payments: ManagedVec<EsdtTokenPayment<Self::Api>>,
+        output_payments: [EgldOrEsdtTokenPayment<Self::Api>; 2],
     ) -> SwapTokensFixedInputResultType<Self::Api> {
-        output_payments.get(0)
+        let [output_payment, _] = output_payments;
+        output_payment
     }
 
     fn build_swap_fixed_output_results(
         &self,
-        output_payments: ManagedVec<EsdtTokenPayment<Self::Api>>,
+        output_payments: [EgldOrEsdtTokenPayment<Self::Api>; 2],
     ) -> SwapTokensFixedOutputResultType<Self::Api> {
-        (output_payments.get(0), output_payments.get(1)).into()
+        let [output_payment, extra_payment] = output_payments;
+        (output_payment, extra_payment).into()
+    }
+
+    /// The EGLD is sent on its own, as it cannot be part of a multi ESDT transfer.
+    fn send_payments_non_zero(
+        &self,
+        to: &ManagedAddress,
+        payments: &[EgldOrEsdtTokenPayment<Self::Api>],
+    ) {
+        let mut esdt_payments = ManagedVec::new();
+        for payment in payments {
+            if payment.token_identifier.is_egld() {
+                self.send().direct_non_zero_egld(to, &payment.amount);
+            } else {
+                esdt_payments.push(payment.clone().unwrap_esdt());
+            }
+        }
+
+        self.send_multiple_tokens_if_not_zero(to, &esdt_payments);
     }
 }
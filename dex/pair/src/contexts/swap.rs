@@ -4,23 +4,24 @@ multiversx_sc::derive_imports!();
 use super::base::SwapTokensOrder;
 
 pub struct SwapContext<M: ManagedTypeApi> {
-    pub input_token_id: TokenIdentifier<M>,
+    pub input_token_id: EgldOrEsdtTokenIdentifier<M>,
     pub input_token_amount: BigUint<M>,
 
-    pub output_token_id: TokenIdentifier<M>,
+    pub output_token_id: EgldOrEsdtTokenIdentifier<M>,
     pub output_token_amount: BigUint<M>,
     pub swap_tokens_order: SwapTokensOrder,
 
     pub final_input_amount: BigUint<M>,
     pub final_output_amount: BigUint<M>,
     pub fee_amount: BigUint<M>,
+    pub fee_percent: u64,
 }
 
 impl<M: ManagedTypeApi> SwapContext<M> {
     pub fn new(
-        input_token_id: TokenIdentifier<M>,
+        input_token_id: EgldOrEsdtTokenIdentifier<M>,
         input_token_amount: BigUint<M>,
-        output_token_id: TokenIdentifier<M>,
+        output_token_id: EgldOrEsdtTokenIdentifier<M>,
         output_token_amount: BigUint<M>,
         swap_tokens_order: SwapTokensOrder,
     ) -> Self {
@@ -33,6 +34,7 @@ impl<M: ManagedTypeApi> SwapContext<M> {
             final_input_amount: BigUint::zero(),
             final_output_amount: BigUint::zero(),
             fee_amount: BigUint::zero(),
+            fee_percent: 0,
         }
     }
 }
@@ -0,0 +1,215 @@
+multiversx_sc::imports!();
+multiversx_sc::derive_imports!();
+
+use pausable::State;
+
+use crate::config::{self, MAX_FEE_PERCENTAGE, MAX_PERCENTAGE};
+use crate::errors::*;
+use crate::safe_price::Round;
+use crate::{amm, safe_price, safe_price_view, stable_swap};
+
+#[derive(TypeAbi, TopEncode, TopDecode, NestedEncode, NestedDecode, Clone, PartialEq, Debug)]
+pub struct DynamicFeeConfig {
+    pub min_fee_percent: u64,
+    pub max_fee_percent: u64,
+    pub volatility_rounds: Round,
+    pub volatility_multiplier: u64,
+}
+
+#[derive(TypeAbi, TopEncode, TopDecode, Clone, PartialEq, Debug)]
+pub struct PendingDynamicFeeConfig {
+    pub config: DynamicFeeConfig,
+    pub effective_block: u64,
+}
+
+#[multiversx_sc::module]
+pub trait DynamicFeeModule:
+    safe_price_view::SafePriceViewModule
+    + safe_price::SafePriceModule
+    + config::ConfigModule
+    + token_send::TokenSendModule
+    + amm::AmmModule
+    + stable_swap::StableSwapModule
+    + permissions_module::PermissionsModule
+    + pausable::PausableModule
+{
+    /// Enables the dynamic fee mode. The swap fee starts at `min_fee_percent` and grows with
+    /// the deviation of the current price from the average price of the last `volatility_rounds`,
+    /// scaled by `volatility_multiplier` (in MAX_PERCENTAGE units), up to `max_fee_percent`.
+    /// While swaps are enabled, the config takes effect instantly only if it does not raise
+    /// the maximum swap fee, otherwise it has to be announced through `announceDynamicFeeConfig`.
+    #[endpoint(setDynamicFeeConfig)]
+    fn set_dynamic_fee_config(
+        &self,
+        min_fee_percent: u64,
+        max_fee_percent: u64,
+        volatility_rounds: Round,
+        volatility_multiplier: u64,
+    ) {
+        self.require_caller_has_owner_permissions();
+        let config = self.build_dynamic_fee_config(
+            min_fee_percent,
+            max_fee_percent,
+            volatility_rounds,
+            volatility_multiplier,
+        );
+        require!(
+            self.state().get() != State::Active
+                || max_fee_percent <= self.get_max_swap_fee_percent(),
+            ERROR_FEE_CHANGE_NOT_ANNOUNCED
+        );
+
+        self.dynamic_fee_config().set(config);
+    }
+
+    /// Schedules a dynamic fee config, which can be applied through `applyDynamicFeeConfig`
+    /// once the fee change delay has passed. Replaces the previously announced config, if any.
+    #[endpoint(announceDynamicFeeConfig)]
+    fn announce_dynamic_fee_config(
+        &self,
+        min_fee_percent: u64,
+        max_fee_percent: u64,
+        volatility_rounds: Round,
+        volatility_multiplier: u64,
+    ) {
+        self.require_caller_has_owner_permissions();
+        let config = self.build_dynamic_fee_config(
+            min_fee_percent,
+            max_fee_percent,
+            volatility_rounds,
+            volatility_multiplier,
+        );
+
+        let effective_block = self.blockchain().get_block_nonce() + self.get_fee_change_delay();
+        let pending_config = PendingDynamicFeeConfig {
+            config,
+            effective_block,
+        };
+        self.dynamic_fee_config_announced_event(&pending_config);
+        self.pending_dynamic_fee_config().set(pending_config);
+    }
+
+    /// Applies the announced dynamic fee config. Callable by anyone once it is effective.
+    #[endpoint(applyDynamicFeeConfig)]
+    fn apply_dynamic_fee_config(&self) {
+        let pending_config_mapper = self.pending_dynamic_fee_config();
+        require!(
+            !pending_config_mapper.is_empty(),
+            ERROR_NO_PENDING_FEE_CHANGE
+        );
+
+        let pending_config = pending_config_mapper.take();
+        require!(
+            self.blockchain().get_block_nonce() >= pending_config.effective_block,
+            ERROR_FEE_CHANGE_NOT_EFFECTIVE
+        );
+
+        self.dynamic_fee_config().set(&pending_config.config);
+        self.dynamic_fee_config_applied_event(&pending_config);
+    }
+
+    #[endpoint(cancelDynamicFeeConfigChange)]
+    fn cancel_dynamic_fee_config_change(&self) {
+        self.require_caller_has_owner_permissions();
+
+        let pending_config_mapper = self.pending_dynamic_fee_config();
+        require!(
+            !pending_config_mapper.is_empty(),
+            ERROR_NO_PENDING_FEE_CHANGE
+        );
+
+        let pending_config = pending_config_mapper.take();
+        self.dynamic_fee_config_cancelled_event(&pending_config);
+    }
+
+    #[endpoint(disableDynamicFee)]
+    fn disable_dynamic_fee(&self) {
+        self.require_caller_has_owner_permissions();
+        self.dynamic_fee_config().clear();
+    }
+
+    /// The fee currently applied to swaps.
+    /// Equal to the total fee percent when the dynamic fee mode is disabled.
+    #[view(getSwapFeePercent)]
+    fn get_swap_fee_percent(&self) -> u64 {
+        let dynamic_fee_config_mapper = self.dynamic_fee_config();
+        if dynamic_fee_config_mapper.is_empty() {
+            return self.total_fee_percent().get();
+        }
+
+        let dynamic_fee_config = dynamic_fee_config_mapper.get();
+        let volatility = self.get_price_volatility(dynamic_fee_config.volatility_rounds);
+        let volatility_fee_percent =
+            volatility.saturating_mul(dynamic_fee_config.volatility_multiplier) / MAX_PERCENTAGE;
+        let fee_percent = core::cmp::min(
+            dynamic_fee_config
+                .min_fee_percent
+                .saturating_add(volatility_fee_percent),
+            dynamic_fee_config.max_fee_percent,
+        );
+
+        // the special fee may have been raised since the config was set
+        core::cmp::max(fee_percent, self.special_fee_percent().get())
+    }
+
+    /// The highest fee a swap can be charged: the max fee of the dynamic fee config when
+    /// enabled, the total fee percent otherwise. Both can only be raised through announcements.
+    fn get_max_swap_fee_percent(&self) -> u64 {
+        let dynamic_fee_config_mapper = self.dynamic_fee_config();
+        if dynamic_fee_config_mapper.is_empty() {
+            return self.total_fee_percent().get();
+        }
+
+        dynamic_fee_config_mapper.get().max_fee_percent
+    }
+
+    fn build_dynamic_fee_config(
+        &self,
+        min_fee_percent: u64,
+        max_fee_percent: u64,
+        volatility_rounds: Round,
+        volatility_multiplier: u64,
+    ) -> DynamicFeeConfig {
+        require!(
+            self.special_fee_percent().get() <= min_fee_percent
+                && min_fee_percent <= max_fee_percent
+                && max_fee_percent <= MAX_FEE_PERCENTAGE,
+            ERROR_BAD_PERCENTS
+        );
+        require!(
+            volatility_rounds > 0 && volatility_multiplier > 0,
+            ERROR_INVALID_ARGS
+        );
+
+        DynamicFeeConfig {
+            min_fee_percent,
+            max_fee_percent,
+            volatility_rounds,
+            volatility_multiplier,
+        }
+    }
+
+    fn get_price_volatility(&self, rounds: Round) -> u64 {
+        let first_token_reserve = self.pair_reserve(&self.first_token_id().get()).get();
+        let second_token_reserve = self.pair_reserve(&self.second_token_id().get()).get();
+
+        self.get_price_deviation_from_average(&first_token_reserve, &second_token_reserve, rounds)
+    }
+
+    #[view(getDynamicFeeConfig)]
+    #[storage_mapper("dynamic_fee_config")]
+    fn dynamic_fee_config(&self) -> SingleValueMapper<DynamicFeeConfig>;
+
+    #[view(getPendingDynamicFeeConfig)]
+    #[storage_mapper("pending_dynamic_fee_config")]
+    fn pending_dynamic_fee_config(&self) -> SingleValueMapper<PendingDynamicFeeConfig>;
+
+    #[event("dynamic_fee_config_announced")]
+    fn dynamic_fee_config_announced_event(&self, pending_config: &PendingDynamicFeeConfig);
+
+    #[event("dynamic_fee_config_applied")]
+    fn dynamic_fee_config_applied_event(&self, config: &PendingDynamicFeeConfig);
+
+    #[event("dynamic_fee_config_cancelled")]
+    fn dynamic_fee_config_cancelled_event(&self, pending_config: &PendingDynamicFeeConfig);
+}
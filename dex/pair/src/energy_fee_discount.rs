@@ -0,0 +1,98 @@
+multiversx_sc::imports!();
+multiversx_sc::derive_imports!();
+
+use crate::config::{self, MAX_PERCENTAGE};
+use crate::errors::*;
+use crate::{amm, dynamic_fee, safe_price, safe_price_view, stable_swap};
+
+/// `discount_percent` is a percentage of the swap fee, in MAX_PERCENTAGE units.
+#[derive(
+    ManagedVecItem, TopEncode, TopDecode, NestedEncode, NestedDecode, TypeAbi, Clone, PartialEq,
+)]
+pub struct FeeDiscountTier<M: ManagedTypeApi> {
+    pub min_energy: BigUint<M>,
+    pub discount_percent: u64,
+}
+
+#[multiversx_sc::module]
+pub trait EnergyFeeDiscountModule:
+    dynamic_fee::DynamicFeeModule
+    + safe_price_view::SafePriceViewModule
+    + safe_price::SafePriceModule
+    + config::ConfigModule
+    + token_send::TokenSendModule
+    + amm::AmmModule
+    + stable_swap::StableSwapModule
+    + energy_query::EnergyQueryModule
+    + permissions_module::PermissionsModule
+    + pausable::PausableModule
+{
+    /// Replaces the fee discount tiers. Each tier is a pair of
+    /// (min_energy, discount_percent), sorted ascending by both values.
+    /// An empty list disables the fee discounts. The discounts only lower the fee,
+    /// down to the special fee, so unlike the fee percents they take effect instantly.
+    #[endpoint(setFeeDiscountTiers)]
+    fn set_fee_discount_tiers(&self, tiers: MultiValueEncoded<MultiValue2<BigUint, u64>>) {
+        self.require_caller_has_owner_or_admin_permissions();
+
+        let mut fee_discount_tiers = ManagedVec::<Self::Api, FeeDiscountTier<Self::Api>>::new();
+        for tier in tiers {
+            let (min_energy, discount_percent) = tier.into_tuple();
+            require!(discount_percent <= MAX_PERCENTAGE, ERROR_BAD_PERCENTS);
+
+            if !fee_discount_tiers.is_empty() {
+                let last_tier = fee_discount_tiers.get(fee_discount_tiers.len() - 1);
+                require!(
+                    min_energy > last_tier.min_energy
+                        && discount_percent >= last_tier.discount_percent,
+                    ERROR_INVALID_FEE_DISCOUNT_TIERS
+                );
+            }
+
+            fee_discount_tiers.push(FeeDiscountTier {
+                min_energy,
+                discount_percent,
+            });
+        }
+
+        self.fee_discount_tiers().set(&fee_discount_tiers);
+    }
+
+    /// The fee applied to the given user's swaps, after the energy discount.
+    /// Never lower than the special fee, which is always taken out of the input.
+    /// Contracts, like the router or other aggregators, get no discount, since they swap
+    /// on behalf of their own callers and would otherwise share their energy with all of them.
+    fn get_user_swap_fee_percent(&self, user: &ManagedAddress) -> u64 {
+        let fee_percent = self.get_swap_fee_percent();
+        let discount_percent = self.get_fee_discount_percent(user);
+        if discount_percent == 0 {
+            return fee_percent;
+        }
+
+        let discounted_fee_percent = fee_percent - fee_percent * discount_percent / MAX_PERCENTAGE;
+        core::cmp::max(discounted_fee_percent, self.special_fee_percent().get())
+    }
+
+    fn get_fee_discount_percent(&self, user: &ManagedAddress) -> u64 {
+        let fee_discount_tiers = self.fee_discount_tiers().get();
+        if fee_discount_tiers.is_empty() || self.blockchain().is_smart_contract(user) {
+            return 0;
+        }
+
+        let user_energy = self.get_energy_amount(user);
+        let mut discount_percent = 0;
+        for tier in fee_discount_tiers.iter() {
+            if user_energy < tier.min_energy {
+                break;
+            }
+
+            discount_percent = tier.discount_percent;
+        }
+
+        discount_percent
+    }
+
+    #[view(getFeeDiscountTiers)]
+    #[storage_mapper("fee_discount_tiers")]
+    fn fee_discount_tiers(&self) -> SingleValueMapper<ManagedVec<FeeDiscountTier<Self::Api>>>;
+}
@@ -25,6 +25,7 @@ pub static ERROR_INITIAL_LIQUIDITY_NOT_ADDED: &[u8] = b"Initial liquidity was no
 pub static ERROR_INITIAL_LIQUIDITY_ALREADY_ADDED: &[u8] = b"Initial liquidity was already added";
 
 pub static ERROR_NOT_AN_ESDT: &[u8] = b"Not a valid esdt id";
+pub static ERROR_NOT_AN_EGLD_PAIR: &[u8] = b"The pair has no EGLD side";
 pub static ERROR_SAME_TOKENS: &[u8] = b"Exchange tokens cannot be the same";
 pub static ERROR_POOL_TOKEN_IS_PLT: &[u8] = b"Token ID cannot be the same as LP token ID";
 pub static ERROR_BAD_PERCENTS: &[u8] = b"Bad percents";
@@ -44,11 +45,62 @@ pub static ERROR_LP_TOKEN_SAME_AS_POOL_TOKENS: &[u8] =
 
 pub static ERROR_SWAP_NOT_ENABLED: &[u8] = b"Swap is not enabled";
 pub static ERROR_SLIPPAGE_EXCEEDED: &[u8] = b"Slippage exceeded";
+pub static ERROR_DEADLINE_EXPIRED: &[u8] = b"Deadline expired";
+pub static ERROR_SWAP_OUTPUT_LOCKED: &[u8] = b"Swap outputs are currently locked";
 pub static ERROR_NOTHING_TO_DO_WITH_FEE_SLICE: &[u8] = b"Nothing to do with fee slice";
 
+pub static ERROR_FLASH_SWAP_CALLER_NOT_SC: &[u8] = b"Flash swaps can only be done by contracts";
+pub static ERROR_FLASH_SWAP_REENTRANCY: &[u8] =
+    b"Pair reserves cannot be changed during a flash swap";
+pub static ERROR_FLASH_SWAP_EGLD_PAIR: &[u8] = b"Flash swaps are not available on EGLD pairs";
+
 pub static ERROR_SAFE_PRICE_SAME_ROUNDS: &[u8] =
     b"The safe price can be computed only between different rounds";
 pub static ERROR_SAFE_PRICE_CURRENT_INDEX: &[u8] =
     b"The current safe price index is greater than the maximum number of observations";
+pub static ERROR_BAD_OBSERVATIONS_CAPACITY: &[u8] = b"Invalid price observations capacity";
 pub static ERROR_SAFE_PRICE_OBSERVATION_DOES_NOT_EXIST: &[u8] =
     b"The price observation does not exist";
+pub static ERROR_GEOMETRIC_SAFE_PRICE_NOT_AVAILABLE: &[u8] =
+    b"The geometric safe price is not available for the given rounds";
+pub static ERROR_PRICE_DEVIATION_TOO_HIGH: &[u8] =
+    b"The price deviates too much from the safe price";
+
+pub static ERROR_CURVE_CHANGE_NOT_ALLOWED: &[u8] =
+    b"The curve can only be changed before liquidity is added";
+pub static ERROR_NOT_STABLE_SWAP_CURVE: &[u8] = b"The pair does not use the StableSwap curve";
+pub static ERROR_NOT_CONSTANT_PRODUCT_CURVE: &[u8] =
+    b"The pair does not use the ConstantProduct curve";
+pub static ERROR_INVALID_AMP_FACTOR: &[u8] = b"Invalid amplification factor";
+pub static ERROR_AMP_FACTOR_RAMP_TOO_EARLY: &[u8] =
+    b"The amplification factor was changed too recently";
+pub static ERROR_INVALID_RAMP_TIMESTAMP: &[u8] = b"Invalid amplification factor ramp timestamp";
+pub static ERROR_AMP_FACTOR_CHANGE_TOO_BIG: &[u8] = b"Amplification factor change too big";
+pub static ERROR_STABLE_SWAP_NO_CONVERGENCE: &[u8] = b"StableSwap computation did not converge";
+pub static ERROR_STABLE_SWAP_INVALID_RESERVES: &[u8] = b"StableSwap reserves out of range";
+
+pub static ERROR_INVALID_FEE_DISCOUNT_TIERS: &[u8] = b"Invalid fee discount tiers";
+
+pub static ERROR_LIQUIDITY_MIGRATION_NOT_CONFIGURED: &[u8] =
+    b"Liquidity migration is not configured";
+
+pub static ERROR_HOOK_ALREADY_REGISTERED: &[u8] = b"Hook already registered";
+pub static ERROR_HOOK_NOT_REGISTERED: &[u8] = b"Hook not registered";
+pub static ERROR_INVALID_HOOK_ADDRESS: &[u8] = b"Invalid hook address";
+
+pub static ERROR_FEES_COLLECTOR_NOT_CONFIGURED: &[u8] = b"Fees collector not configured";
+pub static ERROR_BUYBACK_TOKEN_NOT_IN_PAIR: &[u8] =
+    b"Only pair tokens can be bought back for this destination";
+
+pub static ERROR_FEE_CHANGE_NOT_ANNOUNCED: &[u8] =
+    b"Fee changes have to be announced while swaps are enabled";
+pub static ERROR_NO_PENDING_FEE_CHANGE: &[u8] = b"No pending fee change";
+pub static ERROR_FEE_CHANGE_NOT_EFFECTIVE: &[u8] = b"The fee change is not effective yet";
+pub static ERROR_FEE_CHANGE_DELAY_TOO_SHORT: &[u8] = b"Fee change delay too short";
+
+pub static ERROR_INVALID_FEE_ROUTE: &[u8] = b"Invalid fee route";
+pub static ERROR_INVALID_FEE_ROUTE_CALLER: &[u8] = b"Invalid fee route caller";
+pub static ERROR_ALREADY_FEE_ROUTE_CALLER: &[u8] = b"Already a fee route caller";
+pub static ERROR_NOT_FEE_ROUTE_CALLER: &[u8] = b"Not a fee route caller";
+
+pub static ERROR_NO_FEE_SWAP_QUOTA_EXCEEDED: &[u8] = b"No fee swap quota exceeded";
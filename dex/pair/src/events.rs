@@ -1,7 +1,9 @@
 use crate::contexts::add_liquidity::AddLiquidityContext;
 use crate::contexts::base::StorageCache;
+use crate::contexts::flash_swap::FlashSwapContext;
 use crate::contexts::remove_liquidity::RemoveLiquidityContext;
 use crate::contexts::swap::SwapContext;
+use week_timekeeping::Week;
 
 multiversx_sc::imports!();
 multiversx_sc::derive_imports!();
@@ -9,9 +11,9 @@ multiversx_sc::derive_imports!();
 #[derive(TypeAbi, TopEncode)]
 pub struct SwapEvent<M: ManagedTypeApi> {
     caller: ManagedAddress<M>,
-    token_id_in: TokenIdentifier<M>,
+    token_id_in: EgldOrEsdtTokenIdentifier<M>,
     token_amount_in: BigUint<M>,
-    token_id_out: TokenIdentifier<M>,
+    token_id_out: EgldOrEsdtTokenIdentifier<M>,
     token_amount_out: BigUint<M>,
     fee_amount: BigUint<M>,
     token_in_reserve: BigUint<M>,
@@ -19,14 +21,15 @@ pub struct SwapEvent<M: ManagedTypeApi> {
     block: u64,
     epoch: u64,
     timestamp: u64,
+    fee_percent: u64,
 }
 
 #[derive(TypeAbi, TopEncode)]
 pub struct SwapNoFeeAndForwardEvent<M: ManagedTypeApi> {
     caller: ManagedAddress<M>,
-    token_id_in: TokenIdentifier<M>,
+    token_id_in: EgldOrEsdtTokenIdentifier<M>,
     token_amount_in: BigUint<M>,
-    token_id_out: TokenIdentifier<M>,
+    token_id_out: EgldOrEsdtTokenIdentifier<M>,
     token_amount_out: BigUint<M>,
     destination: ManagedAddress<M>,
     block: u64,
@@ -34,12 +37,28 @@ pub struct SwapNoFeeAndForwardEvent<M: ManagedTypeApi> {
     timestamp: u64,
 }
 
+#[derive(TypeAbi, TopEncode)]
+pub struct FlashSwapEvent<M: ManagedTypeApi> {
+    caller: ManagedAddress<M>,
+    token_id_out: EgldOrEsdtTokenIdentifier<M>,
+    token_amount_out: BigUint<M>,
+    first_token_amount_in: BigUint<M>,
+    second_token_amount_in: BigUint<M>,
+    first_token_fee_amount: BigUint<M>,
+    second_token_fee_amount: BigUint<M>,
+    first_token_reserves: BigUint<M>,
+    second_token_reserves: BigUint<M>,
+    block: u64,
+    epoch: u64,
+    timestamp: u64,
+}
+
 #[derive(TypeAbi, TopEncode)]
 pub struct AddLiquidityEvent<M: ManagedTypeApi> {
     caller: ManagedAddress<M>,
-    first_token_id: TokenIdentifier<M>,
+    first_token_id: EgldOrEsdtTokenIdentifier<M>,
     first_token_amount: BigUint<M>,
-    second_token_id: TokenIdentifier<M>,
+    second_token_id: EgldOrEsdtTokenIdentifier<M>,
     second_token_amount: BigUint<M>,
     lp_token_id: TokenIdentifier<M>,
     lp_token_amount: BigUint<M>,
@@ -54,9 +73,9 @@ pub struct AddLiquidityEvent<M: ManagedTypeApi> {
 #[derive(TypeAbi, TopEncode)]
 pub struct RemoveLiquidityEvent<M: ManagedTypeApi> {
     caller: ManagedAddress<M>,
-    first_token_id: TokenIdentifier<M>,
+    first_token_id: EgldOrEsdtTokenIdentifier<M>,
     first_token_amount: BigUint<M>,
-    second_token_id: TokenIdentifier<M>,
+    second_token_id: EgldOrEsdtTokenIdentifier<M>,
     second_token_amount: BigUint<M>,
     lp_token_id: TokenIdentifier<M>,
     lp_token_amount: BigUint<M>,
@@ -68,6 +87,28 @@ pub struct RemoveLiquidityEvent<M: ManagedTypeApi> {
     timestamp: u64,
 }
 
+#[derive(TypeAbi, TopEncode)]
+pub struct NoFeeSwapQuotaExhaustedEvent<M: ManagedTypeApi> {
+    caller: ManagedAddress<M>,
+    token_id: TokenIdentifier<M>,
+    quota: BigUint<M>,
+    week: Week,
+    block: u64,
+    epoch: u64,
+    timestamp: u64,
+}
+
+#[derive(TypeAbi, TopEncode)]
+pub struct NoFeeSwapQuotaExceededEvent<M: ManagedTypeApi> {
+    pair_address: ManagedAddress<M>,
+    token_id: TokenIdentifier<M>,
+    amount: BigUint<M>,
+    remaining_quota: BigUint<M>,
+    block: u64,
+    epoch: u64,
+    timestamp: u64,
+}
+
 #[multiversx_sc::module]
 pub trait EventsModule:
     crate::config::ConfigModule
@@ -99,6 +140,7 @@ pub trait EventsModule:
                 block: self.blockchain().get_block_nonce(),
                 epoch,
                 timestamp: self.blockchain().get_block_timestamp(),
+                fee_percent: context.fee_percent,
             },
         )
     }
@@ -128,6 +170,34 @@ pub trait EventsModule:
         )
     }
 
+    fn emit_flash_swap_event(
+        &self,
+        storage_cache: &StorageCache<Self>,
+        context: FlashSwapContext<Self::Api>,
+    ) {
+        let epoch = self.blockchain().get_block_epoch();
+        let caller = self.blockchain().get_caller();
+        self.flash_swap_event(
+            &context.output_token_id.clone(),
+            &caller,
+            epoch,
+            &FlashSwapEvent {
+                caller: caller.clone(),
+                token_id_out: context.output_token_id,
+                token_amount_out: context.output_token_amount,
+                first_token_amount_in: context.first_token_amount_in,
+                second_token_amount_in: context.second_token_amount_in,
+                first_token_fee_amount: context.first_token_fee_amount,
+                second_token_fee_amount: context.second_token_fee_amount,
+                first_token_reserves: storage_cache.first_token_reserve.clone(),
+                second_token_reserves: storage_cache.second_token_reserve.clone(),
+                block: self.blockchain().get_block_nonce(),
+                epoch,
+                timestamp: self.blockchain().get_block_timestamp(),
+            },
+        )
+    }
+
     fn emit_add_liquidity_event(
         &self,
         storage_cache: &StorageCache<Self>,
@@ -188,11 +258,59 @@ pub trait EventsModule:
         )
     }
 
+    fn emit_no_fee_swap_quota_exhausted_event(
+        &self,
+        caller: &ManagedAddress,
+        token_id: &TokenIdentifier,
+        quota: BigUint,
+        week: Week,
+    ) {
+        let epoch = self.blockchain().get_block_epoch();
+        self.no_fee_swap_quota_exhausted_event(
+            token_id,
+            caller,
+            epoch,
+            &NoFeeSwapQuotaExhaustedEvent {
+                caller: caller.clone(),
+                token_id: token_id.clone(),
+                quota,
+                week,
+                block: self.blockchain().get_block_nonce(),
+                epoch,
+                timestamp: self.blockchain().get_block_timestamp(),
+            },
+        )
+    }
+
+    fn emit_no_fee_swap_quota_exceeded_event(
+        &self,
+        pair_address: &ManagedAddress,
+        token_id: &TokenIdentifier,
+        amount: &BigUint,
+        remaining_quota: BigUint,
+    ) {
+        let epoch = self.blockchain().get_block_epoch();
+        self.no_fee_swap_quota_exceeded_event(
+            token_id,
+            pair_address,
+            epoch,
+            &NoFeeSwapQuotaExceededEvent {
+                pair_address: pair_address.clone(),
+                token_id: token_id.clone(),
+                amount: amount.clone(),
+                remaining_quota,
+                block: self.blockchain().get_block_nonce(),
+                epoch,
+                timestamp: self.blockchain().get_block_timestamp(),
+            },
+        )
+    }
+
     #[event("swap")]
     fn swap_event(
         &self,
-        #[indexed] token_in: &TokenIdentifier,
-        #[indexed] token_out: &TokenIdentifier,
+        #[indexed] token_in: &EgldOrEsdtTokenIdentifier,
+        #[indexed] token_out: &EgldOrEsdtTokenIdentifier,
         #[indexed] caller: &ManagedAddress,
         #[indexed] epoch: u64,
         swap_event: &SwapEvent<Self::Api>,
@@ -201,17 +319,26 @@ pub trait EventsModule:
     #[event("swap_no_fee_and_forward")]
     fn swap_no_fee_and_forward_event(
         &self,
-        #[indexed] token_id_out: &TokenIdentifier,
+        #[indexed] token_id_out: &EgldOrEsdtTokenIdentifier,
         #[indexed] caller: &ManagedAddress,
         #[indexed] epoch: u64,
         swap_no_fee_and_forward_event: &SwapNoFeeAndForwardEvent<Self::Api>,
     );
 
+    #[event("flash_swap")]
+    fn flash_swap_event(
+        &self,
+        #[indexed] token_id_out: &EgldOrEsdtTokenIdentifier,
+        #[indexed] caller: &ManagedAddress,
+        #[indexed] epoch: u64,
+        flash_swap_event: &FlashSwapEvent<Self::Api>,
+    );
+
     #[event("add_liquidity")]
     fn add_liquidity_event(
         &self,
-        #[indexed] first_token: &TokenIdentifier,
-        #[indexed] second_token: &TokenIdentifier,
+        #[indexed] first_token: &EgldOrEsdtTokenIdentifier,
+        #[indexed] second_token: &EgldOrEsdtTokenIdentifier,
         #[indexed] caller: &ManagedAddress,
         #[indexed] epoch: u64,
         add_liquidity_event: &AddLiquidityEvent<Self::Api>,
@@ -220,10 +347,28 @@ pub trait EventsModule:
     #[event("remove_liquidity")]
     fn remove_liquidity_event(
         &self,
-        #[indexed] first_token: &TokenIdentifier,
-        #[indexed] second_token: &TokenIdentifier,
+        #[indexed] first_token: &EgldOrEsdtTokenIdentifier,
+        #[indexed] second_token: &EgldOrEsdtTokenIdentifier,
         #[indexed] caller: &ManagedAddress,
         #[indexed] epoch: u64,
         remove_liquidity_event: &RemoveLiquidityEvent<Self::Api>,
     );
+
+    #[event("no_fee_swap_quota_exhausted")]
+    fn no_fee_swap_quota_exhausted_event(
+        &self,
+        #[indexed] token_id: &TokenIdentifier,
+        #[indexed] caller: &ManagedAddress,
+        #[indexed] epoch: u64,
+        no_fee_swap_quota_exhausted_event: &NoFeeSwapQuotaExhaustedEvent<Self::Api>,
+    );
+
+    #[event("no_fee_swap_quota_exceeded")]
+    fn no_fee_swap_quota_exceeded_event(
+        &self,
+        #[indexed] token_id: &TokenIdentifier,
+        #[indexed] pair_address: &ManagedAddress,
+        #[indexed] epoch: u64,
+        no_fee_swap_quota_exceeded_event: &NoFeeSwapQuotaExceededEvent<Self::Api>,
+    );
 }
@@ -4,13 +4,25 @@ multiversx_sc::derive_imports!();
 use super::amm;
 use super::config;
 use super::errors::*;
+use super::events;
 use super::liquidity_pool;
+use super::stable_swap;
+use super::trading_stats;
 use crate::config::MAX_PERCENTAGE;
 use crate::contexts::base::StorageCache;
 use crate::contexts::base::SwapTokensOrder;
+use crate::no_fee_swap_quota::NoFeeSwapQuotaUsage;
 
 use common_structs::TokenPair;
 use fees_collector::fees_accumulation::ProxyTrait as _;
+use pausable::State;
+use week_timekeeping::{Epoch, Week, EPOCHS_IN_WEEK};
+
+pub const MAX_FEE_ROUTE_HOPS: usize = 4;
+static PAIR_STATE_STORAGE_KEY: &[u8] = b"state";
+static PAIR_NO_FEE_SWAP_QUOTA_STORAGE_KEY: &[u8] = b"no_fee_swap_quota";
+static PAIR_NO_FEE_SWAP_QUOTA_USAGE_STORAGE_KEY: &[u8] = b"no_fee_swap_quota_usage";
+static PAIR_FIRST_WEEK_START_EPOCH_STORAGE_KEY: &[u8] = b"firstWeekStartEpoch";
 
 mod self_proxy {
     multiversx_sc::imports!();
@@ -20,14 +32,46 @@ mod self_proxy {
         #[payable("*")]
         #[endpoint(swapNoFeeAndForward)]
         fn swap_no_fee(&self, token_out: TokenIdentifier, destination_address: ManagedAddress);
+
+        #[payable("*")]
+        #[endpoint(swapNoFeeAndReturn)]
+        fn swap_no_fee_and_return(&self, token_out: TokenIdentifier) -> EsdtTokenPayment;
     }
 }
 
+/// Where the tokens bought back by `removeLiquidityAndBuyBackAndBurnToken` are sent.
+#[derive(TypeAbi, TopEncode, TopDecode, NestedEncode, NestedDecode, Clone, PartialEq, Debug)]
+pub enum BuybackDestination<M: ManagedTypeApi> {
+    Burn,
+    Treasury(ManagedAddress<M>),
+    FeesCollector,
+}
+
+#[derive(
+    ManagedVecItem,
+    TopEncode,
+    TopDecode,
+    NestedEncode,
+    NestedDecode,
+    TypeAbi,
+    Clone,
+    PartialEq,
+    Debug,
+)]
+pub struct FeeRouteHop<M: ManagedTypeApi> {
+    pub pair_address: ManagedAddress<M>,
+    pub token_out: TokenIdentifier<M>,
+}
+
 #[multiversx_sc::module]
 pub trait FeeModule:
     config::ConfigModule
     + liquidity_pool::LiquidityPoolModule
     + amm::AmmModule
+    + stable_swap::StableSwapModule
+    + trading_stats::TradingStatsModule
+    + week_timekeeping::WeekTimekeepingModule
+    + events::EventsModule
     + token_send::TokenSendModule
     + permissions_module::PermissionsModule
     + pausable::PausableModule
@@ -51,6 +95,28 @@ pub trait FeeModule:
         require!(is_removed, ERROR_NOT_WHITELISTED);
     }
 
+    /// Allows a whitelisted pair to receive the output of its no fee swaps back,
+    /// for the intermediate hops of its fee routes. Other whitelisted addresses can only
+    /// swap with no fee through `swapNoFeeAndForward`, which burns the output.
+    #[endpoint(addFeeRouteCaller)]
+    fn add_fee_route_caller(&self, pair_address: ManagedAddress) {
+        self.require_caller_has_owner_permissions();
+        require!(
+            self.blockchain().is_smart_contract(&pair_address),
+            ERROR_INVALID_FEE_ROUTE_CALLER
+        );
+
+        let is_new = self.fee_route_callers().insert(pair_address);
+        require!(is_new, ERROR_ALREADY_FEE_ROUTE_CALLER);
+    }
+
+    #[endpoint(removeFeeRouteCaller)]
+    fn remove_fee_route_caller(&self, pair_address: ManagedAddress) {
+        self.require_caller_has_owner_permissions();
+        let is_removed = self.fee_route_callers().remove(&pair_address);
+        require!(is_removed, ERROR_NOT_FEE_ROUTE_CALLER);
+    }
+
     #[endpoint(addTrustedSwapPair)]
     fn add_trusted_swap_pair(
         &self,
@@ -97,6 +163,61 @@ pub trait FeeModule:
         }
     }
 
+    /// Adds a route through which the fees in `token_in` are converted into the last token of
+    /// the route, when there is no trusted swap pair between the two. Routes between the same
+    /// tokens are tried in the order they were added, skipping the ones with a paused pair.
+    /// This pair has to be whitelisted in all the pairs of the route,
+    /// and registered as a fee route caller in all of them but the last.
+    #[endpoint(addFeeRoute)]
+    fn add_fee_route(
+        &self,
+        token_in: TokenIdentifier,
+        hops: MultiValueEncoded<MultiValue2<ManagedAddress, TokenIdentifier>>,
+    ) {
+        self.require_caller_has_owner_permissions();
+        require!(
+            !hops.is_empty() && hops.len() <= MAX_FEE_ROUTE_HOPS,
+            ERROR_INVALID_FEE_ROUTE
+        );
+
+        let mut route = ManagedVec::<Self::Api, FeeRouteHop<Self::Api>>::new();
+        let mut current_token = token_in.clone();
+        for hop in hops {
+            let (pair_address, token_out) = hop.into_tuple();
+            require!(
+                token_out != current_token && self.blockchain().is_smart_contract(&pair_address),
+                ERROR_INVALID_FEE_ROUTE
+            );
+
+            current_token = token_out.clone();
+            route.push(FeeRouteHop {
+                pair_address,
+                token_out,
+            });
+        }
+
+        self.fee_routes(&token_in, &current_token).push(&route);
+    }
+
+    #[endpoint(removeFeeRoutes)]
+    fn remove_fee_routes(&self, token_in: TokenIdentifier, token_out: TokenIdentifier) {
+        self.require_caller_has_owner_permissions();
+        self.fee_routes(&token_in, &token_out).clear();
+    }
+
+    #[view(getFeeRoutes)]
+    fn get_fee_routes(
+        &self,
+        token_in: TokenIdentifier,
+        token_out: TokenIdentifier,
+    ) -> MultiValueEncoded<ManagedVec<FeeRouteHop<Self::Api>>> {
+        let mut result = MultiValueEncoded::new();
+        for route in self.fee_routes(&token_in, &token_out).iter() {
+            result.push(route);
+        }
+        result
+    }
+
     /// `fees_collector_cut_percentage` of the special fees are sent to the fees_collector_address SC
     ///
     /// For example, if special fees is 5%, and fees_collector_cut_percentage is 10%,
@@ -122,17 +243,57 @@ pub trait FeeModule:
             .set(fees_collector_cut_percentage);
     }
 
+    /// Sets where the tokens bought back for `caller_address` are sent.
+    /// Callers without a configured destination have the tokens burned.
+    #[endpoint(setBuybackDestination)]
+    fn set_buyback_destination(
+        &self,
+        caller_address: ManagedAddress,
+        destination: BuybackDestination<Self::Api>,
+    ) {
+        self.require_caller_has_owner_permissions();
+        if let BuybackDestination::FeesCollector = destination {
+            require!(
+                !self.fees_collector_address().is_empty(),
+                ERROR_FEES_COLLECTOR_NOT_CONFIGURED
+            );
+        }
+
+        self.buyback_destination(&caller_address).set(destination);
+    }
+
+    #[view(getBuybackDestination)]
+    fn get_buyback_destination(
+        &self,
+        caller_address: ManagedAddress,
+    ) -> BuybackDestination<Self::Api> {
+        let mapper = self.buyback_destination(&caller_address);
+        if mapper.is_empty() {
+            return BuybackDestination::Burn;
+        }
+
+        mapper.get()
+    }
+
     fn send_fee(
         &self,
         storage_cache: &mut StorageCache<Self>,
         swap_tokens_order: SwapTokensOrder,
-        fee_token: &TokenIdentifier,
+        fee_token: &EgldOrEsdtTokenIdentifier,
         fee_amount: &BigUint,
     ) {
         if fee_amount == &0u64 {
             return;
         }
 
+        let (fee_token, fee_amount, swap_tokens_order) =
+            self.swap_egld_fee_to_esdt(storage_cache, swap_tokens_order, fee_token, fee_amount);
+        if fee_amount == 0u64 {
+            return;
+        }
+
+        let fee_token = &fee_token;
+        let fee_amount = &fee_amount;
         let fees_collector_configured = !self.fees_collector_address().is_empty();
         let remaining_fee = if fees_collector_configured {
             let fees_collector_cut_percentage = self.fees_collector_cut_percentage().get();
@@ -159,7 +320,7 @@ pub trait FeeModule:
         }
 
         for (fee_address, fee_token_requested) in self.destination_map().iter() {
-            self.send_fee_slice(
+            self.send_esdt_fee_slice(
                 storage_cache,
                 swap_tokens_order,
                 fee_token,
@@ -180,6 +341,35 @@ pub trait FeeModule:
     }
 
     fn send_fee_slice(
+        &self,
+        storage_cache: &mut StorageCache<Self>,
+        swap_tokens_order: SwapTokensOrder,
+        fee_token: &EgldOrEsdtTokenIdentifier,
+        fee_slice: &BigUint,
+        fee_address: &ManagedAddress,
+        requested_fee_token: &TokenIdentifier,
+    ) {
+        if fee_slice == &0u64 {
+            return;
+        }
+
+        let (fee_token, fee_slice, swap_tokens_order) =
+            self.swap_egld_fee_to_esdt(storage_cache, swap_tokens_order, fee_token, fee_slice);
+        if fee_slice == 0u64 {
+            return;
+        }
+
+        self.send_esdt_fee_slice(
+            storage_cache,
+            swap_tokens_order,
+            &fee_token,
+            &fee_slice,
+            fee_address,
+            requested_fee_token,
+        );
+    }
+
+    fn send_esdt_fee_slice(
         &self,
         storage_cache: &mut StorageCache<Self>,
         swap_tokens_order: SwapTokensOrder,
@@ -202,7 +392,7 @@ pub trait FeeModule:
             &storage_cache.second_token_id,
         );
         if can_resolve_locally {
-            let to_burn = self.swap_safe_no_fee(storage_cache, swap_tokens_order, fee_slice);
+            let to_burn = self.swap_internal_no_fee(storage_cache, swap_tokens_order, fee_slice);
             self.burn(requested_fee_token, &to_burn);
 
             return;
@@ -210,6 +400,12 @@ pub trait FeeModule:
 
         let can_extern_swap = self.can_extern_swap_directly(fee_token, requested_fee_token);
         if can_extern_swap {
+            if !self.has_extern_no_fee_swap_quota(fee_token, fee_slice, requested_fee_token) {
+                self.send_fee_over_quota(fee_token, fee_slice);
+
+                return;
+            }
+
             self.extern_swap_and_forward(fee_token, fee_slice, requested_fee_token, fee_address);
 
             return;
@@ -222,12 +418,17 @@ pub trait FeeModule:
             requested_fee_token,
         );
         if can_extern_swap_after_local {
-            let to_send = self.swap_safe_no_fee(storage_cache, swap_tokens_order, fee_slice);
-            let to_send_token = if fee_token == &storage_cache.first_token_id {
-                storage_cache.second_token_id.clone()
+            let to_send = self.swap_internal_no_fee(storage_cache, swap_tokens_order, fee_slice);
+            let to_send_token = if &storage_cache.first_token_id == fee_token {
+                storage_cache.second_token_id.clone().unwrap_esdt()
             } else {
-                storage_cache.first_token_id.clone()
+                storage_cache.first_token_id.clone().unwrap_esdt()
             };
+            if !self.has_extern_no_fee_swap_quota(&to_send_token, &to_send, requested_fee_token) {
+                self.send_fee_over_quota(&to_send_token, &to_send);
+
+                return;
+            }
 
             self.extern_swap_and_forward(
                 &to_send_token,
@@ -235,9 +436,174 @@ pub trait FeeModule:
                 requested_fee_token,
                 fee_address,
             );
+
+            return;
+        }
+
+        if let Some(route) = self.find_active_fee_route(fee_token, requested_fee_token) {
+            self.swap_through_fee_route(fee_token, fee_slice, &route, fee_address);
+
+            return;
+        }
+
+        // the EGLD side of the pair cannot be routed further
+        let other_token = if &storage_cache.first_token_id == fee_token {
+            storage_cache.second_token_id.clone()
         } else {
-            sc_panic!(ERROR_NOTHING_TO_DO_WITH_FEE_SLICE);
+            storage_cache.first_token_id.clone()
+        };
+        if let Some(other_token) = other_token.into_esdt_option() {
+            if let Some(route) = self.find_active_fee_route(&other_token, requested_fee_token) {
+                let to_send =
+                    self.swap_internal_no_fee(storage_cache, swap_tokens_order, fee_slice);
+                self.swap_through_fee_route(&other_token, &to_send, &route, fee_address);
+
+                return;
+            }
         }
+
+        sc_panic!(ERROR_NOTHING_TO_DO_WITH_FEE_SLICE);
+    }
+
+    /// The fees collector, the trusted swap pairs and the fee routes only handle ESDTs,
+    /// so a fee paid in EGLD is first swapped locally into the other token of the pair.
+    /// An EGLD fee too small to be swapped is left in the reserves.
+    fn swap_egld_fee_to_esdt(
+        &self,
+        storage_cache: &mut StorageCache<Self>,
+        swap_tokens_order: SwapTokensOrder,
+        fee_token: &EgldOrEsdtTokenIdentifier,
+        fee_amount: &BigUint,
+    ) -> (TokenIdentifier, BigUint, SwapTokensOrder) {
+        if let Some(fee_token) = fee_token.as_esdt_option() {
+            return (
+                fee_token.clone_value(),
+                fee_amount.clone(),
+                swap_tokens_order,
+            );
+        }
+
+        let (other_token_id, reversed_swap_tokens_order) = match swap_tokens_order {
+            SwapTokensOrder::PoolOrder => (
+                storage_cache.second_token_id.clone().unwrap_esdt(),
+                SwapTokensOrder::ReverseOrder,
+            ),
+            SwapTokensOrder::ReverseOrder => (
+                storage_cache.first_token_id.clone().unwrap_esdt(),
+                SwapTokensOrder::PoolOrder,
+            ),
+        };
+        let amount_out = self.get_amount_out_no_fee(
+            fee_amount,
+            storage_cache.get_reserve_in(swap_tokens_order),
+            storage_cache.get_reserve_out(swap_tokens_order),
+        );
+        if amount_out == 0u64 {
+            *storage_cache.get_mut_reserve_in(swap_tokens_order) += fee_amount;
+
+            return (other_token_id, BigUint::zero(), reversed_swap_tokens_order);
+        }
+
+        let amount_out = self.swap_internal_no_fee(storage_cache, swap_tokens_order, fee_amount);
+
+        (other_token_id, amount_out, reversed_swap_tokens_order)
+    }
+
+    /// A fee free swap done by the pair itself, recorded in the trading stats like any other swap.
+    fn swap_internal_no_fee(
+        &self,
+        storage_cache: &mut StorageCache<Self>,
+        swap_tokens_order: SwapTokensOrder,
+        amount_in: &BigUint,
+    ) -> BigUint {
+        let amount_out = self.swap_safe_no_fee(storage_cache, swap_tokens_order, amount_in);
+
+        let (token_in, token_out) = match swap_tokens_order {
+            SwapTokensOrder::PoolOrder => (
+                &storage_cache.first_token_id,
+                &storage_cache.second_token_id,
+            ),
+            SwapTokensOrder::ReverseOrder => (
+                &storage_cache.second_token_id,
+                &storage_cache.first_token_id,
+            ),
+        };
+        self.record_input_volume(token_in, amount_in, &BigUint::zero(), &BigUint::zero());
+        self.record_output_volume(token_out, &amount_out);
+
+        amount_out
+    }
+
+    fn find_active_fee_route(
+        &self,
+        token_in: &TokenIdentifier,
+        token_out: &TokenIdentifier,
+    ) -> Option<ManagedVec<FeeRouteHop<Self::Api>>> {
+        self.fee_routes(token_in, token_out).iter().find(|route| {
+            route
+                .iter()
+                .all(|hop| self.is_pair_active(&hop.pair_address))
+        })
+    }
+
+    /// The intermediate hops send the tokens back to this pair,
+    /// while the last one burns them, like a single trusted swap pair does.
+    /// When a hop has not enough no fee swap quota left, the tokens held are sent to the
+    /// fees collector instead.
+    fn swap_through_fee_route(
+        &self,
+        token_in: &TokenIdentifier,
+        amount_in: &BigUint,
+        route: &ManagedVec<FeeRouteHop<Self::Api>>,
+        destination_address: &ManagedAddress,
+    ) {
+        let mut current_payment = EsdtTokenPayment::new(token_in.clone(), 0, amount_in.clone());
+        let last_hop_index = route.len() - 1;
+        for (hop_index, hop) in route.iter().enumerate() {
+            if current_payment.amount == 0 {
+                return;
+            }
+
+            if !self.has_no_fee_swap_quota(
+                &hop.pair_address,
+                &current_payment.token_identifier,
+                &current_payment.amount,
+            ) {
+                self.send_fee_over_quota(
+                    &current_payment.token_identifier,
+                    &current_payment.amount,
+                );
+
+                return;
+            }
+
+            if hop_index == last_hop_index {
+                let _: IgnoreValue = self
+                    .pair_proxy()
+                    .contract(hop.pair_address)
+                    .swap_no_fee(hop.token_out, destination_address.clone())
+                    .with_esdt_transfer(current_payment)
+                    .execute_on_dest_context();
+
+                return;
+            }
+
+            current_payment = self
+                .pair_proxy()
+                .contract(hop.pair_address)
+                .swap_no_fee_and_return(hop.token_out)
+                .with_esdt_transfer(current_payment)
+                .execute_on_dest_context();
+        }
+    }
+
+    fn is_pair_active(&self, pair_address: &ManagedAddress) -> bool {
+        let state: State = self.storage_raw().read_from_address(
+            pair_address,
+            ManagedBuffer::new_from_bytes(PAIR_STATE_STORAGE_KEY),
+        );
+
+        state == State::Active
     }
 
     #[inline]
@@ -253,11 +619,11 @@ pub trait FeeModule:
         &self,
         fee_token: &TokenIdentifier,
         requested_fee_token: &TokenIdentifier,
-        pool_first_token_id: &TokenIdentifier,
-        pool_second_token_id: &TokenIdentifier,
+        pool_first_token_id: &EgldOrEsdtTokenIdentifier,
+        pool_second_token_id: &EgldOrEsdtTokenIdentifier,
     ) -> bool {
-        (requested_fee_token == pool_first_token_id && fee_token == pool_second_token_id)
-            || (requested_fee_token == pool_second_token_id && fee_token == pool_first_token_id)
+        (pool_first_token_id == requested_fee_token && pool_second_token_id == fee_token)
+            || (pool_second_token_id == requested_fee_token && pool_first_token_id == fee_token)
     }
 
     fn can_extern_swap_directly(
@@ -266,24 +632,32 @@ pub trait FeeModule:
         requested_fee_token: &TokenIdentifier,
     ) -> bool {
         let pair_address = self.get_extern_swap_pair_address(fee_token, requested_fee_token);
-        !pair_address.is_zero()
+        !pair_address.is_zero() && self.is_pair_active(&pair_address)
     }
 
+    /// The EGLD side of the pair cannot be swapped externally.
     fn can_extern_swap_after_local_swap(
         &self,
-        first_token: &TokenIdentifier,
-        second_token: &TokenIdentifier,
+        first_token: &EgldOrEsdtTokenIdentifier,
+        second_token: &EgldOrEsdtTokenIdentifier,
         fee_token: &TokenIdentifier,
         requested_fee_token: &TokenIdentifier,
     ) -> bool {
-        if fee_token == first_token {
-            let pair_address = self.get_extern_swap_pair_address(second_token, requested_fee_token);
-            !pair_address.is_zero()
-        } else if fee_token == second_token {
-            let pair_address = self.get_extern_swap_pair_address(first_token, requested_fee_token);
-            !pair_address.is_zero()
+        let other_token = if first_token == fee_token {
+            second_token
+        } else if second_token == fee_token {
+            first_token
         } else {
-            false
+            return false;
+        };
+
+        match other_token.as_esdt_option() {
+            Some(other_token) => {
+                let pair_address =
+                    self.get_extern_swap_pair_address(&other_token, requested_fee_token);
+                !pair_address.is_zero() && self.is_pair_active(&pair_address)
+            }
+            None => false,
         }
     }
 
@@ -304,6 +678,129 @@ pub trait FeeModule:
             .execute_on_dest_context();
     }
 
+    /// The trusted swap pair fails the whole swap when its no fee swap quota for this pair
+    /// is exceeded, so the quota is checked beforehand.
+    fn has_extern_no_fee_swap_quota(
+        &self,
+        available_token: &TokenIdentifier,
+        available_amount: &BigUint,
+        requested_token: &TokenIdentifier,
+    ) -> bool {
+        let pair_address = self.get_extern_swap_pair_address(available_token, requested_token);
+        self.has_no_fee_swap_quota(&pair_address, available_token, available_amount)
+    }
+
+    /// Emits the quota exceeded event when the remaining quota is not enough,
+    /// as the tokens are then kept by this pair instead of being swapped.
+    fn has_no_fee_swap_quota(
+        &self,
+        pair_address: &ManagedAddress,
+        token_id: &TokenIdentifier,
+        amount: &BigUint,
+    ) -> bool {
+        match self.get_extern_remaining_no_fee_swap_quota(pair_address, token_id) {
+            Some(remaining_quota) => {
+                if amount <= &remaining_quota {
+                    return true;
+                }
+
+                self.emit_no_fee_swap_quota_exceeded_event(
+                    pair_address,
+                    token_id,
+                    amount,
+                    remaining_quota,
+                );
+                false
+            }
+            None => true,
+        }
+    }
+
+    /// Read from the storage of the other pair, the same way as its
+    /// `getRemainingNoFeeSwapQuota` view, so that pairs deployed before the quotas
+    /// are not limited. Returns nothing if this pair is not limited for the token.
+    fn get_extern_remaining_no_fee_swap_quota(
+        &self,
+        pair_address: &ManagedAddress,
+        token_id: &TokenIdentifier,
+    ) -> Option<BigUint> {
+        let quota_buffer: ManagedBuffer = self.storage_raw().read_from_address(
+            pair_address,
+            self.build_no_fee_swap_quota_key(PAIR_NO_FEE_SWAP_QUOTA_STORAGE_KEY, token_id),
+        );
+        if quota_buffer.is_empty() {
+            return None;
+        }
+
+        let quota = BigUint::from_bytes_be_buffer(&quota_buffer);
+        let used_amount = self.get_extern_used_no_fee_swap_quota(pair_address, token_id);
+        if used_amount >= quota {
+            return Some(BigUint::zero());
+        }
+
+        Some(quota - used_amount)
+    }
+
+    fn get_extern_used_no_fee_swap_quota(
+        &self,
+        pair_address: &ManagedAddress,
+        token_id: &TokenIdentifier,
+    ) -> BigUint {
+        let usage_buffer: ManagedBuffer = self.storage_raw().read_from_address(
+            pair_address,
+            self.build_no_fee_swap_quota_key(PAIR_NO_FEE_SWAP_QUOTA_USAGE_STORAGE_KEY, token_id),
+        );
+        if usage_buffer.is_empty() {
+            return BigUint::zero();
+        }
+
+        let usage: NoFeeSwapQuotaUsage<Self::Api> = self
+            .serializer()
+            .top_decode_from_managed_buffer(&usage_buffer);
+        if usage.week != self.get_extern_current_week(pair_address) {
+            return BigUint::zero();
+        }
+
+        usage.amount
+    }
+
+    fn build_no_fee_swap_quota_key(
+        &self,
+        storage_key: &[u8],
+        token_id: &TokenIdentifier,
+    ) -> ManagedBuffer {
+        let mut key_buffer = ManagedBuffer::new_from_bytes(storage_key);
+        key_buffer.append(self.blockchain().get_sc_address().as_managed_buffer());
+        let token_id_buffer = token_id.as_managed_buffer();
+        key_buffer.append_u32_be(token_id_buffer.len() as u32);
+        key_buffer.append(token_id_buffer);
+
+        key_buffer
+    }
+
+    /// The weeks of the other pair start from its own first week start epoch.
+    fn get_extern_current_week(&self, pair_address: &ManagedAddress) -> Week {
+        let first_week_start_epoch: Epoch = self.storage_raw().read_from_address(
+            pair_address,
+            ManagedBuffer::new_from_bytes(PAIR_FIRST_WEEK_START_EPOCH_STORAGE_KEY),
+        );
+        let current_epoch = self.blockchain().get_block_epoch();
+        let zero_based_week = current_epoch.saturating_sub(first_week_start_epoch) / EPOCHS_IN_WEEK;
+
+        zero_based_week as Week + 1
+    }
+
+    /// A fee that cannot be swapped for exceeding a no fee swap quota is sent to the fees
+    /// collector. Without a fees collector, the swap that generated the fee fails.
+    fn send_fee_over_quota(&self, token_id: &TokenIdentifier, amount: &BigUint) {
+        require!(
+            !self.fees_collector_address().is_empty(),
+            ERROR_NO_FEE_SWAP_QUOTA_EXCEEDED
+        );
+
+        self.send_fees_collector_cut(token_id.clone(), amount.clone());
+    }
+
     #[inline]
     fn burn(&self, token: &TokenIdentifier, amount: &BigUint) {
         if amount > &0 {
@@ -419,4 +916,21 @@ pub trait FeeModule:
 
     #[storage_mapper("whitelist")]
     fn whitelist(&self) -> SetMapper<ManagedAddress>;
+
+    #[view(getFeeRouteCallers)]
+    #[storage_mapper("fee_route_callers")]
+    fn fee_route_callers(&self) -> SetMapper<ManagedAddress>;
+
+    #[storage_mapper("fee_routes")]
+    fn fee_routes(
+        &self,
+        token_in: &TokenIdentifier,
+        token_out: &TokenIdentifier,
+    ) -> VecMapper<ManagedVec<FeeRouteHop<Self::Api>>>;
+
+    #[storage_mapper("buyback_destination")]
+    fn buyback_destination(
+        &self,
+        caller_address: &ManagedAddress,
+    ) -> SingleValueMapper<BuybackDestination<Self::Api>>;
 }
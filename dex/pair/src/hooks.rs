@@ -0,0 +1,223 @@
+multiversx_sc::imports!();
+multiversx_sc::derive_imports!();
+
+use crate::config;
+use crate::contexts::add_liquidity::AddLiquidityContext;
+use crate::contexts::swap::SwapContext;
+use crate::errors::*;
+
+#[derive(
+    TypeAbi, TopEncode, TopDecode, NestedEncode, NestedDecode, Clone, Copy, PartialEq, Debug,
+)]
+pub enum HookType {
+    BeforeSwap,
+    AfterSwap,
+    BeforeAddLiquidity,
+    AfterAddLiquidity,
+    BeforeRemoveLiquidity,
+    AfterRemoveLiquidity,
+}
+
+/// Before a swap, the token amounts are the ones requested by the caller:
+/// the payment and the minimum (fixed input) or exact (fixed output) output.
+/// When the input is only known after the reserves change, as for flash swaps
+/// and `removeLiquidityToSingleToken`, the input amount is zero before the swap.
+/// After a swap, they are the amounts actually swapped.
+#[derive(TypeAbi, TopEncode, TopDecode, NestedEncode, NestedDecode, Clone)]
+pub struct SwapHookData<M: ManagedTypeApi> {
+    pub caller: ManagedAddress<M>,
+    pub input_token_id: EgldOrEsdtTokenIdentifier<M>,
+    pub input_token_amount: BigUint<M>,
+    pub output_token_id: EgldOrEsdtTokenIdentifier<M>,
+    pub output_token_amount: BigUint<M>,
+    pub fee_percent: u64,
+}
+
+/// Before adding liquidity, the token amounts are the payments and `lp_token_amount` is zero.
+/// Before removing liquidity, the token amounts are the minimums requested by the caller.
+/// After both actions, they are the amounts actually added or removed.
+#[derive(TypeAbi, TopEncode, TopDecode, NestedEncode, NestedDecode, Clone)]
+pub struct LiquidityHookData<M: ManagedTypeApi> {
+    pub caller: ManagedAddress<M>,
+    pub first_token_amount: BigUint<M>,
+    pub second_token_amount: BigUint<M>,
+    pub lp_token_amount: BigUint<M>,
+}
+
+pub mod hook_proxy {
+    multiversx_sc::imports!();
+    use super::{LiquidityHookData, SwapHookData};
+
+    /// The interface a hook contract has to implement for the hook types it is registered for.
+    /// A hook rejects the action by failing.
+    #[multiversx_sc::proxy]
+    pub trait PairHook {
+        /// Can return a fee percent that replaces the one of the swap,
+        /// clamped between the special and the total fee percents.
+        #[endpoint(beforeSwap)]
+        fn before_swap(&self, data: SwapHookData<Self::Api>) -> OptionalValue<u64>;
+
+        #[endpoint(afterSwap)]
+        fn after_swap(&self, data: SwapHookData<Self::Api>);
+
+        #[endpoint(beforeAddLiquidity)]
+        fn before_add_liquidity(&self, data: LiquidityHookData<Self::Api>);
+
+        #[endpoint(afterAddLiquidity)]
+        fn after_add_liquidity(&self, data: LiquidityHookData<Self::Api>);
+
+        #[endpoint(beforeRemoveLiquidity)]
+        fn before_remove_liquidity(&self, data: LiquidityHookData<Self::Api>);
+
+        #[endpoint(afterRemoveLiquidity)]
+        fn after_remove_liquidity(&self, data: LiquidityHookData<Self::Api>);
+    }
+}
+
+#[multiversx_sc::module]
+pub trait HooksModule:
+    config::ConfigModule
+    + token_send::TokenSendModule
+    + permissions_module::PermissionsModule
+    + pausable::PausableModule
+{
+    /// Registers a contract that is called synchronously on every action of the given type.
+    /// Hooks of the same type are called in the order they were added.
+    #[endpoint(addHook)]
+    fn add_hook(&self, hook_type: HookType, hook_address: ManagedAddress) {
+        self.require_caller_has_owner_permissions();
+        require!(
+            self.blockchain().is_smart_contract(&hook_address),
+            ERROR_INVALID_HOOK_ADDRESS
+        );
+
+        let mut hooks_mapper = self.hooks(hook_type);
+        require!(
+            !hooks_mapper.iter().any(|address| address == hook_address),
+            ERROR_HOOK_ALREADY_REGISTERED
+        );
+
+        hooks_mapper.push(&hook_address);
+    }
+
+    #[endpoint(removeHook)]
+    fn remove_hook(&self, hook_type: HookType, hook_address: ManagedAddress) {
+        self.require_caller_has_owner_permissions();
+
+        let mut hooks_mapper = self.hooks(hook_type);
+        let opt_index = hooks_mapper
+            .iter()
+            .position(|address| address == hook_address);
+        require!(opt_index.is_some(), ERROR_HOOK_NOT_REGISTERED);
+
+        // the remaining hooks are shifted down, so they are still called in the order they were added
+        // VecMapper index starts at 1
+        let len = hooks_mapper.len();
+        for index in opt_index.unwrap() + 1..len {
+            let next_hook_address = hooks_mapper.get(index + 1);
+            hooks_mapper.set(index, &next_hook_address);
+        }
+        hooks_mapper.swap_remove(len);
+    }
+
+    #[view(getHooks)]
+    fn get_hooks(&self, hook_type: HookType) -> MultiValueEncoded<ManagedAddress> {
+        let mut hooks = MultiValueEncoded::new();
+        for hook_address in self.hooks(hook_type).iter() {
+            hooks.push(hook_address);
+        }
+
+        hooks
+    }
+
+    /// Returns the fee percent of the swap, as overridden by the hooks,
+    /// which cannot go above `max_fee_percent`.
+    fn call_before_swap_hooks(
+        &self,
+        mut hook_data: SwapHookData<Self::Api>,
+        max_fee_percent: u64,
+    ) -> u64 {
+        let special_fee_percent = self.special_fee_percent().get();
+        for hook_address in self.hooks(HookType::BeforeSwap).iter() {
+            let opt_fee_percent: OptionalValue<u64> = self
+                .hook_proxy(hook_address)
+                .before_swap(hook_data.clone())
+                .execute_on_dest_context();
+
+            if let OptionalValue::Some(fee_percent) = opt_fee_percent {
+                // the special fee has to be covered, and, as hooks are added instantly,
+                // they cannot charge more than the announced maximum swap fee
+                hook_data.fee_percent = core::cmp::max(
+                    core::cmp::min(fee_percent, max_fee_percent),
+                    special_fee_percent,
+                );
+            }
+        }
+
+        hook_data.fee_percent
+    }
+
+    fn call_after_swap_hooks(&self, hook_data: SwapHookData<Self::Api>) {
+        for hook_address in self.hooks(HookType::AfterSwap).iter() {
+            self.hook_proxy(hook_address)
+                .after_swap(hook_data.clone())
+                .execute_on_dest_context::<()>();
+        }
+    }
+
+    fn call_liquidity_hooks(&self, hook_type: HookType, hook_data: LiquidityHookData<Self::Api>) {
+        for hook_address in self.hooks(hook_type).iter() {
+            let mut proxy_instance = self.hook_proxy(hook_address);
+            let contract_call = match hook_type {
+                HookType::BeforeAddLiquidity => {
+                    proxy_instance.before_add_liquidity(hook_data.clone())
+                }
+                HookType::AfterAddLiquidity => {
+                    proxy_instance.after_add_liquidity(hook_data.clone())
+                }
+                HookType::BeforeRemoveLiquidity => {
+                    proxy_instance.before_remove_liquidity(hook_data.clone())
+                }
+                HookType::AfterRemoveLiquidity => {
+                    proxy_instance.after_remove_liquidity(hook_data.clone())
+                }
+                HookType::BeforeSwap | HookType::AfterSwap => sc_panic!(ERROR_INVALID_ARGS),
+            };
+            contract_call.execute_on_dest_context::<()>();
+        }
+    }
+
+    fn build_swap_hook_data(
+        &self,
+        caller: &ManagedAddress,
+        context: &SwapContext<Self::Api>,
+    ) -> SwapHookData<Self::Api> {
+        SwapHookData {
+            caller: caller.clone(),
+            input_token_id: context.input_token_id.clone(),
+            input_token_amount: context.final_input_amount.clone(),
+            output_token_id: context.output_token_id.clone(),
+            output_token_amount: context.final_output_amount.clone(),
+            fee_percent: context.fee_percent,
+        }
+    }
+
+    fn build_add_liquidity_hook_data(
+        &self,
+        caller: &ManagedAddress,
+        context: &AddLiquidityContext<Self::Api>,
+    ) -> LiquidityHookData<Self::Api> {
+        LiquidityHookData {
+            caller: caller.clone(),
+            first_token_amount: context.first_token_optimal_amount.clone(),
+            second_token_amount: context.second_token_optimal_amount.clone(),
+            lp_token_amount: context.liq_added.clone(),
+        }
+    }
+
+    #[proxy]
+    fn hook_proxy(&self, to: ManagedAddress) -> hook_proxy::Proxy<Self::Api>;
+
+    #[storage_mapper("hooks")]
+    fn hooks(&self, hook_type: HookType) -> VecMapper<ManagedAddress>;
+}
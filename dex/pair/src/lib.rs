@@ -4,16 +4,24 @@ multiversx_sc::imports!();
 multiversx_sc::derive_imports!();
 
 mod amm;
+pub mod circuit_breaker;
 pub mod config;
 mod contexts;
+pub mod dynamic_fee;
+pub mod energy_fee_discount;
 pub mod errors;
 mod events;
 pub mod fee;
+pub mod hooks;
 mod liquidity_pool;
 pub mod locking_wrapper;
+pub mod no_fee_swap_quota;
 pub mod pair_actions;
+pub mod protocol_fee;
 pub mod safe_price;
 pub mod safe_price_view;
+pub mod stable_swap;
+pub mod trading_stats;
 
 use crate::errors::*;
 
@@ -28,21 +36,35 @@ use permissions_module::Permissions;
 #[multiversx_sc::contract]
 pub trait Pair<ContractReader>:
     amm::AmmModule
+    + stable_swap::StableSwapModule
     + fee::FeeModule
+    + protocol_fee::ProtocolFeeModule
     + liquidity_pool::LiquidityPoolModule
     + config::ConfigModule
     + token_send::TokenSendModule
     + events::EventsModule
     + safe_price::SafePriceModule
     + safe_price_view::SafePriceViewModule
+    + circuit_breaker::CircuitBreakerModule
+    + dynamic_fee::DynamicFeeModule
+    + energy_fee_discount::EnergyFeeDiscountModule
+    + energy_query::EnergyQueryModule
+    + trading_stats::TradingStatsModule
+    + no_fee_swap_quota::NoFeeSwapQuotaModule
+    + hooks::HooksModule
+    + week_timekeeping::WeekTimekeepingModule
     + contexts::output_builder::OutputBuilderModule
     + locking_wrapper::LockingWrapperModule
     + permissions_module::PermissionsModule
     + pausable::PausableModule
+    + pair_actions::egld_deposit::EgldDepositModule
     + pair_actions::initial_liq::InitialLiquidityModule
     + pair_actions::add_liq::AddLiquidityModule
     + pair_actions::remove_liq::RemoveLiquidityModule
     + pair_actions::swap::SwapModule
+    + pair_actions::flash_swap::FlashSwapModule
+    + pair_actions::single_token_liq::SingleTokenLiquidityModule
+    + pair_actions::migration::MigrationModule
     + pair_actions::views::ViewsModule
     + pair_actions::common_methods::CommonMethodsModule
     + utils::UtilsModule
@@ -50,8 +72,8 @@ pub trait Pair<ContractReader>:
     #[init]
     fn init(
         &self,
-        first_token_id: TokenIdentifier,
-        second_token_id: TokenIdentifier,
+        first_token_id: EgldOrEsdtTokenIdentifier,
+        second_token_id: EgldOrEsdtTokenIdentifier,
         router_address: ManagedAddress,
         router_owner_address: ManagedAddress,
         total_fee_percent: u64,
@@ -59,11 +81,9 @@ pub trait Pair<ContractReader>:
         initial_liquidity_adder: ManagedAddress,
         admins: MultiValueEncoded<ManagedAddress>,
     ) {
-        require!(first_token_id.is_valid_esdt_identifier(), ERROR_NOT_AN_ESDT);
-        require!(
-            second_token_id.is_valid_esdt_identifier(),
-            ERROR_NOT_AN_ESDT
-        );
+        // at most one side can be EGLD, as the tokens have to differ
+        require!(first_token_id.is_valid(), ERROR_NOT_AN_ESDT);
+        require!(second_token_id.is_valid(), ERROR_NOT_AN_ESDT);
         require!(first_token_id != second_token_id, ERROR_SAME_TOKENS);
 
         let lp_token_id = self.lp_token_identifier().get();
@@ -84,6 +104,9 @@ pub trait Pair<ContractReader>:
         self.initial_liquidity_adder()
             .set_if_empty(&initial_liquidity_adder_opt);
 
+        let current_epoch = self.blockchain().get_block_epoch();
+        self.first_week_start_epoch().set_if_empty(current_epoch);
+
         if admins.is_empty() {
             // backwards compatibility
             let all_permissions = Permissions::OWNER | Permissions::ADMIN | Permissions::PAUSE;
@@ -100,7 +123,10 @@ pub trait Pair<ContractReader>:
     }
 
     #[endpoint]
-    fn upgrade(&self) {}
+    fn upgrade(&self) {
+        let current_epoch = self.blockchain().get_block_epoch();
+        self.first_week_start_epoch().set_if_empty(current_epoch);
+    }
 
     #[endpoint(setLpTokenIdentifier)]
     fn set_lp_token_identifier(&self, token_identifier: TokenIdentifier) {
@@ -111,8 +137,8 @@ pub trait Pair<ContractReader>:
             ERROR_LP_TOKEN_NOT_ISSUED
         );
         require!(
-            token_identifier != self.first_token_id().get()
-                && token_identifier != self.second_token_id().get(),
+            self.first_token_id().get() != token_identifier
+                && self.second_token_id().get() != token_identifier,
             ERROR_LP_TOKEN_SAME_AS_POOL_TOKENS
         );
         require!(
@@ -8,12 +8,14 @@ use crate::errors::*;
 
 use super::amm;
 use super::config;
+use super::stable_swap;
 
 const MINIMUM_LIQUIDITY: u64 = 1_000;
 
 #[multiversx_sc::module]
 pub trait LiquidityPoolModule:
     amm::AmmModule
+    + stable_swap::StableSwapModule
     + config::ConfigModule
     + token_send::TokenSendModule
     + permissions_module::PermissionsModule
@@ -167,22 +169,22 @@ pub trait LiquidityPoolModule:
     fn get_token_for_given_position(
         &self,
         liquidity: BigUint,
-        token_id: TokenIdentifier,
-    ) -> EsdtTokenPayment<Self::Api> {
+        token_id: EgldOrEsdtTokenIdentifier,
+    ) -> EgldOrEsdtTokenPayment<Self::Api> {
         let reserve = self.pair_reserve(&token_id).get();
         let total_supply = self.lp_token_supply().get();
         if total_supply != 0 {
             let amount = liquidity * reserve / total_supply;
-            EsdtTokenPayment::new(token_id, 0, amount)
+            EgldOrEsdtTokenPayment::new(token_id, 0, amount)
         } else {
-            EsdtTokenPayment::new(token_id, 0, total_supply)
+            EgldOrEsdtTokenPayment::new(token_id, 0, total_supply)
         }
     }
 
     fn get_both_tokens_for_given_position(
         &self,
         liquidity: BigUint,
-    ) -> MultiValue2<EsdtTokenPayment<Self::Api>, EsdtTokenPayment<Self::Api>> {
+    ) -> MultiValue2<EgldOrEsdtTokenPayment<Self::Api>, EgldOrEsdtTokenPayment<Self::Api>> {
         let first_token_id = self.first_token_id().get();
         let token_first_token_amount =
             self.get_token_for_given_position(liquidity.clone(), first_token_id);
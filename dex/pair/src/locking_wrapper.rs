@@ -33,9 +33,9 @@ pub trait LockingWrapperModule:
     #[inline]
     fn lock_tokens(
         &self,
-        token_id: TokenIdentifier,
+        token_id: EgldOrEsdtTokenIdentifier,
         amount: BigUint,
-    ) -> EsdtTokenPayment<Self::Api> {
+    ) -> EgldOrEsdtTokenPayment<Self::Api> {
         self.lock_common(OptionalValue::None, token_id, amount)
     }
 
@@ -43,28 +43,25 @@ pub trait LockingWrapperModule:
     fn lock_tokens_and_forward(
         &self,
         to: ManagedAddress,
-        token_id: TokenIdentifier,
+        token_id: EgldOrEsdtTokenIdentifier,
         amount: BigUint,
-    ) -> EsdtTokenPayment<Self::Api> {
+    ) -> EgldOrEsdtTokenPayment<Self::Api> {
         self.lock_common(OptionalValue::Some(to), token_id, amount)
     }
 
     fn lock_common(
         &self,
         opt_dest: OptionalValue<ManagedAddress>,
-        token_id: TokenIdentifier,
+        token_id: EgldOrEsdtTokenIdentifier,
         amount: BigUint,
-    ) -> EsdtTokenPayment<Self::Api> {
+    ) -> EgldOrEsdtTokenPayment<Self::Api> {
         let unlock_epoch = self.unlock_epoch().get();
         let mut proxy_instance = self.get_locking_sc_proxy_instance();
 
-        let payment: EgldOrEsdtTokenPayment<Self::Api> = proxy_instance
+        proxy_instance
             .lock_tokens_endpoint(unlock_epoch, opt_dest)
-            .with_esdt_transfer((token_id, 0, amount))
-            .execute_on_dest_context();
-        let (token_id, token_nonce, amount) = payment.into_tuple();
-
-        EsdtTokenPayment::new(token_id.unwrap_esdt(), token_nonce, amount)
+            .with_egld_or_single_esdt_transfer((token_id, 0, amount))
+            .execute_on_dest_context()
     }
 
     fn should_generate_locked_asset(&self) -> bool {
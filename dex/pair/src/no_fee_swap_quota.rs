@@ -0,0 +1,137 @@
+multiversx_sc::imports!();
+multiversx_sc::derive_imports!();
+
+use common_errors::ERROR_BAD_INPUT_TOKEN;
+
+use crate::config;
+use crate::events;
+use crate::ERROR_NO_FEE_SWAP_QUOTA_EXCEEDED;
+use week_timekeeping::Week;
+
+#[derive(TypeAbi, TopEncode, TopDecode, Clone, PartialEq, Debug)]
+pub struct NoFeeSwapQuotaUsage<M: ManagedTypeApi> {
+    pub week: Week,
+    pub amount: BigUint<M>,
+}
+
+#[multiversx_sc::module]
+pub trait NoFeeSwapQuotaModule:
+    config::ConfigModule
+    + events::EventsModule
+    + week_timekeeping::WeekTimekeepingModule
+    + token_send::TokenSendModule
+    + permissions_module::PermissionsModule
+    + pausable::PausableModule
+{
+    /// Limits the amount of `token_id` that `address` can swap through `swapNoFeeAndForward`
+    /// during a week. Addresses without a quota for a token are not limited.
+    #[endpoint(setNoFeeSwapQuota)]
+    fn set_no_fee_swap_quota(
+        &self,
+        address: ManagedAddress,
+        token_id: TokenIdentifier,
+        max_amount_per_week: BigUint,
+    ) {
+        self.require_caller_has_owner_permissions();
+        require!(
+            token_id == self.first_token_id().get() || token_id == self.second_token_id().get(),
+            ERROR_BAD_INPUT_TOKEN
+        );
+
+        self.no_fee_swap_quota(&address, &token_id)
+            .set(max_amount_per_week);
+    }
+
+    #[endpoint(removeNoFeeSwapQuota)]
+    fn remove_no_fee_swap_quota(&self, address: ManagedAddress, token_id: TokenIdentifier) {
+        self.require_caller_has_owner_permissions();
+
+        self.no_fee_swap_quota(&address, &token_id).clear();
+        self.no_fee_swap_quota_usage(&address, &token_id).clear();
+    }
+
+    /// Returns nothing if the address is not limited for the token.
+    #[view(getRemainingNoFeeSwapQuota)]
+    fn get_remaining_no_fee_swap_quota(
+        &self,
+        address: ManagedAddress,
+        token_id: TokenIdentifier,
+    ) -> OptionalValue<BigUint> {
+        let quota_mapper = self.no_fee_swap_quota(&address, &token_id);
+        if quota_mapper.is_empty() {
+            return OptionalValue::None;
+        }
+
+        let quota = quota_mapper.get();
+        let used_amount = self.get_used_no_fee_swap_quota(&address, &token_id);
+        if used_amount >= quota {
+            return OptionalValue::Some(BigUint::zero());
+        }
+
+        OptionalValue::Some(quota - used_amount)
+    }
+
+    /// Fails if `amount` exceeds the remaining quota, so that the calling pair can check
+    /// the quota beforehand and fall back to its own fee handling.
+    /// The quota exhausted event is emitted by the swap that uses up the quota, while the
+    /// calling pairs report the swaps they skipped for exceeding it.
+    fn consume_no_fee_swap_quota(
+        &self,
+        caller: &ManagedAddress,
+        token_id: &TokenIdentifier,
+        amount: &BigUint,
+    ) {
+        let quota_mapper = self.no_fee_swap_quota(caller, token_id);
+        if quota_mapper.is_empty() {
+            return;
+        }
+
+        let quota = quota_mapper.get();
+        let current_week = self.get_current_week();
+        let new_used_amount = self.get_used_no_fee_swap_quota(caller, token_id) + amount;
+        require!(new_used_amount <= quota, ERROR_NO_FEE_SWAP_QUOTA_EXCEEDED);
+
+        if new_used_amount == quota {
+            self.emit_no_fee_swap_quota_exhausted_event(caller, token_id, quota, current_week);
+        }
+
+        self.no_fee_swap_quota_usage(caller, token_id)
+            .set(NoFeeSwapQuotaUsage {
+                week: current_week,
+                amount: new_used_amount,
+            });
+    }
+
+    fn get_used_no_fee_swap_quota(
+        &self,
+        address: &ManagedAddress,
+        token_id: &TokenIdentifier,
+    ) -> BigUint {
+        let usage_mapper = self.no_fee_swap_quota_usage(address, token_id);
+        if usage_mapper.is_empty() {
+            return BigUint::zero();
+        }
+
+        let usage = usage_mapper.get();
+        if usage.week != self.get_current_week() {
+            return BigUint::zero();
+        }
+
+        usage.amount
+    }
+
+    #[view(getNoFeeSwapQuota)]
+    #[storage_mapper("no_fee_swap_quota")]
+    fn no_fee_swap_quota(
+        &self,
+        address: &ManagedAddress,
+        token_id: &TokenIdentifier,
+    ) -> SingleValueMapper<BigUint>;
+
+    #[storage_mapper("no_fee_swap_quota_usage")]
+    fn no_fee_swap_quota_usage(
+        &self,
+        address: &ManagedAddress,
+        token_id: &TokenIdentifier,
+    ) -> SingleValueMapper<NoFeeSwapQuotaUsage<Self::Api>>;
+}
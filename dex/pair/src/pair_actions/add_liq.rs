@@ -1,7 +1,8 @@
 use crate::{
-    contexts::add_liquidity::AddLiquidityContext, StorageCache, ERROR_BAD_PAYMENT_TOKENS,
-    ERROR_INITIAL_LIQUIDITY_NOT_ADDED, ERROR_INVALID_ARGS, ERROR_K_INVARIANT_FAILED,
-    ERROR_LP_TOKEN_NOT_ISSUED, ERROR_NOT_ACTIVE,
+    contexts::add_liquidity::AddLiquidityContext,
+    hooks::{HookType, LiquidityHookData},
+    StorageCache, ERROR_BAD_PAYMENT_TOKENS, ERROR_INITIAL_LIQUIDITY_NOT_ADDED, ERROR_INVALID_ARGS,
+    ERROR_K_INVARIANT_FAILED, ERROR_LP_TOKEN_NOT_ISSUED, ERROR_NOT_ACTIVE,
 };
 
 use super::common_result_types::AddLiquidityResultType;
@@ -12,15 +13,24 @@ multiversx_sc::imports!();
 pub trait AddLiquidityModule:
     crate::liquidity_pool::LiquidityPoolModule
     + crate::amm::AmmModule
+    + crate::stable_swap::StableSwapModule
     + crate::contexts::output_builder::OutputBuilderModule
     + crate::locking_wrapper::LockingWrapperModule
     + crate::events::EventsModule
     + crate::safe_price::SafePriceModule
+    + crate::safe_price_view::SafePriceViewModule
+    + crate::circuit_breaker::CircuitBreakerModule
+    + crate::fee::FeeModule
+    + crate::protocol_fee::ProtocolFeeModule
+    + crate::trading_stats::TradingStatsModule
+    + week_timekeeping::WeekTimekeepingModule
+    + crate::hooks::HooksModule
     + crate::config::ConfigModule
     + token_send::TokenSendModule
     + permissions_module::PermissionsModule
     + pausable::PausableModule
     + super::common_methods::CommonMethodsModule
+    + super::egld_deposit::EgldDepositModule
     + utils::UtilsModule
 {
     #[payable("*")]
@@ -29,16 +39,46 @@ pub trait AddLiquidityModule:
         &self,
         first_token_amount_min: BigUint,
         second_token_amount_min: BigUint,
+        opt_deadline: OptionalValue<u64>,
     ) -> AddLiquidityResultType<Self::Api> {
         require!(
             first_token_amount_min > 0 && second_token_amount_min > 0,
             ERROR_INVALID_ARGS
         );
+        self.require_deadline_not_expired(opt_deadline);
 
-        let mut storage_cache = StorageCache::new(self);
         let caller = self.blockchain().get_caller();
+        let [first_payment, second_payment] = self.get_liquidity_payments(&caller);
 
-        let [first_payment, second_payment] = self.call_value().multi_esdt();
+        self.add_liquidity_common(
+            &caller,
+            first_payment,
+            second_payment,
+            first_token_amount_min,
+            second_token_amount_min,
+        )
+    }
+
+    /// Adds the two payments as liquidity and sends the LP tokens and the leftovers to `caller`.
+    fn add_liquidity_common(
+        &self,
+        caller: &ManagedAddress,
+        first_payment: EgldOrEsdtTokenPayment,
+        second_payment: EgldOrEsdtTokenPayment,
+        first_token_amount_min: BigUint,
+        second_token_amount_min: BigUint,
+    ) -> AddLiquidityResultType<Self::Api> {
+        self.call_liquidity_hooks(
+            HookType::BeforeAddLiquidity,
+            LiquidityHookData {
+                caller: caller.clone(),
+                first_token_amount: first_payment.amount.clone(),
+                second_token_amount: second_payment.amount.clone(),
+                lp_token_amount: BigUint::zero(),
+            },
+        );
+
+        let mut storage_cache = StorageCache::new(self);
         require!(
             first_payment.token_identifier == storage_cache.first_token_id
                 && first_payment.amount > 0,
@@ -66,11 +106,14 @@ pub trait AddLiquidityModule:
             &storage_cache.first_token_reserve,
             &storage_cache.second_token_reserve,
         );
+        self.mint_protocol_fee(&mut storage_cache);
 
         let initial_k = self.calculate_k_constant(
             &storage_cache.first_token_reserve,
             &storage_cache.second_token_reserve,
         );
+        let first_token_reserve_before = storage_cache.first_token_reserve.clone();
+        let second_token_reserve_before = storage_cache.second_token_reserve.clone();
 
         let mut add_liq_context = AddLiquidityContext::new(
             first_payment,
@@ -99,6 +142,14 @@ pub trait AddLiquidityModule:
             &storage_cache.second_token_reserve,
         );
         require!(initial_k <= new_k, ERROR_K_INVARIANT_FAILED);
+        self.require_price_deviation_within_limit(
+            &first_token_reserve_before,
+            &second_token_reserve_before,
+            &storage_cache.first_token_reserve,
+            &storage_cache.second_token_reserve,
+        );
+
+        self.update_k_last(&storage_cache);
 
         self.send()
             .esdt_local_mint(&storage_cache.lp_token_id, 0, &add_liq_context.liq_added);
@@ -109,16 +160,22 @@ pub trait AddLiquidityModule:
             add_liq_context.liq_added.clone(),
         );
 
-        let mut output_payments =
+        let [first_leftover, second_leftover] =
             self.build_add_liq_output_payments(&storage_cache, &add_liq_context);
-        output_payments.push(lp_payment);
-
-        self.send_multiple_tokens_if_not_zero(&caller, &output_payments);
+        self.send_payments_non_zero(
+            caller,
+            &[first_leftover, second_leftover, lp_payment.into()],
+        );
 
         let output = self.build_add_liq_results(&storage_cache, &add_liq_context);
 
+        let hook_data = self.build_add_liquidity_hook_data(caller, &add_liq_context);
         self.emit_add_liquidity_event(&storage_cache, add_liq_context);
 
+        // the reserves are committed before the hooks are called
+        drop(storage_cache);
+        self.call_liquidity_hooks(HookType::AfterAddLiquidity, hook_data);
+
         output
     }
 }
@@ -1,5 +1,7 @@
 use pausable::State;
 
+use crate::ERROR_DEADLINE_EXPIRED;
+
 multiversx_sc::imports!();
 
 #[multiversx_sc::module]
@@ -13,4 +15,14 @@ pub trait CommonMethodsModule {
     fn can_swap(&self, state: State) -> bool {
         state == State::Active
     }
+
+    /// The deadline is a block timestamp, after which the action is rejected.
+    fn require_deadline_not_expired(&self, opt_deadline: OptionalValue<u64>) {
+        if let OptionalValue::Some(deadline) = opt_deadline {
+            require!(
+                self.blockchain().get_block_timestamp() <= deadline,
+                ERROR_DEADLINE_EXPIRED
+            );
+        }
+    }
 }
@@ -1,10 +1,12 @@
 multiversx_sc::imports!();
 
 pub type AddLiquidityResultType<M> =
-    MultiValue3<EsdtTokenPayment<M>, EsdtTokenPayment<M>, EsdtTokenPayment<M>>;
+    MultiValue3<EsdtTokenPayment<M>, EgldOrEsdtTokenPayment<M>, EgldOrEsdtTokenPayment<M>>;
 
-pub type RemoveLiquidityResultType<M> = MultiValue2<EsdtTokenPayment<M>, EsdtTokenPayment<M>>;
+pub type RemoveLiquidityResultType<M> =
+    MultiValue2<EgldOrEsdtTokenPayment<M>, EgldOrEsdtTokenPayment<M>>;
 
-pub type SwapTokensFixedInputResultType<M> = EsdtTokenPayment<M>;
+pub type SwapTokensFixedInputResultType<M> = EgldOrEsdtTokenPayment<M>;
 
-pub type SwapTokensFixedOutputResultType<M> = MultiValue2<EsdtTokenPayment<M>, EsdtTokenPayment<M>>;
+pub type SwapTokensFixedOutputResultType<M> =
+    MultiValue2<EgldOrEsdtTokenPayment<M>, EgldOrEsdtTokenPayment<M>>;
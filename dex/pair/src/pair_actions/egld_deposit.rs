@@ -0,0 +1,83 @@
+use crate::{ERROR_NOT_AN_EGLD_PAIR, ERROR_ZERO_AMOUNT};
+
+multiversx_sc::imports!();
+
+#[multiversx_sc::module]
+pub trait EgldDepositModule:
+    crate::config::ConfigModule
+    + token_send::TokenSendModule
+    + permissions_module::PermissionsModule
+    + pausable::PausableModule
+{
+    /// EGLD cannot be transferred together with an ESDT, so the EGLD side of the liquidity
+    /// is deposited first. The next `addLiquidity` or `addInitialLiquidity` of the caller
+    /// then only transfers the ESDT, and uses the whole deposit as the EGLD payment.
+    #[payable("EGLD")]
+    #[endpoint(depositEgld)]
+    fn deposit_egld(&self) {
+        require!(self.has_egld_side(), ERROR_NOT_AN_EGLD_PAIR);
+
+        let amount = self.call_value().egld_value().clone_value();
+        require!(amount > 0, ERROR_ZERO_AMOUNT);
+
+        let caller = self.blockchain().get_caller();
+        self.egld_deposit(&caller)
+            .update(|deposit| *deposit += &amount);
+        self.egld_deposits_total().update(|total| *total += amount);
+    }
+
+    #[endpoint(withdrawEgldDeposit)]
+    fn withdraw_egld_deposit(&self) -> BigUint {
+        let caller = self.blockchain().get_caller();
+        let amount = self.take_egld_deposit(&caller);
+        self.send().direct_non_zero_egld(&caller, &amount);
+
+        amount
+    }
+
+    fn take_egld_deposit(&self, address: &ManagedAddress) -> BigUint {
+        let amount = self.egld_deposit(address).take();
+        self.egld_deposits_total().update(|total| *total -= &amount);
+
+        amount
+    }
+
+    fn has_egld_side(&self) -> bool {
+        self.first_token_id().get().is_egld() || self.second_token_id().get().is_egld()
+    }
+
+    /// The payments of the two pool tokens, in pool order. For a pair with an EGLD side,
+    /// the ESDT is transferred and the EGLD is taken from the deposit of the caller.
+    fn get_liquidity_payments(
+        &self,
+        caller: &ManagedAddress,
+    ) -> [EgldOrEsdtTokenPayment<Self::Api>; 2] {
+        let first_token_id = self.first_token_id().get();
+        let second_token_id = self.second_token_id().get();
+        if !first_token_id.is_egld() && !second_token_id.is_egld() {
+            let [first_payment, second_payment] = self.call_value().multi_esdt();
+            return [first_payment.into(), second_payment.into()];
+        }
+
+        let esdt_payment = self.call_value().single_esdt().into();
+        let egld_payment = EgldOrEsdtTokenPayment::new(
+            EgldOrEsdtTokenIdentifier::egld(),
+            0,
+            self.take_egld_deposit(caller),
+        );
+        if first_token_id.is_egld() {
+            [egld_payment, esdt_payment]
+        } else {
+            [esdt_payment, egld_payment]
+        }
+    }
+
+    #[view(getEgldDeposit)]
+    #[storage_mapper("egld_deposit")]
+    fn egld_deposit(&self, address: &ManagedAddress) -> SingleValueMapper<BigUint>;
+
+    /// Held by the pair besides the EGLD reserve, so it is left out of the flash swap balances.
+    #[view(getEgldDepositsTotal)]
+    #[storage_mapper("egld_deposits_total")]
+    fn egld_deposits_total(&self) -> SingleValueMapper<BigUint>;
+}
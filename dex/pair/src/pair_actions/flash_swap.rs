@@ -0,0 +1,230 @@
+use crate::{
+    config::MAX_PERCENTAGE, contexts::flash_swap::FlashSwapContext, hooks::SwapHookData,
+    StorageCache, SwapTokensOrder, ERROR_FLASH_SWAP_CALLER_NOT_SC, ERROR_FLASH_SWAP_EGLD_PAIR,
+    ERROR_FLASH_SWAP_REENTRANCY, ERROR_INVALID_ARGS, ERROR_K_INVARIANT_FAILED,
+    ERROR_NOT_ENOUGH_RESERVE, ERROR_SWAP_NOT_ENABLED,
+};
+
+multiversx_sc::imports!();
+
+#[multiversx_sc::module]
+pub trait FlashSwapModule:
+    crate::liquidity_pool::LiquidityPoolModule
+    + crate::amm::AmmModule
+    + crate::stable_swap::StableSwapModule
+    + crate::contexts::output_builder::OutputBuilderModule
+    + crate::locking_wrapper::LockingWrapperModule
+    + crate::events::EventsModule
+    + crate::safe_price::SafePriceModule
+    + crate::safe_price_view::SafePriceViewModule
+    + crate::circuit_breaker::CircuitBreakerModule
+    + crate::dynamic_fee::DynamicFeeModule
+    + crate::energy_fee_discount::EnergyFeeDiscountModule
+    + energy_query::EnergyQueryModule
+    + crate::fee::FeeModule
+    + crate::protocol_fee::ProtocolFeeModule
+    + crate::trading_stats::TradingStatsModule
+    + crate::hooks::HooksModule
+    + week_timekeeping::WeekTimekeepingModule
+    + crate::config::ConfigModule
+    + token_send::TokenSendModule
+    + permissions_module::PermissionsModule
+    + pausable::PausableModule
+    + super::common_methods::CommonMethodsModule
+    + super::egld_deposit::EgldDepositModule
+    + utils::UtilsModule
+{
+    /// Sends `amount_out` of `token_out` to the calling contract, through a synchronous call
+    /// to its `callback_endpoint`. Before the callback returns, the contract has to transfer
+    /// back enough pool tokens so that the K invariant, including the swap fees, still holds.
+    /// Not available on pairs with an EGLD side, as EGLD cannot be paid back through a
+    /// transfer that leaves the reserves untouched.
+    #[endpoint(flashSwap)]
+    fn flash_swap(
+        &self,
+        token_out: EgldOrEsdtTokenIdentifier,
+        amount_out: BigUint,
+        callback_endpoint: ManagedBuffer,
+        callback_args: MultiValueEncoded<ManagedBuffer>,
+    ) {
+        require!(!self.has_egld_side(), ERROR_FLASH_SWAP_EGLD_PAIR);
+        require!(amount_out > 0, ERROR_INVALID_ARGS);
+
+        let caller = self.blockchain().get_caller();
+        require!(
+            self.blockchain().is_smart_contract(&caller),
+            ERROR_FLASH_SWAP_CALLER_NOT_SC
+        );
+
+        let first_token_id = self.first_token_id().get();
+        let second_token_id = self.second_token_id().get();
+        let token_in = if token_out == first_token_id {
+            second_token_id.clone()
+        } else {
+            first_token_id.clone()
+        };
+        let fee_percent = self.call_before_swap_hooks(
+            SwapHookData {
+                caller: caller.clone(),
+                input_token_id: token_in.clone(),
+                input_token_amount: BigUint::zero(),
+                output_token_id: token_out.clone(),
+                output_token_amount: amount_out.clone(),
+                fee_percent: self.get_user_swap_fee_percent(&caller),
+            },
+            self.get_max_swap_fee_percent(),
+        );
+
+        let storage_cache = StorageCache::new(self);
+        let swap_tokens_order = storage_cache.get_swap_tokens_order(&token_in, &token_out);
+
+        require!(
+            self.can_swap(storage_cache.contract_state),
+            ERROR_SWAP_NOT_ENABLED
+        );
+        require!(
+            *storage_cache.get_reserve_out(swap_tokens_order) > amount_out,
+            ERROR_NOT_ENOUGH_RESERVE
+        );
+
+        self.update_safe_price(
+            &storage_cache.first_token_reserve,
+            &storage_cache.second_token_reserve,
+        );
+
+        let first_token_reserve_before = storage_cache.first_token_reserve.clone();
+        let second_token_reserve_before = storage_cache.second_token_reserve.clone();
+        let lp_token_supply_before = storage_cache.lp_token_supply.clone();
+
+        // reserves are committed before the callback, so they can be checked afterwards
+        drop(storage_cache);
+
+        let first_token_balance_before = self.get_pool_token_balance(&first_token_id);
+        let second_token_balance_before = self.get_pool_token_balance(&second_token_id);
+
+        self.send()
+            .contract_call::<()>(caller.clone(), callback_endpoint)
+            .with_egld_or_single_esdt_transfer((token_out.clone(), 0, amount_out.clone()))
+            .with_raw_arguments(callback_args.to_arg_buffer())
+            .execute_on_dest_context::<IgnoreValue>();
+
+        let mut storage_cache = StorageCache::new(self);
+        require!(
+            storage_cache.first_token_reserve == first_token_reserve_before
+                && storage_cache.second_token_reserve == second_token_reserve_before
+                && storage_cache.lp_token_supply == lp_token_supply_before,
+            ERROR_FLASH_SWAP_REENTRANCY
+        );
+
+        let mut context = FlashSwapContext::new(token_out, amount_out, swap_tokens_order);
+        let (first_token_amount_out, second_token_amount_out) = match swap_tokens_order {
+            SwapTokensOrder::PoolOrder => (BigUint::zero(), context.output_token_amount.clone()),
+            SwapTokensOrder::ReverseOrder => (context.output_token_amount.clone(), BigUint::zero()),
+        };
+
+        let first_token_balance_after =
+            self.get_pool_token_balance(&first_token_id) + &first_token_amount_out;
+        let second_token_balance_after =
+            self.get_pool_token_balance(&second_token_id) + &second_token_amount_out;
+        if first_token_balance_after > first_token_balance_before {
+            context.first_token_amount_in = first_token_balance_after - first_token_balance_before;
+        }
+        if second_token_balance_after > second_token_balance_before {
+            context.second_token_amount_in =
+                second_token_balance_after - second_token_balance_before;
+        }
+
+        let new_first_token_reserve = &storage_cache.first_token_reserve
+            + &context.first_token_amount_in
+            - &first_token_amount_out;
+        let new_second_token_reserve = &storage_cache.second_token_reserve
+            + &context.second_token_amount_in
+            - &second_token_amount_out;
+
+        // the amounts paid back are charged the swap fee, same as a regular swap input
+        let first_token_reserve_adjusted = &new_first_token_reserve * MAX_PERCENTAGE
+            - &context.first_token_amount_in * fee_percent;
+        let second_token_reserve_adjusted = &new_second_token_reserve * MAX_PERCENTAGE
+            - &context.second_token_amount_in * fee_percent;
+
+        let initial_k = self.calculate_k_constant(
+            &(&storage_cache.first_token_reserve * MAX_PERCENTAGE),
+            &(&storage_cache.second_token_reserve * MAX_PERCENTAGE),
+        );
+        let new_k = self.calculate_k_constant(
+            &first_token_reserve_adjusted,
+            &second_token_reserve_adjusted,
+        );
+        require!(initial_k <= new_k, ERROR_K_INVARIANT_FAILED);
+
+        storage_cache.first_token_reserve = new_first_token_reserve;
+        storage_cache.second_token_reserve = new_second_token_reserve;
+
+        self.require_price_deviation_within_limit(
+            &first_token_reserve_before,
+            &second_token_reserve_before,
+            &storage_cache.first_token_reserve,
+            &storage_cache.second_token_reserve,
+        );
+
+        if self.should_send_special_fee() {
+            context.first_token_fee_amount =
+                self.get_special_fee_from_input(&context.first_token_amount_in);
+            context.second_token_fee_amount =
+                self.get_special_fee_from_input(&context.second_token_amount_in);
+            storage_cache.first_token_reserve -= &context.first_token_fee_amount;
+            storage_cache.second_token_reserve -= &context.second_token_fee_amount;
+
+            self.send_fee(
+                &mut storage_cache,
+                SwapTokensOrder::PoolOrder,
+                &first_token_id,
+                &context.first_token_fee_amount,
+            );
+            self.send_fee(
+                &mut storage_cache,
+                SwapTokensOrder::ReverseOrder,
+                &second_token_id,
+                &context.second_token_fee_amount,
+            );
+        }
+
+        self.record_input_volume(
+            &first_token_id,
+            &context.first_token_amount_in,
+            &(&context.first_token_amount_in * fee_percent / MAX_PERCENTAGE),
+            &context.first_token_fee_amount,
+        );
+        self.record_input_volume(
+            &second_token_id,
+            &context.second_token_amount_in,
+            &(&context.second_token_amount_in * fee_percent / MAX_PERCENTAGE),
+            &context.second_token_fee_amount,
+        );
+        self.record_output_volume(&context.output_token_id, &context.output_token_amount);
+
+        let input_token_amount = match swap_tokens_order {
+            SwapTokensOrder::PoolOrder => context.first_token_amount_in.clone(),
+            SwapTokensOrder::ReverseOrder => context.second_token_amount_in.clone(),
+        };
+        let hook_data = SwapHookData {
+            caller,
+            input_token_id: token_in,
+            input_token_amount,
+            output_token_id: context.output_token_id.clone(),
+            output_token_amount: context.output_token_amount.clone(),
+            fee_percent,
+        };
+        self.emit_flash_swap_event(&storage_cache, context);
+
+        // the reserves are committed before the hooks are called
+        drop(storage_cache);
+        self.call_after_swap_hooks(hook_data);
+    }
+
+    fn get_pool_token_balance(&self, token_id: &EgldOrEsdtTokenIdentifier) -> BigUint {
+        let sc_address = self.blockchain().get_sc_address();
+        self.blockchain()
+            .get_esdt_balance(&sc_address, &token_id.clone().unwrap_esdt(), 0)
+    }
+}
@@ -2,8 +2,9 @@ use common_errors::ERROR_PERMISSION_DENIED;
 use pausable::State;
 
 use crate::{
-    contexts::add_liquidity::AddLiquidityContext, StorageCache, ERROR_ACTIVE,
-    ERROR_BAD_PAYMENT_TOKENS, ERROR_INITIAL_LIQUIDITY_ALREADY_ADDED,
+    contexts::add_liquidity::AddLiquidityContext,
+    hooks::{HookType, LiquidityHookData},
+    StorageCache, ERROR_ACTIVE, ERROR_BAD_PAYMENT_TOKENS, ERROR_INITIAL_LIQUIDITY_ALREADY_ADDED,
 };
 
 use super::common_result_types::AddLiquidityResultType;
@@ -14,20 +15,26 @@ multiversx_sc::imports!();
 pub trait InitialLiquidityModule:
     crate::liquidity_pool::LiquidityPoolModule
     + crate::amm::AmmModule
+    + crate::stable_swap::StableSwapModule
     + crate::contexts::output_builder::OutputBuilderModule
     + crate::locking_wrapper::LockingWrapperModule
     + crate::events::EventsModule
+    + crate::fee::FeeModule
+    + crate::protocol_fee::ProtocolFeeModule
+    + crate::trading_stats::TradingStatsModule
+    + week_timekeeping::WeekTimekeepingModule
+    + crate::hooks::HooksModule
     + crate::config::ConfigModule
     + token_send::TokenSendModule
     + permissions_module::PermissionsModule
     + pausable::PausableModule
     + super::common_methods::CommonMethodsModule
+    + super::egld_deposit::EgldDepositModule
     + utils::UtilsModule
 {
     #[payable("*")]
     #[endpoint(addInitialLiquidity)]
     fn add_initial_liquidity(&self) -> AddLiquidityResultType<Self::Api> {
-        let mut storage_cache = StorageCache::new(self);
         let caller = self.blockchain().get_caller();
 
         let opt_initial_liq_adder = self.initial_liquidity_adder().get();
@@ -35,7 +42,18 @@ pub trait InitialLiquidityModule:
             require!(caller == initial_liq_adder, ERROR_PERMISSION_DENIED);
         }
 
-        let [first_payment, second_payment] = self.call_value().multi_esdt();
+        let [first_payment, second_payment] = self.get_liquidity_payments(&caller);
+        self.call_liquidity_hooks(
+            HookType::BeforeAddLiquidity,
+            LiquidityHookData {
+                caller: caller.clone(),
+                first_token_amount: first_payment.amount.clone(),
+                second_token_amount: second_payment.amount.clone(),
+                lp_token_amount: BigUint::zero(),
+            },
+        );
+
+        let mut storage_cache = StorageCache::new(self);
         require!(
             first_payment.token_identifier == storage_cache.first_token_id
                 && first_payment.amount > 0,
@@ -62,6 +80,7 @@ pub trait InitialLiquidityModule:
             second_token_optimal_amount,
             &mut storage_cache,
         );
+        self.update_k_last(&storage_cache);
 
         self.send()
             .esdt_local_mint(&storage_cache.lp_token_id, 0, &liq_added);
@@ -85,8 +104,13 @@ pub trait InitialLiquidityModule:
         };
         let output = self.build_add_initial_liq_results(&storage_cache, &add_liq_context);
 
+        let hook_data = self.build_add_liquidity_hook_data(&caller, &add_liq_context);
         self.emit_add_liquidity_event(&storage_cache, add_liq_context);
 
+        // the reserves are committed before the hooks are called
+        drop(storage_cache);
+        self.call_liquidity_hooks(HookType::AfterAddLiquidity, hook_data);
+
         output
     }
 }
@@ -0,0 +1,207 @@
+use common_errors::ERROR_PERMISSION_DENIED;
+
+use crate::{
+    contexts::remove_liquidity::RemoveLiquidityContext,
+    hooks::{HookType, LiquidityHookData},
+    StorageCache, ERROR_BAD_PAYMENT_TOKENS, ERROR_INVALID_ARGS, ERROR_K_INVARIANT_FAILED,
+    ERROR_LIQUIDITY_MIGRATION_NOT_CONFIGURED, ERROR_LP_TOKEN_NOT_ISSUED, ERROR_NOT_ACTIVE,
+};
+
+use super::common_result_types::{AddLiquidityResultType, RemoveLiquidityResultType};
+
+multiversx_sc::imports!();
+
+mod source_pair_proxy {
+    multiversx_sc::imports!();
+    use super::RemoveLiquidityResultType;
+
+    #[multiversx_sc::proxy]
+    pub trait SourcePairProxy {
+        #[payable("*")]
+        #[endpoint(removeLiquidityForMigration)]
+        fn remove_liquidity_for_migration(&self) -> RemoveLiquidityResultType<Self::Api>;
+    }
+}
+
+#[multiversx_sc::module]
+pub trait MigrationModule:
+    crate::liquidity_pool::LiquidityPoolModule
+    + crate::amm::AmmModule
+    + crate::stable_swap::StableSwapModule
+    + crate::contexts::output_builder::OutputBuilderModule
+    + crate::locking_wrapper::LockingWrapperModule
+    + crate::events::EventsModule
+    + crate::safe_price::SafePriceModule
+    + crate::safe_price_view::SafePriceViewModule
+    + crate::circuit_breaker::CircuitBreakerModule
+    + crate::fee::FeeModule
+    + crate::protocol_fee::ProtocolFeeModule
+    + crate::trading_stats::TradingStatsModule
+    + week_timekeeping::WeekTimekeepingModule
+    + crate::hooks::HooksModule
+    + crate::config::ConfigModule
+    + token_send::TokenSendModule
+    + permissions_module::PermissionsModule
+    + pausable::PausableModule
+    + super::common_methods::CommonMethodsModule
+    + super::egld_deposit::EgldDepositModule
+    + super::add_liq::AddLiquidityModule
+    + utils::UtilsModule
+{
+    /// Sets the pair that may migrate the liquidity of this pair's LP tokens.
+    #[endpoint(setMigrationTarget)]
+    fn set_migration_target(&self, target_pair_address: ManagedAddress) {
+        self.require_caller_has_owner_permissions();
+
+        self.migration_target().set(&target_pair_address);
+    }
+
+    /// Sets the pair whose LP tokens are accepted by `migrateLiquidity`.
+    #[endpoint(setMigrationSource)]
+    fn set_migration_source(&self, source_pair_address: ManagedAddress) {
+        self.require_caller_has_owner_permissions();
+
+        self.migration_source().set(&source_pair_address);
+    }
+
+    /// Receives LP tokens of the migration source pair, removes their liquidity from it
+    /// and adds the received tokens as liquidity to this pair.
+    /// The new LP tokens are sent to the caller, together with the tokens
+    /// that could not be added because of the different reserves ratio.
+    #[payable("*")]
+    #[endpoint(migrateLiquidity)]
+    fn migrate_liquidity(
+        &self,
+        first_token_amount_min: BigUint,
+        second_token_amount_min: BigUint,
+    ) -> AddLiquidityResultType<Self::Api> {
+        require!(
+            first_token_amount_min > 0 && second_token_amount_min > 0,
+            ERROR_INVALID_ARGS
+        );
+
+        let migration_source_mapper = self.migration_source();
+        require!(
+            !migration_source_mapper.is_empty(),
+            ERROR_LIQUIDITY_MIGRATION_NOT_CONFIGURED
+        );
+
+        let caller = self.blockchain().get_caller();
+        let payment = self.call_value().single_esdt();
+        let removed_payments: RemoveLiquidityResultType<Self::Api> = self
+            .source_pair_proxy(migration_source_mapper.get())
+            .remove_liquidity_for_migration()
+            .with_esdt_transfer(payment)
+            .execute_on_dest_context();
+
+        let (source_first_payment, source_second_payment) = removed_payments.into_tuple();
+        let first_token_id = self.first_token_id().get();
+        let (first_payment, second_payment) =
+            if source_first_payment.token_identifier == first_token_id {
+                (source_first_payment, source_second_payment)
+            } else {
+                (source_second_payment, source_first_payment)
+            };
+
+        self.add_liquidity_common(
+            &caller,
+            first_payment,
+            second_payment,
+            first_token_amount_min,
+            second_token_amount_min,
+        )
+    }
+
+    /// Only callable by the migration target pair. The removed tokens are sent back to it.
+    #[payable("*")]
+    #[endpoint(removeLiquidityForMigration)]
+    fn remove_liquidity_for_migration(&self) -> RemoveLiquidityResultType<Self::Api> {
+        let caller = self.blockchain().get_caller();
+        let migration_target_mapper = self.migration_target();
+        require!(
+            !migration_target_mapper.is_empty() && caller == migration_target_mapper.get(),
+            ERROR_PERMISSION_DENIED
+        );
+
+        let payment = self.call_value().single_esdt();
+        self.call_liquidity_hooks(
+            HookType::BeforeRemoveLiquidity,
+            LiquidityHookData {
+                caller: caller.clone(),
+                first_token_amount: BigUint::zero(),
+                second_token_amount: BigUint::zero(),
+                lp_token_amount: payment.amount.clone(),
+            },
+        );
+
+        let mut storage_cache = StorageCache::new(self);
+        require!(
+            self.is_state_active(storage_cache.contract_state),
+            ERROR_NOT_ACTIVE
+        );
+        require!(
+            storage_cache.lp_token_id.is_valid_esdt_identifier(),
+            ERROR_LP_TOKEN_NOT_ISSUED
+        );
+        require!(
+            payment.token_identifier == storage_cache.lp_token_id && payment.amount > 0,
+            ERROR_BAD_PAYMENT_TOKENS
+        );
+
+        self.update_safe_price(
+            &storage_cache.first_token_reserve,
+            &storage_cache.second_token_reserve,
+        );
+        self.mint_protocol_fee(&mut storage_cache);
+
+        let initial_k = self.calculate_k_constant(
+            &storage_cache.first_token_reserve,
+            &storage_cache.second_token_reserve,
+        );
+
+        let mut remove_liq_context =
+            RemoveLiquidityContext::new(payment.amount, BigUint::from(1u64), BigUint::from(1u64));
+        self.pool_remove_liquidity(&mut remove_liq_context, &mut storage_cache);
+        self.update_k_last(&storage_cache);
+
+        let new_k = self.calculate_k_constant(
+            &storage_cache.first_token_reserve,
+            &storage_cache.second_token_reserve,
+        );
+        require!(new_k <= initial_k, ERROR_K_INVARIANT_FAILED);
+
+        self.burn(
+            &storage_cache.lp_token_id,
+            &remove_liq_context.lp_token_payment_amount,
+        );
+
+        let output_payments =
+            self.build_remove_liq_output_payments(&storage_cache, &remove_liq_context);
+        self.send_payments_non_zero(&caller, &output_payments);
+
+        let hook_data = LiquidityHookData {
+            caller,
+            first_token_amount: remove_liq_context.first_token_amount_removed.clone(),
+            second_token_amount: remove_liq_context.second_token_amount_removed.clone(),
+            lp_token_amount: remove_liq_context.lp_token_payment_amount.clone(),
+        };
+        self.emit_remove_liquidity_event(&storage_cache, remove_liq_context);
+
+        // the reserves are committed before the hooks are called
+        drop(storage_cache);
+        self.call_liquidity_hooks(HookType::AfterRemoveLiquidity, hook_data);
+
+        self.build_remove_liq_results(output_payments)
+    }
+
+    #[proxy]
+    fn source_pair_proxy(&self, to: ManagedAddress) -> source_pair_proxy::Proxy<Self::Api>;
+
+    #[view(getMigrationTarget)]
+    #[storage_mapper("migration_target")]
+    fn migration_target(&self) -> SingleValueMapper<ManagedAddress>;
+
+    #[view(getMigrationSource)]
+    #[storage_mapper("migration_source")]
+    fn migration_source(&self) -> SingleValueMapper<ManagedAddress>;
+}
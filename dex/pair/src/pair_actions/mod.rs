@@ -1,7 +1,11 @@
 pub mod add_liq;
 pub mod common_methods;
 pub mod common_result_types;
+pub mod egld_deposit;
+pub mod flash_swap;
 pub mod initial_liq;
+pub mod migration;
 pub mod remove_liq;
+pub mod single_token_liq;
 pub mod swap;
 pub mod views;
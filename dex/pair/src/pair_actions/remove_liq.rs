@@ -1,7 +1,10 @@
 use crate::{
-    contexts::remove_liquidity::RemoveLiquidityContext, StorageCache, SwapTokensOrder,
-    ERROR_BAD_PAYMENT_TOKENS, ERROR_INVALID_ARGS, ERROR_K_INVARIANT_FAILED,
-    ERROR_LP_TOKEN_NOT_ISSUED, ERROR_NOT_ACTIVE, ERROR_NOT_WHITELISTED, ERROR_SLIPPAGE_ON_REMOVE,
+    contexts::remove_liquidity::RemoveLiquidityContext,
+    fee::BuybackDestination,
+    hooks::{HookType, LiquidityHookData},
+    StorageCache, SwapTokensOrder, ERROR_BAD_PAYMENT_TOKENS, ERROR_BUYBACK_TOKEN_NOT_IN_PAIR,
+    ERROR_INVALID_ARGS, ERROR_K_INVARIANT_FAILED, ERROR_LP_TOKEN_NOT_ISSUED, ERROR_NOT_ACTIVE,
+    ERROR_NOT_WHITELISTED, ERROR_SLIPPAGE_ON_REMOVE,
 };
 
 use super::common_result_types::RemoveLiquidityResultType;
@@ -12,11 +15,16 @@ multiversx_sc::imports!();
 pub trait RemoveLiquidityModule:
     crate::liquidity_pool::LiquidityPoolModule
     + crate::amm::AmmModule
+    + crate::stable_swap::StableSwapModule
     + crate::contexts::output_builder::OutputBuilderModule
     + crate::locking_wrapper::LockingWrapperModule
     + crate::events::EventsModule
     + crate::safe_price::SafePriceModule
     + crate::fee::FeeModule
+    + crate::protocol_fee::ProtocolFeeModule
+    + crate::trading_stats::TradingStatsModule
+    + week_timekeeping::WeekTimekeepingModule
+    + crate::hooks::HooksModule
     + crate::config::ConfigModule
     + token_send::TokenSendModule
     + permissions_module::PermissionsModule
@@ -30,15 +38,27 @@ pub trait RemoveLiquidityModule:
         &self,
         first_token_amount_min: BigUint,
         second_token_amount_min: BigUint,
+        opt_deadline: OptionalValue<u64>,
     ) -> RemoveLiquidityResultType<Self::Api> {
         require!(
             first_token_amount_min > 0 && second_token_amount_min > 0,
             ERROR_INVALID_ARGS
         );
+        self.require_deadline_not_expired(opt_deadline);
 
-        let mut storage_cache = StorageCache::new(self);
         let caller = self.blockchain().get_caller();
         let payment = self.call_value().single_esdt();
+        self.call_liquidity_hooks(
+            HookType::BeforeRemoveLiquidity,
+            LiquidityHookData {
+                caller: caller.clone(),
+                first_token_amount: first_token_amount_min.clone(),
+                second_token_amount: second_token_amount_min.clone(),
+                lp_token_amount: payment.amount.clone(),
+            },
+        );
+
+        let mut storage_cache = StorageCache::new(self);
 
         require!(
             self.is_state_active(storage_cache.contract_state),
@@ -57,6 +77,7 @@ pub trait RemoveLiquidityModule:
             &storage_cache.first_token_reserve,
             &storage_cache.second_token_reserve,
         );
+        self.mint_protocol_fee(&mut storage_cache);
 
         let initial_k = self.calculate_k_constant(
             &storage_cache.first_token_reserve,
@@ -69,6 +90,7 @@ pub trait RemoveLiquidityModule:
             second_token_amount_min,
         );
         self.pool_remove_liquidity(&mut remove_liq_context, &mut storage_cache);
+        self.update_k_last(&storage_cache);
 
         let new_k = self.calculate_k_constant(
             &storage_cache.first_token_reserve,
@@ -84,8 +106,7 @@ pub trait RemoveLiquidityModule:
         let output_payments =
             self.build_remove_liq_output_payments(&storage_cache, &remove_liq_context);
 
-        let first_payment_after = output_payments.get(0);
-        let second_payment_after = output_payments.get(1);
+        let [first_payment_after, second_payment_after] = &output_payments;
         require!(
             first_payment_after.amount >= remove_liq_context.first_token_amount_min,
             ERROR_SLIPPAGE_ON_REMOVE
@@ -95,21 +116,41 @@ pub trait RemoveLiquidityModule:
             ERROR_SLIPPAGE_ON_REMOVE
         );
 
-        self.send_multiple_tokens_if_not_zero(&caller, &output_payments);
+        self.send_payments_non_zero(&caller, &output_payments);
 
+        let hook_data = LiquidityHookData {
+            caller: caller.clone(),
+            first_token_amount: remove_liq_context.first_token_amount_removed.clone(),
+            second_token_amount: remove_liq_context.second_token_amount_removed.clone(),
+            lp_token_amount: remove_liq_context.lp_token_payment_amount.clone(),
+        };
         self.emit_remove_liquidity_event(&storage_cache, remove_liq_context);
 
+        // the reserves are committed before the hooks are called
+        drop(storage_cache);
+        self.call_liquidity_hooks(HookType::AfterRemoveLiquidity, hook_data);
+
         self.build_remove_liq_results(output_payments)
     }
 
     #[payable("*")]
     #[endpoint(removeLiquidityAndBuyBackAndBurnToken)]
     fn remove_liquidity_and_burn_token(&self, token_to_buyback_and_burn: TokenIdentifier) {
-        let mut storage_cache = StorageCache::new(self);
         let caller = self.blockchain().get_caller();
         let payment = self.call_value().single_esdt();
 
         require!(self.whitelist().contains(&caller), ERROR_NOT_WHITELISTED);
+        self.call_liquidity_hooks(
+            HookType::BeforeRemoveLiquidity,
+            LiquidityHookData {
+                caller: caller.clone(),
+                first_token_amount: BigUint::zero(),
+                second_token_amount: BigUint::zero(),
+                lp_token_amount: payment.amount.clone(),
+            },
+        );
+
+        let mut storage_cache = StorageCache::new(self);
         require!(
             storage_cache.lp_token_id.is_valid_esdt_identifier(),
             ERROR_LP_TOKEN_NOT_ISSUED
@@ -123,35 +164,114 @@ pub trait RemoveLiquidityModule:
             &storage_cache.first_token_reserve,
             &storage_cache.second_token_reserve,
         );
+        self.mint_protocol_fee(&mut storage_cache);
 
         let mut remove_liq_context =
             RemoveLiquidityContext::new(payment.amount, BigUint::from(1u64), BigUint::from(1u64));
         self.pool_remove_liquidity(&mut remove_liq_context, &mut storage_cache);
+        self.update_k_last(&storage_cache);
 
         self.burn(
             &storage_cache.lp_token_id,
             &remove_liq_context.lp_token_payment_amount,
         );
 
-        let dest_address = ManagedAddress::zero();
-        let first_token_id = storage_cache.first_token_id.clone();
-        self.send_fee_slice(
-            &mut storage_cache,
-            SwapTokensOrder::PoolOrder,
-            &first_token_id,
-            &remove_liq_context.first_token_amount_removed,
-            &dest_address,
-            &token_to_buyback_and_burn,
-        );
+        let buyback_destination = self.get_buyback_destination(caller.clone());
+        if buyback_destination != BuybackDestination::Burn {
+            // the trusted swap pairs and fee routes burn their output, so only the
+            // tokens bought back locally can be sent elsewhere
+            require!(
+                storage_cache.first_token_id == token_to_buyback_and_burn
+                    || storage_cache.second_token_id == token_to_buyback_and_burn,
+                ERROR_BUYBACK_TOKEN_NOT_IN_PAIR
+            );
 
-        let second_token_id = storage_cache.second_token_id.clone();
-        self.send_fee_slice(
-            &mut storage_cache,
-            SwapTokensOrder::ReverseOrder,
-            &second_token_id,
-            &remove_liq_context.second_token_amount_removed,
-            &dest_address,
-            &token_to_buyback_and_burn,
-        );
+            self.buy_back_and_send(
+                &mut storage_cache,
+                &remove_liq_context,
+                token_to_buyback_and_burn,
+                buyback_destination,
+            );
+        } else {
+            let dest_address = ManagedAddress::zero();
+            let first_token_id = storage_cache.first_token_id.clone();
+            self.send_fee_slice(
+                &mut storage_cache,
+                SwapTokensOrder::PoolOrder,
+                &first_token_id,
+                &remove_liq_context.first_token_amount_removed,
+                &dest_address,
+                &token_to_buyback_and_burn,
+            );
+
+            let second_token_id = storage_cache.second_token_id.clone();
+            self.send_fee_slice(
+                &mut storage_cache,
+                SwapTokensOrder::ReverseOrder,
+                &second_token_id,
+                &remove_liq_context.second_token_amount_removed,
+                &dest_address,
+                &token_to_buyback_and_burn,
+            );
+        }
+
+        let hook_data = LiquidityHookData {
+            caller,
+            first_token_amount: remove_liq_context.first_token_amount_removed,
+            second_token_amount: remove_liq_context.second_token_amount_removed,
+            lp_token_amount: remove_liq_context.lp_token_payment_amount,
+        };
+
+        // the reserves are committed before the hooks are called
+        drop(storage_cache);
+        self.call_liquidity_hooks(HookType::AfterRemoveLiquidity, hook_data);
+    }
+
+    /// Swaps the removed tokens into `token_to_buyback`, which has to be one of the pair tokens,
+    /// and sends the result to the destination.
+    fn buy_back_and_send(
+        &self,
+        storage_cache: &mut StorageCache<Self>,
+        remove_liq_context: &RemoveLiquidityContext<Self::Api>,
+        token_to_buyback: TokenIdentifier,
+        destination: BuybackDestination<Self::Api>,
+    ) {
+        let (mut bought_back_amount, amount_to_swap, swap_tokens_order) =
+            if storage_cache.first_token_id == token_to_buyback {
+                (
+                    remove_liq_context.first_token_amount_removed.clone(),
+                    &remove_liq_context.second_token_amount_removed,
+                    SwapTokensOrder::ReverseOrder,
+                )
+            } else {
+                (
+                    remove_liq_context.second_token_amount_removed.clone(),
+                    &remove_liq_context.first_token_amount_removed,
+                    SwapTokensOrder::PoolOrder,
+                )
+            };
+
+        if *amount_to_swap > 0 {
+            bought_back_amount +=
+                self.swap_internal_no_fee(storage_cache, swap_tokens_order, amount_to_swap);
+        }
+        if bought_back_amount == 0 {
+            return;
+        }
+
+        match destination {
+            BuybackDestination::Treasury(treasury_address) => {
+                self.send().direct_esdt(
+                    &treasury_address,
+                    &token_to_buyback,
+                    0,
+                    &bought_back_amount,
+                );
+            }
+            BuybackDestination::FeesCollector => {
+                self.send_fees_collector_cut(token_to_buyback, bought_back_amount);
+            }
+            BuybackDestination::Burn => self.burn(&token_to_buyback, &bought_back_amount),
+        }
     }
 }
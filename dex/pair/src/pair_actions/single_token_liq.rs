@@ -0,0 +1,408 @@
+use crate::{
+    contexts::{
+        add_liquidity::AddLiquidityContext, remove_liquidity::RemoveLiquidityContext,
+        swap::SwapContext,
+    },
+    hooks::{HookType, LiquidityHookData},
+    StorageCache, SwapTokensOrder, ERROR_BAD_PAYMENT_TOKENS, ERROR_INITIAL_LIQUIDITY_NOT_ADDED,
+    ERROR_INVALID_ARGS, ERROR_K_INVARIANT_FAILED, ERROR_LP_TOKEN_NOT_ISSUED,
+    ERROR_SLIPPAGE_EXCEEDED, ERROR_SWAP_NOT_ENABLED, ERROR_SWAP_OUTPUT_LOCKED,
+};
+
+use super::common_result_types::AddLiquidityResultType;
+
+multiversx_sc::imports!();
+
+#[multiversx_sc::module]
+pub trait SingleTokenLiquidityModule:
+    crate::liquidity_pool::LiquidityPoolModule
+    + crate::amm::AmmModule
+    + crate::stable_swap::StableSwapModule
+    + crate::contexts::output_builder::OutputBuilderModule
+    + crate::locking_wrapper::LockingWrapperModule
+    + crate::events::EventsModule
+    + crate::safe_price::SafePriceModule
+    + crate::safe_price_view::SafePriceViewModule
+    + crate::circuit_breaker::CircuitBreakerModule
+    + crate::dynamic_fee::DynamicFeeModule
+    + crate::energy_fee_discount::EnergyFeeDiscountModule
+    + energy_query::EnergyQueryModule
+    + crate::fee::FeeModule
+    + crate::protocol_fee::ProtocolFeeModule
+    + crate::trading_stats::TradingStatsModule
+    + crate::no_fee_swap_quota::NoFeeSwapQuotaModule
+    + crate::hooks::HooksModule
+    + week_timekeeping::WeekTimekeepingModule
+    + crate::config::ConfigModule
+    + token_send::TokenSendModule
+    + permissions_module::PermissionsModule
+    + pausable::PausableModule
+    + super::common_methods::CommonMethodsModule
+    + super::swap::SwapModule
+    + utils::UtilsModule
+{
+    /// Adds liquidity with only one of the pool tokens. Part of the payment is first swapped
+    /// for the other pool token, then both are added as liquidity.
+    /// Any leftover of the two tokens is sent back, together with the LP tokens.
+    #[payable("*")]
+    #[endpoint(addLiquiditySingleToken)]
+    fn add_liquidity_single_token(
+        &self,
+        lp_token_amount_min: BigUint,
+        opt_deadline: OptionalValue<u64>,
+    ) -> AddLiquidityResultType<Self::Api> {
+        require!(lp_token_amount_min > 0, ERROR_INVALID_ARGS);
+        self.require_deadline_not_expired(opt_deadline);
+
+        let caller = self.blockchain().get_caller();
+        let payment = self.call_value().egld_or_single_esdt();
+
+        require!(payment.amount > 0, ERROR_BAD_PAYMENT_TOKENS);
+        let first_token_id = self.first_token_id().get();
+        let second_token_id = self.second_token_id().get();
+        let (token_out, first_token_amount, second_token_amount) =
+            if payment.token_identifier == first_token_id {
+                (second_token_id, payment.amount.clone(), BigUint::zero())
+            } else if payment.token_identifier == second_token_id {
+                (first_token_id, BigUint::zero(), payment.amount.clone())
+            } else {
+                sc_panic!(ERROR_BAD_PAYMENT_TOKENS);
+            };
+
+        self.call_liquidity_hooks(
+            HookType::BeforeAddLiquidity,
+            LiquidityHookData {
+                caller: caller.clone(),
+                first_token_amount,
+                second_token_amount,
+                lp_token_amount: BigUint::zero(),
+            },
+        );
+        let fee_percent = self.get_swap_fee_percent_from_hooks(
+            &caller,
+            &payment.token_identifier,
+            &payment.amount,
+            &token_out,
+            &BigUint::from(1u32),
+        );
+
+        let mut storage_cache = StorageCache::new(self);
+        let swap_tokens_order =
+            storage_cache.get_swap_tokens_order(&payment.token_identifier, &token_out);
+
+        require!(
+            self.can_swap(storage_cache.contract_state),
+            ERROR_SWAP_NOT_ENABLED
+        );
+        require!(
+            storage_cache.lp_token_id.is_valid_esdt_identifier(),
+            ERROR_LP_TOKEN_NOT_ISSUED
+        );
+        require!(
+            storage_cache.lp_token_supply != 0,
+            ERROR_INITIAL_LIQUIDITY_NOT_ADDED
+        );
+
+        self.update_safe_price(
+            &storage_cache.first_token_reserve,
+            &storage_cache.second_token_reserve,
+        );
+
+        let initial_k = self.calculate_k_constant(
+            &storage_cache.first_token_reserve,
+            &storage_cache.second_token_reserve,
+        );
+        let first_token_reserve_before = storage_cache.first_token_reserve.clone();
+        let second_token_reserve_before = storage_cache.second_token_reserve.clone();
+
+        let swap_amount = self.get_single_token_swap_amount(
+            &payment.amount,
+            storage_cache.get_reserve_in(swap_tokens_order),
+            fee_percent,
+        );
+        let mut swap_context = SwapContext::new(
+            payment.token_identifier.clone(),
+            swap_amount,
+            token_out,
+            BigUint::from(1u32),
+            swap_tokens_order,
+        );
+        swap_context.fee_percent = fee_percent;
+        self.perform_swap_fixed_input(&mut swap_context, &mut storage_cache);
+
+        let new_k = self.calculate_k_constant(
+            &storage_cache.first_token_reserve,
+            &storage_cache.second_token_reserve,
+        );
+        require!(initial_k <= new_k, ERROR_K_INVARIANT_FAILED);
+        self.require_price_deviation_within_limit(
+            &first_token_reserve_before,
+            &second_token_reserve_before,
+            &storage_cache.first_token_reserve,
+            &storage_cache.second_token_reserve,
+        );
+
+        if swap_context.fee_amount > 0 {
+            self.send_fee(
+                &mut storage_cache,
+                swap_context.swap_tokens_order,
+                &swap_context.input_token_id,
+                &swap_context.fee_amount,
+            );
+        }
+
+        let remaining_payment = EgldOrEsdtTokenPayment::new(
+            payment.token_identifier,
+            0,
+            &payment.amount - &swap_context.final_input_amount,
+        );
+        let swapped_payment = EgldOrEsdtTokenPayment::new(
+            swap_context.output_token_id.clone(),
+            0,
+            swap_context.final_output_amount.clone(),
+        );
+        let (first_payment, second_payment) = match swap_tokens_order {
+            SwapTokensOrder::PoolOrder => (remaining_payment, swapped_payment),
+            SwapTokensOrder::ReverseOrder => (swapped_payment, remaining_payment),
+        };
+
+        let swap_hook_data = self.build_swap_hook_data(&caller, &swap_context);
+        self.emit_swap_event(&storage_cache, swap_context);
+        // after the swap, so that its fee is included in the protocol fee
+        self.mint_protocol_fee(&mut storage_cache);
+
+        let initial_k = self.calculate_k_constant(
+            &storage_cache.first_token_reserve,
+            &storage_cache.second_token_reserve,
+        );
+
+        let mut add_liq_context = AddLiquidityContext::new(
+            first_payment,
+            second_payment,
+            BigUint::from(1u64),
+            BigUint::from(1u64),
+        );
+        self.set_optimal_amounts(&mut add_liq_context, &storage_cache);
+
+        add_liq_context.liq_added = self.pool_add_liquidity(
+            &add_liq_context.first_token_optimal_amount,
+            &add_liq_context.second_token_optimal_amount,
+            &mut storage_cache,
+        );
+        require!(
+            add_liq_context.liq_added >= lp_token_amount_min,
+            ERROR_SLIPPAGE_EXCEEDED
+        );
+
+        let new_k = self.calculate_k_constant(
+            &storage_cache.first_token_reserve,
+            &storage_cache.second_token_reserve,
+        );
+        require!(initial_k <= new_k, ERROR_K_INVARIANT_FAILED);
+        self.update_k_last(&storage_cache);
+
+        self.send()
+            .esdt_local_mint(&storage_cache.lp_token_id, 0, &add_liq_context.liq_added);
+
+        let lp_payment = EsdtTokenPayment::new(
+            storage_cache.lp_token_id.clone(),
+            0,
+            add_liq_context.liq_added.clone(),
+        );
+
+        let [first_leftover, second_leftover] =
+            self.build_add_liq_output_payments(&storage_cache, &add_liq_context);
+        self.send_payments_non_zero(
+            &caller,
+            &[first_leftover, second_leftover, lp_payment.into()],
+        );
+
+        let output = self.build_add_liq_results(&storage_cache, &add_liq_context);
+
+        let add_liq_hook_data = self.build_add_liquidity_hook_data(&caller, &add_liq_context);
+        self.emit_add_liquidity_event(&storage_cache, add_liq_context);
+
+        // the reserves are committed before the hooks are called
+        drop(storage_cache);
+        self.call_after_swap_hooks(swap_hook_data);
+        self.call_liquidity_hooks(HookType::AfterAddLiquidity, add_liq_hook_data);
+
+        output
+    }
+
+    /// Removes liquidity and swaps the other pool token into `token_out`,
+    /// so that the whole position is received as `token_out`.
+    #[payable("*")]
+    #[endpoint(removeLiquidityToSingleToken)]
+    fn remove_liquidity_to_single_token(
+        &self,
+        token_out: EgldOrEsdtTokenIdentifier,
+        amount_out_min: BigUint,
+        opt_deadline: OptionalValue<u64>,
+    ) -> EgldOrEsdtTokenPayment {
+        require!(amount_out_min > 0, ERROR_INVALID_ARGS);
+        self.require_deadline_not_expired(opt_deadline);
+
+        let caller = self.blockchain().get_caller();
+        let payment = self.call_value().single_esdt();
+
+        let first_token_id = self.first_token_id().get();
+        let second_token_id = self.second_token_id().get();
+        let (token_in, first_token_amount_min, second_token_amount_min) =
+            if token_out == first_token_id {
+                (second_token_id, amount_out_min.clone(), BigUint::zero())
+            } else {
+                (first_token_id, BigUint::zero(), amount_out_min.clone())
+            };
+
+        self.call_liquidity_hooks(
+            HookType::BeforeRemoveLiquidity,
+            LiquidityHookData {
+                caller: caller.clone(),
+                first_token_amount: first_token_amount_min,
+                second_token_amount: second_token_amount_min,
+                lp_token_amount: payment.amount.clone(),
+            },
+        );
+        let fee_percent = self.get_swap_fee_percent_from_hooks(
+            &caller,
+            &token_in,
+            &BigUint::zero(),
+            &token_out,
+            &amount_out_min,
+        );
+
+        let mut storage_cache = StorageCache::new(self);
+        let swap_tokens_order = storage_cache.get_swap_tokens_order(&token_in, &token_out);
+
+        require!(
+            self.can_swap(storage_cache.contract_state),
+            ERROR_SWAP_NOT_ENABLED
+        );
+        require!(
+            !self.should_generate_locked_asset(),
+            ERROR_SWAP_OUTPUT_LOCKED
+        );
+        require!(
+            storage_cache.lp_token_id.is_valid_esdt_identifier(),
+            ERROR_LP_TOKEN_NOT_ISSUED
+        );
+        require!(
+            payment.token_identifier == storage_cache.lp_token_id && payment.amount > 0,
+            ERROR_BAD_PAYMENT_TOKENS
+        );
+
+        self.update_safe_price(
+            &storage_cache.first_token_reserve,
+            &storage_cache.second_token_reserve,
+        );
+
+        let initial_k = self.calculate_k_constant(
+            &storage_cache.first_token_reserve,
+            &storage_cache.second_token_reserve,
+        );
+
+        self.mint_protocol_fee(&mut storage_cache);
+
+        let mut remove_liq_context =
+            RemoveLiquidityContext::new(payment.amount, BigUint::from(1u64), BigUint::from(1u64));
+        self.pool_remove_liquidity(&mut remove_liq_context, &mut storage_cache);
+
+        let new_k = self.calculate_k_constant(
+            &storage_cache.first_token_reserve,
+            &storage_cache.second_token_reserve,
+        );
+        require!(new_k <= initial_k, ERROR_K_INVARIANT_FAILED);
+        // before the swap, so that its fee is minted to the protocol on the next liquidity event
+        self.update_k_last(&storage_cache);
+
+        self.burn(
+            &storage_cache.lp_token_id,
+            &remove_liq_context.lp_token_payment_amount,
+        );
+
+        let (swap_amount, amount_removed_out) = match swap_tokens_order {
+            SwapTokensOrder::PoolOrder => (
+                remove_liq_context.first_token_amount_removed.clone(),
+                remove_liq_context.second_token_amount_removed.clone(),
+            ),
+            SwapTokensOrder::ReverseOrder => (
+                remove_liq_context.second_token_amount_removed.clone(),
+                remove_liq_context.first_token_amount_removed.clone(),
+            ),
+        };
+
+        let remove_liq_hook_data = LiquidityHookData {
+            caller: caller.clone(),
+            first_token_amount: remove_liq_context.first_token_amount_removed.clone(),
+            second_token_amount: remove_liq_context.second_token_amount_removed.clone(),
+            lp_token_amount: remove_liq_context.lp_token_payment_amount.clone(),
+        };
+        self.emit_remove_liquidity_event(&storage_cache, remove_liq_context);
+
+        let initial_k = self.calculate_k_constant(
+            &storage_cache.first_token_reserve,
+            &storage_cache.second_token_reserve,
+        );
+        let first_token_reserve_before = storage_cache.first_token_reserve.clone();
+        let second_token_reserve_before = storage_cache.second_token_reserve.clone();
+
+        let mut swap_context = SwapContext::new(
+            token_in,
+            swap_amount,
+            token_out,
+            BigUint::from(1u32),
+            swap_tokens_order,
+        );
+        swap_context.fee_percent = fee_percent;
+        self.perform_swap_fixed_input(&mut swap_context, &mut storage_cache);
+
+        let new_k = self.calculate_k_constant(
+            &storage_cache.first_token_reserve,
+            &storage_cache.second_token_reserve,
+        );
+        require!(initial_k <= new_k, ERROR_K_INVARIANT_FAILED);
+        self.require_price_deviation_within_limit(
+            &first_token_reserve_before,
+            &second_token_reserve_before,
+            &storage_cache.first_token_reserve,
+            &storage_cache.second_token_reserve,
+        );
+
+        if swap_context.fee_amount > 0 {
+            self.send_fee(
+                &mut storage_cache,
+                swap_context.swap_tokens_order,
+                &swap_context.input_token_id,
+                &swap_context.fee_amount,
+            );
+        }
+
+        let output_payment = EgldOrEsdtTokenPayment::new(
+            swap_context.output_token_id.clone(),
+            0,
+            amount_removed_out + &swap_context.final_output_amount,
+        );
+        require!(
+            output_payment.amount >= amount_out_min,
+            ERROR_SLIPPAGE_EXCEEDED
+        );
+
+        self.send().direct_non_zero(
+            &caller,
+            &output_payment.token_identifier,
+            0,
+            &output_payment.amount,
+        );
+
+        let swap_hook_data = self.build_swap_hook_data(&caller, &swap_context);
+        self.emit_swap_event(&storage_cache, swap_context);
+
+        // the reserves are committed before the hooks are called
+        drop(storage_cache);
+        self.call_liquidity_hooks(HookType::AfterRemoveLiquidity, remove_liq_hook_data);
+        self.call_after_swap_hooks(swap_hook_data);
+
+        output_payment
+    }
+}
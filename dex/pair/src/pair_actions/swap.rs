@@ -1,7 +1,7 @@
 use crate::{
-    contexts::swap::SwapContext, StorageCache, ERROR_INVALID_ARGS, ERROR_K_INVARIANT_FAILED,
-    ERROR_NOT_ENOUGH_RESERVE, ERROR_NOT_WHITELISTED, ERROR_SLIPPAGE_EXCEEDED,
-    ERROR_SWAP_NOT_ENABLED, ERROR_ZERO_AMOUNT,
+    contexts::swap::SwapContext, hooks::SwapHookData, StorageCache, ERROR_INVALID_ARGS,
+    ERROR_K_INVARIANT_FAILED, ERROR_NOT_ENOUGH_RESERVE, ERROR_NOT_FEE_ROUTE_CALLER,
+    ERROR_NOT_WHITELISTED, ERROR_SLIPPAGE_EXCEEDED, ERROR_SWAP_NOT_ENABLED, ERROR_ZERO_AMOUNT,
 };
 
 use super::common_result_types::{SwapTokensFixedInputResultType, SwapTokensFixedOutputResultType};
@@ -19,11 +19,22 @@ pub enum SwapType {
 pub trait SwapModule:
     crate::liquidity_pool::LiquidityPoolModule
     + crate::amm::AmmModule
+    + crate::stable_swap::StableSwapModule
     + crate::contexts::output_builder::OutputBuilderModule
     + crate::locking_wrapper::LockingWrapperModule
     + crate::events::EventsModule
     + crate::safe_price::SafePriceModule
+    + crate::safe_price_view::SafePriceViewModule
+    + crate::circuit_breaker::CircuitBreakerModule
+    + crate::dynamic_fee::DynamicFeeModule
+    + crate::energy_fee_discount::EnergyFeeDiscountModule
+    + energy_query::EnergyQueryModule
     + crate::fee::FeeModule
+    + crate::protocol_fee::ProtocolFeeModule
+    + crate::trading_stats::TradingStatsModule
+    + crate::no_fee_swap_quota::NoFeeSwapQuotaModule
+    + crate::hooks::HooksModule
+    + week_timekeeping::WeekTimekeepingModule
     + crate::config::ConfigModule
     + token_send::TokenSendModule
     + permissions_module::PermissionsModule
@@ -35,12 +46,79 @@ pub trait SwapModule:
     #[endpoint(swapNoFeeAndForward)]
     fn swap_no_fee(&self, token_out: TokenIdentifier, destination_address: ManagedAddress) {
         let caller = self.blockchain().get_caller();
-        require!(self.whitelist().contains(&caller), ERROR_NOT_WHITELISTED);
+        let swap_context = self.swap_no_fee_common(&caller, token_out.clone());
+        self.burn(&token_out, &swap_context.final_output_amount);
+
+        let hook_data = self.build_swap_hook_data(&caller, &swap_context);
+        self.emit_swap_no_fee_and_forward_event(swap_context, destination_address);
+
+        self.call_after_swap_hooks(hook_data);
+    }
+
+    /// Same as `swapNoFeeAndForward`, but the output tokens are sent back to the caller
+    /// instead of being burned. Only used by the pairs registered as fee route callers,
+    /// for the intermediate hops of their fee routes.
+    #[payable("*")]
+    #[endpoint(swapNoFeeAndReturn)]
+    fn swap_no_fee_and_return(&self, token_out: TokenIdentifier) -> EsdtTokenPayment {
+        let caller = self.blockchain().get_caller();
+        require!(
+            self.fee_route_callers().contains(&caller),
+            ERROR_NOT_FEE_ROUTE_CALLER
+        );
+
+        let swap_context = self.swap_no_fee_common(&caller, token_out.clone());
+        let output_payment =
+            EsdtTokenPayment::new(token_out, 0, swap_context.final_output_amount.clone());
+        self.send().direct_esdt(
+            &caller,
+            &output_payment.token_identifier,
+            0,
+            &output_payment.amount,
+        );
+
+        let hook_data = self.build_swap_hook_data(&caller, &swap_context);
+        self.emit_swap_no_fee_and_forward_event(swap_context, caller);
+
+        self.call_after_swap_hooks(hook_data);
+
+        output_payment
+    }
+
+    /// Fails if the no fee swap quota of the caller is exceeded.
+    /// The price deviation limit is not checked, as the whitelisted callers are pairs converting
+    /// their fees, whose own swaps would fail otherwise. The quota bounds these swaps instead.
+    /// The reserves are committed when this returns, so the after swap hooks are called by the endpoints.
+    /// Only ESDTs are swapped, as the output is burned or used in the fee routes of other pairs,
+    /// so the EGLD side of a pair cannot be swapped with no fee.
+    fn swap_no_fee_common(
+        &self,
+        caller: &ManagedAddress,
+        token_out: TokenIdentifier,
+    ) -> SwapContext<Self::Api> {
+        require!(self.whitelist().contains(caller), ERROR_NOT_WHITELISTED);
 
-        let mut storage_cache = StorageCache::new(self);
         let payment = self.call_value().single_esdt();
-        let swap_tokens_order =
-            storage_cache.get_swap_tokens_order(&payment.token_identifier, &token_out);
+        self.consume_no_fee_swap_quota(caller, &payment.token_identifier, &payment.amount);
+
+        let token_in = EgldOrEsdtTokenIdentifier::esdt(payment.token_identifier);
+        let token_out = EgldOrEsdtTokenIdentifier::esdt(token_out);
+
+        // no fee is charged, so a fee returned by the hooks is ignored
+        self.call_before_swap_hooks(
+            SwapHookData {
+                caller: caller.clone(),
+                input_token_id: token_in.clone(),
+                input_token_amount: payment.amount.clone(),
+                output_token_id: token_out.clone(),
+                output_token_amount: BigUint::from(1u32),
+                fee_percent: 0,
+            },
+            self.get_max_swap_fee_percent(),
+        );
+
+        let mut storage_cache = StorageCache::new(self);
+        let swap_tokens_order = storage_cache.get_swap_tokens_order(&token_in, &token_out);
 
         require!(
             self.can_swap(storage_cache.contract_state),
@@ -58,7 +136,7 @@ pub trait SwapModule:
         );
 
         let mut swap_context = SwapContext::new(
-            payment.token_identifier,
+            token_in,
             payment.amount.clone(),
             token_out,
             BigUint::from(1u32),
@@ -74,6 +152,7 @@ pub trait SwapModule:
         require!(amount_out > 0u64, ERROR_ZERO_AMOUNT);
 
         swap_context.final_output_amount = amount_out;
+        self.record_swap(&swap_context);
 
         let new_k = self.calculate_k_constant(
             &storage_cache.first_token_reserve,
@@ -81,27 +160,32 @@ pub trait SwapModule:
         );
         require!(initial_k <= new_k, ERROR_K_INVARIANT_FAILED);
 
-        self.burn(
-            &swap_context.output_token_id,
-            &swap_context.final_output_amount,
-        );
-
-        self.emit_swap_no_fee_and_forward_event(swap_context, destination_address);
+        swap_context
     }
 
     #[payable("*")]
     #[endpoint(swapTokensFixedInput)]
     fn swap_tokens_fixed_input(
         &self,
-        token_out: TokenIdentifier,
+        token_out: EgldOrEsdtTokenIdentifier,
         amount_out_min: BigUint,
+        opt_deadline: OptionalValue<u64>,
     ) -> SwapTokensFixedInputResultType<Self::Api> {
         require!(amount_out_min > 0, ERROR_INVALID_ARGS);
+        self.require_deadline_not_expired(opt_deadline);
+
+        let caller = self.blockchain().get_caller();
+        let (token_in, amount_in) = self.call_value().egld_or_single_fungible_esdt();
+        let fee_percent = self.get_swap_fee_percent_from_hooks(
+            &caller,
+            &token_in,
+            &amount_in,
+            &token_out,
+            &amount_out_min,
+        );
 
         let mut storage_cache = StorageCache::new(self);
-        let payment = self.call_value().single_esdt();
-        let swap_tokens_order =
-            storage_cache.get_swap_tokens_order(&payment.token_identifier, &token_out);
+        let swap_tokens_order = storage_cache.get_swap_tokens_order(&token_in, &token_out);
 
         require!(
             self.can_swap(storage_cache.contract_state),
@@ -120,14 +204,17 @@ pub trait SwapModule:
             &storage_cache.first_token_reserve,
             &storage_cache.second_token_reserve,
         );
+        let first_token_reserve_before = storage_cache.first_token_reserve.clone();
+        let second_token_reserve_before = storage_cache.second_token_reserve.clone();
 
         let mut swap_context = SwapContext::new(
-            payment.token_identifier,
-            payment.amount,
+            token_in,
+            amount_in,
             token_out,
             amount_out_min,
             swap_tokens_order,
         );
+        swap_context.fee_percent = fee_percent;
         self.perform_swap_fixed_input(&mut swap_context, &mut storage_cache);
 
         let new_k = self.calculate_k_constant(
@@ -135,6 +222,12 @@ pub trait SwapModule:
             &storage_cache.second_token_reserve,
         );
         require!(initial_k <= new_k, ERROR_K_INVARIANT_FAILED);
+        self.require_price_deviation_within_limit(
+            &first_token_reserve_before,
+            &second_token_reserve_before,
+            &storage_cache.first_token_reserve,
+            &storage_cache.second_token_reserve,
+        );
 
         if swap_context.fee_amount > 0 {
             self.send_fee(
@@ -145,18 +238,22 @@ pub trait SwapModule:
             );
         }
 
-        let caller = self.blockchain().get_caller();
         let output_payments = self.build_swap_output_payments(&swap_context);
 
         require!(
-            output_payments.get(0).amount >= swap_context.output_token_amount,
+            output_payments[0].amount >= swap_context.output_token_amount,
             ERROR_SLIPPAGE_EXCEEDED
         );
 
-        self.send_multiple_tokens_if_not_zero(&caller, &output_payments);
+        self.send_payments_non_zero(&caller, &output_payments);
 
+        let hook_data = self.build_swap_hook_data(&caller, &swap_context);
         self.emit_swap_event(&storage_cache, swap_context);
 
+        // the reserves are committed before the hooks are called
+        drop(storage_cache);
+        self.call_after_swap_hooks(hook_data);
+
         self.build_swap_fixed_input_results(output_payments)
     }
 
@@ -164,15 +261,25 @@ pub trait SwapModule:
     #[endpoint(swapTokensFixedOutput)]
     fn swap_tokens_fixed_output(
         &self,
-        token_out: TokenIdentifier,
+        token_out: EgldOrEsdtTokenIdentifier,
         amount_out: BigUint,
+        opt_deadline: OptionalValue<u64>,
     ) -> SwapTokensFixedOutputResultType<Self::Api> {
         require!(amount_out > 0, ERROR_INVALID_ARGS);
+        self.require_deadline_not_expired(opt_deadline);
+
+        let caller = self.blockchain().get_caller();
+        let (token_in, amount_in) = self.call_value().egld_or_single_fungible_esdt();
+        let fee_percent = self.get_swap_fee_percent_from_hooks(
+            &caller,
+            &token_in,
+            &amount_in,
+            &token_out,
+            &amount_out,
+        );
 
         let mut storage_cache = StorageCache::new(self);
-        let payment = self.call_value().single_esdt();
-        let swap_tokens_order =
-            storage_cache.get_swap_tokens_order(&payment.token_identifier, &token_out);
+        let swap_tokens_order = storage_cache.get_swap_tokens_order(&token_in, &token_out);
 
         require!(
             self.can_swap(storage_cache.contract_state),
@@ -191,14 +298,17 @@ pub trait SwapModule:
             &storage_cache.first_token_reserve,
             &storage_cache.second_token_reserve,
         );
+        let first_token_reserve_before = storage_cache.first_token_reserve.clone();
+        let second_token_reserve_before = storage_cache.second_token_reserve.clone();
 
         let mut swap_context = SwapContext::new(
-            payment.token_identifier,
-            payment.amount,
+            token_in,
+            amount_in,
             token_out,
             amount_out,
             swap_tokens_order,
         );
+        swap_context.fee_percent = fee_percent;
         self.perform_swap_fixed_output(&mut swap_context, &mut storage_cache);
 
         let new_k = self.calculate_k_constant(
@@ -206,6 +316,12 @@ pub trait SwapModule:
             &storage_cache.second_token_reserve,
         );
         require!(initial_k <= new_k, ERROR_K_INVARIANT_FAILED);
+        self.require_price_deviation_within_limit(
+            &first_token_reserve_before,
+            &second_token_reserve_before,
+            &storage_cache.first_token_reserve,
+            &storage_cache.second_token_reserve,
+        );
 
         if swap_context.fee_amount > 0 {
             self.send_fee(
@@ -216,16 +332,42 @@ pub trait SwapModule:
             );
         }
 
-        let caller = self.blockchain().get_caller();
         let output_payments = self.build_swap_output_payments(&swap_context);
 
-        self.send_multiple_tokens_if_not_zero(&caller, &output_payments);
+        self.send_payments_non_zero(&caller, &output_payments);
 
+        let hook_data = self.build_swap_hook_data(&caller, &swap_context);
         self.emit_swap_event(&storage_cache, swap_context);
 
+        // the reserves are committed before the hooks are called
+        drop(storage_cache);
+        self.call_after_swap_hooks(hook_data);
+
         self.build_swap_fixed_output_results(output_payments)
     }
 
+    /// Calls the before swap hooks with the fee of the caller, and returns the fee they settle on.
+    fn get_swap_fee_percent_from_hooks(
+        &self,
+        caller: &ManagedAddress,
+        input_token_id: &EgldOrEsdtTokenIdentifier,
+        input_token_amount: &BigUint,
+        output_token_id: &EgldOrEsdtTokenIdentifier,
+        output_token_amount: &BigUint,
+    ) -> u64 {
+        self.call_before_swap_hooks(
+            SwapHookData {
+                caller: caller.clone(),
+                input_token_id: input_token_id.clone(),
+                input_token_amount: input_token_amount.clone(),
+                output_token_id: output_token_id.clone(),
+                output_token_amount: output_token_amount.clone(),
+                fee_percent: self.get_user_swap_fee_percent(caller),
+            },
+            self.get_max_swap_fee_percent(),
+        )
+    }
+
     fn perform_swap_fixed_input(
         &self,
         context: &mut SwapContext<Self::Api>,
@@ -236,8 +378,12 @@ pub trait SwapModule:
         let reserve_in = storage_cache.get_reserve_in(context.swap_tokens_order);
         let reserve_out = storage_cache.get_reserve_out(context.swap_tokens_order);
 
-        let amount_out_optimal =
-            self.get_amount_out(&context.input_token_amount, reserve_in, reserve_out);
+        let amount_out_optimal = self.get_amount_out(
+            &context.input_token_amount,
+            reserve_in,
+            reserve_out,
+            context.fee_percent,
+        );
         require!(
             amount_out_optimal >= context.output_token_amount,
             ERROR_SLIPPAGE_EXCEEDED
@@ -248,7 +394,7 @@ pub trait SwapModule:
         context.final_output_amount = amount_out_optimal;
 
         let mut amount_in_after_fee = context.input_token_amount.clone();
-        if self.is_fee_enabled() {
+        if self.should_send_special_fee() {
             let fee_amount = self.get_special_fee_from_input(&amount_in_after_fee);
             amount_in_after_fee -= &fee_amount;
 
@@ -258,6 +404,8 @@ pub trait SwapModule:
         *storage_cache.get_mut_reserve_in(context.swap_tokens_order) += amount_in_after_fee;
         *storage_cache.get_mut_reserve_out(context.swap_tokens_order) -=
             &context.final_output_amount;
+
+        self.record_swap(context);
     }
 
     fn perform_swap_fixed_output(
@@ -270,8 +418,12 @@ pub trait SwapModule:
         let reserve_in = storage_cache.get_reserve_in(context.swap_tokens_order);
         let reserve_out = storage_cache.get_reserve_out(context.swap_tokens_order);
 
-        let amount_in_optimal =
-            self.get_amount_in(&context.output_token_amount, reserve_in, reserve_out);
+        let amount_in_optimal = self.get_amount_in(
+            &context.output_token_amount,
+            reserve_in,
+            reserve_out,
+            context.fee_percent,
+        );
         require!(
             amount_in_optimal <= context.input_token_amount,
             ERROR_SLIPPAGE_EXCEEDED
@@ -281,7 +433,7 @@ pub trait SwapModule:
         context.final_input_amount = amount_in_optimal.clone();
 
         let mut amount_in_optimal_after_fee = amount_in_optimal;
-        if self.is_fee_enabled() {
+        if self.should_send_special_fee() {
             let fee_amount = self.get_special_fee_from_input(&amount_in_optimal_after_fee);
             amount_in_optimal_after_fee -= &fee_amount;
 
@@ -291,5 +443,7 @@ pub trait SwapModule:
         *storage_cache.get_mut_reserve_in(context.swap_tokens_order) += amount_in_optimal_after_fee;
         *storage_cache.get_mut_reserve_out(context.swap_tokens_order) -=
             &context.final_output_amount;
+
+        self.record_swap(context);
     }
 }
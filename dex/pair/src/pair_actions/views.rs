@@ -1,4 +1,4 @@
-use crate::{ERROR_NOT_ENOUGH_RESERVE, ERROR_UNKNOWN_TOKEN, ERROR_ZERO_AMOUNT};
+use crate::{config::CurveType, ERROR_NOT_ENOUGH_RESERVE, ERROR_UNKNOWN_TOKEN, ERROR_ZERO_AMOUNT};
 
 multiversx_sc::imports!();
 
@@ -6,11 +6,18 @@ multiversx_sc::imports!();
 pub trait ViewsModule:
     crate::liquidity_pool::LiquidityPoolModule
     + crate::amm::AmmModule
+    + crate::stable_swap::StableSwapModule
     + crate::contexts::output_builder::OutputBuilderModule
     + crate::locking_wrapper::LockingWrapperModule
     + crate::events::EventsModule
     + crate::safe_price::SafePriceModule
+    + crate::safe_price_view::SafePriceViewModule
+    + crate::dynamic_fee::DynamicFeeModule
+    + crate::energy_fee_discount::EnergyFeeDiscountModule
+    + energy_query::EnergyQueryModule
     + crate::fee::FeeModule
+    + crate::trading_stats::TradingStatsModule
+    + week_timekeeping::WeekTimekeepingModule
     + crate::config::ConfigModule
     + token_send::TokenSendModule
     + permissions_module::PermissionsModule
@@ -21,7 +28,7 @@ pub trait ViewsModule:
     fn get_tokens_for_given_position(
         &self,
         liquidity: BigUint,
-    ) -> MultiValue2<EsdtTokenPayment<Self::Api>, EsdtTokenPayment<Self::Api>> {
+    ) -> MultiValue2<EgldOrEsdtTokenPayment<Self::Api>, EgldOrEsdtTokenPayment<Self::Api>> {
         self.get_both_tokens_for_given_position(liquidity)
     }
 
@@ -35,25 +42,40 @@ pub trait ViewsModule:
         (first_token_reserve, second_token_reserve, total_supply).into()
     }
 
+    /// When `opt_user` is provided, the user's energy fee discount is also applied.
     #[view(getAmountOut)]
-    fn get_amount_out_view(&self, token_in: TokenIdentifier, amount_in: BigUint) -> BigUint {
+    fn get_amount_out_view(
+        &self,
+        token_in: EgldOrEsdtTokenIdentifier,
+        amount_in: BigUint,
+        opt_user: OptionalValue<ManagedAddress>,
+    ) -> BigUint {
         require!(amount_in > 0u64, ERROR_ZERO_AMOUNT);
 
         let first_token_id = self.first_token_id().get();
         let second_token_id = self.second_token_id().get();
         let first_token_reserve = self.pair_reserve(&first_token_id).get();
         let second_token_reserve = self.pair_reserve(&second_token_id).get();
+        let fee_percent = self.get_view_swap_fee_percent(opt_user);
 
         if token_in == first_token_id {
             require!(second_token_reserve > 0u64, ERROR_NOT_ENOUGH_RESERVE);
-            let amount_out =
-                self.get_amount_out(&amount_in, &first_token_reserve, &second_token_reserve);
+            let amount_out = self.get_amount_out(
+                &amount_in,
+                &first_token_reserve,
+                &second_token_reserve,
+                fee_percent,
+            );
             require!(second_token_reserve > amount_out, ERROR_NOT_ENOUGH_RESERVE);
             amount_out
         } else if token_in == second_token_id {
             require!(first_token_reserve > 0u64, ERROR_NOT_ENOUGH_RESERVE);
-            let amount_out =
-                self.get_amount_out(&amount_in, &second_token_reserve, &first_token_reserve);
+            let amount_out = self.get_amount_out(
+                &amount_in,
+                &second_token_reserve,
+                &first_token_reserve,
+                fee_percent,
+            );
             require!(first_token_reserve > amount_out, ERROR_NOT_ENOUGH_RESERVE);
             amount_out
         } else {
@@ -61,14 +83,21 @@ pub trait ViewsModule:
         }
     }
 
+    /// When `opt_user` is provided, the user's energy fee discount is also applied.
     #[view(getAmountIn)]
-    fn get_amount_in_view(&self, token_wanted: TokenIdentifier, amount_wanted: BigUint) -> BigUint {
+    fn get_amount_in_view(
+        &self,
+        token_wanted: EgldOrEsdtTokenIdentifier,
+        amount_wanted: BigUint,
+        opt_user: OptionalValue<ManagedAddress>,
+    ) -> BigUint {
         require!(amount_wanted > 0u64, ERROR_ZERO_AMOUNT);
 
         let first_token_id = self.first_token_id().get();
         let second_token_id = self.second_token_id().get();
         let first_token_reserve = self.pair_reserve(&first_token_id).get();
         let second_token_reserve = self.pair_reserve(&second_token_id).get();
+        let fee_percent = self.get_view_swap_fee_percent(opt_user);
 
         if token_wanted == first_token_id {
             require!(
@@ -76,21 +105,31 @@ pub trait ViewsModule:
                 ERROR_NOT_ENOUGH_RESERVE
             );
 
-            self.get_amount_in(&amount_wanted, &second_token_reserve, &first_token_reserve)
+            self.get_amount_in(
+                &amount_wanted,
+                &second_token_reserve,
+                &first_token_reserve,
+                fee_percent,
+            )
         } else if token_wanted == second_token_id {
             require!(
                 second_token_reserve > amount_wanted,
                 ERROR_NOT_ENOUGH_RESERVE
             );
 
-            self.get_amount_in(&amount_wanted, &first_token_reserve, &second_token_reserve)
+            self.get_amount_in(
+                &amount_wanted,
+                &first_token_reserve,
+                &second_token_reserve,
+                fee_percent,
+            )
         } else {
             sc_panic!(ERROR_UNKNOWN_TOKEN);
         }
     }
 
     #[view(getEquivalent)]
-    fn get_equivalent(&self, token_in: TokenIdentifier, amount_in: BigUint) -> BigUint {
+    fn get_equivalent(&self, token_in: EgldOrEsdtTokenIdentifier, amount_in: BigUint) -> BigUint {
         require!(amount_in > 0u64, ERROR_ZERO_AMOUNT);
         let zero = BigUint::zero();
 
@@ -102,12 +141,27 @@ pub trait ViewsModule:
             return zero;
         }
 
-        if token_in == first_token_id {
-            self.quote(&amount_in, &first_token_reserve, &second_token_reserve)
+        let (reserve_in, reserve_out) = if token_in == first_token_id {
+            (first_token_reserve, second_token_reserve)
         } else if token_in == second_token_id {
-            self.quote(&amount_in, &second_token_reserve, &first_token_reserve)
+            (second_token_reserve, first_token_reserve)
         } else {
             sc_panic!(ERROR_UNKNOWN_TOKEN);
+        };
+
+        // The reserves ratio is not the price on the StableSwap curve
+        match self.curve_type().get() {
+            CurveType::ConstantProduct => self.quote(&amount_in, &reserve_in, &reserve_out),
+            CurveType::StableSwap => {
+                self.get_amount_out_no_fee(&amount_in, &reserve_in, &reserve_out)
+            }
+        }
+    }
+
+    fn get_view_swap_fee_percent(&self, opt_user: OptionalValue<ManagedAddress>) -> u64 {
+        match opt_user {
+            OptionalValue::Some(user) => self.get_user_swap_fee_percent(&user),
+            OptionalValue::None => self.get_swap_fee_percent(),
         }
     }
 }
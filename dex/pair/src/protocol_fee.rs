@@ -0,0 +1,177 @@
+multiversx_sc::imports!();
+multiversx_sc::derive_imports!();
+
+use crate::config::{self, CurveType, MAX_PERCENTAGE};
+use crate::contexts::base::StorageCache;
+use crate::errors::*;
+use crate::{amm, events, fee, liquidity_pool, stable_swap, trading_stats};
+
+/// `share_percent` is the part of the liquidity growth, in MAX_PERCENTAGE units,
+/// that is minted as LP tokens to the `recipient`.
+#[derive(TopEncode, TopDecode, NestedEncode, NestedDecode, TypeAbi, Clone, PartialEq)]
+pub struct ProtocolFeeConfig<M: ManagedTypeApi> {
+    pub recipient: ManagedAddress<M>,
+    pub share_percent: u64,
+}
+
+#[multiversx_sc::module]
+pub trait ProtocolFeeModule:
+    fee::FeeModule
+    + config::ConfigModule
+    + liquidity_pool::LiquidityPoolModule
+    + amm::AmmModule
+    + stable_swap::StableSwapModule
+    + trading_stats::TradingStatsModule
+    + week_timekeeping::WeekTimekeepingModule
+    + events::EventsModule
+    + token_send::TokenSendModule
+    + permissions_module::PermissionsModule
+    + pausable::PausableModule
+{
+    /// Enables the protocol fee mode. While enabled, the special fee is left in the reserves
+    /// instead of being sent out on every swap, and the protocol's share of the sqrt(K) growth
+    /// is minted as LP tokens on the next liquidity event, to be claimed by the recipient.
+    /// Only available for the ConstantProduct curve.
+    #[endpoint(setProtocolFee)]
+    fn set_protocol_fee(&self, recipient: ManagedAddress, share_percent: u64) {
+        self.require_caller_has_owner_permissions();
+        require!(
+            self.curve_type().get() == CurveType::ConstantProduct,
+            ERROR_NOT_CONSTANT_PRODUCT_CURVE
+        );
+        require!(
+            share_percent > 0 && share_percent < MAX_PERCENTAGE,
+            ERROR_BAD_PERCENTS
+        );
+
+        let mut storage_cache = StorageCache::new(self);
+        self.mint_protocol_fee(&mut storage_cache);
+
+        self.protocol_fee_config().set(ProtocolFeeConfig {
+            recipient,
+            share_percent,
+        });
+        self.update_k_last(&storage_cache);
+    }
+
+    /// Mints the pending protocol fee, then goes back to sending the special fee on every swap.
+    #[endpoint(disableProtocolFee)]
+    fn disable_protocol_fee(&self) {
+        self.require_caller_has_owner_permissions();
+
+        let mut storage_cache = StorageCache::new(self);
+        self.mint_protocol_fee(&mut storage_cache);
+
+        self.protocol_fee_config().clear();
+        self.k_last().clear();
+    }
+
+    /// Sends the LP tokens accrued as protocol fee to the caller.
+    #[endpoint(claimProtocolFee)]
+    fn claim_protocol_fee(&self) -> EsdtTokenPayment {
+        let caller = self.blockchain().get_caller();
+        let accrued_lp = self.accrued_protocol_fee_lp(&caller).take();
+        let lp_payment = EsdtTokenPayment::new(self.lp_token_identifier().get(), 0, accrued_lp);
+        self.send_payment_non_zero(&caller, &lp_payment);
+
+        lp_payment
+    }
+
+    /// The amount of LP tokens that would be minted to the protocol fee recipient
+    /// if a liquidity event happened now.
+    #[view(getPendingProtocolFeeLp)]
+    fn get_pending_protocol_fee_lp(&self) -> BigUint {
+        let config_mapper = self.protocol_fee_config();
+        if config_mapper.is_empty() {
+            return BigUint::zero();
+        }
+
+        self.compute_protocol_fee_lp(
+            config_mapper.get().share_percent,
+            &self.pair_reserve(&self.first_token_id().get()).get(),
+            &self.pair_reserve(&self.second_token_id().get()).get(),
+            &self.lp_token_supply().get(),
+        )
+    }
+
+    fn should_send_special_fee(&self) -> bool {
+        self.is_fee_enabled() && self.protocol_fee_config().is_empty()
+    }
+
+    /// Has to be called before the reserves or the LP supply change in a liquidity event.
+    fn mint_protocol_fee(&self, storage_cache: &mut StorageCache<Self>) {
+        let config_mapper = self.protocol_fee_config();
+        if config_mapper.is_empty() {
+            return;
+        }
+
+        let config = config_mapper.get();
+        let protocol_fee_lp = self.compute_protocol_fee_lp(
+            config.share_percent,
+            &storage_cache.first_token_reserve,
+            &storage_cache.second_token_reserve,
+            &storage_cache.lp_token_supply,
+        );
+        if protocol_fee_lp == 0 {
+            return;
+        }
+
+        // the LP tokens are kept by the pair until claimed, so a recipient
+        // that can not receive tokens does not block the liquidity actions
+        storage_cache.lp_token_supply += &protocol_fee_lp;
+        self.send()
+            .esdt_local_mint(&storage_cache.lp_token_id, 0, &protocol_fee_lp);
+        self.accrued_protocol_fee_lp(&config.recipient)
+            .update(|accrued_lp| *accrued_lp += protocol_fee_lp);
+    }
+
+    /// Has to be called after the reserves and the LP supply change in a liquidity event.
+    fn update_k_last(&self, storage_cache: &StorageCache<Self>) {
+        if self.protocol_fee_config().is_empty() {
+            return;
+        }
+
+        self.k_last()
+            .set(&storage_cache.first_token_reserve * &storage_cache.second_token_reserve);
+    }
+
+    fn compute_protocol_fee_lp(
+        &self,
+        share_percent: u64,
+        first_token_reserve: &BigUint,
+        second_token_reserve: &BigUint,
+        lp_token_supply: &BigUint,
+    ) -> BigUint {
+        let k_last = self.k_last().get();
+        if k_last == 0
+            || *lp_token_supply == 0
+            || self.curve_type().get() != CurveType::ConstantProduct
+        {
+            return BigUint::zero();
+        }
+
+        let root_k = (first_token_reserve * second_token_reserve).sqrt();
+        let root_k_last = k_last.sqrt();
+        if root_k <= root_k_last {
+            return BigUint::zero();
+        }
+
+        // liquidity = S * (sqrt(K) - sqrt(K_last)) * p / ((1 - p) * sqrt(K) + p * sqrt(K_last))
+        let numerator = lp_token_supply * &(&root_k - &root_k_last) * share_percent;
+        let denominator = root_k * (MAX_PERCENTAGE - share_percent) + root_k_last * share_percent;
+
+        numerator / denominator
+    }
+
+    #[view(getProtocolFeeConfig)]
+    #[storage_mapper("protocol_fee_config")]
+    fn protocol_fee_config(&self) -> SingleValueMapper<ProtocolFeeConfig<Self::Api>>;
+
+    #[view(getKLast)]
+    #[storage_mapper("k_last")]
+    fn k_last(&self) -> SingleValueMapper<BigUint>;
+
+    #[view(getAccruedProtocolFeeLp)]
+    #[storage_mapper("accrued_protocol_fee_lp")]
+    fn accrued_protocol_fee_lp(&self, recipient: &ManagedAddress) -> SingleValueMapper<BigUint>;
+}
@@ -1,18 +1,35 @@
 multiversx_sc::imports!();
 multiversx_sc::derive_imports!();
 
-use crate::{amm, config, errors::ERROR_SAFE_PRICE_CURRENT_INDEX};
+use multiversx_sc::codec::{NestedDecodeInput, TopDecodeInput};
+
+use crate::{
+    amm, config,
+    errors::{ERROR_BAD_OBSERVATIONS_CAPACITY, ERROR_SAFE_PRICE_CURRENT_INDEX},
+    stable_swap,
+};
 
 pub type Round = u64;
 
-pub const MAX_OBSERVATIONS: usize = 65_536; // 2^{16} records, to optimise binary search
+pub const DEFAULT_OBSERVATIONS_CAPACITY: usize = 65_536; // 2^{16} records, to optimise binary search
+pub const MIN_OBSERVATIONS_CAPACITY: usize = 2;
+pub const MAX_OBSERVATIONS_CAPACITY: usize = 1_048_576; // 2^{20} records
+pub const MAX_OBSERVATIONS_REMOVED_PER_UPDATE: usize = 2;
+pub const LOG2_PRECISION_BITS: usize = 32;
+pub const LOG2_MANTISSA_BITS: usize = 64;
 
-#[derive(ManagedVecItem, Clone, TopEncode, TopDecode, NestedEncode, NestedDecode, TypeAbi)]
+/// The log reserve accumulators hold weighted log2 values of the reserves,
+/// in fixed point with LOG2_PRECISION_BITS fractional bits.
+/// Observations recorded before the log accumulators were added decode with zero values.
+#[derive(ManagedVecItem, Clone, TopEncode, NestedEncode, TypeAbi)]
 pub struct PriceObservation<M: ManagedTypeApi> {
     pub first_token_reserve_accumulated: BigUint<M>,
     pub second_token_reserve_accumulated: BigUint<M>,
     pub weight_accumulated: u64,
     pub recording_round: Round,
+    pub first_token_log_reserve_accumulated: BigUint<M>,
+    pub second_token_log_reserve_accumulated: BigUint<M>,
+    pub log_weight_accumulated: u64,
 }
 
 impl<M: ManagedTypeApi> Default for PriceObservation<M> {
@@ -22,18 +39,112 @@ impl<M: ManagedTypeApi> Default for PriceObservation<M> {
             second_token_reserve_accumulated: BigUint::zero(),
             weight_accumulated: 0,
             recording_round: 0,
+            first_token_log_reserve_accumulated: BigUint::zero(),
+            second_token_log_reserve_accumulated: BigUint::zero(),
+            log_weight_accumulated: 0,
         }
     }
 }
 
+/// Only the top level encoding may come from the old layout, without the log accumulators.
+/// The nested encoding always holds all the fields.
+impl<M: ManagedTypeApi> TopDecode for PriceObservation<M> {
+    fn top_decode<I>(input: I) -> Result<Self, DecodeError>
+    where
+        I: TopDecodeInput,
+    {
+        let mut buffer = input.into_nested_buffer();
+        let first_token_reserve_accumulated = BigUint::dep_decode(&mut buffer)?;
+        let second_token_reserve_accumulated = BigUint::dep_decode(&mut buffer)?;
+        let weight_accumulated = u64::dep_decode(&mut buffer)?;
+        let recording_round = Round::dep_decode(&mut buffer)?;
+
+        let (
+            first_token_log_reserve_accumulated,
+            second_token_log_reserve_accumulated,
+            log_weight_accumulated,
+        ) = if !buffer.is_depleted() {
+            (
+                BigUint::dep_decode(&mut buffer)?,
+                BigUint::dep_decode(&mut buffer)?,
+                u64::dep_decode(&mut buffer)?,
+            )
+        } else {
+            (BigUint::zero(), BigUint::zero(), 0)
+        };
+
+        if !buffer.is_depleted() {
+            return Result::Err(DecodeError::INPUT_TOO_LONG);
+        }
+
+        Result::Ok(PriceObservation {
+            first_token_reserve_accumulated,
+            second_token_reserve_accumulated,
+            weight_accumulated,
+            recording_round,
+            first_token_log_reserve_accumulated,
+            second_token_log_reserve_accumulated,
+            log_weight_accumulated,
+        })
+    }
+}
+
+impl<M: ManagedTypeApi> NestedDecode for PriceObservation<M> {
+    fn dep_decode<I: NestedDecodeInput>(input: &mut I) -> Result<Self, DecodeError> {
+        Result::Ok(PriceObservation {
+            first_token_reserve_accumulated: BigUint::dep_decode(input)?,
+            second_token_reserve_accumulated: BigUint::dep_decode(input)?,
+            weight_accumulated: u64::dep_decode(input)?,
+            recording_round: Round::dep_decode(input)?,
+            first_token_log_reserve_accumulated: BigUint::dep_decode(input)?,
+            second_token_log_reserve_accumulated: BigUint::dep_decode(input)?,
+            log_weight_accumulated: u64::dep_decode(input)?,
+        })
+    }
+}
+
 #[multiversx_sc::module]
 pub trait SafePriceModule:
     config::ConfigModule
     + token_send::TokenSendModule
     + amm::AmmModule
+    + stable_swap::StableSwapModule
     + permissions_module::PermissionsModule
     + pausable::PausableModule
 {
+    /// Sets the maximum number of price observations kept by the pair.
+    /// When the capacity is raised, the buffer grows once the current slot reaches its end.
+    /// When it is lowered, the excess observations are removed gradually, on the next updates.
+    #[endpoint(setPriceObservationsCapacity)]
+    fn set_price_observations_capacity(&self, capacity: usize) {
+        self.require_caller_has_owner_or_admin_permissions();
+        require!(
+            (MIN_OBSERVATIONS_CAPACITY..=MAX_OBSERVATIONS_CAPACITY).contains(&capacity),
+            ERROR_BAD_OBSERVATIONS_CAPACITY
+        );
+
+        self.price_observations_capacity().set(capacity);
+    }
+
+    /// A new price observation is recorded only if at least this many rounds
+    /// passed since the last one. Zero or one records an observation on every active round.
+    #[endpoint(setMinRoundsBetweenObservations)]
+    fn set_min_rounds_between_observations(&self, min_rounds: Round) {
+        self.require_caller_has_owner_or_admin_permissions();
+
+        self.min_rounds_between_observations().set(min_rounds);
+    }
+
+    #[view(getPriceObservationsCapacity)]
+    fn get_price_observations_capacity(&self) -> usize {
+        let capacity_mapper = self.price_observations_capacity();
+        if capacity_mapper.is_empty() {
+            return DEFAULT_OBSERVATIONS_CAPACITY;
+        }
+
+        capacity_mapper.get()
+    }
+
     fn update_safe_price(&self, first_token_reserve: &BigUint, second_token_reserve: &BigUint) {
         if first_token_reserve == &0u64 || second_token_reserve == &0u64 {
             return;
@@ -41,23 +152,36 @@ pub trait SafePriceModule:
 
         let current_round = self.blockchain().get_block_round();
         let safe_price_current_index = self.safe_price_current_index().get();
+        let mut price_observations = self.price_observations();
         require!(
-            safe_price_current_index <= MAX_OBSERVATIONS,
+            safe_price_current_index <= price_observations.len(),
             ERROR_SAFE_PRICE_CURRENT_INDEX
         );
 
-        let mut price_observations = self.price_observations();
         let mut last_price_observation = PriceObservation::default();
-        let mut new_index = 1;
         if !price_observations.is_empty() {
             last_price_observation = price_observations.get(safe_price_current_index);
-            new_index = (safe_price_current_index % MAX_OBSERVATIONS) + 1;
         }
 
         if last_price_observation.recording_round == current_round {
             return;
         }
 
+        let min_rounds_between_observations = self.min_rounds_between_observations().get();
+        if last_price_observation.recording_round > 0
+            && current_round
+                < last_price_observation.recording_round + min_rounds_between_observations
+        {
+            return;
+        }
+
+        let capacity = self.get_price_observations_capacity();
+        self.remove_excess_price_observations(
+            &mut price_observations,
+            safe_price_current_index,
+            capacity,
+        );
+
         let new_price_observation = self.compute_new_observation(
             current_round,
             first_token_reserve,
@@ -65,15 +189,43 @@ pub trait SafePriceModule:
             &last_price_observation,
         );
 
-        if price_observations.len() == MAX_OBSERVATIONS {
-            price_observations.set(new_index, &new_price_observation);
+        // Once the buffer wrapped, the next slot holds the oldest observation
+        let observations_len = price_observations.len();
+        let new_index = if safe_price_current_index < observations_len {
+            safe_price_current_index + 1
+        } else if observations_len < capacity {
+            observations_len + 1
         } else {
+            1
+        };
+
+        if new_index > observations_len {
             price_observations.push(&new_price_observation);
+        } else {
+            price_observations.set(new_index, &new_price_observation);
         }
 
         self.safe_price_current_index().set(new_index);
     }
 
+    /// Removes the last observations of the buffer while it is above capacity.
+    /// The newest observation is never removed, so the remaining ones stay in round order.
+    fn remove_excess_price_observations(
+        &self,
+        price_observations: &mut VecMapper<PriceObservation<Self::Api>>,
+        current_index: usize,
+        capacity: usize,
+    ) {
+        for _ in 0..MAX_OBSERVATIONS_REMOVED_PER_UPDATE {
+            let observations_len = price_observations.len();
+            if observations_len <= capacity || observations_len == current_index {
+                return;
+            }
+
+            price_observations.swap_remove(observations_len);
+        }
+    }
+
     fn compute_new_observation(
         &self,
         new_round: Round,
@@ -95,13 +247,78 @@ pub trait SafePriceModule:
         new_price_observation.weight_accumulated += new_weight;
         new_price_observation.recording_round = new_round;
 
+        new_price_observation.first_token_log_reserve_accumulated +=
+            BigUint::from(new_weight) * self.log2_fixed_point(new_first_reserve);
+        new_price_observation.second_token_log_reserve_accumulated +=
+            BigUint::from(new_weight) * self.log2_fixed_point(new_second_reserve);
+        new_price_observation.log_weight_accumulated += new_weight;
+
         new_price_observation
     }
 
+    /// Computes log2(value), in fixed point with LOG2_PRECISION_BITS fractional bits.
+    /// The fractional bits are found by repeatedly squaring the normalised mantissa.
+    fn log2_fixed_point(&self, value: &BigUint) -> BigUint {
+        if value == &0u64 {
+            return BigUint::zero();
+        }
+
+        let integer_part = value.log2() as usize;
+        let mut result = BigUint::from(integer_part as u64) << LOG2_PRECISION_BITS;
+
+        // mantissa in [1, 2), with LOG2_MANTISSA_BITS fractional bits
+        let mut mantissa = if integer_part >= LOG2_MANTISSA_BITS {
+            value >> (integer_part - LOG2_MANTISSA_BITS)
+        } else {
+            value << (LOG2_MANTISSA_BITS - integer_part)
+        };
+        let two = BigUint::from(2u64) << LOG2_MANTISSA_BITS;
+        for bit in (0..LOG2_PRECISION_BITS).rev() {
+            mantissa = (&mantissa * &mantissa) >> LOG2_MANTISSA_BITS;
+            if mantissa >= two {
+                mantissa >>= 1;
+                result += BigUint::from(1u64) << bit;
+            }
+        }
+
+        result
+    }
+
+    /// Computes 2^exponent, for an exponent in fixed point with LOG2_PRECISION_BITS fractional bits.
+    /// The result is scaled by 2^LOG2_MANTISSA_BITS.
+    fn pow2_fixed_point(&self, exponent: &BigUint) -> BigUint {
+        let integer_part = (exponent >> LOG2_PRECISION_BITS)
+            .to_u64()
+            .unwrap_or_default();
+        let fractional_part = (exponent - &(BigUint::from(integer_part) << LOG2_PRECISION_BITS))
+            .to_u64()
+            .unwrap_or_default();
+
+        let scale = BigUint::from(1u64) << LOG2_MANTISSA_BITS;
+        let mut result = scale.clone();
+        // 2^(1 / 2^i), starting from sqrt(2)
+        let mut factor = (BigUint::from(2u64) << (2 * LOG2_MANTISSA_BITS)).sqrt();
+        for bit in (0..LOG2_PRECISION_BITS).rev() {
+            if (fractional_part >> bit) & 1 == 1 {
+                result = (result * &factor) >> LOG2_MANTISSA_BITS;
+            }
+            factor = (factor * &scale).sqrt();
+        }
+
+        result << integer_part as usize
+    }
+
     #[storage_mapper("price_observations")]
     fn price_observations(&self) -> VecMapper<PriceObservation<Self::Api>>;
 
     #[view(getSafePriceCurrentIndex)]
     #[storage_mapper("safe_price_current_index")]
     fn safe_price_current_index(&self) -> SingleValueMapper<usize>;
+
+    #[storage_mapper("price_observations_capacity")]
+    fn price_observations_capacity(&self) -> SingleValueMapper<usize>;
+
+    #[view(getMinRoundsBetweenObservations)]
+    #[storage_mapper("min_rounds_between_observations")]
+    fn min_rounds_between_observations(&self) -> SingleValueMapper<Round>;
 }
@@ -4,9 +4,14 @@ use common_errors::{ERROR_BAD_INPUT_TOKEN, ERROR_PARAMETERS};
 use core::cmp::Ordering;
 
 use crate::{
-    amm, config,
-    errors::{ERROR_SAFE_PRICE_OBSERVATION_DOES_NOT_EXIST, ERROR_SAFE_PRICE_SAME_ROUNDS},
-    safe_price::{self, PriceObservation, Round, MAX_OBSERVATIONS},
+    amm,
+    config::{self, MAX_PERCENTAGE},
+    errors::{
+        ERROR_GEOMETRIC_SAFE_PRICE_NOT_AVAILABLE, ERROR_SAFE_PRICE_OBSERVATION_DOES_NOT_EXIST,
+        ERROR_SAFE_PRICE_SAME_ROUNDS,
+    },
+    safe_price::{self, PriceObservation, Round, LOG2_MANTISSA_BITS},
+    stable_swap,
 };
 
 pub const DEFAULT_SAFE_PRICE_ROUNDS_OFFSET: u64 = 10 * 60;
@@ -18,6 +23,7 @@ pub trait SafePriceViewModule:
     + config::ConfigModule
     + token_send::TokenSendModule
     + amm::AmmModule
+    + stable_swap::StableSwapModule
     + permissions_module::PermissionsModule
     + pausable::PausableModule
 {
@@ -27,7 +33,7 @@ pub trait SafePriceViewModule:
         &self,
         pair_address: ManagedAddress,
         liquidity: BigUint,
-    ) -> MultiValue2<EsdtTokenPayment, EsdtTokenPayment> {
+    ) -> MultiValue2<EgldOrEsdtTokenPayment, EgldOrEsdtTokenPayment> {
         let current_round = self.blockchain().get_block_round();
         let default_offset_rounds = self.get_default_offset_rounds(&pair_address, current_round);
         let start_round = current_round - default_offset_rounds;
@@ -42,7 +48,7 @@ pub trait SafePriceViewModule:
         pair_address: ManagedAddress,
         round_offset: Round,
         liquidity: BigUint,
-    ) -> MultiValue2<EsdtTokenPayment, EsdtTokenPayment> {
+    ) -> MultiValue2<EgldOrEsdtTokenPayment, EgldOrEsdtTokenPayment> {
         let current_round = self.blockchain().get_block_round();
         require!(
             round_offset > 0 && round_offset < current_round,
@@ -60,7 +66,7 @@ pub trait SafePriceViewModule:
         pair_address: ManagedAddress,
         timestamp_offset: u64,
         liquidity: BigUint,
-    ) -> MultiValue2<EsdtTokenPayment, EsdtTokenPayment> {
+    ) -> MultiValue2<EgldOrEsdtTokenPayment, EgldOrEsdtTokenPayment> {
         let current_round = self.blockchain().get_block_round();
         let round_offset = timestamp_offset / SECONDS_PER_ROUND;
         require!(
@@ -80,7 +86,7 @@ pub trait SafePriceViewModule:
         start_round: Round,
         end_round: Round,
         liquidity: BigUint,
-    ) -> MultiValue2<EsdtTokenPayment, EsdtTokenPayment> {
+    ) -> MultiValue2<EgldOrEsdtTokenPayment, EgldOrEsdtTokenPayment> {
         require!(end_round > start_round, ERROR_PARAMETERS);
 
         let lp_total_supply = self.lp_token_supply().get_from_address(&pair_address);
@@ -88,8 +94,8 @@ pub trait SafePriceViewModule:
         let second_token_id = self.second_token_id().get_from_address(&pair_address);
         if lp_total_supply == 0 {
             return (
-                EsdtTokenPayment::new(first_token_id, 0, BigUint::zero()),
-                EsdtTokenPayment::new(second_token_id, 0, BigUint::zero()),
+                EgldOrEsdtTokenPayment::new(first_token_id, 0, BigUint::zero()),
+                EgldOrEsdtTokenPayment::new(second_token_id, 0, BigUint::zero()),
             )
                 .into();
         }
@@ -133,8 +139,9 @@ pub trait SafePriceViewModule:
 
         let first_token_worth = &liquidity * &weighted_first_token_reserve / &lp_total_supply;
         let second_token_worth = &liquidity * &weighted_second_token_reserve / &lp_total_supply;
-        let first_token_payment = EsdtTokenPayment::new(first_token_id, 0, first_token_worth);
-        let second_token_payment = EsdtTokenPayment::new(second_token_id, 0, second_token_worth);
+        let first_token_payment = EgldOrEsdtTokenPayment::new(first_token_id, 0, first_token_worth);
+        let second_token_payment =
+            EgldOrEsdtTokenPayment::new(second_token_id, 0, second_token_worth);
 
         (first_token_payment, second_token_payment).into()
     }
@@ -144,8 +151,8 @@ pub trait SafePriceViewModule:
     fn get_safe_price_by_default_offset(
         &self,
         pair_address: ManagedAddress,
-        input_payment: EsdtTokenPayment,
-    ) -> EsdtTokenPayment {
+        input_payment: EgldOrEsdtTokenPayment,
+    ) -> EgldOrEsdtTokenPayment {
         let current_round = self.blockchain().get_block_round();
         let default_offset_rounds = self.get_default_offset_rounds(&pair_address, current_round);
         let start_round = current_round - default_offset_rounds;
@@ -158,8 +165,8 @@ pub trait SafePriceViewModule:
         &self,
         pair_address: ManagedAddress,
         round_offset: u64,
-        input_payment: EsdtTokenPayment,
-    ) -> EsdtTokenPayment {
+        input_payment: EgldOrEsdtTokenPayment,
+    ) -> EgldOrEsdtTokenPayment {
         let current_round = self.blockchain().get_block_round();
         require!(
             round_offset > 0 && round_offset < current_round,
@@ -175,8 +182,8 @@ pub trait SafePriceViewModule:
         &self,
         pair_address: ManagedAddress,
         timestamp_offset: u64,
-        input_payment: EsdtTokenPayment,
-    ) -> EsdtTokenPayment {
+        input_payment: EgldOrEsdtTokenPayment,
+    ) -> EgldOrEsdtTokenPayment {
         let current_round = self.blockchain().get_block_round();
         let round_offset = timestamp_offset / SECONDS_PER_ROUND;
         require!(
@@ -194,8 +201,8 @@ pub trait SafePriceViewModule:
         pair_address: ManagedAddress,
         start_round: Round,
         end_round: Round,
-        input_payment: EsdtTokenPayment,
-    ) -> EsdtTokenPayment {
+        input_payment: EgldOrEsdtTokenPayment,
+    ) -> EgldOrEsdtTokenPayment {
         require!(end_round > start_round, ERROR_PARAMETERS);
 
         let safe_price_current_index = self
@@ -240,6 +247,109 @@ pub trait SafePriceViewModule:
         )
     }
 
+    #[label("safe-price-view")]
+    #[view(getGeometricSafePriceByDefaultOffset)]
+    fn get_geometric_safe_price_by_default_offset(
+        &self,
+        pair_address: ManagedAddress,
+        input_payment: EgldOrEsdtTokenPayment,
+    ) -> EgldOrEsdtTokenPayment {
+        let current_round = self.blockchain().get_block_round();
+        let default_offset_rounds = self.get_default_offset_rounds(&pair_address, current_round);
+        let start_round = current_round - default_offset_rounds;
+        self.get_geometric_safe_price(pair_address, start_round, current_round, input_payment)
+    }
+
+    #[label("safe-price-view")]
+    #[view(getGeometricSafePriceByRoundOffset)]
+    fn get_geometric_safe_price_by_round_offset(
+        &self,
+        pair_address: ManagedAddress,
+        round_offset: u64,
+        input_payment: EgldOrEsdtTokenPayment,
+    ) -> EgldOrEsdtTokenPayment {
+        let current_round = self.blockchain().get_block_round();
+        require!(
+            round_offset > 0 && round_offset < current_round,
+            ERROR_PARAMETERS
+        );
+        let start_round = current_round - round_offset;
+        self.get_geometric_safe_price(pair_address, start_round, current_round, input_payment)
+    }
+
+    #[label("safe-price-view")]
+    #[view(getGeometricSafePriceByTimestampOffset)]
+    fn get_geometric_safe_price_by_timestamp_offset(
+        &self,
+        pair_address: ManagedAddress,
+        timestamp_offset: u64,
+        input_payment: EgldOrEsdtTokenPayment,
+    ) -> EgldOrEsdtTokenPayment {
+        let current_round = self.blockchain().get_block_round();
+        let round_offset = timestamp_offset / SECONDS_PER_ROUND;
+        require!(
+            round_offset > 0 && round_offset < current_round,
+            ERROR_PARAMETERS
+        );
+        let start_round = current_round - round_offset;
+        self.get_geometric_safe_price(pair_address, start_round, current_round, input_payment)
+    }
+
+    /// Same as getSafePrice, but uses the geometric mean of the price over the given rounds,
+    /// which is less sensitive to a few rounds with extreme reserves.
+    #[label("safe-price-view")]
+    #[view(getGeometricSafePrice)]
+    fn get_geometric_safe_price(
+        &self,
+        pair_address: ManagedAddress,
+        start_round: Round,
+        end_round: Round,
+        input_payment: EgldOrEsdtTokenPayment,
+    ) -> EgldOrEsdtTokenPayment {
+        require!(end_round > start_round, ERROR_PARAMETERS);
+
+        let safe_price_current_index = self
+            .safe_price_current_index()
+            .get_from_address(&pair_address);
+        let price_observations = self.price_observations();
+
+        let oldest_price_observation = self.get_oldest_price_observation(
+            &pair_address,
+            safe_price_current_index,
+            &price_observations,
+        );
+        require!(
+            oldest_price_observation.recording_round <= start_round,
+            ERROR_SAFE_PRICE_OBSERVATION_DOES_NOT_EXIST
+        );
+
+        let first_token_id = self.first_token_id().get_from_address(&pair_address);
+        let second_token_id = self.second_token_id().get_from_address(&pair_address);
+        let first_price_observation = self.get_price_observation(
+            &pair_address,
+            &first_token_id,
+            &second_token_id,
+            safe_price_current_index,
+            &price_observations,
+            start_round,
+        );
+        let last_price_observation = self.get_price_observation(
+            &pair_address,
+            &first_token_id,
+            &second_token_id,
+            safe_price_current_index,
+            &price_observations,
+            end_round,
+        );
+
+        self.compute_geometric_weighted_price(
+            &pair_address,
+            input_payment,
+            &first_price_observation,
+            &last_price_observation,
+        )
+    }
+
     #[label("safe-price-view")]
     #[view(getPriceObservation)]
     fn get_price_observation_view(
@@ -274,13 +384,214 @@ pub trait SafePriceViewModule:
         )
     }
 
+    /// Returns the safe prices for many pairs and windows at once.
+    /// Each query is made of the pair address, the start and end rounds and the input payment.
+    #[label("safe-price-view")]
+    #[view(getSafePrices)]
+    fn get_safe_prices(
+        &self,
+        queries: MultiValueEncoded<
+            MultiValue4<ManagedAddress, Round, Round, EgldOrEsdtTokenPayment>,
+        >,
+    ) -> MultiValueEncoded<EgldOrEsdtTokenPayment> {
+        let mut result = MultiValueEncoded::new();
+        for query in queries {
+            let (pair_address, start_round, end_round, input_payment) = query.into_tuple();
+            let safe_price =
+                self.get_safe_price(pair_address, start_round, end_round, input_payment);
+            result.push(safe_price);
+        }
+
+        result
+    }
+
+    /// Returns the LP tokens valuations for many pairs and windows at once.
+    /// Each query is made of the pair address, the start and end rounds and the LP tokens amount.
+    #[label("safe-price-view")]
+    #[view(getLpTokensSafePrices)]
+    fn get_lp_tokens_safe_prices(
+        &self,
+        queries: MultiValueEncoded<MultiValue4<ManagedAddress, Round, Round, BigUint>>,
+    ) -> MultiValueEncoded<MultiValue2<EgldOrEsdtTokenPayment, EgldOrEsdtTokenPayment>> {
+        let mut result = MultiValueEncoded::new();
+        for query in queries {
+            let (pair_address, start_round, end_round, liquidity) = query.into_tuple();
+            let lp_tokens_safe_price =
+                self.get_lp_tokens_safe_price(pair_address, start_round, end_round, liquidity);
+            result.push(lp_tokens_safe_price);
+        }
+
+        result
+    }
+
+    /// Returns the recording rounds of the oldest and the newest price observations of each pair.
+    /// Safe price windows cannot start before the oldest one. Zero for pairs without observations.
+    #[label("safe-price-view")]
+    #[view(getPriceObservationRounds)]
+    fn get_price_observation_rounds(
+        &self,
+        pair_addresses: MultiValueEncoded<ManagedAddress>,
+    ) -> MultiValueEncoded<MultiValue3<ManagedAddress, Round, Round>> {
+        let price_observations = self.price_observations();
+        let mut result = MultiValueEncoded::new();
+        for pair_address in pair_addresses {
+            if price_observations.is_empty_at_address(&pair_address) {
+                result.push((pair_address, 0, 0).into());
+                continue;
+            }
+
+            let safe_price_current_index = self
+                .safe_price_current_index()
+                .get_from_address(&pair_address);
+            let oldest_price_observation = self.get_oldest_price_observation(
+                &pair_address,
+                safe_price_current_index,
+                &price_observations,
+            );
+            let newest_price_observation =
+                price_observations.get_at_address(&pair_address, safe_price_current_index);
+
+            result.push(
+                (
+                    pair_address,
+                    oldest_price_observation.recording_round,
+                    newest_price_observation.recording_round,
+                )
+                    .into(),
+            );
+        }
+
+        result
+    }
+
+    #[label("safe-price-view")]
+    #[view(getAverageReservesByRoundOffset)]
+    fn get_average_reserves_by_round_offset(
+        &self,
+        pair_address: ManagedAddress,
+        round_offset: Round,
+    ) -> MultiValue2<EgldOrEsdtTokenPayment, EgldOrEsdtTokenPayment> {
+        let current_round = self.blockchain().get_block_round();
+        require!(
+            round_offset > 0 && round_offset < current_round,
+            ERROR_PARAMETERS
+        );
+        let start_round = current_round - round_offset;
+        self.get_average_reserves(pair_address, start_round, current_round)
+    }
+
+    #[label("safe-price-view")]
+    #[view(getAverageReservesByTimestampOffset)]
+    fn get_average_reserves_by_timestamp_offset(
+        &self,
+        pair_address: ManagedAddress,
+        timestamp_offset: u64,
+    ) -> MultiValue2<EgldOrEsdtTokenPayment, EgldOrEsdtTokenPayment> {
+        let current_round = self.blockchain().get_block_round();
+        let round_offset = timestamp_offset / SECONDS_PER_ROUND;
+        require!(
+            round_offset > 0 && round_offset < current_round,
+            ERROR_PARAMETERS
+        );
+        let start_round = current_round - round_offset;
+        self.get_average_reserves(pair_address, start_round, current_round)
+    }
+
+    /// Returns the time-weighted average reserve of each token of the pair over the given rounds.
+    #[label("safe-price-view")]
+    #[view(getAverageReserves)]
+    fn get_average_reserves(
+        &self,
+        pair_address: ManagedAddress,
+        start_round: Round,
+        end_round: Round,
+    ) -> MultiValue2<EgldOrEsdtTokenPayment, EgldOrEsdtTokenPayment> {
+        let (first_price_observation, last_price_observation) =
+            self.get_window_price_observations(&pair_address, start_round, end_round);
+        let (weighted_first_token_reserve, weighted_second_token_reserve) =
+            self.compute_weighted_reserves(&first_price_observation, &last_price_observation);
+
+        let first_token_id = self.first_token_id().get_from_address(&pair_address);
+        let second_token_id = self.second_token_id().get_from_address(&pair_address);
+        (
+            EgldOrEsdtTokenPayment::new(first_token_id, 0, weighted_first_token_reserve),
+            EgldOrEsdtTokenPayment::new(second_token_id, 0, weighted_second_token_reserve),
+        )
+            .into()
+    }
+
+    #[label("safe-price-view")]
+    #[view(getAverageLiquidityByRoundOffset)]
+    fn get_average_liquidity_by_round_offset(
+        &self,
+        pair_address: ManagedAddress,
+        round_offset: Round,
+    ) -> BigUint {
+        let current_round = self.blockchain().get_block_round();
+        require!(
+            round_offset > 0 && round_offset < current_round,
+            ERROR_PARAMETERS
+        );
+        let start_round = current_round - round_offset;
+        self.get_average_liquidity(pair_address, start_round, current_round)
+    }
+
+    #[label("safe-price-view")]
+    #[view(getAverageLiquidityByTimestampOffset)]
+    fn get_average_liquidity_by_timestamp_offset(
+        &self,
+        pair_address: ManagedAddress,
+        timestamp_offset: u64,
+    ) -> BigUint {
+        let current_round = self.blockchain().get_block_round();
+        let round_offset = timestamp_offset / SECONDS_PER_ROUND;
+        require!(
+            round_offset > 0 && round_offset < current_round,
+            ERROR_PARAMETERS
+        );
+        let start_round = current_round - round_offset;
+        self.get_average_liquidity(pair_address, start_round, current_round)
+    }
+
+    /// Returns the geometric mean of sqrt(K) over the given rounds, K being the product of the reserves.
+    /// It is never higher than the arithmetic mean, so it is a conservative measure of the pair's depth.
+    #[label("safe-price-view")]
+    #[view(getAverageLiquidity)]
+    fn get_average_liquidity(
+        &self,
+        pair_address: ManagedAddress,
+        start_round: Round,
+        end_round: Round,
+    ) -> BigUint {
+        let (first_price_observation, last_price_observation) =
+            self.get_window_price_observations(&pair_address, start_round, end_round);
+
+        // observations recorded before the log accumulators existed cannot be used
+        require!(
+            first_price_observation.log_weight_accumulated > 0,
+            ERROR_GEOMETRIC_SAFE_PRICE_NOT_AVAILABLE
+        );
+        let log_weight_diff = last_price_observation.log_weight_accumulated
+            - first_price_observation.log_weight_accumulated;
+        require!(log_weight_diff > 0, ERROR_SAFE_PRICE_SAME_ROUNDS);
+
+        let log_reserves_sum = &last_price_observation.first_token_log_reserve_accumulated
+            - &first_price_observation.first_token_log_reserve_accumulated
+            + &last_price_observation.second_token_log_reserve_accumulated
+            - &first_price_observation.second_token_log_reserve_accumulated;
+
+        // sqrt(K) = 2^((log2(first reserve) + log2(second reserve)) / 2)
+        let log_sqrt_k = log_reserves_sum / (2 * log_weight_diff);
+        self.pow2_fixed_point(&log_sqrt_k) >> LOG2_MANTISSA_BITS
+    }
+
     fn compute_weighted_price(
         &self,
         pair_address: &ManagedAddress,
-        input_payment: EsdtTokenPayment,
+        input_payment: EgldOrEsdtTokenPayment,
         first_price_observation: &PriceObservation<Self::Api>,
         last_price_observation: &PriceObservation<Self::Api>,
-    ) -> EsdtTokenPayment {
+    ) -> EgldOrEsdtTokenPayment {
         let first_token_id = self.first_token_id().get_from_address(pair_address);
         let second_token_id = self.second_token_id().get_from_address(pair_address);
 
@@ -290,21 +601,121 @@ pub trait SafePriceViewModule:
         if input_payment.token_identifier == first_token_id {
             let output_amount =
                 input_payment.amount * weighted_second_token_reserve / weighted_first_token_reserve;
-            EsdtTokenPayment::new(second_token_id, 0, output_amount)
+            EgldOrEsdtTokenPayment::new(second_token_id, 0, output_amount)
         } else if input_payment.token_identifier == second_token_id {
             let output_amount =
                 input_payment.amount * weighted_first_token_reserve / weighted_second_token_reserve;
-            EsdtTokenPayment::new(first_token_id, 0, output_amount)
+            EgldOrEsdtTokenPayment::new(first_token_id, 0, output_amount)
         } else {
             sc_panic!(ERROR_BAD_INPUT_TOKEN);
         }
     }
 
+    fn compute_geometric_weighted_price(
+        &self,
+        pair_address: &ManagedAddress,
+        input_payment: EgldOrEsdtTokenPayment,
+        first_price_observation: &PriceObservation<Self::Api>,
+        last_price_observation: &PriceObservation<Self::Api>,
+    ) -> EgldOrEsdtTokenPayment {
+        // observations recorded before the log accumulators existed cannot be used
+        require!(
+            first_price_observation.log_weight_accumulated > 0,
+            ERROR_GEOMETRIC_SAFE_PRICE_NOT_AVAILABLE
+        );
+        let log_weight_diff = last_price_observation.log_weight_accumulated
+            - first_price_observation.log_weight_accumulated;
+        require!(log_weight_diff > 0, ERROR_SAFE_PRICE_SAME_ROUNDS);
+
+        let first_token_log_reserve = (&last_price_observation.first_token_log_reserve_accumulated
+            - &first_price_observation.first_token_log_reserve_accumulated)
+            / log_weight_diff;
+        let second_token_log_reserve = (&last_price_observation
+            .second_token_log_reserve_accumulated
+            - &first_price_observation.second_token_log_reserve_accumulated)
+            / log_weight_diff;
+
+        let first_token_id = self.first_token_id().get_from_address(pair_address);
+        let second_token_id = self.second_token_id().get_from_address(pair_address);
+        let (output_token_id, input_log_reserve, output_log_reserve) =
+            if input_payment.token_identifier == first_token_id {
+                (
+                    second_token_id,
+                    first_token_log_reserve,
+                    second_token_log_reserve,
+                )
+            } else if input_payment.token_identifier == second_token_id {
+                (
+                    first_token_id,
+                    second_token_log_reserve,
+                    first_token_log_reserve,
+                )
+            } else {
+                sc_panic!(ERROR_BAD_INPUT_TOKEN);
+            };
+
+        // output = input * 2^(log2(output reserve) - log2(input reserve))
+        let output_amount = if output_log_reserve >= input_log_reserve {
+            let price = self.pow2_fixed_point(&(output_log_reserve - input_log_reserve));
+            (input_payment.amount * price) >> LOG2_MANTISSA_BITS
+        } else {
+            let price = self.pow2_fixed_point(&(input_log_reserve - output_log_reserve));
+            (input_payment.amount << LOG2_MANTISSA_BITS) / price
+        };
+
+        EgldOrEsdtTokenPayment::new(output_token_id, 0, output_amount)
+    }
+
+    fn get_window_price_observations(
+        &self,
+        pair_address: &ManagedAddress,
+        start_round: Round,
+        end_round: Round,
+    ) -> (PriceObservation<Self::Api>, PriceObservation<Self::Api>) {
+        require!(end_round > start_round, ERROR_PARAMETERS);
+
+        let safe_price_current_index = self
+            .safe_price_current_index()
+            .get_from_address(pair_address);
+        let price_observations = self.price_observations();
+
+        let oldest_price_observation = self.get_oldest_price_observation(
+            pair_address,
+            safe_price_current_index,
+            &price_observations,
+        );
+        require!(
+            oldest_price_observation.recording_round <= start_round,
+            ERROR_SAFE_PRICE_OBSERVATION_DOES_NOT_EXIST
+        );
+
+        let first_token_id = self.first_token_id().get_from_address(pair_address);
+        let second_token_id = self.second_token_id().get_from_address(pair_address);
+        let first_price_observation = self.get_price_observation(
+            pair_address,
+            &first_token_id,
+            &second_token_id,
+            safe_price_current_index,
+            &price_observations,
+            start_round,
+        );
+        let last_price_observation = self.get_price_observation(
+            pair_address,
+            &first_token_id,
+            &second_token_id,
+            safe_price_current_index,
+            &price_observations,
+            end_round,
+        );
+
+        (first_price_observation, last_price_observation)
+    }
+
     fn get_price_observation(
         &self,
         pair_address: &ManagedAddress,
-        first_token_id: &TokenIdentifier,
-        second_token_id: &TokenIdentifier,
+        first_token_id: &EgldOrEsdtTokenIdentifier,
+        second_token_id: &EgldOrEsdtTokenIdentifier,
         current_index: usize,
         price_observations: &VecMapper<Self::Api, PriceObservation<Self::Api>>,
         search_round: Round,
@@ -377,10 +788,9 @@ pub trait SafePriceViewModule:
         );
 
         // VecMapper index starts at 1
-        let mut oldest_observation_index = 1;
-        if price_observations.len_at_address(pair_address) == MAX_OBSERVATIONS {
-            oldest_observation_index = (current_index % MAX_OBSERVATIONS) + 1
-        }
+        // Once the buffer wrapped, the oldest observation follows the current one
+        let observations_len = price_observations.len_at_address(pair_address);
+        let oldest_observation_index = (current_index % observations_len) + 1;
         price_observations.get_at_address(pair_address, oldest_observation_index)
     }
 
@@ -426,14 +836,15 @@ pub trait SafePriceViewModule:
         let last_found_observation = price_observations.get_at_address(pair_address, search_index);
         let left_observation;
         let right_observation;
+        let observations_len = price_observations.len_at_address(pair_address);
         if last_found_observation.recording_round < search_round {
             left_observation = last_found_observation;
-            let right_observation_index = (search_index % MAX_OBSERVATIONS) + 1;
+            let right_observation_index = (search_index % observations_len) + 1;
             right_observation =
                 price_observations.get_at_address(pair_address, right_observation_index);
         } else {
             let left_observation_index = if search_index == 1 {
-                MAX_OBSERVATIONS
+                observations_len
             } else {
                 search_index - 1
             };
@@ -461,11 +872,28 @@ pub trait SafePriceViewModule:
         let weight_accumulated =
             left_observation.weight_accumulated + search_round - left_observation.recording_round;
 
+        let first_token_log_reserve_sum = BigUint::from(left_weight)
+            * left_observation.first_token_log_reserve_accumulated
+            + BigUint::from(right_weight) * right_observation.first_token_log_reserve_accumulated;
+        let second_token_log_reserve_sum = BigUint::from(left_weight)
+            * left_observation.second_token_log_reserve_accumulated
+            + BigUint::from(right_weight) * right_observation.second_token_log_reserve_accumulated;
+
+        let first_token_log_reserve_accumulated = first_token_log_reserve_sum / weight_sum;
+        let second_token_log_reserve_accumulated = second_token_log_reserve_sum / weight_sum;
+        let log_weight_accumulated = left_observation.log_weight_accumulated
+            + (right_observation.log_weight_accumulated - left_observation.log_weight_accumulated)
+                * right_weight
+                / weight_sum;
+
         PriceObservation {
             first_token_reserve_accumulated,
             second_token_reserve_accumulated,
             weight_accumulated,
             recording_round: search_round,
+            first_token_log_reserve_accumulated,
+            second_token_log_reserve_accumulated,
+            log_weight_accumulated,
         }
     }
 
@@ -497,6 +925,95 @@ pub trait SafePriceViewModule:
         (weighted_first_token_reserve, weighted_second_token_reserve)
     }
 
+    /// Deviation of the price given by the reserves from the pair's average price
+    /// of the last `rounds` rounds, in MAX_PERCENTAGE units.
+    /// Zero when there is not enough price history.
+    fn get_price_deviation_from_average(
+        &self,
+        first_token_reserve: &BigUint,
+        second_token_reserve: &BigUint,
+        rounds: Round,
+    ) -> u64 {
+        let (deviation, _) = self.get_price_deviation_and_side_from_average(
+            first_token_reserve,
+            second_token_reserve,
+            rounds,
+        );
+        deviation
+    }
+
+    /// Same as `get_price_deviation_from_average`, also returning whether the price
+    /// of the first token is above the average price.
+    fn get_price_deviation_and_side_from_average(
+        &self,
+        first_token_reserve: &BigUint,
+        second_token_reserve: &BigUint,
+        rounds: Round,
+    ) -> (u64, bool) {
+        let current_round = self.blockchain().get_block_round();
+        let price_observations = self.price_observations();
+        if rounds == 0
+            || rounds >= current_round
+            || price_observations.is_empty()
+            || first_token_reserve == &0u64
+            || second_token_reserve == &0u64
+        {
+            return (0, false);
+        }
+
+        let pair_address = self.blockchain().get_sc_address();
+        let safe_price_current_index = self.safe_price_current_index().get();
+        let start_round = current_round - rounds;
+        let oldest_price_observation = self.get_oldest_price_observation(
+            &pair_address,
+            safe_price_current_index,
+            &price_observations,
+        );
+        if oldest_price_observation.recording_round > start_round {
+            return (0, false);
+        }
+
+        let first_token_id = self.first_token_id().get();
+        let second_token_id = self.second_token_id().get();
+        let first_price_observation = self.get_price_observation(
+            &pair_address,
+            &first_token_id,
+            &second_token_id,
+            safe_price_current_index,
+            &price_observations,
+            start_round,
+        );
+        let last_price_observation = self.get_price_observation(
+            &pair_address,
+            &first_token_id,
+            &second_token_id,
+            safe_price_current_index,
+            &price_observations,
+            current_round,
+        );
+        let (weighted_first_token_reserve, weighted_second_token_reserve) =
+            self.compute_weighted_reserves(&first_price_observation, &last_price_observation);
+
+        // |price - average price| / average price
+        let price_term = second_token_reserve * &weighted_first_token_reserve;
+        let average_price_term = &weighted_second_token_reserve * first_token_reserve;
+        let is_price_above_average = price_term > average_price_term;
+        let price_diff = if is_price_above_average {
+            price_term - &average_price_term
+        } else {
+            &average_price_term - &price_term
+        };
+        let deviation = price_diff * MAX_PERCENTAGE / average_price_term;
+        if deviation > MAX_PERCENTAGE {
+            return (MAX_PERCENTAGE, is_price_above_average);
+        }
+
+        (
+            deviation.to_u64().unwrap_or_default(),
+            is_price_above_average,
+        )
+    }
+
     fn get_default_offset_rounds(&self, pair_address: &ManagedAddress, end_round: Round) -> u64 {
         let safe_price_current_index = self
             .safe_price_current_index()
@@ -522,7 +1039,7 @@ pub trait SafePriceViewModule:
     fn update_and_get_tokens_for_given_position_with_safe_price(
         &self,
         liquidity: BigUint,
-    ) -> MultiValue2<EsdtTokenPayment<Self::Api>, EsdtTokenPayment<Self::Api>> {
+    ) -> MultiValue2<EgldOrEsdtTokenPayment<Self::Api>, EgldOrEsdtTokenPayment<Self::Api>> {
         let pair_address = self.blockchain().get_sc_address();
         self.get_lp_tokens_safe_price_by_default_offset(pair_address, liquidity)
     }
@@ -530,8 +1047,8 @@ pub trait SafePriceViewModule:
     #[endpoint(updateAndGetSafePrice)]
     fn update_and_get_safe_price(
         &self,
-        input: EsdtTokenPayment<Self::Api>,
-    ) -> EsdtTokenPayment<Self::Api> {
+        input: EgldOrEsdtTokenPayment<Self::Api>,
+    ) -> EgldOrEsdtTokenPayment<Self::Api> {
         let pair_address = self.blockchain().get_sc_address();
         self.get_safe_price_by_default_offset(pair_address, input)
     }
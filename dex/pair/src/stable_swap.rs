@@ -0,0 +1,231 @@
+multiversx_sc::imports!();
+multiversx_sc::derive_imports!();
+
+use crate::config::{self, CurveType};
+use crate::errors::*;
+
+pub const AMP_PRECISION: u64 = 100;
+pub const MAX_AMP_FACTOR: u64 = 1_000_000;
+pub const MAX_AMP_FACTOR_CHANGE: u64 = 10;
+pub const MIN_RAMP_DURATION: u64 = 24 * 60 * 60; // seconds
+
+const NR_POOL_TOKENS: u64 = 2;
+const MAX_ITERATIONS: usize = 255;
+
+/// Amplification factors are stored multiplied by AMP_PRECISION.
+#[derive(TypeAbi, TopEncode, TopDecode, Clone, PartialEq, Debug)]
+pub struct AmpFactorRamp {
+    pub initial_amp_factor: u64,
+    pub future_amp_factor: u64,
+    pub initial_timestamp: u64,
+    pub future_timestamp: u64,
+}
+
+#[multiversx_sc::module]
+pub trait StableSwapModule:
+    config::ConfigModule
+    + token_send::TokenSendModule
+    + permissions_module::PermissionsModule
+    + pausable::PausableModule
+{
+    /// Switches the pair to the StableSwap invariant.
+    /// Can only be called before any liquidity was added.
+    #[endpoint(setStableSwapCurve)]
+    fn set_stable_swap_curve(&self, amp_factor: u64) {
+        self.require_caller_has_owner_permissions();
+        require!(
+            self.lp_token_supply().get() == 0,
+            ERROR_CURVE_CHANGE_NOT_ALLOWED
+        );
+        require!(
+            amp_factor > 0 && amp_factor <= MAX_AMP_FACTOR,
+            ERROR_INVALID_AMP_FACTOR
+        );
+
+        let current_timestamp = self.blockchain().get_block_timestamp();
+        let amp_factor_precise = amp_factor * AMP_PRECISION;
+        self.curve_type().set(CurveType::StableSwap);
+        self.amp_factor_ramp().set(AmpFactorRamp {
+            initial_amp_factor: amp_factor_precise,
+            future_amp_factor: amp_factor_precise,
+            initial_timestamp: current_timestamp,
+            future_timestamp: current_timestamp,
+        });
+    }
+
+    /// Linearly changes the amplification factor from its current value
+    /// to `future_amp_factor`, reached at `future_timestamp`.
+    #[endpoint(rampAmpFactor)]
+    fn ramp_amp_factor(&self, future_amp_factor: u64, future_timestamp: u64) {
+        self.require_caller_has_owner_or_admin_permissions();
+        self.require_stable_swap_curve();
+
+        let current_timestamp = self.blockchain().get_block_timestamp();
+        let current_ramp = self.amp_factor_ramp().get();
+        require!(
+            current_timestamp >= current_ramp.initial_timestamp + MIN_RAMP_DURATION,
+            ERROR_AMP_FACTOR_RAMP_TOO_EARLY
+        );
+        require!(
+            future_timestamp >= current_timestamp + MIN_RAMP_DURATION,
+            ERROR_INVALID_RAMP_TIMESTAMP
+        );
+        require!(
+            future_amp_factor > 0 && future_amp_factor <= MAX_AMP_FACTOR,
+            ERROR_INVALID_AMP_FACTOR
+        );
+
+        let initial_amp_factor = self.get_amp_factor_precise();
+        let future_amp_factor_precise = future_amp_factor * AMP_PRECISION;
+        if future_amp_factor_precise < initial_amp_factor {
+            require!(
+                future_amp_factor_precise * MAX_AMP_FACTOR_CHANGE >= initial_amp_factor,
+                ERROR_AMP_FACTOR_CHANGE_TOO_BIG
+            );
+        } else {
+            require!(
+                future_amp_factor_precise <= initial_amp_factor * MAX_AMP_FACTOR_CHANGE,
+                ERROR_AMP_FACTOR_CHANGE_TOO_BIG
+            );
+        }
+
+        self.amp_factor_ramp().set(AmpFactorRamp {
+            initial_amp_factor,
+            future_amp_factor: future_amp_factor_precise,
+            initial_timestamp: current_timestamp,
+            future_timestamp,
+        });
+    }
+
+    #[endpoint(stopRampAmpFactor)]
+    fn stop_ramp_amp_factor(&self) {
+        self.require_caller_has_owner_or_admin_permissions();
+        self.require_stable_swap_curve();
+
+        let current_timestamp = self.blockchain().get_block_timestamp();
+        let current_amp_factor = self.get_amp_factor_precise();
+        self.amp_factor_ramp().set(AmpFactorRamp {
+            initial_amp_factor: current_amp_factor,
+            future_amp_factor: current_amp_factor,
+            initial_timestamp: current_timestamp,
+            future_timestamp: current_timestamp,
+        });
+    }
+
+    #[view(getAmpFactor)]
+    fn get_amp_factor(&self) -> u64 {
+        self.get_amp_factor_precise() / AMP_PRECISION
+    }
+
+    fn get_amp_factor_precise(&self) -> u64 {
+        let ramp = self.amp_factor_ramp().get();
+        let current_timestamp = self.blockchain().get_block_timestamp();
+        if current_timestamp >= ramp.future_timestamp {
+            return ramp.future_amp_factor;
+        }
+
+        let elapsed = current_timestamp - ramp.initial_timestamp;
+        let duration = ramp.future_timestamp - ramp.initial_timestamp;
+        if ramp.future_amp_factor > ramp.initial_amp_factor {
+            ramp.initial_amp_factor
+                + (ramp.future_amp_factor - ramp.initial_amp_factor) * elapsed / duration
+        } else {
+            ramp.initial_amp_factor
+                - (ramp.initial_amp_factor - ramp.future_amp_factor) * elapsed / duration
+        }
+    }
+
+    #[inline]
+    fn require_stable_swap_curve(&self) {
+        require!(
+            self.curve_type().get() == CurveType::StableSwap,
+            ERROR_NOT_STABLE_SWAP_CURVE
+        );
+    }
+
+    /// Computes the StableSwap invariant D for the given reserves, using Newton's method:
+    ///
+    /// A * n^n * (x + y) + D = A * D * n^n + D^(n + 1) / (n^n * x * y)
+    fn compute_stable_swap_invariant(
+        &self,
+        first_token_reserve: &BigUint,
+        second_token_reserve: &BigUint,
+        amp_factor_precise: u64,
+    ) -> BigUint {
+        if first_token_reserve == &0u64 || second_token_reserve == &0u64 {
+            return BigUint::zero();
+        }
+
+        let reserves_sum = first_token_reserve + second_token_reserve;
+
+        let ann = amp_factor_precise * NR_POOL_TOKENS;
+        let mut invariant = reserves_sum.clone();
+        for _ in 0..MAX_ITERATIONS {
+            let invariant_product =
+                &invariant * &invariant / (first_token_reserve * NR_POOL_TOKENS) * &invariant
+                    / (second_token_reserve * NR_POOL_TOKENS);
+            let previous_invariant = invariant.clone();
+
+            let numerator = (&reserves_sum * ann / AMP_PRECISION
+                + &invariant_product * NR_POOL_TOKENS)
+                * &invariant;
+            let denominator = &invariant * (ann - AMP_PRECISION) / AMP_PRECISION
+                + invariant_product * (NR_POOL_TOKENS + 1);
+            invariant = numerator / denominator;
+
+            if self.converged(&invariant, &previous_invariant) {
+                return invariant;
+            }
+        }
+
+        sc_panic!(ERROR_STABLE_SWAP_NO_CONVERGENCE);
+    }
+
+    /// Computes the reserve of one token, given the reserve of the other token
+    /// and the StableSwap invariant D
+    fn compute_stable_swap_reserve(
+        &self,
+        other_token_reserve: &BigUint,
+        invariant: &BigUint,
+        amp_factor_precise: u64,
+    ) -> BigUint {
+        let ann = amp_factor_precise * NR_POOL_TOKENS;
+        let c = invariant * invariant / (other_token_reserve * NR_POOL_TOKENS)
+            * invariant
+            * AMP_PRECISION
+            / (ann * NR_POOL_TOKENS);
+        let b = other_token_reserve + &(invariant * AMP_PRECISION / ann);
+
+        let mut reserve = invariant.clone();
+        for _ in 0..MAX_ITERATIONS {
+            let previous_reserve = reserve.clone();
+            let numerator = &reserve * &reserve + &c;
+            let denominator_sum = &reserve * NR_POOL_TOKENS + &b;
+            require!(
+                &denominator_sum > invariant,
+                ERROR_STABLE_SWAP_INVALID_RESERVES
+            );
+            let denominator = denominator_sum - invariant;
+            reserve = numerator / denominator;
+
+            if self.converged(&reserve, &previous_reserve) {
+                return reserve;
+            }
+        }
+
+        sc_panic!(ERROR_STABLE_SWAP_NO_CONVERGENCE);
+    }
+
+    #[inline]
+    fn converged(&self, value: &BigUint, previous_value: &BigUint) -> bool {
+        if value > previous_value {
+            value - previous_value <= 1u64
+        } else {
+            previous_value - value <= 1u64
+        }
+    }
+
+    #[view(getAmpFactorRamp)]
+    #[storage_mapper("amp_factor_ramp")]
+    fn amp_factor_ramp(&self) -> SingleValueMapper<AmpFactorRamp>;
+}
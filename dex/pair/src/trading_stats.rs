@@ -0,0 +1,109 @@
+multiversx_sc::imports!();
+
+use crate::config::MAX_PERCENTAGE;
+use crate::contexts::swap::SwapContext;
+use week_timekeeping::Week;
+
+#[multiversx_sc::module]
+pub trait TradingStatsModule: week_timekeeping::WeekTimekeepingModule {
+    /// Returns the input and output volume of the given token during the given week.
+    #[view(getVolumeForWeek)]
+    fn get_volume_for_week(
+        &self,
+        week: Week,
+        token_id: EgldOrEsdtTokenIdentifier,
+    ) -> MultiValue2<BigUint, BigUint> {
+        let input_volume = self.input_volume_for_week(week, &token_id).get();
+        let output_volume = self.output_volume_for_week(week, &token_id).get();
+
+        (input_volume, output_volume).into()
+    }
+
+    /// Returns the LP fees and the special fees paid in the given token during the given week.
+    #[view(getFeesForWeek)]
+    fn get_fees_for_week(
+        &self,
+        week: Week,
+        token_id: EgldOrEsdtTokenIdentifier,
+    ) -> MultiValue2<BigUint, BigUint> {
+        let lp_fees = self.lp_fees_for_week(week, &token_id).get();
+        let special_fees = self.special_fees_for_week(week, &token_id).get();
+
+        (lp_fees, special_fees).into()
+    }
+
+    fn record_swap(&self, context: &SwapContext<Self::Api>) {
+        let total_fee_amount = &context.final_input_amount * context.fee_percent / MAX_PERCENTAGE;
+        self.record_input_volume(
+            &context.input_token_id,
+            &context.final_input_amount,
+            &total_fee_amount,
+            &context.fee_amount,
+        );
+        self.record_output_volume(&context.output_token_id, &context.final_output_amount);
+    }
+
+    /// The special fee is part of the total fee, so the LP fee is what remains of it.
+    fn record_input_volume(
+        &self,
+        token_id: &EgldOrEsdtTokenIdentifier,
+        amount: &BigUint,
+        total_fee_amount: &BigUint,
+        special_fee_amount: &BigUint,
+    ) {
+        if *amount == 0 {
+            return;
+        }
+
+        let week = self.get_current_week();
+        self.input_volume_for_week(week, token_id)
+            .update(|volume| *volume += amount);
+
+        if total_fee_amount > special_fee_amount {
+            self.lp_fees_for_week(week, token_id)
+                .update(|fees| *fees += total_fee_amount - special_fee_amount);
+        }
+        if *special_fee_amount > 0 {
+            self.special_fees_for_week(week, token_id)
+                .update(|fees| *fees += special_fee_amount);
+        }
+    }
+
+    fn record_output_volume(&self, token_id: &EgldOrEsdtTokenIdentifier, amount: &BigUint) {
+        if *amount == 0 {
+            return;
+        }
+
+        let week = self.get_current_week();
+        self.output_volume_for_week(week, token_id)
+            .update(|volume| *volume += amount);
+    }
+
+    #[storage_mapper("input_volume_for_week")]
+    fn input_volume_for_week(
+        &self,
+        week: Week,
+        token_id: &EgldOrEsdtTokenIdentifier,
+    ) -> SingleValueMapper<BigUint>;
+
+    #[storage_mapper("output_volume_for_week")]
+    fn output_volume_for_week(
+        &self,
+        week: Week,
+        token_id: &EgldOrEsdtTokenIdentifier,
+    ) -> SingleValueMapper<BigUint>;
+
+    #[storage_mapper("lp_fees_for_week")]
+    fn lp_fees_for_week(
+        &self,
+        week: Week,
+        token_id: &EgldOrEsdtTokenIdentifier,
+    ) -> SingleValueMapper<BigUint>;
+
+    #[storage_mapper("special_fees_for_week")]
+    fn special_fees_for_week(
+        &self,
+        week: Week,
+        token_id: &EgldOrEsdtTokenIdentifier,
+    ) -> SingleValueMapper<BigUint>;
+}
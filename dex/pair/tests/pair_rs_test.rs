@@ -1,28 +1,55 @@
 #![allow(deprecated)]
 
 mod pair_setup;
+use energy_factory::energy::EnergyModule;
+use energy_query::{Energy, EnergyQueryModule};
 use fees_collector::{
     config::ConfigModule, fees_accumulation::FeesAccumulationModule, FeesCollector,
 };
 use multiversx_sc::{
-    codec::multi_types::OptionalValue,
+    codec::{multi_types::OptionalValue, TopDecode, TopEncode},
+    contract_base::ContractBase,
     storage::mappers::StorageTokenWrapper,
-    types::{EsdtLocalRole, MultiValueEncoded},
+    types::{
+        Address, BigInt, BigUint, EgldOrEsdtTokenIdentifier, EgldOrEsdtTokenPayment, EsdtLocalRole,
+        ManagedAddress, ManagedBuffer, MultiValueEncoded,
+    },
 };
 use multiversx_sc_scenario::{
-    managed_address, managed_biguint, managed_token_id, managed_token_id_wrapped, rust_biguint,
-    whitebox_legacy::TxTokenTransfer, DebugApi,
+    managed_address, managed_biguint, managed_buffer, managed_token_id, managed_token_id_wrapped,
+    rust_biguint, whitebox_legacy::TxTokenTransfer, DebugApi,
 };
 use pair::{
-    config::MAX_PERCENTAGE, fee::FeeModule, locking_wrapper::LockingWrapperModule,
-    pair_actions::swap::SwapModule,
+    circuit_breaker::CircuitBreakerModule,
+    config::{
+        ConfigModule as PairConfigModule, CurveType, MAX_PERCENTAGE, MIN_FEE_CHANGE_DELAY_BLOCKS,
+    },
+    dynamic_fee::DynamicFeeModule,
+    energy_fee_discount::EnergyFeeDiscountModule,
+    fee::{BuybackDestination, FeeModule, FeeRouteHop},
+    hooks::{HookType, HooksModule},
+    locking_wrapper::LockingWrapperModule,
+    no_fee_swap_quota::NoFeeSwapQuotaModule,
+    pair_actions::{
+        add_liq::AddLiquidityModule, flash_swap::FlashSwapModule, migration::MigrationModule,
+        remove_liq::RemoveLiquidityModule, single_token_liq::SingleTokenLiquidityModule,
+        swap::SwapModule, views::ViewsModule,
+    },
+    protocol_fee::ProtocolFeeModule,
+    safe_price::{PriceObservation, SafePriceModule},
+    safe_price_view::SafePriceViewModule,
+    stable_swap::StableSwapModule,
+    trading_stats::TradingStatsModule,
+    Pair,
 };
-use pair_setup::*;
+use pair_setup::{hook_mock::*, *};
+use pausable::{PausableModule, State};
 use simple_lock::{
     locked_token::{LockedTokenAttributes, LockedTokenModule},
     proxy_lp::{LpProxyTokenAttributes, ProxyLpModule},
     SimpleLock,
 };
+use week_timekeeping::WeekTimekeepingModule;
 
 #[test]
 fn test_pair_setup() {
@@ -638,201 +665,2838 @@ fn test_safe_price_linear_interpolation() {
     );
 }
 
-// The safe price from the first pair is read from the second pair
-// The purpose of this test is to see if values are returned from the correct contract
 #[test]
-fn test_both_legacy_and_new_safe_price_from_other_contract() {
+fn test_geometric_safe_price() {
     let mut pair_setup = PairSetup::new(pair::contract_obj);
     let pair_address = pair_setup.pair_wrapper.address_ref().clone();
-    let payment_amount = 1000;
-    let starting_round = 1000;
-    let mut expected_amount = 996;
-    let weight = 1;
-    let mut block_round = starting_round + weight;
-    pair_setup.b_mock.set_block_round(block_round);
 
-    let mut first_token_reserve = 1_002_000;
-    let mut second_token_reserve = 1_000_004;
-    let mut first_token_accumulated = 1_001_000;
-    let mut second_token_accumulated = 1_001_000;
+    pair_setup.b_mock.set_block_round(1_000);
     pair_setup.add_liquidity(
-        1_001_000,
-        1_000_000,
-        1_001_000,
-        1_000_000,
-        1_000_000,
-        first_token_accumulated,
-        second_token_accumulated,
-    );
-    pair_setup.swap_fixed_input(
-        WEGLD_TOKEN_ID,
-        payment_amount,
-        MEX_TOKEN_ID,
-        900,
-        expected_amount,
+        1_001_000, 1_000_000, 1_001_000, 1_000_000, 1_000_000, 1_001_000, 1_001_000,
     );
 
-    pair_setup.check_price_observation(
-        &pair_address,
-        block_round,
-        1, // The accumulated weight should be 1, as it is the first element from the list
-        first_token_accumulated,
-        second_token_accumulated,
-    );
+    // small swaps keep the price close to 1:1
+    pair_setup.b_mock.set_block_round(1_010);
+    pair_setup.swap_fixed_input(WEGLD_TOKEN_ID, 1_000, MEX_TOKEN_ID, 900, 996);
 
-    block_round += weight;
-    expected_amount -= 2; // slippage
-    pair_setup.b_mock.set_block_round(block_round);
-    pair_setup.swap_fixed_input(
-        WEGLD_TOKEN_ID,
-        payment_amount,
-        MEX_TOKEN_ID,
-        900,
-        expected_amount,
-    );
+    // the price is pushed far away for a single round, then brought back
+    pair_setup.b_mock.set_block_round(1_020);
+    pair_setup.swap_fixed_input(WEGLD_TOKEN_ID, 3_000_000, MEX_TOKEN_ID, 1, 749_063);
+    pair_setup.b_mock.set_block_round(1_021);
+    pair_setup.swap_fixed_input(MEX_TOKEN_ID, 749_063, WEGLD_TOKEN_ID, 1, 2_995_476);
 
-    first_token_accumulated += weight * first_token_reserve;
-    second_token_accumulated += weight * second_token_reserve;
+    pair_setup.b_mock.set_block_round(1_030);
+    pair_setup.swap_fixed_input(WEGLD_TOKEN_ID, 1_000, MEX_TOKEN_ID, 900, 989);
 
-    pair_setup.check_price_observation_from_second_pair(
-        &pair_address,
-        block_round,
-        block_round - starting_round,
-        first_token_accumulated,
-        second_token_accumulated,
-    );
+    pair_setup
+        .b_mock
+        .execute_query(&pair_setup.pair_wrapper, |sc| {
+            let input_payment = EgldOrEsdtTokenPayment::new(
+                managed_token_id_wrapped!(WEGLD_TOKEN_ID),
+                0,
+                managed_biguint!(1_000),
+            );
 
-    block_round += weight;
-    first_token_reserve += payment_amount;
-    second_token_reserve -= expected_amount;
-    first_token_accumulated += weight * first_token_reserve;
-    second_token_accumulated += weight * second_token_reserve;
-    expected_amount -= 2;
-    pair_setup.b_mock.set_block_round(block_round);
-    pair_setup.swap_fixed_input(
-        WEGLD_TOKEN_ID,
-        payment_amount,
-        MEX_TOKEN_ID,
-        900,
-        expected_amount,
-    );
-    pair_setup.check_price_observation_from_second_pair(
-        &pair_address,
-        block_round,
-        block_round - starting_round,
-        first_token_accumulated,
-        second_token_accumulated,
-    );
+            // with a constant price, both averages are equal
+            let arithmetic_price = sc.get_safe_price(
+                managed_address!(&pair_address),
+                1_010,
+                1_020,
+                input_payment.clone(),
+            );
+            let geometric_price = sc.get_geometric_safe_price(
+                managed_address!(&pair_address),
+                1_010,
+                1_020,
+                input_payment.clone(),
+            );
+            let price_diff = if arithmetic_price.amount > geometric_price.amount {
+                &arithmetic_price.amount - &geometric_price.amount
+            } else {
+                &geometric_price.amount - &arithmetic_price.amount
+            };
+            assert!(price_diff <= managed_biguint!(1));
+
+            // the geometric mean is less affected by the extreme round
+            let arithmetic_price = sc.get_safe_price(
+                managed_address!(&pair_address),
+                1_010,
+                1_030,
+                input_payment.clone(),
+            );
+            let geometric_price = sc.get_geometric_safe_price(
+                managed_address!(&pair_address),
+                1_010,
+                1_030,
+                input_payment,
+            );
+            assert_eq!(
+                geometric_price.token_identifier,
+                managed_token_id_wrapped!(MEX_TOKEN_ID)
+            );
+            assert!(geometric_price.amount > arithmetic_price.amount);
+            assert!(geometric_price.amount < managed_biguint!(1_000));
+        })
+        .assert_ok();
 
-    block_round += weight;
-    first_token_reserve += payment_amount;
-    second_token_reserve -= expected_amount;
-    first_token_accumulated += weight * first_token_reserve;
-    second_token_accumulated += weight * second_token_reserve;
-    expected_amount -= 2;
-    pair_setup.b_mock.set_block_round(block_round);
-    pair_setup.swap_fixed_input(
-        WEGLD_TOKEN_ID,
-        payment_amount,
-        MEX_TOKEN_ID,
-        900,
-        expected_amount,
-    );
-    pair_setup.check_price_observation_from_second_pair(
-        &pair_address,
-        block_round,
-        block_round - starting_round,
-        first_token_accumulated,
-        second_token_accumulated,
+    // observations recorded before the log accumulators were added can still be decoded
+    DebugApi::dummy();
+    let mut legacy_observation = ManagedBuffer::<DebugApi>::new();
+    let legacy_fields: (BigUint<DebugApi>, BigUint<DebugApi>, u64, u64) = (
+        managed_biguint!(1_001_000),
+        managed_biguint!(1_000_000),
+        1,
+        1_000,
     );
-
-    let first_token_payment_amount = 100;
-    let expected_token_payment_amount = 99;
-    pair_setup.check_safe_price_from_second_pair(
-        &pair_address,
-        starting_round + 1,
-        block_round,
-        WEGLD_TOKEN_ID,
-        first_token_payment_amount,
-        MEX_TOKEN_ID,
-        expected_token_payment_amount,
+    legacy_fields.top_encode(&mut legacy_observation).unwrap();
+    let price_observation = PriceObservation::<DebugApi>::top_decode(legacy_observation).unwrap();
+    assert_eq!(price_observation.weight_accumulated, 1);
+    assert_eq!(price_observation.recording_round, 1_000);
+    assert_eq!(price_observation.log_weight_accumulated, 0);
+    assert_eq!(
+        price_observation.first_token_log_reserve_accumulated,
+        managed_biguint!(0)
     );
 
-    // Check legacy endpoint
-    // Should be the same as the result from the new get_safe_price view
-    pair_setup.check_safe_price_from_legacy_endpoint(
-        WEGLD_TOKEN_ID,
-        first_token_payment_amount,
-        MEX_TOKEN_ID,
-        expected_token_payment_amount,
-    );
+    // nested observations always hold all the fields
+    let mut nested_observations = ManagedBuffer::<DebugApi>::new();
+    let mut second_observation = price_observation.clone();
+    second_observation.log_weight_accumulated = 5;
+    (price_observation, second_observation)
+        .top_encode(&mut nested_observations)
+        .unwrap();
+    let (first_decoded, second_decoded) =
+        <(PriceObservation<DebugApi>, PriceObservation<DebugApi>)>::top_decode(nested_observations)
+            .unwrap();
+    assert_eq!(first_decoded.recording_round, 1_000);
+    assert_eq!(first_decoded.log_weight_accumulated, 0);
+    assert_eq!(second_decoded.recording_round, 1_000);
+    assert_eq!(second_decoded.log_weight_accumulated, 5);
 }
 
-// Test is commented as it needs a variable change in order to run succesfully
-// In order to run the test with the current setup, MAX_OBSERVATIONS const must be set to 100
-// This is necessary as using the MAINNET variable requires too many operations for a unit test
-// #[test]
-// fn test_safe_price_max_length() {
-//     let mut pair_setup = PairSetup::new(pair::contract_obj);
-//     let pair_address = pair_setup.pair_wrapper.address_ref().clone();
-//     let max_observations = MAX_OBSERVATIONS.try_into().unwrap(); // should be 100
-//     let min_pool_reserve = 1_000;
-//     let weight = 1;
-//     let mut block_round = 0u64;
+#[test]
+fn test_dynamic_fee() {
+    let mut pair_setup = PairSetup::new(pair::contract_obj);
+    let user_address = pair_setup.user_address.clone();
 
-//     let mut first_token_reserve = 1_001_000;
-//     let mut second_token_reserve = 30_030_000;
-//     let mut first_token_accumulated = weight * first_token_reserve;
-//     let mut second_token_accumulated = weight * second_token_reserve;
+    pair_setup.b_mock.set_block_round(1_000);
+    pair_setup.add_liquidity(
+        1_001_000, 1_000_000, 1_001_000, 1_000_000, 1_000_000, 1_001_000, 1_001_000,
+    );
 
-//     pair_setup.add_liquidity(
-//         first_token_reserve,
-//         first_token_reserve,
-//         second_token_reserve,
-//         first_token_reserve,
-//         first_token_reserve - min_pool_reserve,
-//         first_token_reserve,
-//         second_token_reserve,
-//     );
+    pair_setup.b_mock.set_block_round(1_010);
+    pair_setup.swap_fixed_input(WEGLD_TOKEN_ID, 1_000, MEX_TOKEN_ID, 900, 996);
 
-//     let mut first_token_payment_amount = 1;
-//     let mut second_token_expected_amount = 29;
+    // the min fee can not be lower than the special fee
+    pair_setup
+        .b_mock
+        .execute_tx(
+            &pair_setup.owner_address,
+            &pair_setup.pair_wrapper,
+            &rust_biguint!(0),
+            |sc| {
+                sc.set_dynamic_fee_config(40, 1_000, 10, MAX_PERCENTAGE);
+            },
+        )
+        .assert_user_error("Bad percents");
 
-//     while block_round <= max_observations {
-//         // println!("Round: {}", (block_round));
+    // while swaps are enabled, a max fee above the total fee has to be announced
+    pair_setup
+        .b_mock
+        .execute_tx(
+            &pair_setup.owner_address,
+            &pair_setup.pair_wrapper,
+            &rust_biguint!(0),
+            |sc| {
+                sc.set_dynamic_fee_config(300, 1_000, 10, MAX_PERCENTAGE);
+            },
+        )
+        .assert_user_error("Fee changes have to be announced while swaps are enabled");
 
-//         block_round += weight;
-//         pair_setup.b_mock.set_block_round(block_round);
-//         pair_setup.swap_fixed_input(
-//             WEGLD_TOKEN_ID,
-//             first_token_payment_amount,
-//             MEX_TOKEN_ID,
-//             1,
-//             second_token_expected_amount,
-//         );
+    pair_setup.b_mock.set_block_nonce(1);
+    pair_setup
+        .b_mock
+        .execute_tx(
+            &pair_setup.owner_address,
+            &pair_setup.pair_wrapper,
+            &rust_biguint!(0),
+            |sc| {
+                sc.announce_dynamic_fee_config(300, 1_000, 10, MAX_PERCENTAGE);
+            },
+        )
+        .assert_ok();
+    pair_setup
+        .b_mock
+        .execute_tx(
+            &user_address,
+            &pair_setup.pair_wrapper,
+            &rust_biguint!(0),
+            |sc| {
+                sc.apply_dynamic_fee_config();
+            },
+        )
+        .assert_user_error("The fee change is not effective yet");
 
-//         first_token_reserve += first_token_payment_amount;
-//         second_token_reserve -= second_token_expected_amount;
-//         first_token_accumulated += weight * first_token_reserve;
-//         second_token_accumulated += weight * second_token_reserve;
+    pair_setup
+        .b_mock
+        .set_block_nonce(1 + MIN_FEE_CHANGE_DELAY_BLOCKS);
+    pair_setup
+        .b_mock
+        .execute_tx(
+            &user_address,
+            &pair_setup.pair_wrapper,
+            &rust_biguint!(0),
+            |sc| {
+                sc.apply_dynamic_fee_config();
+                assert!(sc.pending_dynamic_fee_config().is_empty());
+            },
+        )
+        .assert_ok();
 
-//         second_token_expected_amount = second_token_reserve / first_token_reserve;
-//     }
+    // the price did not move, so the min fee is applied
+    pair_setup.b_mock.set_block_round(1_020);
+    pair_setup
+        .b_mock
+        .execute_query(&pair_setup.pair_wrapper, |sc| {
+            assert_eq!(sc.get_swap_fee_percent(), 300);
+        })
+        .assert_ok();
+    pair_setup.swap_fixed_input(WEGLD_TOKEN_ID, 3_000_000, MEX_TOKEN_ID, 1, 749_063);
 
-//     let mut second_token_payment_amount = 1_000_000;
-//     let mut first_token_expected_amount = 32_171;
+    // the price moved far away from its average, so the fee is capped at the max fee,
+    // which is above the total fee
+    pair_setup.b_mock.set_block_round(1_021);
+    pair_setup
+        .b_mock
+        .execute_query(&pair_setup.pair_wrapper, |sc| {
+            assert_eq!(sc.get_swap_fee_percent(), 1_000);
+        })
+        .assert_ok();
 
-//     // Price change
-//     block_round += weight;
-//     println!("Price change round: {}", (block_round));
-//     pair_setup.b_mock.set_block_round(block_round);
-//     pair_setup.swap_fixed_input(
-//         MEX_TOKEN_ID,
-//         second_token_payment_amount,
-//         WEGLD_TOKEN_ID,
-//         1,
-//         first_token_expected_amount,
-//     );
+    // the volatility is scaled by the multiplier, and lowering the max fee is instant
+    pair_setup
+        .b_mock
+        .execute_tx(
+            &pair_setup.owner_address,
+            &pair_setup.pair_wrapper,
+            &rust_biguint!(0),
+            |sc| {
+                sc.set_dynamic_fee_config(300, 800, 10, 250);
+                assert_eq!(sc.get_price_volatility(10), 91_174);
+                assert_eq!(
+                    sc.get_swap_fee_percent(),
+                    300 + 91_174 * 250 / MAX_PERCENTAGE
+                );
+            },
+        )
+        .assert_ok();
+
+    pair_setup
+        .b_mock
+        .execute_tx(
+            &pair_setup.owner_address,
+            &pair_setup.pair_wrapper,
+            &rust_biguint!(0),
+            |sc| {
+                sc.disable_dynamic_fee();
+                assert_eq!(sc.get_swap_fee_percent(), 300);
+            },
+        )
+        .assert_ok();
+}
+
+#[test]
+fn test_energy_fee_discount() {
+    let mut pair_setup = PairSetup::new(pair::contract_obj);
+    let rust_zero = rust_biguint!(0);
+    let owner_address = pair_setup.owner_address.clone();
+    let user_address = pair_setup.user_address.clone();
+
+    let energy_factory_wrapper = pair_setup.b_mock.create_sc_account(
+        &rust_zero,
+        Some(&owner_address),
+        energy_factory::contract_obj,
+        "energy factory path",
+    );
+    let contract_address = pair_setup.second_pair_wrapper.address_ref().clone();
+    for (address, energy_amount) in [
+        (&owner_address, 5_000u64),
+        (&user_address, 50_000u64),
+        (&contract_address, 50_000u64),
+    ] {
+        pair_setup
+            .b_mock
+            .execute_tx(address, &energy_factory_wrapper, &rust_zero, |sc| {
+                sc.user_energy(&managed_address!(address)).set(&Energy::new(
+                    BigInt::from(managed_biguint!(energy_amount)),
+                    0,
+                    managed_biguint!(1),
+                ));
+            })
+            .assert_ok();
+    }
+
+    pair_setup
+        .b_mock
+        .execute_tx(&owner_address, &pair_setup.pair_wrapper, &rust_zero, |sc| {
+            sc.set_energy_factory_address(managed_address!(energy_factory_wrapper.address_ref()));
+
+            // tiers must be sorted
+            let mut tiers = MultiValueEncoded::new();
+            tiers.push((managed_biguint!(10_000), 50_000u64).into());
+            tiers.push((managed_biguint!(1_000), 20_000u64).into());
+            sc.set_fee_discount_tiers(tiers);
+        })
+        .assert_user_error("Invalid fee discount tiers");
+
+    pair_setup
+        .b_mock
+        .execute_tx(&owner_address, &pair_setup.pair_wrapper, &rust_zero, |sc| {
+            sc.set_energy_factory_address(managed_address!(energy_factory_wrapper.address_ref()));
+
+            let mut tiers = MultiValueEncoded::new();
+            tiers.push((managed_biguint!(1_000), 20_000u64).into());
+            tiers.push((managed_biguint!(10_000), 50_000u64).into());
+            sc.set_fee_discount_tiers(tiers);
+        })
+        .assert_ok();
+
+    pair_setup.add_liquidity(
+        1_001_000, 1_000_000, 1_001_000, 1_000_000, 1_000_000, 1_001_000, 1_001_000,
+    );
+
+    pair_setup
+        .b_mock
+        .execute_query(&pair_setup.pair_wrapper, |sc| {
+            // no user, so the full fee is applied
+            let amount_out = sc.get_amount_out_view(
+                managed_token_id_wrapped!(WEGLD_TOKEN_ID),
+                managed_biguint!(100_000),
+                OptionalValue::None,
+            );
+            assert_eq!(amount_out, managed_biguint!(90_669));
+
+            // 20% fee discount
+            let amount_out = sc.get_amount_out_view(
+                managed_token_id_wrapped!(WEGLD_TOKEN_ID),
+                managed_biguint!(100_000),
+                OptionalValue::Some(managed_address!(&owner_address)),
+            );
+            assert_eq!(amount_out, managed_biguint!(90_718));
+
+            // 50% fee discount
+            let amount_out = sc.get_amount_out_view(
+                managed_token_id_wrapped!(WEGLD_TOKEN_ID),
+                managed_biguint!(100_000),
+                OptionalValue::Some(managed_address!(&user_address)),
+            );
+            assert_eq!(amount_out, managed_biguint!(90_793));
+
+            // contracts get no discount
+            let amount_out = sc.get_amount_out_view(
+                managed_token_id_wrapped!(WEGLD_TOKEN_ID),
+                managed_biguint!(100_000),
+                OptionalValue::Some(managed_address!(&contract_address)),
+            );
+            assert_eq!(amount_out, managed_biguint!(90_669));
+
+            let amount_in = sc.get_amount_in_view(
+                managed_token_id_wrapped!(MEX_TOKEN_ID),
+                managed_biguint!(99_000),
+                OptionalValue::Some(managed_address!(&user_address)),
+            );
+            assert_eq!(amount_in, managed_biguint!(110_031));
+        })
+        .assert_ok();
+
+    pair_setup.swap_fixed_input(WEGLD_TOKEN_ID, 100_000, MEX_TOKEN_ID, 90_000, 90_793);
+}
+
+#[test]
+fn test_single_token_liquidity() {
+    let mut pair_setup = PairSetup::new(pair::contract_obj);
+    let user_address = pair_setup.user_address.clone();
+
+    pair_setup.add_liquidity(
+        1_001_000, 1_000_000, 1_001_000, 1_000_000, 1_000_000, 1_001_000, 1_001_000,
+    );
+
+    pair_setup
+        .b_mock
+        .execute_esdt_transfer(
+            &user_address,
+            &pair_setup.pair_wrapper,
+            WEGLD_TOKEN_ID,
+            0,
+            &rust_biguint!(100_000),
+            |sc| {
+                sc.add_liquidity_single_token(managed_biguint!(50_000), OptionalValue::None);
+            },
+        )
+        .assert_user_error("Slippage exceeded");
+
+    pair_setup
+        .b_mock
+        .execute_esdt_transfer(
+            &user_address,
+            &pair_setup.pair_wrapper,
+            WEGLD_TOKEN_ID,
+            0,
+            &rust_biguint!(100_000),
+            |sc| {
+                let (lp_payment, first_payment, second_payment) = sc
+                    .add_liquidity_single_token(managed_biguint!(48_000), OptionalValue::None)
+                    .into_tuple();
+                assert_eq!(lp_payment.amount, managed_biguint!(48_735));
+                assert_eq!(first_payment.amount, managed_biguint!(51_115));
+                assert_eq!(second_payment.amount, managed_biguint!(46_473));
+            },
+        )
+        .assert_ok();
+
+    // the leftover of the swap is sent back
+    pair_setup.b_mock.check_esdt_balance(
+        &user_address,
+        WEGLD_TOKEN_ID,
+        &rust_biguint!(USER_TOTAL_WEGLD_TOKENS - 1_001_000 - 100_000 + 2),
+    );
+    pair_setup.b_mock.check_esdt_balance(
+        &user_address,
+        LP_TOKEN_ID,
+        &rust_biguint!(1_000_000 + 48_735),
+    );
+
+    pair_setup
+        .b_mock
+        .execute_esdt_transfer(
+            &user_address,
+            &pair_setup.pair_wrapper,
+            LP_TOKEN_ID,
+            0,
+            &rust_biguint!(48_735),
+            |sc| {
+                let output_payment = sc.remove_liquidity_to_single_token(
+                    managed_token_id_wrapped!(WEGLD_TOKEN_ID),
+                    managed_biguint!(99_000),
+                    OptionalValue::None,
+                );
+                assert_eq!(
+                    output_payment.token_identifier,
+                    managed_token_id!(WEGLD_TOKEN_ID)
+                );
+                assert_eq!(output_payment.amount, managed_biguint!(99_716));
+            },
+        )
+        .assert_ok();
+
+    pair_setup.b_mock.check_esdt_balance(
+        &user_address,
+        WEGLD_TOKEN_ID,
+        &rust_biguint!(USER_TOTAL_WEGLD_TOKENS - 1_001_000 - 100_000 + 2 + 99_716),
+    );
+    pair_setup.b_mock.check_esdt_balance(
+        &user_address,
+        MEX_TOKEN_ID,
+        &rust_biguint!(USER_TOTAL_MEX_TOKENS - 1_001_000),
+    );
+}
+
+#[test]
+fn test_price_deviation_circuit_breaker() {
+    let mut pair_setup = PairSetup::new(pair::contract_obj);
+
+    pair_setup.b_mock.set_block_round(1_000);
+    pair_setup.add_liquidity(
+        1_001_000, 1_000_000, 1_001_000, 1_000_000, 1_000_000, 1_001_000, 1_001_000,
+    );
+
+    pair_setup.b_mock.set_block_round(1_010);
+    pair_setup.swap_fixed_input(WEGLD_TOKEN_ID, 1_000, MEX_TOKEN_ID, 900, 996);
+
+    pair_setup
+        .b_mock
+        .execute_tx(
+            &pair_setup.owner_address,
+            &pair_setup.pair_wrapper,
+            &rust_biguint!(0),
+            |sc| {
+                sc.set_max_price_deviation(10_000);
+                assert_eq!(sc.max_price_deviation().get(), 10_000);
+            },
+        )
+        .assert_ok();
+
+    // the price would move by more than 10% from the safe price
+    pair_setup.b_mock.set_block_round(1_020);
+    pair_setup
+        .b_mock
+        .execute_esdt_transfer(
+            &pair_setup.user_address,
+            &pair_setup.pair_wrapper,
+            WEGLD_TOKEN_ID,
+            0,
+            &rust_biguint!(3_000_000),
+            |sc| {
+                sc.swap_tokens_fixed_input(
+                    managed_token_id_wrapped!(MEX_TOKEN_ID),
+                    managed_biguint!(1),
+                    OptionalValue::None,
+                );
+            },
+        )
+        .assert_user_error("The price deviates too much from the safe price");
+
+    pair_setup.swap_fixed_input(WEGLD_TOKEN_ID, 1_000, MEX_TOKEN_ID, 900, 994);
+
+    pair_setup
+        .b_mock
+        .execute_tx(
+            &pair_setup.owner_address,
+            &pair_setup.pair_wrapper,
+            &rust_biguint!(0),
+            |sc| {
+                sc.set_max_price_deviation(0);
+            },
+        )
+        .assert_ok();
+
+    pair_setup.swap_fixed_input(WEGLD_TOKEN_ID, 3_000_000, MEX_TOKEN_ID, 1, 748_131);
+
+    pair_setup
+        .b_mock
+        .execute_tx(
+            &pair_setup.owner_address,
+            &pair_setup.pair_wrapper,
+            &rust_biguint!(0),
+            |sc| {
+                sc.set_max_price_deviation(10_000);
+            },
+        )
+        .assert_ok();
+
+    // the price is still too far from the safe price, but a swap bringing it back is allowed
+    pair_setup.b_mock.set_block_round(1_030);
+    pair_setup
+        .b_mock
+        .execute_esdt_transfer(
+            &pair_setup.user_address,
+            &pair_setup.pair_wrapper,
+            MEX_TOKEN_ID,
+            0,
+            &rust_biguint!(1_000),
+            |sc| {
+                sc.swap_tokens_fixed_input(
+                    managed_token_id_wrapped!(WEGLD_TOKEN_ID),
+                    managed_biguint!(1),
+                    OptionalValue::None,
+                );
+            },
+        )
+        .assert_ok();
+
+    // while a swap moving it further away is not
+    pair_setup
+        .b_mock
+        .execute_esdt_transfer(
+            &pair_setup.user_address,
+            &pair_setup.pair_wrapper,
+            WEGLD_TOKEN_ID,
+            0,
+            &rust_biguint!(1_000),
+            |sc| {
+                sc.swap_tokens_fixed_input(
+                    managed_token_id_wrapped!(MEX_TOKEN_ID),
+                    managed_biguint!(1),
+                    OptionalValue::None,
+                );
+            },
+        )
+        .assert_user_error("The price deviates too much from the safe price");
+
+    // the price is about 75% below the safe price, and would end up about 69% above it,
+    // so crossing the safe price is rejected even if the deviation gets a bit smaller
+    pair_setup
+        .b_mock
+        .execute_esdt_transfer(
+            &pair_setup.user_address,
+            &pair_setup.pair_wrapper,
+            MEX_TOKEN_ID,
+            0,
+            &rust_biguint!(400_000),
+            |sc| {
+                sc.swap_tokens_fixed_input(
+                    managed_token_id_wrapped!(WEGLD_TOKEN_ID),
+                    managed_biguint!(1),
+                    OptionalValue::None,
+                );
+            },
+        )
+        .assert_user_error("The price deviates too much from the safe price");
+
+    // the no fee swaps of the whitelisted contracts are not checked, so that
+    // the fee conversions of the other pairs never fail because of this pair's price
+    let pair_address = pair_setup.pair_wrapper.address_ref().clone();
+    let user_address = pair_setup.user_address.clone();
+    pair_setup
+        .b_mock
+        .set_esdt_local_roles(&pair_address, WEGLD_TOKEN_ID, &[EsdtLocalRole::Burn]);
+    pair_setup
+        .b_mock
+        .execute_tx(
+            &pair_setup.owner_address,
+            &pair_setup.pair_wrapper,
+            &rust_biguint!(0),
+            |sc| {
+                sc.whitelist_endpoint(managed_address!(&user_address));
+            },
+        )
+        .assert_ok();
+    pair_setup
+        .b_mock
+        .execute_esdt_transfer(
+            &user_address,
+            &pair_setup.pair_wrapper,
+            MEX_TOKEN_ID,
+            0,
+            &rust_biguint!(400_000),
+            |sc| {
+                sc.swap_no_fee(
+                    managed_token_id!(WEGLD_TOKEN_ID),
+                    managed_address!(&user_address),
+                );
+            },
+        )
+        .assert_ok();
+}
+
+#[test]
+fn test_protocol_fee() {
+    let mut pair_setup = PairSetup::new(pair::contract_obj);
+    let recipient = pair_setup.b_mock.create_user_account(&rust_biguint!(0));
+
+    pair_setup.add_liquidity(
+        1_001_000, 1_000_000, 1_001_000, 1_000_000, 1_000_000, 1_001_000, 1_001_000,
+    );
+
+    pair_setup
+        .b_mock
+        .execute_tx(
+            &pair_setup.owner_address,
+            &pair_setup.pair_wrapper,
+            &rust_biguint!(0),
+            |sc| {
+                sc.set_protocol_fee(managed_address!(&recipient), 20_000);
+                assert_eq!(
+                    sc.k_last().get(),
+                    managed_biguint!(1_001_000u64 * 1_001_000)
+                );
+                assert_eq!(sc.get_pending_protocol_fee_lp(), managed_biguint!(0));
+            },
+        )
+        .assert_ok();
+
+    pair_setup.swap_fixed_input(WEGLD_TOKEN_ID, 100_000, MEX_TOKEN_ID, 1, 90_669);
+
+    pair_setup
+        .b_mock
+        .execute_query(&pair_setup.pair_wrapper, |sc| {
+            assert_eq!(sc.get_pending_protocol_fee_lp(), managed_biguint!(27));
+        })
+        .assert_ok();
+
+    // the pending protocol fee is minted before the liquidity is removed
+    pair_setup
+        .b_mock
+        .execute_esdt_transfer(
+            &pair_setup.user_address,
+            &pair_setup.pair_wrapper,
+            LP_TOKEN_ID,
+            0,
+            &rust_biguint!(100_000),
+            |sc| {
+                let (first_payment, second_payment) = sc
+                    .remove_liquidity(
+                        managed_biguint!(1),
+                        managed_biguint!(1),
+                        OptionalValue::None,
+                    )
+                    .into_tuple();
+                assert_eq!(first_payment.amount, managed_biguint!(109_987));
+                assert_eq!(second_payment.amount, managed_biguint!(90_939));
+
+                assert_eq!(sc.lp_token_supply().get(), managed_biguint!(901_027));
+                assert_eq!(sc.k_last().get(), managed_biguint!(812_028_124_096u64));
+                assert_eq!(sc.get_pending_protocol_fee_lp(), managed_biguint!(0));
+            },
+        )
+        .assert_ok();
+
+    // the minted LP tokens accrue until the recipient claims them
+    pair_setup
+        .b_mock
+        .check_esdt_balance(&recipient, LP_TOKEN_ID, &rust_biguint!(0));
+    pair_setup
+        .b_mock
+        .execute_query(&pair_setup.pair_wrapper, |sc| {
+            assert_eq!(
+                sc.accrued_protocol_fee_lp(&managed_address!(&recipient))
+                    .get(),
+                managed_biguint!(27)
+            );
+        })
+        .assert_ok();
+
+    pair_setup
+        .b_mock
+        .execute_tx(
+            &recipient,
+            &pair_setup.pair_wrapper,
+            &rust_biguint!(0),
+            |sc| {
+                let lp_payment = sc.claim_protocol_fee();
+                assert_eq!(lp_payment.amount, managed_biguint!(27));
+                assert_eq!(
+                    sc.accrued_protocol_fee_lp(&managed_address!(&recipient))
+                        .get(),
+                    managed_biguint!(0)
+                );
+            },
+        )
+        .assert_ok();
+
+    pair_setup
+        .b_mock
+        .check_esdt_balance(&recipient, LP_TOKEN_ID, &rust_biguint!(27));
+
+    pair_setup
+        .b_mock
+        .execute_tx(
+            &pair_setup.owner_address,
+            &pair_setup.pair_wrapper,
+            &rust_biguint!(0),
+            |sc| {
+                sc.disable_protocol_fee();
+                assert!(sc.protocol_fee_config().is_empty());
+                assert_eq!(sc.k_last().get(), managed_biguint!(0));
+            },
+        )
+        .assert_ok();
+}
+
+#[test]
+fn test_protocol_fee_single_token_liquidity() {
+    let mut single_token_setup = PairSetup::new(pair::contract_obj);
+    let mut two_step_setup = PairSetup::new(pair::contract_obj);
+    let recipient = single_token_setup
+        .b_mock
+        .create_user_account(&rust_biguint!(0));
+
+    for pair_setup in [&mut single_token_setup, &mut two_step_setup] {
+        pair_setup.add_liquidity(
+            1_001_000, 1_000_000, 1_001_000, 1_000_000, 1_000_000, 1_001_000, 1_001_000,
+        );
+        pair_setup
+            .b_mock
+            .execute_tx(
+                &pair_setup.owner_address,
+                &pair_setup.pair_wrapper,
+                &rust_biguint!(0),
+                |sc| {
+                    sc.set_protocol_fee(managed_address!(&recipient), 20_000);
+                },
+            )
+            .assert_ok();
+    }
+
+    // adding liquidity with a single token accrues the same protocol fee as a swap
+    // followed by adding liquidity with both tokens
+    single_token_setup
+        .b_mock
+        .execute_esdt_transfer(
+            &single_token_setup.user_address,
+            &single_token_setup.pair_wrapper,
+            WEGLD_TOKEN_ID,
+            0,
+            &rust_biguint!(100_000),
+            |sc| {
+                let (lp_payment, first_payment, second_payment) = sc
+                    .add_liquidity_single_token(managed_biguint!(1), OptionalValue::None)
+                    .into_tuple();
+                assert_eq!(lp_payment.amount, managed_biguint!(48_735));
+                assert_eq!(first_payment.amount, managed_biguint!(51_115));
+                assert_eq!(second_payment.amount, managed_biguint!(46_473));
+            },
+        )
+        .assert_ok();
+
+    two_step_setup.swap_fixed_input(WEGLD_TOKEN_ID, 48_883, MEX_TOKEN_ID, 1, 46_473);
+    two_step_setup.add_liquidity(51_117, 1, 46_473, 1, 48_735, 51_115, 46_473);
+
+    // removing liquidity to a single token accrues the same protocol fee as
+    // removing liquidity followed by a swap
+    single_token_setup
+        .b_mock
+        .execute_esdt_transfer(
+            &single_token_setup.user_address,
+            &single_token_setup.pair_wrapper,
+            LP_TOKEN_ID,
+            0,
+            &rust_biguint!(100_000),
+            |sc| {
+                let output_payment = sc.remove_liquidity_to_single_token(
+                    managed_token_id_wrapped!(WEGLD_TOKEN_ID),
+                    managed_biguint!(1),
+                    OptionalValue::None,
+                );
+                assert_eq!(output_payment.amount, managed_biguint!(199_515));
+            },
+        )
+        .assert_ok();
+    two_step_setup
+        .b_mock
+        .execute_esdt_transfer(
+            &two_step_setup.user_address,
+            &two_step_setup.pair_wrapper,
+            LP_TOKEN_ID,
+            0,
+            &rust_biguint!(100_000),
+            |sc| {
+                let (first_payment, second_payment) = sc
+                    .remove_liquidity(
+                        managed_biguint!(1),
+                        managed_biguint!(1),
+                        OptionalValue::None,
+                    )
+                    .into_tuple();
+                assert_eq!(first_payment.amount, managed_biguint!(104_882));
+                assert_eq!(second_payment.amount, managed_biguint!(95_356));
+            },
+        )
+        .assert_ok();
+    two_step_setup.swap_fixed_input(MEX_TOKEN_ID, 95_356, WEGLD_TOKEN_ID, 1, 94_633);
+    // the fee of the internal swap is minted on the next liquidity event
+    for pair_setup in [&mut single_token_setup, &mut two_step_setup] {
+        pair_setup
+            .b_mock
+            .execute_query(&pair_setup.pair_wrapper, |sc| {
+                assert_eq!(
+                    sc.accrued_protocol_fee_lp(&managed_address!(&recipient))
+                        .get(),
+                    managed_biguint!(13)
+                );
+                assert_eq!(sc.get_pending_protocol_fee_lp(), managed_biguint!(27));
+                assert_eq!(sc.k_last().get(), managed_biguint!(902_126_478_704u64));
+            })
+            .assert_ok();
+    }
+}
+
+#[test]
+fn test_trading_stats() {
+    let mut pair_setup = PairSetup::new(pair::contract_obj);
+
+    pair_setup.add_liquidity(
+        1_001_000, 1_000_000, 1_001_000, 1_000_000, 1_000_000, 1_001_000, 1_001_000,
+    );
+    pair_setup.swap_fixed_input(WEGLD_TOKEN_ID, 1_000, MEX_TOKEN_ID, 900, 996);
+
+    pair_setup.b_mock.set_block_epoch(7);
+    pair_setup.swap_fixed_input(MEX_TOKEN_ID, 1_000, WEGLD_TOKEN_ID, 900, 997);
+
+    pair_setup
+        .b_mock
+        .execute_query(&pair_setup.pair_wrapper, |sc| {
+            assert_eq!(sc.get_current_week(), 2);
+
+            let (input_volume, output_volume) = sc
+                .get_volume_for_week(1, managed_token_id_wrapped!(WEGLD_TOKEN_ID))
+                .into_tuple();
+            assert_eq!(input_volume, managed_biguint!(1_000));
+            assert_eq!(output_volume, managed_biguint!(0));
+            let (input_volume, output_volume) = sc
+                .get_volume_for_week(1, managed_token_id_wrapped!(MEX_TOKEN_ID))
+                .into_tuple();
+            assert_eq!(input_volume, managed_biguint!(0));
+            assert_eq!(output_volume, managed_biguint!(996));
+
+            let (input_volume, output_volume) = sc
+                .get_volume_for_week(2, managed_token_id_wrapped!(MEX_TOKEN_ID))
+                .into_tuple();
+            assert_eq!(input_volume, managed_biguint!(1_000));
+            assert_eq!(output_volume, managed_biguint!(0));
+            let (input_volume, output_volume) = sc
+                .get_volume_for_week(2, managed_token_id_wrapped!(WEGLD_TOKEN_ID))
+                .into_tuple();
+            assert_eq!(input_volume, managed_biguint!(0));
+            assert_eq!(output_volume, managed_biguint!(997));
+
+            // fees are disabled, so the whole fee stays in the pool
+            let (lp_fees, special_fees) = sc
+                .get_fees_for_week(1, managed_token_id_wrapped!(WEGLD_TOKEN_ID))
+                .into_tuple();
+            assert_eq!(lp_fees, managed_biguint!(3));
+            assert_eq!(special_fees, managed_biguint!(0));
+            let (lp_fees, special_fees) = sc
+                .get_fees_for_week(2, managed_token_id_wrapped!(MEX_TOKEN_ID))
+                .into_tuple();
+            assert_eq!(lp_fees, managed_biguint!(3));
+            assert_eq!(special_fees, managed_biguint!(0));
+        })
+        .assert_ok();
+}
+
+#[test]
+fn test_deadline() {
+    let mut pair_setup = PairSetup::new(pair::contract_obj);
+
+    pair_setup.add_liquidity(
+        1_001_000, 1_000_000, 1_001_000, 1_000_000, 1_000_000, 1_001_000, 1_001_000,
+    );
+
+    pair_setup.b_mock.set_block_timestamp(100);
+    pair_setup
+        .b_mock
+        .execute_esdt_transfer(
+            &pair_setup.user_address,
+            &pair_setup.pair_wrapper,
+            WEGLD_TOKEN_ID,
+            0,
+            &rust_biguint!(1_000),
+            |sc| {
+                sc.swap_tokens_fixed_input(
+                    managed_token_id_wrapped!(MEX_TOKEN_ID),
+                    managed_biguint!(900),
+                    OptionalValue::Some(99),
+                );
+            },
+        )
+        .assert_user_error("Deadline expired");
+
+    pair_setup
+        .b_mock
+        .execute_esdt_transfer(
+            &pair_setup.user_address,
+            &pair_setup.pair_wrapper,
+            WEGLD_TOKEN_ID,
+            0,
+            &rust_biguint!(1_000),
+            |sc| {
+                let ret = sc.swap_tokens_fixed_input(
+                    managed_token_id_wrapped!(MEX_TOKEN_ID),
+                    managed_biguint!(900),
+                    OptionalValue::Some(100),
+                );
+                assert_eq!(ret.amount, managed_biguint!(996));
+            },
+        )
+        .assert_ok();
+
+    pair_setup
+        .b_mock
+        .execute_esdt_transfer(
+            &pair_setup.user_address,
+            &pair_setup.pair_wrapper,
+            LP_TOKEN_ID,
+            0,
+            &rust_biguint!(1_000),
+            |sc| {
+                sc.remove_liquidity(
+                    managed_biguint!(1),
+                    managed_biguint!(1),
+                    OptionalValue::Some(99),
+                );
+            },
+        )
+        .assert_user_error("Deadline expired");
+
+    pair_setup
+        .b_mock
+        .execute_esdt_transfer(
+            &pair_setup.user_address,
+            &pair_setup.pair_wrapper,
+            WEGLD_TOKEN_ID,
+            0,
+            &rust_biguint!(1_000),
+            |sc| {
+                sc.add_liquidity_single_token(managed_biguint!(1), OptionalValue::Some(99));
+            },
+        )
+        .assert_user_error("Deadline expired");
+
+    pair_setup
+        .b_mock
+        .execute_esdt_transfer(
+            &pair_setup.user_address,
+            &pair_setup.pair_wrapper,
+            LP_TOKEN_ID,
+            0,
+            &rust_biguint!(1_000),
+            |sc| {
+                sc.remove_liquidity_to_single_token(
+                    managed_token_id_wrapped!(WEGLD_TOKEN_ID),
+                    managed_biguint!(1),
+                    OptionalValue::Some(99),
+                );
+            },
+        )
+        .assert_user_error("Deadline expired");
+}
+
+#[test]
+fn test_hooks_registration() {
+    let mut pair_setup = PairSetup::new(pair::contract_obj);
+    let owner_address = pair_setup.owner_address.clone();
+    let user_address = pair_setup.user_address.clone();
+    let hook_address = pair_setup.second_pair_wrapper.address_ref().clone();
+
+    pair_setup
+        .b_mock
+        .execute_tx(
+            &user_address,
+            &pair_setup.pair_wrapper,
+            &rust_biguint!(0),
+            |sc| {
+                sc.add_hook(HookType::BeforeSwap, managed_address!(&hook_address));
+            },
+        )
+        .assert_user_error("Permission denied");
+
+    pair_setup
+        .b_mock
+        .execute_tx(
+            &owner_address,
+            &pair_setup.pair_wrapper,
+            &rust_biguint!(0),
+            |sc| {
+                sc.add_hook(HookType::BeforeSwap, managed_address!(&user_address));
+            },
+        )
+        .assert_user_error("Invalid hook address");
+
+    pair_setup
+        .b_mock
+        .execute_tx(
+            &owner_address,
+            &pair_setup.pair_wrapper,
+            &rust_biguint!(0),
+            |sc| {
+                sc.add_hook(HookType::BeforeSwap, managed_address!(&hook_address));
+            },
+        )
+        .assert_ok();
+
+    pair_setup
+        .b_mock
+        .execute_tx(
+            &owner_address,
+            &pair_setup.pair_wrapper,
+            &rust_biguint!(0),
+            |sc| {
+                sc.add_hook(HookType::BeforeSwap, managed_address!(&hook_address));
+            },
+        )
+        .assert_user_error("Hook already registered");
+
+    pair_setup
+        .b_mock
+        .execute_query(&pair_setup.pair_wrapper, |sc| {
+            let before_swap_hooks = sc.get_hooks(HookType::BeforeSwap).to_vec();
+            assert_eq!(before_swap_hooks.len(), 1);
+            assert_eq!(
+                before_swap_hooks.get(0).clone_value(),
+                managed_address!(&hook_address)
+            );
+            assert!(sc.get_hooks(HookType::AfterSwap).is_empty());
+        })
+        .assert_ok();
+
+    pair_setup
+        .b_mock
+        .execute_tx(
+            &owner_address,
+            &pair_setup.pair_wrapper,
+            &rust_biguint!(0),
+            |sc| {
+                sc.remove_hook(HookType::BeforeSwap, managed_address!(&hook_address));
+            },
+        )
+        .assert_ok();
+
+    pair_setup
+        .b_mock
+        .execute_tx(
+            &owner_address,
+            &pair_setup.pair_wrapper,
+            &rust_biguint!(0),
+            |sc| {
+                sc.remove_hook(HookType::BeforeSwap, managed_address!(&hook_address));
+            },
+        )
+        .assert_user_error("Hook not registered");
+
+    // removing a hook keeps the order of the others
+    let mut hook_addresses = vec![hook_address];
+    for _ in 0..2 {
+        let hook_wrapper = pair_setup.b_mock.create_sc_account(
+            &rust_biguint!(0),
+            Some(&owner_address),
+            pair::contract_obj,
+            PAIR_WASM_PATH,
+        );
+        hook_addresses.push(hook_wrapper.address_ref().clone());
+    }
+    for address in hook_addresses.iter() {
+        pair_setup
+            .b_mock
+            .execute_tx(
+                &owner_address,
+                &pair_setup.pair_wrapper,
+                &rust_biguint!(0),
+                |sc| {
+                    sc.add_hook(HookType::AfterSwap, managed_address!(address));
+                },
+            )
+            .assert_ok();
+    }
+
+    pair_setup
+        .b_mock
+        .execute_tx(
+            &owner_address,
+            &pair_setup.pair_wrapper,
+            &rust_biguint!(0),
+            |sc| {
+                sc.remove_hook(HookType::AfterSwap, managed_address!(&hook_addresses[0]));
+            },
+        )
+        .assert_ok();
+
+    pair_setup
+        .b_mock
+        .execute_query(&pair_setup.pair_wrapper, |sc| {
+            let after_swap_hooks = sc.get_hooks(HookType::AfterSwap).to_vec();
+            assert_eq!(after_swap_hooks.len(), 2);
+            assert_eq!(
+                after_swap_hooks.get(0).clone_value(),
+                managed_address!(&hook_addresses[1])
+            );
+            assert_eq!(
+                after_swap_hooks.get(1).clone_value(),
+                managed_address!(&hook_addresses[2])
+            );
+        })
+        .assert_ok();
+
+    for address in hook_addresses[1..].iter() {
+        pair_setup
+            .b_mock
+            .execute_tx(
+                &owner_address,
+                &pair_setup.pair_wrapper,
+                &rust_biguint!(0),
+                |sc| {
+                    sc.remove_hook(HookType::AfterSwap, managed_address!(address));
+                },
+            )
+            .assert_ok();
+    }
+
+    // without hooks, the actions are not affected
+    pair_setup.add_liquidity(
+        1_001_000, 1_000_000, 1_001_000, 1_000_000, 1_000_000, 1_001_000, 1_001_000,
+    );
+    pair_setup.swap_fixed_input(WEGLD_TOKEN_ID, 1_000, MEX_TOKEN_ID, 900, 996);
+}
+
+#[test]
+fn test_before_swap_hooks() {
+    let mut pair_setup = PairSetup::new(pair::contract_obj);
+    let owner_address = pair_setup.owner_address.clone();
+    let user_address = pair_setup.user_address.clone();
+
+    pair_setup.add_liquidity(
+        1_001_000, 1_000_000, 1_001_000, 1_000_000, 1_000_000, 1_001_000, 1_001_000,
+    );
+    pair_setup.change_fee_percents(1, 3_000, 50);
+
+    // each mock needs its own contract path, as the builders are registered by path
+    let mut hooks_count = 0;
+    let mut create_hook = |fee_percent: Option<u64>, reject: bool| {
+        hooks_count += 1;
+        pair_setup
+            .b_mock
+            .create_sc_account(
+                &rust_biguint!(0),
+                Some(&owner_address),
+                move || HookMock::new(fee_percent, reject),
+                &format!("hook mock {}", hooks_count),
+            )
+            .address_ref()
+            .clone()
+    };
+    let counting_hook = create_hook(None, false);
+    let low_fee_hook = create_hook(Some(1_000), false);
+    let high_fee_hook = create_hook(Some(2_000), false);
+    let above_total_fee_hook = create_hook(Some(3_001), false);
+    let below_special_fee_hook = create_hook(Some(0), false);
+    let rejecting_hook = create_hook(None, true);
+
+    let set_hook = |pair_setup: &mut PairSetup<_>, hook_address: &Address, add: bool| {
+        pair_setup
+            .b_mock
+            .execute_tx(
+                &pair_setup.owner_address,
+                &pair_setup.pair_wrapper,
+                &rust_biguint!(0),
+                |sc: pair::ContractObj<DebugApi>| {
+                    if add {
+                        sc.add_hook(HookType::BeforeSwap, managed_address!(hook_address));
+                    } else {
+                        sc.remove_hook(HookType::BeforeSwap, managed_address!(hook_address));
+                    }
+                },
+            )
+            .assert_ok();
+    };
+    let swap = |pair_setup: &mut PairSetup<_>| {
+        let mut amount_out = 0;
+        let tx_result = pair_setup.b_mock.execute_esdt_transfer(
+            &user_address,
+            &pair_setup.pair_wrapper,
+            WEGLD_TOKEN_ID,
+            0,
+            &rust_biguint!(10_000),
+            |sc: pair::ContractObj<DebugApi>| {
+                let payment = sc.swap_tokens_fixed_input(
+                    managed_token_id_wrapped!(MEX_TOKEN_ID),
+                    managed_biguint!(1),
+                    OptionalValue::None,
+                );
+                amount_out = payment.amount.to_u64().unwrap();
+            },
+        );
+        (tx_result, amount_out)
+    };
+    let check_calls_count =
+        |pair_setup: &mut PairSetup<_>, hook_address: &Address, expected_calls_count: u64| {
+            pair_setup
+                .b_mock
+                .execute_query(
+                    &pair_setup.pair_wrapper,
+                    |sc: pair::ContractObj<DebugApi>| {
+                        let calls_count: u64 = sc.storage_raw().read_from_address(
+                            &managed_address!(hook_address),
+                            managed_buffer!(CALLS_COUNT_STORAGE_KEY),
+                        );
+                        assert_eq!(calls_count, expected_calls_count);
+                    },
+                )
+                .assert_ok();
+        };
+
+    // the hook is called before every swap, without changing the total fee
+    set_hook(&mut pair_setup, &counting_hook, true);
+    let (tx_result, amount_out) = swap(&mut pair_setup);
+    tx_result.assert_ok();
+    assert_eq!(amount_out, 9_606);
+    check_calls_count(&mut pair_setup, &counting_hook, 1);
+
+    // the fee returned by a hook replaces the fee of the swap
+    set_hook(&mut pair_setup, &low_fee_hook, true);
+    let (tx_result, amount_out) = swap(&mut pair_setup);
+    tx_result.assert_ok();
+    assert_eq!(amount_out, 9_613);
+    check_calls_count(&mut pair_setup, &counting_hook, 2);
+    check_calls_count(&mut pair_setup, &low_fee_hook, 1);
+    set_hook(&mut pair_setup, &low_fee_hook, false);
+
+    // the fee is clamped between the special fee and the total fee
+    set_hook(&mut pair_setup, &above_total_fee_hook, true);
+    let (tx_result, amount_out) = swap(&mut pair_setup);
+    tx_result.assert_ok();
+    assert_eq!(amount_out, 9_239);
+    set_hook(&mut pair_setup, &above_total_fee_hook, false);
+
+    set_hook(&mut pair_setup, &below_special_fee_hook, true);
+    let (tx_result, amount_out) = swap(&mut pair_setup);
+    tx_result.assert_ok();
+    assert_eq!(amount_out, 9_337);
+    set_hook(&mut pair_setup, &below_special_fee_hook, false);
+
+    // removing a hook keeps the order of the others, so the last override still wins
+    for hook_address in [&low_fee_hook, &high_fee_hook, &above_total_fee_hook] {
+        set_hook(&mut pair_setup, hook_address, true);
+    }
+    set_hook(&mut pair_setup, &high_fee_hook, false);
+    let (tx_result, amount_out) = swap(&mut pair_setup);
+    tx_result.assert_ok();
+    assert_eq!(amount_out, 8_892);
+    for hook_address in [&low_fee_hook, &above_total_fee_hook] {
+        set_hook(&mut pair_setup, hook_address, false);
+    }
+
+    // a rejecting hook aborts the swap
+    set_hook(&mut pair_setup, &rejecting_hook, true);
+    let (tx_result, _) = swap(&mut pair_setup);
+    tx_result.assert_user_error("Rejected by hook");
+    // the whole swap is reverted, including the calls of the other hooks
+    check_calls_count(&mut pair_setup, &counting_hook, 5);
+    check_calls_count(&mut pair_setup, &rejecting_hook, 0);
+}
+
+#[test]
+fn test_no_fee_swap_quota() {
+    let mut pair_setup = PairSetup::new(pair::contract_obj);
+    let owner_address = pair_setup.owner_address.clone();
+    let user_address = pair_setup.user_address.clone();
+
+    pair_setup.add_liquidity(
+        1_001_000, 1_000_000, 1_001_000, 1_000_000, 1_000_000, 1_001_000, 1_001_000,
+    );
+    pair_setup.b_mock.set_esdt_local_roles(
+        pair_setup.pair_wrapper.address_ref(),
+        MEX_TOKEN_ID,
+        &[EsdtLocalRole::Burn],
+    );
+
+    pair_setup
+        .b_mock
+        .execute_tx(
+            &owner_address,
+            &pair_setup.pair_wrapper,
+            &rust_biguint!(0),
+            |sc| {
+                sc.whitelist_endpoint(managed_address!(&user_address));
+                sc.set_no_fee_swap_quota(
+                    managed_address!(&user_address),
+                    managed_token_id!(WEGLD_TOKEN_ID),
+                    managed_biguint!(1_000),
+                );
+            },
+        )
+        .assert_ok();
+
+    pair_setup
+        .b_mock
+        .execute_esdt_transfer(
+            &user_address,
+            &pair_setup.pair_wrapper,
+            WEGLD_TOKEN_ID,
+            0,
+            &rust_biguint!(600),
+            |sc| {
+                sc.swap_no_fee(
+                    managed_token_id!(MEX_TOKEN_ID),
+                    managed_address!(&user_address),
+                );
+            },
+        )
+        .assert_ok();
+
+    // a swap above the remaining quota fails instead of being partially executed
+    pair_setup
+        .b_mock
+        .execute_esdt_transfer(
+            &user_address,
+            &pair_setup.pair_wrapper,
+            WEGLD_TOKEN_ID,
+            0,
+            &rust_biguint!(600),
+            |sc| {
+                sc.swap_no_fee(
+                    managed_token_id!(MEX_TOKEN_ID),
+                    managed_address!(&user_address),
+                );
+            },
+        )
+        .assert_user_error("No fee swap quota exceeded");
+
+    pair_setup
+        .b_mock
+        .execute_esdt_transfer(
+            &user_address,
+            &pair_setup.pair_wrapper,
+            WEGLD_TOKEN_ID,
+            0,
+            &rust_biguint!(400),
+            |sc| {
+                sc.swap_no_fee(
+                    managed_token_id!(MEX_TOKEN_ID),
+                    managed_address!(&user_address),
+                );
+            },
+        )
+        .assert_ok();
+
+    pair_setup.b_mock.check_esdt_balance(
+        &user_address,
+        WEGLD_TOKEN_ID,
+        &rust_biguint!(USER_TOTAL_WEGLD_TOKENS - 1_001_000 - 1_000),
+    );
+
+    pair_setup
+        .b_mock
+        .execute_query(&pair_setup.pair_wrapper, |sc| {
+            assert_eq!(
+                sc.pair_reserve(&managed_token_id_wrapped!(WEGLD_TOKEN_ID))
+                    .get(),
+                managed_biguint!(1_002_000)
+            );
+            assert_eq!(
+                sc.get_remaining_no_fee_swap_quota(
+                    managed_address!(&user_address),
+                    managed_token_id!(WEGLD_TOKEN_ID)
+                )
+                .into_option(),
+                Some(managed_biguint!(0))
+            );
+            assert_eq!(
+                sc.get_remaining_no_fee_swap_quota(
+                    managed_address!(&user_address),
+                    managed_token_id!(MEX_TOKEN_ID)
+                )
+                .into_option(),
+                None
+            );
+        })
+        .assert_ok();
+
+    // the quota is restored in the next week
+    pair_setup.b_mock.set_block_epoch(7);
+    pair_setup
+        .b_mock
+        .execute_query(&pair_setup.pair_wrapper, |sc| {
+            assert_eq!(
+                sc.get_remaining_no_fee_swap_quota(
+                    managed_address!(&user_address),
+                    managed_token_id!(WEGLD_TOKEN_ID)
+                )
+                .into_option(),
+                Some(managed_biguint!(1_000))
+            );
+        })
+        .assert_ok();
+}
+
+#[test]
+fn test_trusted_swap_pair_quota_fallback() {
+    let mut pair_setup = PairSetup::new(pair::contract_obj);
+    let owner_address = pair_setup.owner_address.clone();
+    let pair_address = pair_setup.pair_wrapper.address_ref().clone();
+    let second_pair_address = pair_setup.second_pair_wrapper.address_ref().clone();
+
+    pair_setup.add_liquidity(
+        1_001_000, 1_000_000, 1_001_000, 1_000_000, 1_000_000, 1_001_000, 1_001_000,
+    );
+    pair_setup
+        .b_mock
+        .set_esdt_local_roles(&pair_address, WEGLD_TOKEN_ID, &[EsdtLocalRole::Burn]);
+
+    // the fee is converted to OTHER through the second pair, which limits this pair to 10 WEGLD
+    pair_setup
+        .b_mock
+        .execute_tx(
+            &owner_address,
+            &pair_setup.pair_wrapper,
+            &rust_biguint!(0),
+            |sc| {
+                sc.set_fee_on(
+                    true,
+                    managed_address!(&owner_address),
+                    managed_token_id!(OTHER_TOKEN_ID),
+                );
+                sc.add_trusted_swap_pair(
+                    managed_address!(&second_pair_address),
+                    managed_token_id!(WEGLD_TOKEN_ID),
+                    managed_token_id!(OTHER_TOKEN_ID),
+                );
+            },
+        )
+        .assert_ok();
+    pair_setup
+        .b_mock
+        .execute_tx(
+            &owner_address,
+            &pair_setup.second_pair_wrapper,
+            &rust_biguint!(0),
+            |sc| {
+                sc.whitelist_endpoint(managed_address!(&pair_address));
+                sc.set_no_fee_swap_quota(
+                    managed_address!(&pair_address),
+                    managed_token_id!(WEGLD_TOKEN_ID),
+                    managed_biguint!(10),
+                );
+            },
+        )
+        .assert_ok();
+
+    // the fee of 50 WEGLD exceeds the quota and, without a fees collector to keep it,
+    // the swap fails
+    let user_address = pair_setup.user_address.clone();
+    pair_setup
+        .b_mock
+        .execute_esdt_transfer(
+            &user_address,
+            &pair_setup.pair_wrapper,
+            WEGLD_TOKEN_ID,
+            0,
+            &rust_biguint!(100_000),
+            |sc| {
+                let _ = sc.swap_tokens_fixed_input(
+                    managed_token_id_wrapped!(MEX_TOKEN_ID),
+                    managed_biguint!(1),
+                    OptionalValue::None,
+                );
+            },
+        )
+        .assert_user_error("No fee swap quota exceeded");
+
+    let fees_collector_wrapper = pair_setup.b_mock.create_sc_account(
+        &rust_biguint!(0),
+        None,
+        fees_collector::contract_obj,
+        "fees collector path",
+    );
+    pair_setup
+        .b_mock
+        .execute_tx(
+            &owner_address,
+            &fees_collector_wrapper,
+            &rust_biguint!(0),
+            |sc| {
+                sc.init(
+                    managed_token_id!(LOCKED_TOKEN_ID),
+                    managed_address!(&pair_address),
+                );
+                let _ = sc.known_contracts().insert(managed_address!(&pair_address));
+
+                let mut tokens = MultiValueEncoded::new();
+                tokens.push(managed_token_id!(WEGLD_TOKEN_ID));
+                sc.add_known_tokens(tokens);
+            },
+        )
+        .assert_ok();
+    pair_setup
+        .b_mock
+        .execute_tx(
+            &owner_address,
+            &pair_setup.pair_wrapper,
+            &rust_biguint!(0),
+            |sc| {
+                sc.setup_fees_collector(
+                    managed_address!(fees_collector_wrapper.address_ref()),
+                    MAX_PERCENTAGE / 2,
+                );
+            },
+        )
+        .assert_ok();
+
+    // half of the fee is the cut of the fees collector, and the other half, which exceeds
+    // the quota, is sent there too, while the quota that cannot be used up is reported
+    let swap_result = pair_setup.b_mock.execute_esdt_transfer(
+        &user_address,
+        &pair_setup.pair_wrapper,
+        WEGLD_TOKEN_ID,
+        0,
+        &rust_biguint!(100_000),
+        |sc| {
+            let output_payment = sc.swap_tokens_fixed_input(
+                managed_token_id_wrapped!(MEX_TOKEN_ID),
+                managed_biguint!(1),
+                OptionalValue::None,
+            );
+            assert_eq!(output_payment.amount, managed_biguint!(90_669));
+        },
+    );
+    swap_result.assert_ok();
+    assert!(swap_result.result_logs.iter().any(|log| {
+        log.topics.first() == Some(&b"no_fee_swap_quota_exceeded".to_vec())
+            && log.address.as_bytes() == pair_address.as_bytes()
+    }));
+
+    pair_setup.b_mock.check_esdt_balance(
+        &pair_address,
+        WEGLD_TOKEN_ID,
+        &rust_biguint!(1_001_000 + 100_000 - 50),
+    );
+    pair_setup.b_mock.check_esdt_balance(
+        fees_collector_wrapper.address_ref(),
+        WEGLD_TOKEN_ID,
+        &rust_biguint!(50),
+    );
+    pair_setup
+        .b_mock
+        .check_esdt_balance(&second_pair_address, WEGLD_TOKEN_ID, &rust_biguint!(0));
+}
+
+#[test]
+fn test_buyback_destination() {
+    let mut pair_setup = PairSetup::new(pair::contract_obj);
+    let owner_address = pair_setup.owner_address.clone();
+    let user_address = pair_setup.user_address.clone();
+
+    pair_setup.add_liquidity(
+        1_001_000, 1_000_000, 1_001_000, 1_000_000, 1_000_000, 1_001_000, 1_001_000,
+    );
+
+    pair_setup
+        .b_mock
+        .execute_tx(
+            &owner_address,
+            &pair_setup.pair_wrapper,
+            &rust_biguint!(0),
+            |sc| {
+                sc.whitelist_endpoint(managed_address!(&user_address));
+                sc.set_buyback_destination(
+                    managed_address!(&user_address),
+                    BuybackDestination::Treasury(managed_address!(&owner_address)),
+                );
+            },
+        )
+        .assert_ok();
+
+    // 1_000 MEX removed and 999 MEX bought with the 1_000 WEGLD removed
+    pair_setup
+        .b_mock
+        .execute_esdt_transfer(
+            &user_address,
+            &pair_setup.pair_wrapper,
+            LP_TOKEN_ID,
+            0,
+            &rust_biguint!(1_000),
+            |sc| {
+                sc.remove_liquidity_and_burn_token(managed_token_id!(MEX_TOKEN_ID));
+            },
+        )
+        .assert_ok();
+
+    pair_setup
+        .b_mock
+        .check_esdt_balance(&owner_address, MEX_TOKEN_ID, &rust_biguint!(1_999));
+
+    pair_setup
+        .b_mock
+        .execute_query(&pair_setup.pair_wrapper, |sc| {
+            assert_eq!(
+                sc.pair_reserve(&managed_token_id_wrapped!(WEGLD_TOKEN_ID))
+                    .get(),
+                managed_biguint!(1_001_000)
+            );
+            assert_eq!(
+                sc.pair_reserve(&managed_token_id_wrapped!(MEX_TOKEN_ID))
+                    .get(),
+                managed_biguint!(999_001)
+            );
+            assert_eq!(
+                sc.get_buyback_destination(managed_address!(&owner_address)),
+                BuybackDestination::Burn
+            );
+
+            // the buyback swap is recorded in the trading stats
+            let (input_volume, _) = sc
+                .get_volume_for_week(1, managed_token_id_wrapped!(WEGLD_TOKEN_ID))
+                .into_tuple();
+            assert_eq!(input_volume, managed_biguint!(1_000));
+            let (_, output_volume) = sc
+                .get_volume_for_week(1, managed_token_id_wrapped!(MEX_TOKEN_ID))
+                .into_tuple();
+            assert_eq!(output_volume, managed_biguint!(999));
+        })
+        .assert_ok();
+
+    // a token outside the pair can only be bought back through the trusted swap pairs,
+    // which burn it, so it is rejected for the treasury
+    pair_setup
+        .b_mock
+        .execute_esdt_transfer(
+            &user_address,
+            &pair_setup.pair_wrapper,
+            LP_TOKEN_ID,
+            0,
+            &rust_biguint!(1_000),
+            |sc| {
+                sc.remove_liquidity_and_burn_token(managed_token_id!(OTHER_TOKEN_ID));
+            },
+        )
+        .assert_user_error("Only pair tokens can be bought back for this destination");
+
+    let pair_address = pair_setup.pair_wrapper.address_ref().clone();
+    let second_pair_address = pair_setup.second_pair_wrapper.address_ref().clone();
+    pair_setup
+        .b_mock
+        .set_esdt_balance(&user_address, OTHER_TOKEN_ID, &rust_biguint!(1_001_000));
+    pair_setup.b_mock.set_esdt_local_roles(
+        &second_pair_address,
+        LP_TOKEN_ID,
+        &[EsdtLocalRole::Mint, EsdtLocalRole::Burn],
+    );
+    pair_setup.b_mock.set_esdt_local_roles(
+        &second_pair_address,
+        OTHER_TOKEN_ID,
+        &[EsdtLocalRole::Burn],
+    );
+
+    let second_pair_payments = vec![
+        TxTokenTransfer {
+            token_identifier: WEGLD_TOKEN_ID.to_vec(),
+            nonce: 0,
+            value: rust_biguint!(1_001_000),
+        },
+        TxTokenTransfer {
+            token_identifier: OTHER_TOKEN_ID.to_vec(),
+            nonce: 0,
+            value: rust_biguint!(1_001_000),
+        },
+    ];
+    pair_setup
+        .b_mock
+        .execute_esdt_multi_transfer(
+            &user_address,
+            &pair_setup.second_pair_wrapper,
+            &second_pair_payments,
+            |sc| {
+                sc.add_liquidity(
+                    managed_biguint!(1_000_000),
+                    managed_biguint!(1_000_000),
+                    OptionalValue::None,
+                );
+            },
+        )
+        .assert_ok();
+    pair_setup
+        .b_mock
+        .execute_tx(
+            &owner_address,
+            &pair_setup.second_pair_wrapper,
+            &rust_biguint!(0),
+            |sc| {
+                sc.whitelist_endpoint(managed_address!(&pair_address));
+            },
+        )
+        .assert_ok();
+    pair_setup
+        .b_mock
+        .execute_tx(
+            &owner_address,
+            &pair_setup.pair_wrapper,
+            &rust_biguint!(0),
+            |sc| {
+                sc.add_trusted_swap_pair(
+                    managed_address!(&second_pair_address),
+                    managed_token_id!(WEGLD_TOKEN_ID),
+                    managed_token_id!(OTHER_TOKEN_ID),
+                );
+                sc.set_buyback_destination(
+                    managed_address!(&user_address),
+                    BuybackDestination::Burn,
+                );
+            },
+        )
+        .assert_ok();
+
+    // with the Burn destination, the 1_000 WEGLD removed and the WEGLD bought with
+    // the 1_000 MEX removed are swapped in the second pair and burned
+    pair_setup
+        .b_mock
+        .execute_esdt_transfer(
+            &user_address,
+            &pair_setup.pair_wrapper,
+            LP_TOKEN_ID,
+            0,
+            &rust_biguint!(1_000),
+            |sc| {
+                sc.remove_liquidity_and_burn_token(managed_token_id!(OTHER_TOKEN_ID));
+            },
+        )
+        .assert_ok();
+
+    pair_setup
+        .b_mock
+        .check_esdt_balance(&owner_address, OTHER_TOKEN_ID, &rust_biguint!(0));
+    pair_setup
+        .b_mock
+        .execute_query(&pair_setup.second_pair_wrapper, |sc| {
+            assert_eq!(
+                sc.pair_reserve(&managed_token_id_wrapped!(WEGLD_TOKEN_ID))
+                    .get(),
+                managed_biguint!(1_003_000)
+            );
+            assert_eq!(
+                sc.pair_reserve(&managed_token_id_wrapped!(OTHER_TOKEN_ID))
+                    .get(),
+                managed_biguint!(999_004)
+            );
+        })
+        .assert_ok();
+}
+
+#[test]
+fn test_fee_change_announcement() {
+    let mut pair_setup = PairSetup::new(pair::contract_obj);
+    let owner_address = pair_setup.owner_address.clone();
+    let user_address = pair_setup.user_address.clone();
+
+    pair_setup
+        .b_mock
+        .execute_tx(
+            &owner_address,
+            &pair_setup.pair_wrapper,
+            &rust_biguint!(0),
+            |sc| {
+                sc.set_fee_percent(200, 50);
+            },
+        )
+        .assert_user_error("Fee changes have to be announced while swaps are enabled");
+
+    pair_setup
+        .b_mock
+        .execute_tx(
+            &user_address,
+            &pair_setup.pair_wrapper,
+            &rust_biguint!(0),
+            |sc| {
+                sc.announce_fee_percents(200, 50);
+            },
+        )
+        .assert_user_error("Permission denied");
+
+    pair_setup.b_mock.set_block_nonce(10);
+    pair_setup
+        .b_mock
+        .execute_tx(
+            &owner_address,
+            &pair_setup.pair_wrapper,
+            &rust_biguint!(0),
+            |sc| {
+                sc.announce_fee_percents(200, 50);
+                assert_eq!(
+                    sc.pending_fee_change().get().effective_block,
+                    10 + MIN_FEE_CHANGE_DELAY_BLOCKS
+                );
+            },
+        )
+        .assert_ok();
+
+    pair_setup
+        .b_mock
+        .set_block_nonce(10 + MIN_FEE_CHANGE_DELAY_BLOCKS - 1);
+    pair_setup
+        .b_mock
+        .execute_tx(
+            &user_address,
+            &pair_setup.pair_wrapper,
+            &rust_biguint!(0),
+            |sc| {
+                sc.apply_fee_percents();
+            },
+        )
+        .assert_user_error("The fee change is not effective yet");
+
+    pair_setup
+        .b_mock
+        .set_block_nonce(10 + MIN_FEE_CHANGE_DELAY_BLOCKS);
+    pair_setup
+        .b_mock
+        .execute_tx(
+            &user_address,
+            &pair_setup.pair_wrapper,
+            &rust_biguint!(0),
+            |sc| {
+                sc.apply_fee_percents();
+                assert_eq!(sc.total_fee_percent().get(), 200);
+                assert!(sc.pending_fee_change().is_empty());
+            },
+        )
+        .assert_ok();
+
+    pair_setup
+        .b_mock
+        .execute_tx(
+            &owner_address,
+            &pair_setup.pair_wrapper,
+            &rust_biguint!(0),
+            |sc| {
+                sc.announce_fee_percents(400, 50);
+                sc.cancel_fee_percents_change();
+            },
+        )
+        .assert_ok();
+
+    pair_setup
+        .b_mock
+        .execute_tx(
+            &user_address,
+            &pair_setup.pair_wrapper,
+            &rust_biguint!(0),
+            |sc| {
+                sc.apply_fee_percents();
+            },
+        )
+        .assert_user_error("No pending fee change");
+}
+
+#[test]
+fn test_fee_routes() {
+    let mut pair_setup = PairSetup::new(pair::contract_obj);
+    let owner_address = pair_setup.owner_address.clone();
+    let user_address = pair_setup.user_address.clone();
+    let second_pair_address = pair_setup.second_pair_wrapper.address_ref().clone();
+
+    pair_setup.add_liquidity(
+        1_001_000, 1_000_000, 1_001_000, 1_000_000, 1_000_000, 1_001_000, 1_001_000,
+    );
+
+    pair_setup
+        .b_mock
+        .execute_tx(
+            &owner_address,
+            &pair_setup.pair_wrapper,
+            &rust_biguint!(0),
+            |sc| {
+                let mut hops = MultiValueEncoded::new();
+                hops.push(
+                    (
+                        managed_address!(&second_pair_address),
+                        managed_token_id!(MEX_TOKEN_ID),
+                    )
+                        .into(),
+                );
+                hops.push(
+                    (
+                        managed_address!(&second_pair_address),
+                        managed_token_id!(MEX_TOKEN_ID),
+                    )
+                        .into(),
+                );
+                sc.add_fee_route(managed_token_id!(OTHER_TOKEN_ID), hops);
+            },
+        )
+        .assert_user_error("Invalid fee route");
+
+    pair_setup
+        .b_mock
+        .execute_tx(
+            &owner_address,
+            &pair_setup.pair_wrapper,
+            &rust_biguint!(0),
+            |sc| {
+                let mut hops = MultiValueEncoded::new();
+                hops.push(
+                    (
+                        managed_address!(&second_pair_address),
+                        managed_token_id!(WEGLD_TOKEN_ID),
+                    )
+                        .into(),
+                );
+                hops.push(
+                    (
+                        managed_address!(&second_pair_address),
+                        managed_token_id!(MEX_TOKEN_ID),
+                    )
+                        .into(),
+                );
+                sc.add_fee_route(managed_token_id!(OTHER_TOKEN_ID), hops);
+
+                let routes = sc
+                    .get_fee_routes(
+                        managed_token_id!(OTHER_TOKEN_ID),
+                        managed_token_id!(MEX_TOKEN_ID),
+                    )
+                    .to_vec();
+                assert_eq!(routes.len(), 1);
+                assert_eq!(
+                    routes.get(0).get(1),
+                    FeeRouteHop {
+                        pair_address: managed_address!(&second_pair_address),
+                        token_out: managed_token_id!(MEX_TOKEN_ID),
+                    }
+                );
+
+                sc.remove_fee_routes(
+                    managed_token_id!(OTHER_TOKEN_ID),
+                    managed_token_id!(MEX_TOKEN_ID),
+                );
+                assert!(sc
+                    .get_fee_routes(
+                        managed_token_id!(OTHER_TOKEN_ID),
+                        managed_token_id!(MEX_TOKEN_ID),
+                    )
+                    .is_empty());
+
+                sc.whitelist_endpoint(managed_address!(&user_address));
+            },
+        )
+        .assert_ok();
+
+    // being whitelisted is not enough to get the output of a no fee swap back
+    pair_setup
+        .b_mock
+        .execute_esdt_transfer(
+            &user_address,
+            &pair_setup.pair_wrapper,
+            WEGLD_TOKEN_ID,
+            0,
+            &rust_biguint!(1_000),
+            |sc| {
+                let _ = sc.swap_no_fee_and_return(managed_token_id!(MEX_TOKEN_ID));
+            },
+        )
+        .assert_user_error("Not a fee route caller");
+
+    pair_setup
+        .b_mock
+        .execute_tx(
+            &owner_address,
+            &pair_setup.pair_wrapper,
+            &rust_biguint!(0),
+            |sc| {
+                sc.add_fee_route_caller(managed_address!(&user_address));
+            },
+        )
+        .assert_user_error("Invalid fee route caller");
+}
+
+#[test]
+fn test_fee_route_swaps() {
+    let mut pair_setup = PairSetup::new(pair::contract_obj);
+    let owner_address = pair_setup.owner_address.clone();
+    let user_address = pair_setup.user_address.clone();
+    let pair_address = pair_setup.pair_wrapper.address_ref().clone();
+    let second_pair_address = pair_setup.second_pair_wrapper.address_ref().clone();
+
+    pair_setup.add_liquidity(
+        1_001_000, 1_000_000, 1_001_000, 1_000_000, 1_000_000, 1_001_000, 1_001_000,
+    );
+    pair_setup
+        .b_mock
+        .set_esdt_local_roles(&pair_address, OTHER_TOKEN_ID, &[EsdtLocalRole::Burn]);
+
+    pair_setup
+        .b_mock
+        .set_esdt_balance(&user_address, OTHER_TOKEN_ID, &rust_biguint!(1_001_000));
+    pair_setup.b_mock.set_esdt_local_roles(
+        &second_pair_address,
+        LP_TOKEN_ID,
+        &[EsdtLocalRole::Mint, EsdtLocalRole::Burn],
+    );
+    pair_setup.b_mock.set_esdt_local_roles(
+        &second_pair_address,
+        OTHER_TOKEN_ID,
+        &[EsdtLocalRole::Burn],
+    );
+    let second_pair_payments = vec![
+        TxTokenTransfer {
+            token_identifier: WEGLD_TOKEN_ID.to_vec(),
+            nonce: 0,
+            value: rust_biguint!(1_001_000),
+        },
+        TxTokenTransfer {
+            token_identifier: OTHER_TOKEN_ID.to_vec(),
+            nonce: 0,
+            value: rust_biguint!(1_001_000),
+        },
+    ];
+    pair_setup
+        .b_mock
+        .execute_esdt_multi_transfer(
+            &user_address,
+            &pair_setup.second_pair_wrapper,
+            &second_pair_payments,
+            |sc| {
+                sc.add_liquidity(
+                    managed_biguint!(1_000_000),
+                    managed_biguint!(1_000_000),
+                    OptionalValue::None,
+                );
+            },
+        )
+        .assert_ok();
+
+    // the same pair is used for every hop, WEGLD -> OTHER -> WEGLD -> OTHER,
+    // so that the route has intermediate hops
+    pair_setup
+        .b_mock
+        .execute_tx(
+            &owner_address,
+            &pair_setup.pair_wrapper,
+            &rust_biguint!(0),
+            |sc| {
+                sc.set_fee_on(
+                    true,
+                    managed_address!(&owner_address),
+                    managed_token_id!(OTHER_TOKEN_ID),
+                );
+
+                let mut hops = MultiValueEncoded::new();
+                for token_out in [OTHER_TOKEN_ID, WEGLD_TOKEN_ID, OTHER_TOKEN_ID] {
+                    hops.push(
+                        (
+                            managed_address!(&second_pair_address),
+                            managed_token_id!(token_out),
+                        )
+                            .into(),
+                    );
+                }
+                sc.add_fee_route(managed_token_id!(WEGLD_TOKEN_ID), hops);
+            },
+        )
+        .assert_ok();
+    pair_setup
+        .b_mock
+        .execute_tx(
+            &owner_address,
+            &pair_setup.second_pair_wrapper,
+            &rust_biguint!(0),
+            |sc| {
+                sc.whitelist_endpoint(managed_address!(&pair_address));
+            },
+        )
+        .assert_ok();
+
+    // the intermediate hops only send the output back to the registered pairs
+    pair_setup
+        .b_mock
+        .execute_esdt_transfer(
+            &user_address,
+            &pair_setup.pair_wrapper,
+            WEGLD_TOKEN_ID,
+            0,
+            &rust_biguint!(100_000),
+            |sc| {
+                let _ = sc.swap_tokens_fixed_input(
+                    managed_token_id_wrapped!(MEX_TOKEN_ID),
+                    managed_biguint!(1),
+                    OptionalValue::None,
+                );
+            },
+        )
+        .assert_user_error("Not a fee route caller");
+
+    pair_setup
+        .b_mock
+        .execute_tx(
+            &owner_address,
+            &pair_setup.second_pair_wrapper,
+            &rust_biguint!(0),
+            |sc| {
+                sc.add_fee_route_caller(managed_address!(&pair_address));
+            },
+        )
+        .assert_ok();
+
+    // the 50 WEGLD fee goes through the three hops and the OTHER tokens are burned
+    pair_setup.swap_fixed_input(WEGLD_TOKEN_ID, 100_000, MEX_TOKEN_ID, 1, 90_669);
+    pair_setup
+        .b_mock
+        .execute_query(&pair_setup.second_pair_wrapper, |sc| {
+            assert_eq!(
+                sc.pair_reserve(&managed_token_id_wrapped!(WEGLD_TOKEN_ID))
+                    .get(),
+                managed_biguint!(1_001_050)
+            );
+            assert_eq!(
+                sc.pair_reserve(&managed_token_id_wrapped!(OTHER_TOKEN_ID))
+                    .get(),
+                managed_biguint!(1_000_952)
+            );
+        })
+        .assert_ok();
+
+    // without quota for the second hop, the OTHER tokens held by the pair cannot be kept,
+    // as there is no fees collector, so the swap fails
+    pair_setup
+        .b_mock
+        .execute_tx(
+            &owner_address,
+            &pair_setup.second_pair_wrapper,
+            &rust_biguint!(0),
+            |sc| {
+                sc.set_no_fee_swap_quota(
+                    managed_address!(&pair_address),
+                    managed_token_id!(OTHER_TOKEN_ID),
+                    managed_biguint!(10),
+                );
+            },
+        )
+        .assert_ok();
+
+    let user_address = pair_setup.user_address.clone();
+    pair_setup
+        .b_mock
+        .execute_esdt_transfer(
+            &user_address,
+            &pair_setup.pair_wrapper,
+            WEGLD_TOKEN_ID,
+            0,
+            &rust_biguint!(100_000),
+            |sc| {
+                let _ = sc.swap_tokens_fixed_input(
+                    managed_token_id_wrapped!(MEX_TOKEN_ID),
+                    managed_biguint!(1),
+                    OptionalValue::None,
+                );
+            },
+        )
+        .assert_user_error("No fee swap quota exceeded");
+    pair_setup
+        .b_mock
+        .execute_query(&pair_setup.second_pair_wrapper, |sc| {
+            assert_eq!(
+                sc.pair_reserve(&managed_token_id_wrapped!(WEGLD_TOKEN_ID))
+                    .get(),
+                managed_biguint!(1_001_050)
+            );
+            assert_eq!(
+                sc.pair_reserve(&managed_token_id_wrapped!(OTHER_TOKEN_ID))
+                    .get(),
+                managed_biguint!(1_000_952)
+            );
+        })
+        .assert_ok();
+}
+
+#[test]
+fn test_liquidity_migration() {
+    let mut pair_setup = PairSetup::new(pair::contract_obj);
+    let new_lp_token_id = b"LPNEW-abcdef";
+
+    pair_setup.add_liquidity(
+        1_001_000, 1_000_000, 1_001_000, 1_000_000, 1_000_000, 1_001_000, 1_001_000,
+    );
+
+    let owner_address = pair_setup.owner_address.clone();
+    let target_pair_wrapper = pair_setup.b_mock.create_sc_account(
+        &rust_biguint!(0),
+        Some(&owner_address),
+        pair::contract_obj,
+        PAIR_WASM_PATH,
+    );
+    pair_setup
+        .b_mock
+        .execute_tx(
+            &owner_address,
+            &target_pair_wrapper,
+            &rust_biguint!(0),
+            |sc| {
+                sc.init(
+                    managed_token_id_wrapped!(WEGLD_TOKEN_ID),
+                    managed_token_id_wrapped!(MEX_TOKEN_ID),
+                    managed_address!(&owner_address),
+                    managed_address!(&owner_address),
+                    300,
+                    50,
+                    ManagedAddress::<DebugApi>::zero(),
+                    MultiValueEncoded::<DebugApi, ManagedAddress<DebugApi>>::new(),
+                );
+                sc.lp_token_identifier()
+                    .set(&managed_token_id!(new_lp_token_id));
+                sc.state().set(State::Active);
+            },
+        )
+        .assert_ok();
+    pair_setup.b_mock.set_esdt_local_roles(
+        target_pair_wrapper.address_ref(),
+        new_lp_token_id,
+        &[EsdtLocalRole::Mint, EsdtLocalRole::Burn],
+    );
+
+    // the migration is not configured yet
+    pair_setup
+        .b_mock
+        .execute_esdt_transfer(
+            &pair_setup.user_address,
+            &target_pair_wrapper,
+            LP_TOKEN_ID,
+            0,
+            &rust_biguint!(500_000),
+            |sc| {
+                sc.migrate_liquidity(managed_biguint!(1), managed_biguint!(1));
+            },
+        )
+        .assert_user_error("Liquidity migration is not configured");
+
+    let source_pair_address = pair_setup.pair_wrapper.address_ref().clone();
+    let target_pair_address = target_pair_wrapper.address_ref().clone();
+    pair_setup
+        .b_mock
+        .execute_tx(
+            &owner_address,
+            &pair_setup.pair_wrapper,
+            &rust_biguint!(0),
+            |sc| {
+                sc.set_migration_target(managed_address!(&target_pair_address));
+            },
+        )
+        .assert_ok();
+    pair_setup
+        .b_mock
+        .execute_tx(
+            &owner_address,
+            &target_pair_wrapper,
+            &rust_biguint!(0),
+            |sc| {
+                sc.set_migration_source(managed_address!(&source_pair_address));
+            },
+        )
+        .assert_ok();
+
+    // only the target pair can remove liquidity for migration
+    pair_setup
+        .b_mock
+        .execute_esdt_transfer(
+            &pair_setup.user_address,
+            &pair_setup.pair_wrapper,
+            LP_TOKEN_ID,
+            0,
+            &rust_biguint!(500_000),
+            |sc| {
+                sc.remove_liquidity_for_migration();
+            },
+        )
+        .assert_user_error("Permission denied");
+
+    // the liquidity of an inactive source pair cannot be migrated
+    pair_setup
+        .b_mock
+        .execute_tx(
+            &pair_setup.owner_address,
+            &pair_setup.pair_wrapper,
+            &rust_biguint!(0),
+            |sc| {
+                sc.state().set(State::Inactive);
+            },
+        )
+        .assert_ok();
+    pair_setup
+        .b_mock
+        .execute_esdt_transfer(
+            &pair_setup.user_address,
+            &target_pair_wrapper,
+            LP_TOKEN_ID,
+            0,
+            &rust_biguint!(500_000),
+            |sc| {
+                sc.migrate_liquidity(managed_biguint!(1), managed_biguint!(1));
+            },
+        )
+        .assert_user_error("Not active");
+    pair_setup
+        .b_mock
+        .execute_tx(
+            &pair_setup.owner_address,
+            &pair_setup.pair_wrapper,
+            &rust_biguint!(0),
+            |sc| {
+                sc.state().set(State::Active);
+            },
+        )
+        .assert_ok();
+
+    // the first migration sets the price of the target pair
+    pair_setup
+        .b_mock
+        .execute_esdt_transfer(
+            &pair_setup.user_address,
+            &target_pair_wrapper,
+            LP_TOKEN_ID,
+            0,
+            &rust_biguint!(500_000),
+            |sc| {
+                let (lp_payment, _, _) = sc
+                    .migrate_liquidity(managed_biguint!(1), managed_biguint!(1))
+                    .into_tuple();
+                assert_eq!(lp_payment.amount, managed_biguint!(499_000));
+            },
+        )
+        .assert_ok();
+
+    pair_setup
+        .b_mock
+        .execute_esdt_transfer(
+            &pair_setup.user_address,
+            &target_pair_wrapper,
+            LP_TOKEN_ID,
+            0,
+            &rust_biguint!(100_000),
+            |sc| {
+                let (lp_payment, _, _) = sc
+                    .migrate_liquidity(managed_biguint!(1), managed_biguint!(1))
+                    .into_tuple();
+                assert_eq!(lp_payment.amount, managed_biguint!(100_000));
+            },
+        )
+        .assert_ok();
+
+    pair_setup.b_mock.check_esdt_balance(
+        &pair_setup.user_address,
+        LP_TOKEN_ID,
+        &rust_biguint!(400_000),
+    );
+    pair_setup.b_mock.check_esdt_balance(
+        &pair_setup.user_address,
+        new_lp_token_id,
+        &rust_biguint!(599_000),
+    );
+
+    pair_setup
+        .b_mock
+        .execute_query(&pair_setup.pair_wrapper, |sc| {
+            assert_eq!(sc.lp_token_supply().get(), managed_biguint!(401_000));
+        })
+        .assert_ok();
+    pair_setup
+        .b_mock
+        .execute_query(&target_pair_wrapper, |sc| {
+            assert_eq!(sc.lp_token_supply().get(), managed_biguint!(600_000));
+        })
+        .assert_ok();
+}
+
+#[test]
+fn test_batch_safe_price_views() {
+    let mut pair_setup = PairSetup::new(pair::contract_obj);
+    let pair_address = pair_setup.pair_wrapper.address_ref().clone();
+    let second_pair_address = pair_setup.second_pair_wrapper.address_ref().clone();
+    let starting_round = 1000;
+    let mut block_round = starting_round + 1;
+    pair_setup.b_mock.set_block_round(block_round);
+
+    pair_setup.add_liquidity(
+        1_001_000, 1_000_000, 1_001_000, 1_000_000, 1_000_000, 1_001_000, 1_001_000,
+    );
+
+    let mut expected_amount = 996;
+    for _ in 0..4 {
+        pair_setup.b_mock.set_block_round(block_round);
+        pair_setup.swap_fixed_input(WEGLD_TOKEN_ID, 1_000, MEX_TOKEN_ID, 900, expected_amount);
+        block_round += 1;
+        expected_amount -= 2; // slippage
+    }
+    let last_round = block_round - 1;
+
+    let _ = pair_setup
+        .b_mock
+        .execute_query(&pair_setup.second_pair_wrapper, |sc| {
+            let mut pair_addresses = MultiValueEncoded::new();
+            pair_addresses.push(managed_address!(&pair_address));
+            pair_addresses.push(managed_address!(&second_pair_address));
+            let observation_rounds: Vec<_> = sc
+                .get_price_observation_rounds(pair_addresses)
+                .into_iter()
+                .map(|rounds| rounds.into_tuple())
+                .collect();
+            assert_eq!(
+                observation_rounds,
+                vec![
+                    (
+                        managed_address!(&pair_address),
+                        starting_round + 1,
+                        last_round
+                    ),
+                    (managed_address!(&second_pair_address), 0, 0),
+                ]
+            );
+
+            let mut queries = MultiValueEncoded::new();
+            for amount in [100u64, 1_000] {
+                queries.push(
+                    (
+                        managed_address!(&pair_address),
+                        starting_round + 1,
+                        last_round,
+                        EgldOrEsdtTokenPayment::new(
+                            managed_token_id_wrapped!(WEGLD_TOKEN_ID),
+                            0,
+                            managed_biguint!(amount),
+                        ),
+                    )
+                        .into(),
+                );
+            }
+            let safe_prices: Vec<_> = sc.get_safe_prices(queries).into_iter().collect();
+            assert_eq!(safe_prices.len(), 2);
+            for (safe_price, amount) in safe_prices.iter().zip([100u64, 1_000]) {
+                let expected_safe_price = sc.get_safe_price(
+                    managed_address!(&pair_address),
+                    starting_round + 1,
+                    last_round,
+                    EgldOrEsdtTokenPayment::new(
+                        managed_token_id_wrapped!(WEGLD_TOKEN_ID),
+                        0,
+                        managed_biguint!(amount),
+                    ),
+                );
+                assert_eq!(
+                    safe_price.token_identifier,
+                    managed_token_id_wrapped!(MEX_TOKEN_ID)
+                );
+                assert_eq!(safe_price.amount, expected_safe_price.amount);
+            }
+            assert_eq!(safe_prices[0].amount, managed_biguint!(99));
+        });
+}
+
+#[test]
+fn test_price_observations_capacity_and_sampling() {
+    let mut pair_setup = PairSetup::new(pair::contract_obj);
+    let pair_address = pair_setup.pair_wrapper.address_ref().clone();
+
+    pair_setup
+        .b_mock
+        .execute_tx(
+            &pair_setup.owner_address,
+            &pair_setup.pair_wrapper,
+            &rust_biguint!(0),
+            |sc| {
+                sc.set_price_observations_capacity(3);
+                sc.set_min_rounds_between_observations(2);
+            },
+        )
+        .assert_ok();
+
+    pair_setup.b_mock.set_block_round(10);
+    pair_setup.add_liquidity(
+        1_001_000, 1_000_000, 1_001_000, 1_000_000, 1_000_000, 1_001_000, 1_001_000,
+    );
+
+    let check_observations = |pair_setup: &mut PairSetup<_>,
+                              rounds: &[u64],
+                              expected_len: usize,
+                              expected_oldest_round: u64,
+                              expected_newest_round: u64| {
+        for round in rounds {
+            pair_setup.b_mock.set_block_round(*round);
+            pair_setup
+                .b_mock
+                .execute_esdt_transfer(
+                    &pair_setup.user_address,
+                    &pair_setup.pair_wrapper,
+                    WEGLD_TOKEN_ID,
+                    0,
+                    &rust_biguint!(100),
+                    |sc: pair::ContractObj<DebugApi>| {
+                        let _ = sc.swap_tokens_fixed_input(
+                            managed_token_id_wrapped!(MEX_TOKEN_ID),
+                            managed_biguint!(1),
+                            OptionalValue::None,
+                        );
+                    },
+                )
+                .assert_ok();
+        }
+
+        let _ = pair_setup.b_mock.execute_query(
+            &pair_setup.pair_wrapper,
+            |sc: pair::ContractObj<DebugApi>| {
+                assert_eq!(sc.price_observations().len(), expected_len);
+
+                let mut pair_addresses = MultiValueEncoded::new();
+                pair_addresses.push(managed_address!(&pair_address));
+                let (_, oldest_round, newest_round) = sc
+                    .get_price_observation_rounds(pair_addresses)
+                    .into_iter()
+                    .next()
+                    .unwrap()
+                    .into_tuple();
+                assert_eq!(oldest_round, expected_oldest_round);
+                assert_eq!(newest_round, expected_newest_round);
+            },
+        );
+    };
+
+    // Round 11 is skipped, the buffer wraps at round 16
+    check_observations(&mut pair_setup, &[10, 11, 12, 14, 16], 3, 12, 16);
+
+    // The buffer grows once the current slot reaches its end
+    pair_setup
+        .b_mock
+        .execute_tx(
+            &pair_setup.owner_address,
+            &pair_setup.pair_wrapper,
+            &rust_biguint!(0),
+            |sc| {
+                sc.set_price_observations_capacity(4);
+            },
+        )
+        .assert_ok();
+    check_observations(&mut pair_setup, &[18, 20], 3, 16, 20);
+    check_observations(&mut pair_setup, &[22], 4, 16, 22);
+
+    // The excess observations are removed on the next updates
+    pair_setup
+        .b_mock
+        .execute_tx(
+            &pair_setup.owner_address,
+            &pair_setup.pair_wrapper,
+            &rust_biguint!(0),
+            |sc| {
+                sc.set_price_observations_capacity(2);
+            },
+        )
+        .assert_ok();
+    check_observations(&mut pair_setup, &[24], 4, 18, 24);
+    check_observations(&mut pair_setup, &[26], 2, 24, 26);
+
+    pair_setup
+        .b_mock
+        .execute_tx(
+            &pair_setup.owner_address,
+            &pair_setup.pair_wrapper,
+            &rust_biguint!(0),
+            |sc| {
+                sc.set_price_observations_capacity(1);
+            },
+        )
+        .assert_user_error("Invalid price observations capacity");
+}
+
+#[test]
+fn test_average_liquidity_views() {
+    let mut pair_setup = PairSetup::new(pair::contract_obj);
+    let pair_address = pair_setup.pair_wrapper.address_ref().clone();
+
+    pair_setup.b_mock.set_block_round(10);
+    pair_setup.add_liquidity(
+        1_001_000, 1_000_000, 1_001_000, 1_000_000, 1_000_000, 1_001_000, 1_001_000,
+    );
+    pair_setup.swap_fixed_input(WEGLD_TOKEN_ID, 1_000, MEX_TOKEN_ID, 900, 996);
+
+    // The reserves stay at 1_002_000 and 1_000_004 until round 20
+    pair_setup.b_mock.set_block_round(20);
+    pair_setup.swap_fixed_input(WEGLD_TOKEN_ID, 1_000, MEX_TOKEN_ID, 900, 994);
+
+    let _ = pair_setup
+        .b_mock
+        .execute_query(&pair_setup.second_pair_wrapper, |sc| {
+            let (first_token_reserve, second_token_reserve) = sc
+                .get_average_reserves(managed_address!(&pair_address), 10, 20)
+                .into_tuple();
+            assert_eq!(
+                first_token_reserve,
+                EgldOrEsdtTokenPayment::new(
+                    managed_token_id_wrapped!(WEGLD_TOKEN_ID),
+                    0,
+                    managed_biguint!(1_002_000)
+                )
+            );
+            assert_eq!(
+                second_token_reserve,
+                EgldOrEsdtTokenPayment::new(
+                    managed_token_id_wrapped!(MEX_TOKEN_ID),
+                    0,
+                    managed_biguint!(1_000_004)
+                )
+            );
+
+            // sqrt(1_002_000 * 1_000_004) = 1_001_001.49...
+            let average_liquidity =
+                sc.get_average_liquidity(managed_address!(&pair_address), 10, 20);
+            assert_eq!(average_liquidity, managed_biguint!(1_001_001));
+        });
+
+    pair_setup
+        .b_mock
+        .execute_query(&pair_setup.second_pair_wrapper, |sc| {
+            let _ = sc.get_average_liquidity(managed_address!(&pair_address), 5, 20);
+        })
+        .assert_user_error("The price observation does not exist");
+}
+
+// The safe price from the first pair is read from the second pair
+// The purpose of this test is to see if values are returned from the correct contract
+#[test]
+fn test_both_legacy_and_new_safe_price_from_other_contract() {
+    let mut pair_setup = PairSetup::new(pair::contract_obj);
+    let pair_address = pair_setup.pair_wrapper.address_ref().clone();
+    let payment_amount = 1000;
+    let starting_round = 1000;
+    let mut expected_amount = 996;
+    let weight = 1;
+    let mut block_round = starting_round + weight;
+    pair_setup.b_mock.set_block_round(block_round);
+
+    let mut first_token_reserve = 1_002_000;
+    let mut second_token_reserve = 1_000_004;
+    let mut first_token_accumulated = 1_001_000;
+    let mut second_token_accumulated = 1_001_000;
+    pair_setup.add_liquidity(
+        1_001_000,
+        1_000_000,
+        1_001_000,
+        1_000_000,
+        1_000_000,
+        first_token_accumulated,
+        second_token_accumulated,
+    );
+    pair_setup.swap_fixed_input(
+        WEGLD_TOKEN_ID,
+        payment_amount,
+        MEX_TOKEN_ID,
+        900,
+        expected_amount,
+    );
+
+    pair_setup.check_price_observation(
+        &pair_address,
+        block_round,
+        1, // The accumulated weight should be 1, as it is the first element from the list
+        first_token_accumulated,
+        second_token_accumulated,
+    );
+
+    block_round += weight;
+    expected_amount -= 2; // slippage
+    pair_setup.b_mock.set_block_round(block_round);
+    pair_setup.swap_fixed_input(
+        WEGLD_TOKEN_ID,
+        payment_amount,
+        MEX_TOKEN_ID,
+        900,
+        expected_amount,
+    );
+
+    first_token_accumulated += weight * first_token_reserve;
+    second_token_accumulated += weight * second_token_reserve;
+
+    pair_setup.check_price_observation_from_second_pair(
+        &pair_address,
+        block_round,
+        block_round - starting_round,
+        first_token_accumulated,
+        second_token_accumulated,
+    );
+
+    block_round += weight;
+    first_token_reserve += payment_amount;
+    second_token_reserve -= expected_amount;
+    first_token_accumulated += weight * first_token_reserve;
+    second_token_accumulated += weight * second_token_reserve;
+    expected_amount -= 2;
+    pair_setup.b_mock.set_block_round(block_round);
+    pair_setup.swap_fixed_input(
+        WEGLD_TOKEN_ID,
+        payment_amount,
+        MEX_TOKEN_ID,
+        900,
+        expected_amount,
+    );
+    pair_setup.check_price_observation_from_second_pair(
+        &pair_address,
+        block_round,
+        block_round - starting_round,
+        first_token_accumulated,
+        second_token_accumulated,
+    );
+
+    block_round += weight;
+    first_token_reserve += payment_amount;
+    second_token_reserve -= expected_amount;
+    first_token_accumulated += weight * first_token_reserve;
+    second_token_accumulated += weight * second_token_reserve;
+    expected_amount -= 2;
+    pair_setup.b_mock.set_block_round(block_round);
+    pair_setup.swap_fixed_input(
+        WEGLD_TOKEN_ID,
+        payment_amount,
+        MEX_TOKEN_ID,
+        900,
+        expected_amount,
+    );
+    pair_setup.check_price_observation_from_second_pair(
+        &pair_address,
+        block_round,
+        block_round - starting_round,
+        first_token_accumulated,
+        second_token_accumulated,
+    );
+
+    let first_token_payment_amount = 100;
+    let expected_token_payment_amount = 99;
+    pair_setup.check_safe_price_from_second_pair(
+        &pair_address,
+        starting_round + 1,
+        block_round,
+        WEGLD_TOKEN_ID,
+        first_token_payment_amount,
+        MEX_TOKEN_ID,
+        expected_token_payment_amount,
+    );
+
+    // Check legacy endpoint
+    // Should be the same as the result from the new get_safe_price view
+    pair_setup.check_safe_price_from_legacy_endpoint(
+        WEGLD_TOKEN_ID,
+        first_token_payment_amount,
+        MEX_TOKEN_ID,
+        expected_token_payment_amount,
+    );
+}
+
+// Test is commented as it needs a variable change in order to run succesfully
+// In order to run the test with the current setup, DEFAULT_OBSERVATIONS_CAPACITY const must be set to 100
+// This is necessary as using the MAINNET variable requires too many operations for a unit test
+// #[test]
+// fn test_safe_price_max_length() {
+//     let mut pair_setup = PairSetup::new(pair::contract_obj);
+//     let pair_address = pair_setup.pair_wrapper.address_ref().clone();
+//     let max_observations = DEFAULT_OBSERVATIONS_CAPACITY.try_into().unwrap(); // should be 100
+//     let min_pool_reserve = 1_000;
+//     let weight = 1;
+//     let mut block_round = 0u64;
+
+//     let mut first_token_reserve = 1_001_000;
+//     let mut second_token_reserve = 30_030_000;
+//     let mut first_token_accumulated = weight * first_token_reserve;
+//     let mut second_token_accumulated = weight * second_token_reserve;
+
+//     pair_setup.add_liquidity(
+//         first_token_reserve,
+//         first_token_reserve,
+//         second_token_reserve,
+//         first_token_reserve,
+//         first_token_reserve - min_pool_reserve,
+//         first_token_reserve,
+//         second_token_reserve,
+//     );
+
+//     let mut first_token_payment_amount = 1;
+//     let mut second_token_expected_amount = 29;
+
+//     while block_round <= max_observations {
+//         // println!("Round: {}", (block_round));
+
+//         block_round += weight;
+//         pair_setup.b_mock.set_block_round(block_round);
+//         pair_setup.swap_fixed_input(
+//             WEGLD_TOKEN_ID,
+//             first_token_payment_amount,
+//             MEX_TOKEN_ID,
+//             1,
+//             second_token_expected_amount,
+//         );
+
+//         first_token_reserve += first_token_payment_amount;
+//         second_token_reserve -= second_token_expected_amount;
+//         first_token_accumulated += weight * first_token_reserve;
+//         second_token_accumulated += weight * second_token_reserve;
+
+//         second_token_expected_amount = second_token_reserve / first_token_reserve;
+//     }
+
+//     let mut second_token_payment_amount = 1_000_000;
+//     let mut first_token_expected_amount = 32_171;
+
+//     // Price change
+//     block_round += weight;
+//     println!("Price change round: {}", (block_round));
+//     pair_setup.b_mock.set_block_round(block_round);
+//     pair_setup.swap_fixed_input(
+//         MEX_TOKEN_ID,
+//         second_token_payment_amount,
+//         WEGLD_TOKEN_ID,
+//         1,
+//         first_token_expected_amount,
+//     );
 
 //     pair_setup.check_price_observation(
 //         &pair_address,
@@ -842,116 +3506,639 @@ fn test_both_legacy_and_new_safe_price_from_other_contract() {
 //         second_token_accumulated,
 //     );
 
-//     first_token_reserve -= first_token_expected_amount;
-//     second_token_reserve += second_token_payment_amount;
-//     first_token_accumulated += weight * first_token_reserve;
-//     second_token_accumulated += weight * second_token_reserve;
+//     first_token_reserve -= first_token_expected_amount;
+//     second_token_reserve += second_token_payment_amount;
+//     first_token_accumulated += weight * first_token_reserve;
+//     second_token_accumulated += weight * second_token_reserve;
+
+//     second_token_payment_amount = 1_000;
+//     first_token_expected_amount =
+//         second_token_payment_amount * first_token_reserve / second_token_reserve;
+
+//     // Save 10 more price observations, at the beginning of the list
+//     while block_round % max_observations <= 10 {
+//         // println!("Round: {}", (block_round));
+
+//         block_round += weight;
+//         pair_setup.b_mock.set_block_round(block_round);
+//         pair_setup.swap_fixed_input(
+//             MEX_TOKEN_ID,
+//             second_token_payment_amount,
+//             WEGLD_TOKEN_ID,
+//             1,
+//             first_token_expected_amount,
+//         );
+
+//         first_token_reserve -= first_token_expected_amount;
+//         second_token_reserve += second_token_payment_amount;
+//         first_token_accumulated += weight * first_token_reserve;
+//         second_token_accumulated += weight * second_token_reserve;
+
+//         first_token_expected_amount =
+//             second_token_payment_amount * first_token_reserve / second_token_reserve;
+//     }
+
+//     first_token_payment_amount = 1_000;
+
+//     let mut safe_price_rounds_offset = 20;
+//     let mut safe_price_expected_amount = 30_894;
+//     pair_setup.check_safe_price(
+//         &pair_address,
+//         block_round - safe_price_rounds_offset,
+//         block_round,
+//         WEGLD_TOKEN_ID,
+//         first_token_payment_amount,
+//         MEX_TOKEN_ID,
+//         safe_price_expected_amount,
+//     );
+
+//     safe_price_rounds_offset = 10;
+//     safe_price_expected_amount = 31_820;
+//     pair_setup.check_safe_price(
+//         &pair_address,
+//         block_round - safe_price_rounds_offset,
+//         block_round,
+//         WEGLD_TOKEN_ID,
+//         first_token_payment_amount,
+//         MEX_TOKEN_ID,
+//         safe_price_expected_amount,
+//     );
+
+//     safe_price_rounds_offset = 1;
+//     safe_price_expected_amount = 32_038;
+//     pair_setup.check_safe_price(
+//         &pair_address,
+//         block_round - safe_price_rounds_offset,
+//         block_round,
+//         WEGLD_TOKEN_ID,
+//         first_token_payment_amount,
+//         MEX_TOKEN_ID,
+//         safe_price_expected_amount,
+//     );
+// }
+
+#[test]
+fn test_locked_asset() {
+    let mut pair_setup = PairSetup::new(pair::contract_obj);
+
+    pair_setup.add_liquidity(
+        1_001_000, 1_000_000, 1_001_000, 1_000_000, 1_000_000, 1_001_000, 1_001_000,
+    );
+
+    // init locking SC
+    let rust_zero = rust_biguint!(0);
+    let locking_owner = pair_setup.b_mock.create_user_account(&rust_zero);
+    let locking_sc_wrapper = pair_setup.b_mock.create_sc_account(
+        &rust_zero,
+        Some(&locking_owner),
+        simple_lock::contract_obj,
+        "Some path",
+    );
+
+    pair_setup
+        .b_mock
+        .execute_tx(&locking_owner, &locking_sc_wrapper, &rust_zero, |sc| {
+            sc.init();
+            sc.locked_token()
+                .set_token_id(managed_token_id!(LOCKED_TOKEN_ID));
+        })
+        .assert_ok();
+
+    pair_setup.b_mock.set_esdt_local_roles(
+        locking_sc_wrapper.address_ref(),
+        LOCKED_TOKEN_ID,
+        &[
+            EsdtLocalRole::NftCreate,
+            EsdtLocalRole::NftAddQuantity,
+            EsdtLocalRole::NftBurn,
+        ],
+    );
+
+    pair_setup.b_mock.set_block_epoch(4);
+
+    pair_setup
+        .b_mock
+        .execute_tx(
+            &pair_setup.owner_address,
+            &pair_setup.pair_wrapper,
+            &rust_biguint!(0),
+            |sc| {
+                sc.set_locking_sc_address(managed_address!(locking_sc_wrapper.address_ref()));
+                sc.set_locking_deadline_epoch(5);
+                sc.set_unlock_epoch(10);
+            },
+        )
+        .assert_ok();
+
+    pair_setup
+        .b_mock
+        .execute_esdt_transfer(
+            &pair_setup.user_address,
+            &pair_setup.pair_wrapper,
+            MEX_TOKEN_ID,
+            0,
+            &rust_biguint!(1_000),
+            |sc| {
+                let ret = sc.swap_tokens_fixed_input(
+                    managed_token_id_wrapped!(WEGLD_TOKEN_ID),
+                    managed_biguint!(10),
+                    OptionalValue::None,
+                );
+
+                assert_eq!(ret.token_identifier, managed_token_id!(LOCKED_TOKEN_ID));
+                assert_eq!(ret.token_nonce, 1);
+                assert_eq!(ret.amount, managed_biguint!(996));
+            },
+        )
+        .assert_ok();
+
+    DebugApi::dummy();
+    pair_setup.b_mock.check_nft_balance(
+        &pair_setup.user_address,
+        LOCKED_TOKEN_ID,
+        1,
+        &rust_biguint!(996),
+        Some(&LockedTokenAttributes::<DebugApi> {
+            original_token_id: managed_token_id_wrapped!(WEGLD_TOKEN_ID),
+            original_token_nonce: 0,
+            unlock_epoch: 10,
+        }),
+    );
+
+    let user_wegld_balance_before =
+        pair_setup
+            .b_mock
+            .get_esdt_balance(&pair_setup.user_address, WEGLD_TOKEN_ID, 0);
+
+    // try unlock too early
+    pair_setup
+        .b_mock
+        .execute_esdt_transfer(
+            &pair_setup.user_address,
+            &locking_sc_wrapper,
+            LOCKED_TOKEN_ID,
+            1,
+            &rust_biguint!(996),
+            |sc| {
+                sc.unlock_tokens_endpoint(OptionalValue::None);
+            },
+        )
+        .assert_user_error("Cannot unlock yet");
+
+    // unlock ok
+    pair_setup.b_mock.set_block_epoch(20);
+
+    pair_setup
+        .b_mock
+        .execute_esdt_transfer(
+            &pair_setup.user_address,
+            &locking_sc_wrapper,
+            LOCKED_TOKEN_ID,
+            1,
+            &rust_biguint!(996),
+            |sc| {
+                sc.unlock_tokens_endpoint(OptionalValue::None);
+            },
+        )
+        .assert_ok();
+    pair_setup.b_mock.check_esdt_balance(
+        &pair_setup.user_address,
+        WEGLD_TOKEN_ID,
+        &(user_wegld_balance_before + rust_biguint!(996)),
+    );
+}
+
+#[test]
+fn add_liquidity_through_simple_lock_proxy() {
+    let mut pair_setup = PairSetup::new(pair::contract_obj);
+
+    pair_setup.add_liquidity(
+        1_001_000, 1_000_000, 1_001_000, 1_000_000, 1_000_000, 1_001_000, 1_001_000,
+    );
+
+    // init locking SC
+    let lp_address = pair_setup.pair_wrapper.address_ref().clone();
+    let rust_zero = rust_biguint!(0);
+    let locking_owner = pair_setup.b_mock.create_user_account(&rust_zero);
+    let locking_sc_wrapper = pair_setup.b_mock.create_sc_account(
+        &rust_zero,
+        Some(&locking_owner),
+        simple_lock::contract_obj,
+        "Some path",
+    );
+
+    // setup locked token
+    pair_setup
+        .b_mock
+        .execute_tx(&locking_owner, &locking_sc_wrapper, &rust_zero, |sc| {
+            sc.init();
+            sc.locked_token()
+                .set_token_id(managed_token_id!(LOCKED_TOKEN_ID));
+            sc.add_lp_to_whitelist(
+                managed_address!(&lp_address),
+                managed_token_id!(WEGLD_TOKEN_ID),
+                managed_token_id!(MEX_TOKEN_ID),
+            );
+        })
+        .assert_ok();
+
+    pair_setup.b_mock.set_esdt_local_roles(
+        locking_sc_wrapper.address_ref(),
+        LOCKED_TOKEN_ID,
+        &[
+            EsdtLocalRole::NftCreate,
+            EsdtLocalRole::NftAddQuantity,
+            EsdtLocalRole::NftBurn,
+        ],
+    );
+
+    // setup lp proxy token
+    pair_setup
+        .b_mock
+        .execute_tx(&locking_owner, &locking_sc_wrapper, &rust_zero, |sc| {
+            sc.init();
+            sc.lp_proxy_token()
+                .set_token_id(managed_token_id!(LP_PROXY_TOKEN_ID));
+        })
+        .assert_ok();
+
+    pair_setup.b_mock.set_esdt_local_roles(
+        locking_sc_wrapper.address_ref(),
+        LP_PROXY_TOKEN_ID,
+        &[
+            EsdtLocalRole::NftCreate,
+            EsdtLocalRole::NftAddQuantity,
+            EsdtLocalRole::NftBurn,
+        ],
+    );
+
+    pair_setup.b_mock.set_block_epoch(5);
+    DebugApi::dummy();
+
+    // lock some tokens first
+    pair_setup
+        .b_mock
+        .execute_esdt_transfer(
+            &pair_setup.user_address,
+            &locking_sc_wrapper,
+            WEGLD_TOKEN_ID,
+            0,
+            &rust_biguint!(1_000_000),
+            |sc| {
+                sc.lock_tokens_endpoint(10, OptionalValue::None);
+            },
+        )
+        .assert_ok();
+    pair_setup.b_mock.check_nft_balance(
+        &pair_setup.user_address,
+        LOCKED_TOKEN_ID,
+        1,
+        &rust_biguint!(1_000_000),
+        Some(&LockedTokenAttributes::<DebugApi> {
+            original_token_id: managed_token_id_wrapped!(WEGLD_TOKEN_ID),
+            original_token_nonce: 0,
+            unlock_epoch: 10,
+        }),
+    );
+
+    pair_setup
+        .b_mock
+        .execute_esdt_transfer(
+            &pair_setup.user_address,
+            &locking_sc_wrapper,
+            MEX_TOKEN_ID,
+            0,
+            &rust_biguint!(2_000_000),
+            |sc| {
+                sc.lock_tokens_endpoint(15, OptionalValue::None);
+            },
+        )
+        .assert_ok();
+    pair_setup.b_mock.check_nft_balance(
+        &pair_setup.user_address,
+        LOCKED_TOKEN_ID,
+        2,
+        &rust_biguint!(2_000_000),
+        Some(&LockedTokenAttributes::<DebugApi> {
+            original_token_id: managed_token_id_wrapped!(MEX_TOKEN_ID),
+            original_token_nonce: 0,
+            unlock_epoch: 15,
+        }),
+    );
+
+    pair_setup.b_mock.set_block_epoch(5);
+
+    // add liquidity through simple-lock SC - one locked (WEGLD) token, one unlocked (MEX)
+    let transfers = vec![
+        TxTokenTransfer {
+            token_identifier: LOCKED_TOKEN_ID.to_vec(),
+            nonce: 1,
+            value: rust_biguint!(500_000),
+        },
+        TxTokenTransfer {
+            token_identifier: MEX_TOKEN_ID.to_vec(),
+            nonce: 0,
+            value: rust_biguint!(500_000),
+        },
+    ];
+
+    pair_setup
+        .b_mock
+        .execute_esdt_multi_transfer(
+            &pair_setup.user_address,
+            &locking_sc_wrapper,
+            &transfers[..],
+            |sc| {
+                let (dust_first_token, dust_second_token, lp_proxy_payment) = sc
+                    .add_liquidity_locked_token(
+                        managed_biguint!(1),
+                        managed_biguint!(1),
+                        OptionalValue::None,
+                    )
+                    .into_tuple();
+
+                assert_eq!(
+                    dust_first_token.token_identifier,
+                    managed_token_id!(WEGLD_TOKEN_ID)
+                );
+                assert_eq!(dust_first_token.token_nonce, 0);
+                assert_eq!(dust_first_token.amount, managed_biguint!(0));
+
+                assert_eq!(
+                    dust_second_token.token_identifier,
+                    managed_token_id!(MEX_TOKEN_ID)
+                );
+                assert_eq!(dust_second_token.token_nonce, 0);
+                assert_eq!(dust_second_token.amount, managed_biguint!(0));
+
+                assert_eq!(
+                    lp_proxy_payment.token_identifier,
+                    managed_token_id!(LP_PROXY_TOKEN_ID)
+                );
+                assert_eq!(lp_proxy_payment.token_nonce, 1);
+                assert_eq!(lp_proxy_payment.amount, managed_biguint!(500_000));
+            },
+        )
+        .assert_ok();
+    pair_setup.b_mock.check_nft_balance(
+        &pair_setup.user_address,
+        LP_PROXY_TOKEN_ID,
+        1,
+        &rust_biguint!(500_000),
+        Some(&LpProxyTokenAttributes::<DebugApi> {
+            lp_token_id: managed_token_id!(LP_TOKEN_ID),
+            first_token_id: managed_token_id!(WEGLD_TOKEN_ID),
+            first_token_locked_nonce: 1,
+            second_token_id: managed_token_id!(MEX_TOKEN_ID),
+            second_token_locked_nonce: 0,
+        }),
+    );
+    pair_setup.b_mock.check_esdt_balance(
+        locking_sc_wrapper.address_ref(),
+        LP_TOKEN_ID,
+        &rust_biguint!(500_000),
+    );
+
+    let user_locked_token_balance_before =
+        pair_setup
+            .b_mock
+            .get_esdt_balance(&pair_setup.user_address, LOCKED_TOKEN_ID, 1);
+    let user_mex_balance_before =
+        pair_setup
+            .b_mock
+            .get_esdt_balance(&pair_setup.user_address, MEX_TOKEN_ID, 0);
+
+    // remove liquidity
+    pair_setup
+        .b_mock
+        .execute_esdt_transfer(
+            &pair_setup.user_address,
+            &locking_sc_wrapper,
+            LP_PROXY_TOKEN_ID,
+            1,
+            &rust_biguint!(500_000),
+            |sc| {
+                let (first_payment_result, second_payment_result) = sc
+                    .remove_liquidity_locked_token(
+                        managed_biguint!(1),
+                        managed_biguint!(1),
+                        OptionalValue::None,
+                    )
+                    .into_tuple();
+
+                assert_eq!(
+                    first_payment_result.token_identifier,
+                    managed_token_id!(LOCKED_TOKEN_ID)
+                );
+                assert_eq!(first_payment_result.token_nonce, 1);
+                assert_eq!(first_payment_result.amount, managed_biguint!(500_000));
+
+                assert_eq!(
+                    second_payment_result.token_identifier,
+                    managed_token_id!(MEX_TOKEN_ID)
+                );
+                assert_eq!(second_payment_result.token_nonce, 0);
+                assert_eq!(second_payment_result.amount, managed_biguint!(500_000));
+            },
+        )
+        .assert_ok();
+
+    pair_setup.b_mock.check_nft_balance(
+        &pair_setup.user_address,
+        LOCKED_TOKEN_ID,
+        1,
+        &(user_locked_token_balance_before + 500_000u32),
+        Some(&LockedTokenAttributes::<DebugApi> {
+            original_token_id: managed_token_id_wrapped!(WEGLD_TOKEN_ID),
+            original_token_nonce: 0,
+            unlock_epoch: 10,
+        }),
+    );
+    pair_setup.b_mock.check_esdt_balance(
+        &pair_setup.user_address,
+        MEX_TOKEN_ID,
+        &(user_mex_balance_before + 500_000u32),
+    );
+
+    // Add liquidity - same token pair as before -> same nonce (1)
+    pair_setup
+        .b_mock
+        .execute_esdt_multi_transfer(
+            &pair_setup.user_address,
+            &locking_sc_wrapper,
+            &transfers[..],
+            |sc| {
+                let (_, _, lp_proxy_payment) = sc
+                    .add_liquidity_locked_token(
+                        managed_biguint!(1),
+                        managed_biguint!(1),
+                        OptionalValue::None,
+                    )
+                    .into_tuple();
+
+                assert_eq!(
+                    lp_proxy_payment.token_identifier,
+                    managed_token_id!(LP_PROXY_TOKEN_ID)
+                );
+                assert_eq!(lp_proxy_payment.token_nonce, 1);
+                assert_eq!(lp_proxy_payment.amount, managed_biguint!(500_000));
+            },
+        )
+        .assert_ok();
 
-//     second_token_payment_amount = 1_000;
-//     first_token_expected_amount =
-//         second_token_payment_amount * first_token_reserve / second_token_reserve;
+    // test auto-unlock for tokens on remove liquidity
+    pair_setup.b_mock.set_block_epoch(30);
 
-//     // Save 10 more price observations, at the beginning of the list
-//     while block_round % max_observations <= 10 {
-//         // println!("Round: {}", (block_round));
+    pair_setup
+        .b_mock
+        .execute_esdt_transfer(
+            &pair_setup.user_address,
+            &locking_sc_wrapper,
+            LP_PROXY_TOKEN_ID,
+            1,
+            &rust_biguint!(500_000),
+            |sc| {
+                let (first_payment_result, second_payment_result) = sc
+                    .remove_liquidity_locked_token(
+                        managed_biguint!(1),
+                        managed_biguint!(1),
+                        OptionalValue::None,
+                    )
+                    .into_tuple();
 
-//         block_round += weight;
-//         pair_setup.b_mock.set_block_round(block_round);
-//         pair_setup.swap_fixed_input(
-//             MEX_TOKEN_ID,
-//             second_token_payment_amount,
-//             WEGLD_TOKEN_ID,
-//             1,
-//             first_token_expected_amount,
-//         );
+                assert_eq!(
+                    first_payment_result.token_identifier,
+                    managed_token_id!(WEGLD_TOKEN_ID)
+                );
+                assert_eq!(first_payment_result.token_nonce, 0);
+                assert_eq!(first_payment_result.amount, managed_biguint!(500_000));
 
-//         first_token_reserve -= first_token_expected_amount;
-//         second_token_reserve += second_token_payment_amount;
-//         first_token_accumulated += weight * first_token_reserve;
-//         second_token_accumulated += weight * second_token_reserve;
+                assert_eq!(
+                    second_payment_result.token_identifier,
+                    managed_token_id!(MEX_TOKEN_ID)
+                );
+                assert_eq!(second_payment_result.token_nonce, 0);
+                assert_eq!(second_payment_result.amount, managed_biguint!(500_000));
+            },
+        )
+        .assert_ok();
 
-//         first_token_expected_amount =
-//             second_token_payment_amount * first_token_reserve / second_token_reserve;
-//     }
+    pair_setup
+        .b_mock
+        .execute_query(&locking_sc_wrapper, |sc| {
+            assert_eq!(sc.known_liquidity_pools().len(), 1);
+            assert!(sc
+                .known_liquidity_pools()
+                .contains(&managed_address!(&lp_address)));
+        })
+        .assert_ok();
+}
 
-//     first_token_payment_amount = 1_000;
+#[test]
+fn fees_collector_pair_test() {
+    let mut pair_setup = PairSetup::new(pair::contract_obj);
+    let fees_collector_wrapper = pair_setup.b_mock.create_sc_account(
+        &rust_biguint!(0),
+        None,
+        fees_collector::contract_obj,
+        "fees collector path",
+    );
 
-//     let mut safe_price_rounds_offset = 20;
-//     let mut safe_price_expected_amount = 30_894;
-//     pair_setup.check_safe_price(
-//         &pair_address,
-//         block_round - safe_price_rounds_offset,
-//         block_round,
-//         WEGLD_TOKEN_ID,
-//         first_token_payment_amount,
-//         MEX_TOKEN_ID,
-//         safe_price_expected_amount,
-//     );
+    let pair_addr = pair_setup.pair_wrapper.address_ref().clone();
+    let energy_factory_mock_addr = pair_setup.pair_wrapper.address_ref().clone();
+    pair_setup
+        .b_mock
+        .execute_tx(
+            &pair_setup.owner_address,
+            &fees_collector_wrapper,
+            &rust_biguint!(0),
+            |sc| {
+                sc.init(
+                    managed_token_id!(LOCKED_TOKEN_ID),
+                    managed_address!(&energy_factory_mock_addr),
+                );
+                let _ = sc.known_contracts().insert(managed_address!(&pair_addr));
 
-//     safe_price_rounds_offset = 10;
-//     safe_price_expected_amount = 31_820;
-//     pair_setup.check_safe_price(
-//         &pair_address,
-//         block_round - safe_price_rounds_offset,
-//         block_round,
-//         WEGLD_TOKEN_ID,
-//         first_token_payment_amount,
-//         MEX_TOKEN_ID,
-//         safe_price_expected_amount,
-//     );
+                let mut tokens = MultiValueEncoded::new();
+                tokens.push(managed_token_id!(WEGLD_TOKEN_ID));
+                tokens.push(managed_token_id!(MEX_TOKEN_ID));
 
-//     safe_price_rounds_offset = 1;
-//     safe_price_expected_amount = 32_038;
-//     pair_setup.check_safe_price(
-//         &pair_address,
-//         block_round - safe_price_rounds_offset,
-//         block_round,
-//         WEGLD_TOKEN_ID,
-//         first_token_payment_amount,
-//         MEX_TOKEN_ID,
-//         safe_price_expected_amount,
-//     );
-// }
+                sc.add_known_tokens(tokens);
+            },
+        )
+        .assert_ok();
 
-#[test]
-fn test_locked_asset() {
-    let mut pair_setup = PairSetup::new(pair::contract_obj);
+    pair_setup
+        .b_mock
+        .execute_tx(
+            &pair_setup.owner_address,
+            &pair_setup.pair_wrapper,
+            &rust_biguint!(0),
+            |sc| {
+                sc.setup_fees_collector(
+                    managed_address!(fees_collector_wrapper.address_ref()),
+                    MAX_PERCENTAGE / 2,
+                );
+            },
+        )
+        .assert_ok();
 
     pair_setup.add_liquidity(
         1_001_000, 1_000_000, 1_001_000, 1_000_000, 1_000_000, 1_001_000, 1_001_000,
     );
 
-    // init locking SC
-    let rust_zero = rust_biguint!(0);
-    let locking_owner = pair_setup.b_mock.create_user_account(&rust_zero);
-    let locking_sc_wrapper = pair_setup.b_mock.create_sc_account(
-        &rust_zero,
-        Some(&locking_owner),
-        simple_lock::contract_obj,
-        "Some path",
+    pair_setup.swap_fixed_input(WEGLD_TOKEN_ID, 100_000, MEX_TOKEN_ID, 900, 90_669);
+
+    pair_setup.b_mock.check_esdt_balance(
+        fees_collector_wrapper.address_ref(),
+        WEGLD_TOKEN_ID,
+        &rust_biguint!(25),
     );
 
     pair_setup
         .b_mock
-        .execute_tx(&locking_owner, &locking_sc_wrapper, &rust_zero, |sc| {
-            sc.init();
-            sc.locked_token()
-                .set_token_id(managed_token_id!(LOCKED_TOKEN_ID));
+        .execute_query(&fees_collector_wrapper, |sc| {
+            assert_eq!(
+                sc.accumulated_fees(1, &managed_token_id!(WEGLD_TOKEN_ID))
+                    .get(),
+                managed_biguint!(25)
+            );
         })
         .assert_ok();
+}
 
-    pair_setup.b_mock.set_esdt_local_roles(
-        locking_sc_wrapper.address_ref(),
-        LOCKED_TOKEN_ID,
-        &[
-            EsdtLocalRole::NftCreate,
-            EsdtLocalRole::NftAddQuantity,
-            EsdtLocalRole::NftBurn,
-        ],
-    );
+#[test]
+fn stable_swap_curve_test() {
+    let mut pair_setup = PairSetup::new(pair::contract_obj);
 
-    pair_setup.b_mock.set_block_epoch(4);
+    pair_setup
+        .b_mock
+        .execute_tx(
+            &pair_setup.owner_address,
+            &pair_setup.pair_wrapper,
+            &rust_biguint!(0),
+            |sc| {
+                sc.set_stable_swap_curve(100);
+
+                assert_eq!(sc.curve_type().get(), CurveType::StableSwap);
+                assert_eq!(sc.get_amp_factor(), 100);
+
+                // a single empty reserve gives a zero invariant
+                let invariant = sc.compute_stable_swap_invariant(
+                    &managed_biguint!(1_000),
+                    &managed_biguint!(0),
+                    sc.get_amp_factor_precise(),
+                );
+                assert_eq!(invariant, managed_biguint!(0));
+            },
+        )
+        .assert_ok();
+
+    pair_setup.add_liquidity(
+        1_001_000, 1_000_000, 1_001_000, 1_000_000, 1_000_000, 1_001_000, 1_001_000,
+    );
 
+    // curve cannot be changed once liquidity was added
     pair_setup
         .b_mock
         .execute_tx(
@@ -959,469 +4146,680 @@ fn test_locked_asset() {
             &pair_setup.pair_wrapper,
             &rust_biguint!(0),
             |sc| {
-                sc.set_locking_sc_address(managed_address!(locking_sc_wrapper.address_ref()));
-                sc.set_locking_deadline_epoch(5);
-                sc.set_unlock_epoch(10);
+                sc.set_stable_swap_curve(200);
             },
         )
+        .assert_user_error("The curve can only be changed before liquidity is added");
+
+    pair_setup
+        .b_mock
+        .execute_query(&pair_setup.pair_wrapper, |sc| {
+            let amount_out = sc.get_amount_out_view(
+                managed_token_id_wrapped!(WEGLD_TOKEN_ID),
+                managed_biguint!(100_000),
+                OptionalValue::None,
+            );
+            assert_eq!(amount_out, managed_biguint!(99_600));
+
+            let amount_in = sc.get_amount_in_view(
+                managed_token_id_wrapped!(MEX_TOKEN_ID),
+                managed_biguint!(99_000),
+                OptionalValue::None,
+            );
+            assert_eq!(amount_in, managed_biguint!(99_397));
+        })
         .assert_ok();
 
+    // same swap gives 90_669 MEX on the constant product curve
+    pair_setup.swap_fixed_input(WEGLD_TOKEN_ID, 100_000, MEX_TOKEN_ID, 99_000, 99_600);
+    pair_setup.swap_fixed_output(MEX_TOKEN_ID, 100_000, WEGLD_TOKEN_ID, 99_000, 801);
+
     pair_setup
         .b_mock
         .execute_esdt_transfer(
             &pair_setup.user_address,
             &pair_setup.pair_wrapper,
-            MEX_TOKEN_ID,
+            LP_TOKEN_ID,
             0,
-            &rust_biguint!(1_000),
+            &rust_biguint!(500_000),
             |sc| {
-                let ret = sc.swap_tokens_fixed_input(
-                    managed_token_id!(WEGLD_TOKEN_ID),
-                    managed_biguint!(10),
-                );
-
-                assert_eq!(ret.token_identifier, managed_token_id!(LOCKED_TOKEN_ID));
-                assert_eq!(ret.token_nonce, 1);
-                assert_eq!(ret.amount, managed_biguint!(996));
+                let (first_payment, second_payment) = sc
+                    .remove_liquidity(
+                        managed_biguint!(1),
+                        managed_biguint!(1),
+                        OptionalValue::None,
+                    )
+                    .into_tuple();
+                assert_eq!(first_payment.amount, managed_biguint!(500_499));
+                assert_eq!(second_payment.amount, managed_biguint!(499_799));
             },
         )
         .assert_ok();
+}
 
-    DebugApi::dummy();
-    pair_setup.b_mock.check_nft_balance(
-        &pair_setup.user_address,
-        LOCKED_TOKEN_ID,
-        1,
-        &rust_biguint!(996),
-        Some(&LockedTokenAttributes::<DebugApi> {
-            original_token_id: managed_token_id_wrapped!(WEGLD_TOKEN_ID),
-            original_token_nonce: 0,
-            unlock_epoch: 10,
-        }),
-    );
+#[test]
+fn stable_swap_amp_factor_ramp_test() {
+    let mut pair_setup = PairSetup::new(pair::contract_obj);
 
-    let user_wegld_balance_before =
-        pair_setup
-            .b_mock
-            .get_esdt_balance(&pair_setup.user_address, WEGLD_TOKEN_ID, 0);
+    pair_setup
+        .b_mock
+        .execute_tx(
+            &pair_setup.owner_address,
+            &pair_setup.pair_wrapper,
+            &rust_biguint!(0),
+            |sc| {
+                sc.ramp_amp_factor(200, 86_400);
+            },
+        )
+        .assert_user_error("The pair does not use the StableSwap curve");
+
+    pair_setup
+        .b_mock
+        .execute_tx(
+            &pair_setup.owner_address,
+            &pair_setup.pair_wrapper,
+            &rust_biguint!(0),
+            |sc| {
+                sc.set_stable_swap_curve(100);
+            },
+        )
+        .assert_ok();
 
-    // try unlock too early
     pair_setup
         .b_mock
-        .execute_esdt_transfer(
-            &pair_setup.user_address,
-            &locking_sc_wrapper,
-            LOCKED_TOKEN_ID,
-            1,
-            &rust_biguint!(996),
+        .execute_tx(
+            &pair_setup.owner_address,
+            &pair_setup.pair_wrapper,
+            &rust_biguint!(0),
             |sc| {
-                sc.unlock_tokens_endpoint(OptionalValue::None);
+                sc.ramp_amp_factor(200, 86_400);
             },
         )
-        .assert_user_error("Cannot unlock yet");
+        .assert_user_error("The amplification factor was changed too recently");
 
-    // unlock ok
-    pair_setup.b_mock.set_block_epoch(20);
+    pair_setup.b_mock.set_block_timestamp(86_400);
 
     pair_setup
         .b_mock
-        .execute_esdt_transfer(
-            &pair_setup.user_address,
-            &locking_sc_wrapper,
-            LOCKED_TOKEN_ID,
-            1,
-            &rust_biguint!(996),
+        .execute_tx(
+            &pair_setup.owner_address,
+            &pair_setup.pair_wrapper,
+            &rust_biguint!(0),
             |sc| {
-                sc.unlock_tokens_endpoint(OptionalValue::None);
+                sc.ramp_amp_factor(2_000, 3 * 86_400);
             },
         )
-        .assert_ok();
-    pair_setup.b_mock.check_esdt_balance(
-        &pair_setup.user_address,
-        WEGLD_TOKEN_ID,
-        &(user_wegld_balance_before + rust_biguint!(996)),
-    );
-}
-
-#[test]
-fn add_liquidity_through_simple_lock_proxy() {
-    let mut pair_setup = PairSetup::new(pair::contract_obj);
+        .assert_user_error("Amplification factor change too big");
 
-    pair_setup.add_liquidity(
-        1_001_000, 1_000_000, 1_001_000, 1_000_000, 1_000_000, 1_001_000, 1_001_000,
-    );
+    pair_setup
+        .b_mock
+        .execute_tx(
+            &pair_setup.owner_address,
+            &pair_setup.pair_wrapper,
+            &rust_biguint!(0),
+            |sc| {
+                sc.ramp_amp_factor(200, 3 * 86_400);
+            },
+        )
+        .assert_ok();
 
-    // init locking SC
-    let lp_address = pair_setup.pair_wrapper.address_ref().clone();
-    let rust_zero = rust_biguint!(0);
-    let locking_owner = pair_setup.b_mock.create_user_account(&rust_zero);
-    let locking_sc_wrapper = pair_setup.b_mock.create_sc_account(
-        &rust_zero,
-        Some(&locking_owner),
-        simple_lock::contract_obj,
-        "Some path",
-    );
+    pair_setup.b_mock.set_block_timestamp(2 * 86_400);
 
-    // setup locked token
     pair_setup
         .b_mock
-        .execute_tx(&locking_owner, &locking_sc_wrapper, &rust_zero, |sc| {
-            sc.init();
-            sc.locked_token()
-                .set_token_id(managed_token_id!(LOCKED_TOKEN_ID));
-            sc.add_lp_to_whitelist(
-                managed_address!(&lp_address),
-                managed_token_id!(WEGLD_TOKEN_ID),
-                managed_token_id!(MEX_TOKEN_ID),
-            );
-        })
+        .execute_tx(
+            &pair_setup.owner_address,
+            &pair_setup.pair_wrapper,
+            &rust_biguint!(0),
+            |sc| {
+                assert_eq!(sc.get_amp_factor(), 150);
+
+                sc.stop_ramp_amp_factor();
+            },
+        )
         .assert_ok();
 
-    pair_setup.b_mock.set_esdt_local_roles(
-        locking_sc_wrapper.address_ref(),
-        LOCKED_TOKEN_ID,
-        &[
-            EsdtLocalRole::NftCreate,
-            EsdtLocalRole::NftAddQuantity,
-            EsdtLocalRole::NftBurn,
-        ],
-    );
+    pair_setup.b_mock.set_block_timestamp(3 * 86_400);
 
-    // setup lp proxy token
     pair_setup
         .b_mock
-        .execute_tx(&locking_owner, &locking_sc_wrapper, &rust_zero, |sc| {
-            sc.init();
-            sc.lp_proxy_token()
-                .set_token_id(managed_token_id!(LP_PROXY_TOKEN_ID));
+        .execute_query(&pair_setup.pair_wrapper, |sc| {
+            assert_eq!(sc.get_amp_factor(), 150);
         })
         .assert_ok();
+}
 
-    pair_setup.b_mock.set_esdt_local_roles(
-        locking_sc_wrapper.address_ref(),
-        LP_PROXY_TOKEN_ID,
-        &[
-            EsdtLocalRole::NftCreate,
-            EsdtLocalRole::NftAddQuantity,
-            EsdtLocalRole::NftBurn,
-        ],
-    );
+mod flash_swap_receiver_mock {
+    multiversx_sc::imports!();
+
+    #[multiversx_sc::contract]
+    pub trait FlashSwapReceiverMock {
+        #[init]
+        fn init(&self) {}
+
+        #[payable("*")]
+        #[endpoint(repayFlashSwap)]
+        fn repay_flash_swap(&self, token_id: TokenIdentifier, amount: BigUint) {
+            let caller = self.blockchain().get_caller();
+            self.send().direct_esdt(&caller, &token_id, 0, &amount);
+        }
+    }
+}
 
-    pair_setup.b_mock.set_block_epoch(5);
-    DebugApi::dummy();
+#[test]
+fn flash_swap_test() {
+    use flash_swap_receiver_mock::FlashSwapReceiverMock;
 
-    // lock some tokens first
+    let mut pair_setup = PairSetup::new(pair::contract_obj);
+    pair_setup.add_liquidity(
+        1_001_000, 1_000_000, 1_001_000, 1_000_000, 1_000_000, 1_001_000, 1_001_000,
+    );
+
+    let receiver_wrapper = pair_setup.b_mock.create_sc_account(
+        &rust_biguint!(0),
+        Some(&pair_setup.owner_address),
+        flash_swap_receiver_mock::contract_obj,
+        "flash swap receiver mock",
+    );
     pair_setup
         .b_mock
-        .execute_esdt_transfer(
-            &pair_setup.user_address,
-            &locking_sc_wrapper,
-            WEGLD_TOKEN_ID,
-            0,
-            &rust_biguint!(1_000_000),
+        .execute_tx(
+            &pair_setup.owner_address,
+            &receiver_wrapper,
+            &rust_biguint!(0),
             |sc| {
-                sc.lock_tokens_endpoint(10, OptionalValue::None);
+                sc.init();
             },
         )
         .assert_ok();
-    pair_setup.b_mock.check_nft_balance(
-        &pair_setup.user_address,
-        LOCKED_TOKEN_ID,
-        1,
-        &rust_biguint!(1_000_000),
-        Some(&LockedTokenAttributes::<DebugApi> {
-            original_token_id: managed_token_id_wrapped!(WEGLD_TOKEN_ID),
-            original_token_nonce: 0,
-            unlock_epoch: 10,
-        }),
+    pair_setup.b_mock.set_esdt_balance(
+        receiver_wrapper.address_ref(),
+        MEX_TOKEN_ID,
+        &rust_biguint!(31),
     );
 
+    // only contracts can do flash swaps
     pair_setup
         .b_mock
-        .execute_esdt_transfer(
+        .execute_tx(
             &pair_setup.user_address,
-            &locking_sc_wrapper,
-            MEX_TOKEN_ID,
-            0,
-            &rust_biguint!(2_000_000),
+            &pair_setup.pair_wrapper,
+            &rust_biguint!(0),
             |sc| {
-                sc.lock_tokens_endpoint(15, OptionalValue::None);
+                sc.flash_swap(
+                    managed_token_id_wrapped!(MEX_TOKEN_ID),
+                    managed_biguint!(10_000),
+                    managed_buffer!(b"repayFlashSwap"),
+                    MultiValueEncoded::new(),
+                );
             },
         )
-        .assert_ok();
-    pair_setup.b_mock.check_nft_balance(
-        &pair_setup.user_address,
-        LOCKED_TOKEN_ID,
-        2,
-        &rust_biguint!(2_000_000),
-        Some(&LockedTokenAttributes::<DebugApi> {
-            original_token_id: managed_token_id_wrapped!(MEX_TOKEN_ID),
-            original_token_nonce: 0,
-            unlock_epoch: 15,
-        }),
-    );
-
-    pair_setup.b_mock.set_block_epoch(5);
-
-    // add liquidity through simple-lock SC - one locked (WEGLD) token, one unlocked (MEX)
-    let transfers = vec![
-        TxTokenTransfer {
-            token_identifier: LOCKED_TOKEN_ID.to_vec(),
-            nonce: 1,
-            value: rust_biguint!(500_000),
-        },
-        TxTokenTransfer {
-            token_identifier: MEX_TOKEN_ID.to_vec(),
-            nonce: 0,
-            value: rust_biguint!(500_000),
-        },
-    ];
+        .assert_user_error("Flash swaps can only be done by contracts");
 
+    // paying back only the borrowed amount fails
     pair_setup
         .b_mock
-        .execute_esdt_multi_transfer(
-            &pair_setup.user_address,
-            &locking_sc_wrapper,
-            &transfers[..],
+        .execute_tx(
+            receiver_wrapper.address_ref(),
+            &pair_setup.pair_wrapper,
+            &rust_biguint!(0),
             |sc| {
-                let (dust_first_token, dust_second_token, lp_proxy_payment) = sc
-                    .add_liquidity_locked_token(managed_biguint!(1), managed_biguint!(1))
-                    .into_tuple();
-
-                assert_eq!(
-                    dust_first_token.token_identifier,
-                    managed_token_id!(WEGLD_TOKEN_ID)
-                );
-                assert_eq!(dust_first_token.token_nonce, 0);
-                assert_eq!(dust_first_token.amount, managed_biguint!(0));
-
-                assert_eq!(
-                    dust_second_token.token_identifier,
-                    managed_token_id!(MEX_TOKEN_ID)
-                );
-                assert_eq!(dust_second_token.token_nonce, 0);
-                assert_eq!(dust_second_token.amount, managed_biguint!(0));
-
-                assert_eq!(
-                    lp_proxy_payment.token_identifier,
-                    managed_token_id!(LP_PROXY_TOKEN_ID)
+                let mut args = MultiValueEncoded::new();
+                args.push(managed_buffer!(MEX_TOKEN_ID));
+                args.push(managed_biguint!(10_000).to_bytes_be_buffer());
+                sc.flash_swap(
+                    managed_token_id_wrapped!(MEX_TOKEN_ID),
+                    managed_biguint!(10_000),
+                    managed_buffer!(b"repayFlashSwap"),
+                    args,
                 );
-                assert_eq!(lp_proxy_payment.token_nonce, 1);
-                assert_eq!(lp_proxy_payment.amount, managed_biguint!(500_000));
             },
         )
-        .assert_ok();
-    pair_setup.b_mock.check_nft_balance(
-        &pair_setup.user_address,
-        LP_PROXY_TOKEN_ID,
-        1,
-        &rust_biguint!(500_000),
-        Some(&LpProxyTokenAttributes::<DebugApi> {
-            lp_token_id: managed_token_id!(LP_TOKEN_ID),
-            first_token_id: managed_token_id!(WEGLD_TOKEN_ID),
-            first_token_locked_nonce: 1,
-            second_token_id: managed_token_id!(MEX_TOKEN_ID),
-            second_token_locked_nonce: 0,
-        }),
-    );
-    pair_setup.b_mock.check_esdt_balance(
-        locking_sc_wrapper.address_ref(),
-        LP_TOKEN_ID,
-        &rust_biguint!(500_000),
-    );
-
-    let user_locked_token_balance_before =
-        pair_setup
-            .b_mock
-            .get_esdt_balance(&pair_setup.user_address, LOCKED_TOKEN_ID, 1);
-    let user_mex_balance_before =
-        pair_setup
-            .b_mock
-            .get_esdt_balance(&pair_setup.user_address, MEX_TOKEN_ID, 0);
+        .assert_user_error("K invariant failed");
 
-    // remove liquidity
+    // paying back the borrowed amount plus the fee
     pair_setup
         .b_mock
-        .execute_esdt_transfer(
-            &pair_setup.user_address,
-            &locking_sc_wrapper,
-            LP_PROXY_TOKEN_ID,
-            1,
-            &rust_biguint!(500_000),
-            |sc| {
-                let (first_payment_result, second_payment_result) = sc
-                    .remove_liquidity_locked_token(managed_biguint!(1), managed_biguint!(1))
-                    .into_tuple();
-
-                assert_eq!(
-                    first_payment_result.token_identifier,
-                    managed_token_id!(LOCKED_TOKEN_ID)
-                );
-                assert_eq!(first_payment_result.token_nonce, 1);
-                assert_eq!(first_payment_result.amount, managed_biguint!(500_000));
-
-                assert_eq!(
-                    second_payment_result.token_identifier,
-                    managed_token_id!(MEX_TOKEN_ID)
+        .execute_tx(
+            receiver_wrapper.address_ref(),
+            &pair_setup.pair_wrapper,
+            &rust_biguint!(0),
+            |sc| {
+                let mut args = MultiValueEncoded::new();
+                args.push(managed_buffer!(MEX_TOKEN_ID));
+                args.push(managed_biguint!(10_031).to_bytes_be_buffer());
+                sc.flash_swap(
+                    managed_token_id_wrapped!(MEX_TOKEN_ID),
+                    managed_biguint!(10_000),
+                    managed_buffer!(b"repayFlashSwap"),
+                    args,
                 );
-                assert_eq!(second_payment_result.token_nonce, 0);
-                assert_eq!(second_payment_result.amount, managed_biguint!(500_000));
             },
         )
         .assert_ok();
 
-    pair_setup.b_mock.check_nft_balance(
-        &pair_setup.user_address,
-        LOCKED_TOKEN_ID,
-        1,
-        &(user_locked_token_balance_before + 500_000u32),
-        Some(&LockedTokenAttributes::<DebugApi> {
-            original_token_id: managed_token_id_wrapped!(WEGLD_TOKEN_ID),
-            original_token_nonce: 0,
-            unlock_epoch: 10,
-        }),
-    );
     pair_setup.b_mock.check_esdt_balance(
-        &pair_setup.user_address,
+        receiver_wrapper.address_ref(),
         MEX_TOKEN_ID,
-        &(user_mex_balance_before + 500_000u32),
+        &rust_biguint!(0),
     );
+    pair_setup
+        .b_mock
+        .execute_query(&pair_setup.pair_wrapper, |sc| {
+            let (first_token_reserve, second_token_reserve, _) =
+                sc.get_reserves_and_total_supply().into_tuple();
+            assert_eq!(first_token_reserve, managed_biguint!(1_001_000));
+            assert_eq!(second_token_reserve, managed_biguint!(1_001_031));
+        })
+        .assert_ok();
 
-    // Add liquidity - same token pair as before -> same nonce (1)
+    // flash swaps are charged the same fee as regular swaps
+    pair_setup.change_fee_percents(1, 1_000, 50);
     pair_setup
         .b_mock
-        .execute_esdt_multi_transfer(
-            &pair_setup.user_address,
-            &locking_sc_wrapper,
-            &transfers[..],
+        .execute_tx(
+            &pair_setup.owner_address,
+            &pair_setup.pair_wrapper,
+            &rust_biguint!(0),
             |sc| {
-                let (_, _, lp_proxy_payment) = sc
-                    .add_liquidity_locked_token(managed_biguint!(1), managed_biguint!(1))
-                    .into_tuple();
-
-                assert_eq!(
-                    lp_proxy_payment.token_identifier,
-                    managed_token_id!(LP_PROXY_TOKEN_ID)
-                );
-                assert_eq!(lp_proxy_payment.token_nonce, 1);
-                assert_eq!(lp_proxy_payment.amount, managed_biguint!(500_000));
+                sc.set_dynamic_fee_config(1_000, 1_000, 10, MAX_PERCENTAGE);
             },
         )
         .assert_ok();
-
-    // test auto-unlock for tokens on remove liquidity
-    pair_setup.b_mock.set_block_epoch(30);
+    pair_setup.b_mock.set_esdt_balance(
+        receiver_wrapper.address_ref(),
+        MEX_TOKEN_ID,
+        &rust_biguint!(102),
+    );
 
     pair_setup
         .b_mock
-        .execute_esdt_transfer(
-            &pair_setup.user_address,
-            &locking_sc_wrapper,
-            LP_PROXY_TOKEN_ID,
-            1,
-            &rust_biguint!(500_000),
+        .execute_tx(
+            receiver_wrapper.address_ref(),
+            &pair_setup.pair_wrapper,
+            &rust_biguint!(0),
             |sc| {
-                let (first_payment_result, second_payment_result) = sc
-                    .remove_liquidity_locked_token(managed_biguint!(1), managed_biguint!(1))
-                    .into_tuple();
-
-                assert_eq!(
-                    first_payment_result.token_identifier,
-                    managed_token_id!(WEGLD_TOKEN_ID)
+                let mut args = MultiValueEncoded::new();
+                args.push(managed_buffer!(MEX_TOKEN_ID));
+                args.push(managed_biguint!(10_031).to_bytes_be_buffer());
+                sc.flash_swap(
+                    managed_token_id_wrapped!(MEX_TOKEN_ID),
+                    managed_biguint!(10_000),
+                    managed_buffer!(b"repayFlashSwap"),
+                    args,
                 );
-                assert_eq!(first_payment_result.token_nonce, 0);
-                assert_eq!(first_payment_result.amount, managed_biguint!(500_000));
+            },
+        )
+        .assert_user_error("K invariant failed");
 
-                assert_eq!(
-                    second_payment_result.token_identifier,
-                    managed_token_id!(MEX_TOKEN_ID)
+    pair_setup
+        .b_mock
+        .execute_tx(
+            receiver_wrapper.address_ref(),
+            &pair_setup.pair_wrapper,
+            &rust_biguint!(0),
+            |sc| {
+                let mut args = MultiValueEncoded::new();
+                args.push(managed_buffer!(MEX_TOKEN_ID));
+                args.push(managed_biguint!(10_102).to_bytes_be_buffer());
+                sc.flash_swap(
+                    managed_token_id_wrapped!(MEX_TOKEN_ID),
+                    managed_biguint!(10_000),
+                    managed_buffer!(b"repayFlashSwap"),
+                    args,
                 );
-                assert_eq!(second_payment_result.token_nonce, 0);
-                assert_eq!(second_payment_result.amount, managed_biguint!(500_000));
             },
         )
         .assert_ok();
 
     pair_setup
         .b_mock
-        .execute_query(&locking_sc_wrapper, |sc| {
-            assert_eq!(sc.known_liquidity_pools().len(), 1);
-            assert!(sc
-                .known_liquidity_pools()
-                .contains(&managed_address!(&lp_address)));
+        .execute_query(&pair_setup.pair_wrapper, |sc| {
+            let (_, second_token_reserve, _) = sc.get_reserves_and_total_supply().into_tuple();
+            assert_eq!(second_token_reserve, managed_biguint!(1_001_133));
         })
         .assert_ok();
 }
 
 #[test]
-fn fees_collector_pair_test() {
+fn egld_pair_test() {
+    use pair::pair_actions::egld_deposit::EgldDepositModule;
+
     let mut pair_setup = PairSetup::new(pair::contract_obj);
-    let fees_collector_wrapper = pair_setup.b_mock.create_sc_account(
+    let owner_address = pair_setup.owner_address.clone();
+    let user_address = pair_setup.user_address.clone();
+    let fee_address = pair_setup.b_mock.create_user_account(&rust_biguint!(0));
+
+    // a pair without an EGLD side takes no deposits
+    pair_setup
+        .b_mock
+        .execute_tx(
+            &user_address,
+            &pair_setup.pair_wrapper,
+            &rust_biguint!(1_000),
+            |sc| {
+                sc.deposit_egld();
+            },
+        )
+        .assert_user_error("The pair has no EGLD side");
+
+    let egld_pair_wrapper = pair_setup.b_mock.create_sc_account(
         &rust_biguint!(0),
-        None,
-        fees_collector::contract_obj,
-        "fees collector path",
+        Some(&owner_address),
+        pair::contract_obj,
+        "egld pair path",
     );
-
-    let pair_addr = pair_setup.pair_wrapper.address_ref().clone();
-    let energy_factory_mock_addr = pair_setup.pair_wrapper.address_ref().clone();
     pair_setup
         .b_mock
         .execute_tx(
-            &pair_setup.owner_address,
-            &fees_collector_wrapper,
+            &owner_address,
+            &egld_pair_wrapper,
             &rust_biguint!(0),
             |sc| {
                 sc.init(
-                    managed_token_id!(LOCKED_TOKEN_ID),
-                    managed_address!(&energy_factory_mock_addr),
+                    EgldOrEsdtTokenIdentifier::egld(),
+                    managed_token_id_wrapped!(MEX_TOKEN_ID),
+                    managed_address!(&owner_address),
+                    managed_address!(&owner_address),
+                    300,
+                    50,
+                    ManagedAddress::<DebugApi>::zero(),
+                    MultiValueEncoded::<DebugApi, ManagedAddress<DebugApi>>::new(),
                 );
-                let _ = sc.known_contracts().insert(managed_address!(&pair_addr));
+                sc.lp_token_identifier()
+                    .set(&managed_token_id!(LP_TOKEN_ID));
+                sc.state().set(State::Active);
+            },
+        )
+        .assert_ok();
+    pair_setup.b_mock.set_esdt_local_roles(
+        egld_pair_wrapper.address_ref(),
+        LP_TOKEN_ID,
+        &[EsdtLocalRole::Mint, EsdtLocalRole::Burn],
+    );
 
-                let mut tokens = MultiValueEncoded::new();
-                tokens.push(managed_token_id!(WEGLD_TOKEN_ID));
-                tokens.push(managed_token_id!(MEX_TOKEN_ID));
+    // the EGLD is deposited first, then the ESDT is transferred with addLiquidity
+    pair_setup
+        .b_mock
+        .execute_tx(
+            &user_address,
+            &egld_pair_wrapper,
+            &rust_biguint!(1_001_000),
+            |sc| {
+                sc.deposit_egld();
+            },
+        )
+        .assert_ok();
+    pair_setup
+        .b_mock
+        .execute_esdt_transfer(
+            &user_address,
+            &egld_pair_wrapper,
+            MEX_TOKEN_ID,
+            0,
+            &rust_biguint!(1_001_000),
+            |sc| {
+                let (lp_payment, egld_payment, mex_payment) = sc
+                    .add_liquidity(
+                        managed_biguint!(1_000_000),
+                        managed_biguint!(1_000_000),
+                        OptionalValue::None,
+                    )
+                    .into_tuple();
+                assert_eq!(lp_payment.amount, managed_biguint!(1_000_000));
+                assert!(egld_payment.token_identifier.is_egld());
+                assert_eq!(egld_payment.amount, managed_biguint!(1_001_000));
+                assert_eq!(mex_payment.amount, managed_biguint!(1_001_000));
+            },
+        )
+        .assert_ok();
 
-                sc.add_known_tokens(tokens);
+    // the unused part of the deposit is sent back
+    pair_setup
+        .b_mock
+        .execute_tx(
+            &user_address,
+            &egld_pair_wrapper,
+            &rust_biguint!(2_000),
+            |sc| {
+                sc.deposit_egld();
+            },
+        )
+        .assert_ok();
+    pair_setup
+        .b_mock
+        .execute_esdt_transfer(
+            &user_address,
+            &egld_pair_wrapper,
+            MEX_TOKEN_ID,
+            0,
+            &rust_biguint!(1_000),
+            |sc| {
+                let (lp_payment, egld_payment, mex_payment) = sc
+                    .add_liquidity(
+                        managed_biguint!(1),
+                        managed_biguint!(1),
+                        OptionalValue::None,
+                    )
+                    .into_tuple();
+                assert_eq!(lp_payment.amount, managed_biguint!(1_000));
+                assert_eq!(egld_payment.amount, managed_biguint!(1_000));
+                assert_eq!(mex_payment.amount, managed_biguint!(1_000));
+
+                assert_eq!(
+                    sc.egld_deposit(&managed_address!(&user_address)).get(),
+                    managed_biguint!(0)
+                );
+                assert_eq!(sc.egld_deposits_total().get(), managed_biguint!(0));
             },
         )
         .assert_ok();
+    pair_setup
+        .b_mock
+        .check_egld_balance(&user_address, &rust_biguint!(98_998_000));
+    pair_setup
+        .b_mock
+        .check_egld_balance(egld_pair_wrapper.address_ref(), &rust_biguint!(1_002_000));
 
+    // the special fee paid in EGLD is swapped to MEX before being burned
+    pair_setup.b_mock.set_esdt_local_roles(
+        egld_pair_wrapper.address_ref(),
+        MEX_TOKEN_ID,
+        &[EsdtLocalRole::Burn],
+    );
     pair_setup
         .b_mock
         .execute_tx(
-            &pair_setup.owner_address,
-            &pair_setup.pair_wrapper,
+            &owner_address,
+            &egld_pair_wrapper,
             &rust_biguint!(0),
             |sc| {
-                sc.setup_fees_collector(
-                    managed_address!(fees_collector_wrapper.address_ref()),
-                    MAX_PERCENTAGE / 2,
+                sc.set_fee_on(
+                    true,
+                    managed_address!(&fee_address),
+                    managed_token_id!(MEX_TOKEN_ID),
                 );
             },
         )
         .assert_ok();
-
-    pair_setup.add_liquidity(
-        1_001_000, 1_000_000, 1_001_000, 1_000_000, 1_000_000, 1_001_000, 1_001_000,
+    pair_setup
+        .b_mock
+        .execute_tx(
+            &user_address,
+            &egld_pair_wrapper,
+            &rust_biguint!(10_000),
+            |sc| {
+                let payment_out = sc.swap_tokens_fixed_input(
+                    managed_token_id_wrapped!(MEX_TOKEN_ID),
+                    managed_biguint!(9_871),
+                    OptionalValue::None,
+                );
+                assert_eq!(payment_out.amount, managed_biguint!(9_871));
+            },
+        )
+        .assert_ok();
+    pair_setup
+        .b_mock
+        .check_egld_balance(&user_address, &rust_biguint!(98_988_000));
+    pair_setup.b_mock.check_esdt_balance(
+        egld_pair_wrapper.address_ref(),
+        MEX_TOKEN_ID,
+        &rust_biguint!(992_125),
     );
+    pair_setup
+        .b_mock
+        .execute_query(&egld_pair_wrapper, |sc| {
+            let (egld_reserve, mex_reserve, _) = sc.get_reserves_and_total_supply().into_tuple();
+            assert_eq!(egld_reserve, managed_biguint!(1_012_000));
+            assert_eq!(mex_reserve, managed_biguint!(992_125));
+        })
+        .assert_ok();
 
-    pair_setup.swap_fixed_input(WEGLD_TOKEN_ID, 100_000, MEX_TOKEN_ID, 900, 90_669);
-
+    pair_setup
+        .b_mock
+        .execute_esdt_transfer(
+            &user_address,
+            &egld_pair_wrapper,
+            MEX_TOKEN_ID,
+            0,
+            &rust_biguint!(5_000),
+            |sc| {
+                let (payment_out, leftover) = sc
+                    .swap_tokens_fixed_output(
+                        EgldOrEsdtTokenIdentifier::egld(),
+                        managed_biguint!(5_000),
+                        OptionalValue::None,
+                    )
+                    .into_tuple();
+                assert!(payment_out.token_identifier.is_egld());
+                assert_eq!(payment_out.amount, managed_biguint!(5_000));
+                assert_eq!(leftover.amount, managed_biguint!(59));
+            },
+        )
+        .assert_ok();
+    pair_setup
+        .b_mock
+        .check_egld_balance(&user_address, &rust_biguint!(98_993_000));
     pair_setup.b_mock.check_esdt_balance(
-        fees_collector_wrapper.address_ref(),
-        WEGLD_TOKEN_ID,
-        &rust_biguint!(25),
+        egld_pair_wrapper.address_ref(),
+        MEX_TOKEN_ID,
+        &rust_biguint!(997_064),
     );
 
     pair_setup
         .b_mock
-        .execute_query(&fees_collector_wrapper, |sc| {
-            assert_eq!(
-                sc.accumulated_fees(1, &managed_token_id!(WEGLD_TOKEN_ID))
-                    .get(),
-                managed_biguint!(25)
+        .execute_esdt_transfer(
+            &user_address,
+            &egld_pair_wrapper,
+            LP_TOKEN_ID,
+            0,
+            &rust_biguint!(1_000),
+            |sc| {
+                let (egld_payment, mex_payment) = sc
+                    .remove_liquidity(
+                        managed_biguint!(1_004),
+                        managed_biguint!(995),
+                        OptionalValue::None,
+                    )
+                    .into_tuple();
+                assert_eq!(egld_payment.amount, managed_biguint!(1_004));
+                assert_eq!(mex_payment.amount, managed_biguint!(995));
+            },
+        )
+        .assert_ok();
+    pair_setup
+        .b_mock
+        .check_egld_balance(&user_address, &rust_biguint!(98_994_004));
+
+    // single token liquidity in and out of the EGLD side, the EGLD leftover is sent back
+    let mut lp_amount = 0u64;
+    pair_setup
+        .b_mock
+        .execute_tx(
+            &user_address,
+            &egld_pair_wrapper,
+            &rust_biguint!(10_000),
+            |sc| {
+                let (lp_payment, egld_payment, _) = sc
+                    .add_liquidity_single_token(managed_biguint!(1), OptionalValue::None)
+                    .into_tuple();
+                assert!(egld_payment.token_identifier.is_egld());
+                lp_amount = lp_payment.amount.to_u64().unwrap();
+            },
+        )
+        .assert_ok();
+    assert!(lp_amount > 0);
+    pair_setup
+        .b_mock
+        .execute_query(&egld_pair_wrapper, |sc| {
+            let egld_balance = sc
+                .blockchain()
+                .get_sc_balance(&EgldOrEsdtTokenIdentifier::egld(), 0);
+            let (egld_reserve, _, _) = sc.get_reserves_and_total_supply().into_tuple();
+            assert_eq!(egld_balance, egld_reserve);
+        })
+        .assert_ok();
+
+    let user_egld_balance = pair_setup.b_mock.get_egld_balance(&user_address);
+    let mut egld_out = 0u64;
+    pair_setup
+        .b_mock
+        .execute_esdt_transfer(
+            &user_address,
+            &egld_pair_wrapper,
+            LP_TOKEN_ID,
+            0,
+            &rust_biguint!(lp_amount),
+            |sc| {
+                let payment_out = sc.remove_liquidity_to_single_token(
+                    EgldOrEsdtTokenIdentifier::egld(),
+                    managed_biguint!(1),
+                    OptionalValue::None,
+                );
+                assert!(payment_out.token_identifier.is_egld());
+                egld_out = payment_out.amount.to_u64().unwrap();
+            },
+        )
+        .assert_ok();
+    pair_setup
+        .b_mock
+        .check_egld_balance(&user_address, &(user_egld_balance + egld_out));
+
+    // the EGLD could not be paid back without changing the reserves
+    pair_setup
+        .b_mock
+        .execute_tx(&user_address, &egld_pair_wrapper, &rust_biguint!(0), |sc| {
+            sc.flash_swap(
+                EgldOrEsdtTokenIdentifier::egld(),
+                managed_biguint!(1_000),
+                managed_buffer!(b"repayFlashSwap"),
+                MultiValueEncoded::new(),
             );
         })
+        .assert_user_error("Flash swaps are not available on EGLD pairs");
+
+    // an unused deposit can be withdrawn
+    let user_egld_balance = pair_setup.b_mock.get_egld_balance(&user_address);
+    pair_setup
+        .b_mock
+        .execute_tx(
+            &user_address,
+            &egld_pair_wrapper,
+            &rust_biguint!(500),
+            |sc| {
+                sc.deposit_egld();
+            },
+        )
+        .assert_ok();
+    pair_setup
+        .b_mock
+        .execute_tx(&user_address, &egld_pair_wrapper, &rust_biguint!(0), |sc| {
+            assert_eq!(sc.egld_deposits_total().get(), managed_biguint!(500));
+            assert_eq!(sc.withdraw_egld_deposit(), managed_biguint!(500));
+            assert_eq!(sc.egld_deposits_total().get(), managed_biguint!(0));
+        })
         .assert_ok();
+    pair_setup
+        .b_mock
+        .check_egld_balance(&user_address, &user_egld_balance);
 }
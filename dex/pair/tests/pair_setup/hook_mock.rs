@@ -0,0 +1,50 @@
+use multiversx_sc::{
+    contract_base::{CallableContract, ContractBase, ErrorHelper},
+    io::finish_multi,
+    storage::{storage_get, storage_set, StorageKey},
+};
+use multiversx_sc_scenario::DebugApi;
+
+static BEFORE_SWAP_FN_NAME: &str = "beforeSwap";
+pub static CALLS_COUNT_STORAGE_KEY: &[u8] = b"calls_count";
+static HOOK_REJECTED_ERROR: &[u8] = b"Rejected by hook";
+
+/// A `beforeSwap` hook which counts its calls, and can override the fee or reject the swap.
+#[derive(Clone)]
+pub struct HookMock {
+    fee_percent: Option<u64>,
+    reject: bool,
+}
+
+impl ContractBase for HookMock {
+    type Api = DebugApi;
+}
+
+impl CallableContract for HookMock {
+    fn call(&self, fn_name: &str) -> bool {
+        if fn_name != BEFORE_SWAP_FN_NAME {
+            return false;
+        }
+
+        let calls_count_key = StorageKey::<DebugApi>::new(CALLS_COUNT_STORAGE_KEY);
+        let calls_count: u64 = storage_get((&calls_count_key).into());
+        storage_set((&calls_count_key).into(), &(calls_count + 1));
+        if self.reject {
+            ErrorHelper::<DebugApi>::signal_error_with_message(HOOK_REJECTED_ERROR);
+        }
+        if let Some(fee_percent) = self.fee_percent {
+            finish_multi::<DebugApi, _>(&fee_percent);
+        }
+
+        true
+    }
+}
+
+impl HookMock {
+    pub fn new(fee_percent: Option<u64>, reject: bool) -> Self {
+        HookMock {
+            fee_percent,
+            reject,
+        }
+    }
+}
@@ -1,12 +1,15 @@
-use multiversx_sc::codec::multi_types::MultiValue3;
+use multiversx_sc::codec::multi_types::{MultiValue3, OptionalValue};
 use multiversx_sc::types::{
-    Address, EsdtLocalRole, EsdtTokenPayment, ManagedAddress, MultiValueEncoded,
+    Address, EgldOrEsdtTokenPayment, EsdtLocalRole, ManagedAddress, MultiValueEncoded,
 };
 use multiversx_sc_scenario::whitebox_legacy::TxTokenTransfer;
 use multiversx_sc_scenario::{
-    managed_address, managed_biguint, managed_token_id, rust_biguint, whitebox_legacy::*, DebugApi,
+    managed_address, managed_biguint, managed_token_id, managed_token_id_wrapped, rust_biguint,
+    whitebox_legacy::*, DebugApi,
 };
 
+pub mod hook_mock;
+
 pub const PAIR_WASM_PATH: &str = "pair/output/pair.wasm";
 pub const MEX_TOKEN_ID: &[u8] = b"MEX-abcdef";
 pub const OTHER_TOKEN_ID: &[u8] = b"OTHER-abcdef";
@@ -19,7 +22,7 @@ pub const LP_PROXY_TOKEN_ID: &[u8] = b"LPPROXY-abcdef";
 pub const USER_TOTAL_MEX_TOKENS: u64 = 5_000_000_000;
 pub const USER_TOTAL_WEGLD_TOKENS: u64 = 5_000_000_000;
 
-use pair::config::ConfigModule as PairConfigModule;
+use pair::config::{ConfigModule as PairConfigModule, MIN_FEE_CHANGE_DELAY_BLOCKS};
 use pair::pair_actions::add_liq::AddLiquidityModule;
 use pair::pair_actions::swap::SwapModule;
 use pair::safe_price_view::*;
@@ -54,8 +57,8 @@ where
 
         b_mock
             .execute_tx(&owner_addr, &pair_wrapper, &rust_zero, |sc| {
-                let first_token_id = managed_token_id!(WEGLD_TOKEN_ID);
-                let second_token_id = managed_token_id!(MEX_TOKEN_ID);
+                let first_token_id = managed_token_id_wrapped!(WEGLD_TOKEN_ID);
+                let second_token_id = managed_token_id_wrapped!(MEX_TOKEN_ID);
                 let router_address = managed_address!(&owner_addr);
                 let router_owner_address = managed_address!(&owner_addr);
                 let total_fee_percent = 300u64;
@@ -81,8 +84,8 @@ where
 
         b_mock
             .execute_tx(&owner_addr, &second_pair_wrapper, &rust_zero, |sc| {
-                let first_token_id = managed_token_id!(WEGLD_TOKEN_ID);
-                let second_token_id = managed_token_id!(OTHER_TOKEN_ID);
+                let first_token_id = managed_token_id_wrapped!(WEGLD_TOKEN_ID);
+                let second_token_id = managed_token_id_wrapped!(OTHER_TOKEN_ID);
                 let router_address = managed_address!(&owner_addr);
                 let router_owner_address = managed_address!(&owner_addr);
                 let total_fee_percent = 300u64;
@@ -159,6 +162,7 @@ where
                 let MultiValue3 { 0: payments } = sc.add_liquidity(
                     managed_biguint!(first_token_min),
                     managed_biguint!(second_token_min),
+                    OptionalValue::None,
                 );
 
                 assert_eq!(payments.0.token_identifier, managed_token_id!(LP_TOKEN_ID));
@@ -179,6 +183,39 @@ where
             .assert_ok();
     }
 
+    /// Announces the fee percents at `block_nonce` and applies them once effective.
+    pub fn change_fee_percents(
+        &mut self,
+        block_nonce: u64,
+        total_fee_percent: u64,
+        special_fee_percent: u64,
+    ) {
+        self.b_mock.set_block_nonce(block_nonce);
+        self.b_mock
+            .execute_tx(
+                &self.owner_address,
+                &self.pair_wrapper,
+                &rust_biguint!(0),
+                |sc| {
+                    sc.announce_fee_percents(total_fee_percent, special_fee_percent);
+                },
+            )
+            .assert_ok();
+
+        self.b_mock
+            .set_block_nonce(block_nonce + MIN_FEE_CHANGE_DELAY_BLOCKS);
+        self.b_mock
+            .execute_tx(
+                &self.owner_address,
+                &self.pair_wrapper,
+                &rust_biguint!(0),
+                |sc| {
+                    sc.apply_fee_percents();
+                },
+            )
+            .assert_ok();
+    }
+
     pub fn swap_fixed_input(
         &mut self,
         payment_token_id: &[u8],
@@ -196,11 +233,15 @@ where
                 &rust_biguint!(payment_amount),
                 |sc| {
                     let ret = sc.swap_tokens_fixed_input(
-                        managed_token_id!(desired_token_id),
+                        managed_token_id_wrapped!(desired_token_id),
                         managed_biguint!(desired_amount_min),
+                        OptionalValue::None,
                     );
 
-                    assert_eq!(ret.token_identifier, managed_token_id!(desired_token_id));
+                    assert_eq!(
+                        ret.token_identifier,
+                        managed_token_id_wrapped!(desired_token_id)
+                    );
                     assert_eq!(ret.token_nonce, 0);
                     assert_eq!(ret.amount, managed_biguint!(expected_amount));
                 },
@@ -235,8 +276,9 @@ where
                 &rust_biguint!(payment_amount_max),
                 |sc| {
                     let ret = sc.swap_tokens_fixed_output(
-                        managed_token_id!(desired_token_id),
+                        managed_token_id_wrapped!(desired_token_id),
                         managed_biguint!(desired_amount),
+                        OptionalValue::None,
                     );
 
                     let (desired_token_output, payment_token_residuum) = ret.into_tuple();
@@ -336,8 +378,8 @@ where
         expected_token_amount: u64,
     ) {
         let _ = self.b_mock.execute_query(&self.pair_wrapper, |sc| {
-            let input_payment = EsdtTokenPayment::new(
-                managed_token_id!(payment_token_id),
+            let input_payment = EgldOrEsdtTokenPayment::new(
+                managed_token_id_wrapped!(payment_token_id),
                 0,
                 managed_biguint!(payment_token_amount),
             );
@@ -349,7 +391,7 @@ where
             );
             assert_eq!(
                 expected_payment.token_identifier,
-                managed_token_id!(expected_token_id)
+                managed_token_id_wrapped!(expected_token_id)
             );
             assert_eq!(
                 expected_payment.amount,
@@ -370,8 +412,8 @@ where
         expected_token_amount: u64,
     ) {
         let _ = self.b_mock.execute_query(&self.second_pair_wrapper, |sc| {
-            let input_payment = EsdtTokenPayment::new(
-                managed_token_id!(payment_token_id),
+            let input_payment = EgldOrEsdtTokenPayment::new(
+                managed_token_id_wrapped!(payment_token_id),
                 0,
                 managed_biguint!(payment_token_amount),
             );
@@ -383,7 +425,7 @@ where
             );
             assert_eq!(
                 expected_payment.token_identifier,
-                managed_token_id!(expected_token_id)
+                managed_token_id_wrapped!(expected_token_id)
             );
             assert_eq!(
                 expected_payment.amount,
@@ -400,15 +442,15 @@ where
         expected_token_amount: u64,
     ) {
         let _ = self.b_mock.execute_query(&self.pair_wrapper, |sc| {
-            let input_payment = EsdtTokenPayment::new(
-                managed_token_id!(payment_token_id),
+            let input_payment = EgldOrEsdtTokenPayment::new(
+                managed_token_id_wrapped!(payment_token_id),
                 0,
                 managed_biguint!(payment_token_amount),
             );
             let expected_payment = sc.update_and_get_safe_price(input_payment);
             assert_eq!(
                 expected_payment.token_identifier,
-                managed_token_id!(expected_token_id)
+                managed_token_id_wrapped!(expected_token_id)
             );
             assert_eq!(
                 expected_payment.amount,
@@ -5,9 +5,9 @@
 ////////////////////////////////////////////////////
 
 // Init:                                 1
-// Endpoints:                           64
+// Endpoints:                           144
 // Async Callback (empty):               1
-// Total number of exported functions:  66
+// Total number of exported functions:  146
 
 #![no_std]
 #![allow(internal_features)]
@@ -22,32 +22,85 @@ multiversx_sc_wasm_adapter::endpoints! {
         init => init
         upgrade => upgrade
         setLpTokenIdentifier => set_lp_token_identifier
+        setStableSwapCurve => set_stable_swap_curve
+        rampAmpFactor => ramp_amp_factor
+        stopRampAmpFactor => stop_ramp_amp_factor
+        getAmpFactor => get_amp_factor
+        getAmpFactorRamp => amp_factor_ramp
         getFeeState => is_fee_enabled
         whitelist => whitelist_endpoint
         removeWhitelist => remove_whitelist
         addTrustedSwapPair => add_trusted_swap_pair
         removeTrustedSwapPair => remove_trusted_swap_pair
+        addFeeRoute => add_fee_route
+        removeFeeRoutes => remove_fee_routes
+        getFeeRoutes => get_fee_routes
         setupFeesCollector => setup_fees_collector
+        setBuybackDestination => set_buyback_destination
+        getBuybackDestination => get_buyback_destination
         setFeeOn => set_fee_on
         getFeeDestinations => get_fee_destinations
         getTrustedSwapPairs => get_trusted_swap_pairs
         getWhitelistedManagedAddresses => get_whitelisted_managed_addresses
         getFeesCollectorAddress => fees_collector_address
         getFeesCollectorCutPercentage => fees_collector_cut_percentage
+        setProtocolFee => set_protocol_fee
+        disableProtocolFee => disable_protocol_fee
+        claimProtocolFee => claim_protocol_fee
+        getPendingProtocolFeeLp => get_pending_protocol_fee_lp
+        getProtocolFeeConfig => protocol_fee_config
+        getKLast => k_last
+        getAccruedProtocolFeeLp => accrued_protocol_fee_lp
         setStateActiveNoSwaps => set_state_active_no_swaps
         setFeePercents => set_fee_percent
+        announceFeePercents => announce_fee_percents
+        applyFeePercents => apply_fee_percents
+        cancelFeePercentsChange => cancel_fee_percents_change
+        setFeeChangeDelay => set_fee_change_delay
+        getFeeChangeDelay => get_fee_change_delay
         getLpTokenIdentifier => get_lp_token_identifier
         getTotalFeePercent => total_fee_percent
         getSpecialFee => special_fee_percent
+        getCurveType => curve_type
         getRouterManagedAddress => router_address
         getFirstTokenId => first_token_id
         getSecondTokenId => second_token_id
         getTotalSupply => lp_token_supply
         getInitialLiquidtyAdder => initial_liquidity_adder
         getReserve => pair_reserve
+        getPendingFeeChange => pending_fee_change
+        setPriceObservationsCapacity => set_price_observations_capacity
+        setMinRoundsBetweenObservations => set_min_rounds_between_observations
+        getPriceObservationsCapacity => get_price_observations_capacity
         getSafePriceCurrentIndex => safe_price_current_index
+        getMinRoundsBetweenObservations => min_rounds_between_observations
         updateAndGetTokensForGivenPositionWithSafePrice => update_and_get_tokens_for_given_position_with_safe_price
         updateAndGetSafePrice => update_and_get_safe_price
+        setMaxPriceDeviation => set_max_price_deviation
+        getMaxPriceDeviation => max_price_deviation
+        setDynamicFeeConfig => set_dynamic_fee_config
+        announceDynamicFeeConfig => announce_dynamic_fee_config
+        applyDynamicFeeConfig => apply_dynamic_fee_config
+        cancelDynamicFeeConfigChange => cancel_dynamic_fee_config_change
+        disableDynamicFee => disable_dynamic_fee
+        getSwapFeePercent => get_swap_fee_percent
+        getDynamicFeeConfig => dynamic_fee_config
+        getPendingDynamicFeeConfig => pending_dynamic_fee_config
+        setFeeDiscountTiers => set_fee_discount_tiers
+        getFeeDiscountTiers => fee_discount_tiers
+        setEnergyFactoryAddress => set_energy_factory_address
+        getEnergyFactoryAddress => energy_factory_address
+        getVolumeForWeek => get_volume_for_week
+        getFeesForWeek => get_fees_for_week
+        getCurrentWeek => get_current_week
+        getFirstWeekStartEpoch => first_week_start_epoch
+        addHook => add_hook
+        removeHook => remove_hook
+        getHooks => get_hooks
+        setNoFeeSwapQuota => set_no_fee_swap_quota
+        removeNoFeeSwapQuota => remove_no_fee_swap_quota
+        getRemainingNoFeeSwapQuota => get_remaining_no_fee_swap_quota
+        getNoFeeSwapQuota => no_fee_swap_quota
         setLockingDeadlineEpoch => set_locking_deadline_epoch
         setLockingScAddress => set_locking_sc_address
         setUnlockEpoch => set_unlock_epoch
@@ -63,13 +116,27 @@ multiversx_sc_wasm_adapter::endpoints! {
         pause => pause
         resume => resume
         getState => state
+        depositEgld => deposit_egld
+        withdrawEgldDeposit => withdraw_egld_deposit
+        getEgldDeposit => egld_deposit
+        getEgldDepositsTotal => egld_deposits_total
         addInitialLiquidity => add_initial_liquidity
         addLiquidity => add_liquidity
         removeLiquidity => remove_liquidity
         removeLiquidityAndBuyBackAndBurnToken => remove_liquidity_and_burn_token
         swapNoFeeAndForward => swap_no_fee
+        swapNoFeeAndReturn => swap_no_fee_and_return
         swapTokensFixedInput => swap_tokens_fixed_input
         swapTokensFixedOutput => swap_tokens_fixed_output
+        flashSwap => flash_swap
+        addLiquiditySingleToken => add_liquidity_single_token
+        removeLiquidityToSingleToken => remove_liquidity_to_single_token
+        setMigrationTarget => set_migration_target
+        setMigrationSource => set_migration_source
+        migrateLiquidity => migrate_liquidity
+        removeLiquidityForMigration => remove_liquidity_for_migration
+        getMigrationTarget => migration_target
+        getMigrationSource => migration_source
         getTokensForGivenPosition => get_tokens_for_given_position
         getReservesAndTotalSupply => get_reserves_and_total_supply
         getAmountOut => get_amount_out_view
@@ -83,7 +150,20 @@ multiversx_sc_wasm_adapter::endpoints! {
         getSafePriceByRoundOffset => get_safe_price_by_round_offset
         getSafePriceByTimestampOffset => get_safe_price_by_timestamp_offset
         getSafePrice => get_safe_price
+        getGeometricSafePriceByDefaultOffset => get_geometric_safe_price_by_default_offset
+        getGeometricSafePriceByRoundOffset => get_geometric_safe_price_by_round_offset
+        getGeometricSafePriceByTimestampOffset => get_geometric_safe_price_by_timestamp_offset
+        getGeometricSafePrice => get_geometric_safe_price
         getPriceObservation => get_price_observation_view
+        getSafePrices => get_safe_prices
+        getLpTokensSafePrices => get_lp_tokens_safe_prices
+        getPriceObservationRounds => get_price_observation_rounds
+        getAverageReservesByRoundOffset => get_average_reserves_by_round_offset
+        getAverageReservesByTimestampOffset => get_average_reserves_by_timestamp_offset
+        getAverageReserves => get_average_reserves
+        getAverageLiquidityByRoundOffset => get_average_liquidity_by_round_offset
+        getAverageLiquidityByTimestampOffset => get_average_liquidity_by_timestamp_offset
+        getAverageLiquidity => get_average_liquidity
     )
 }
 
@@ -5,9 +5,9 @@
 ////////////////////////////////////////////////////
 
 // Init:                                 1
-// Endpoints:                            9
+// Endpoints:                           22
 // Async Callback (empty):               1
-// Total number of exported functions:  11
+// Total number of exported functions:  24
 
 #![no_std]
 #![allow(internal_features)]
@@ -29,7 +29,20 @@ multiversx_sc_wasm_adapter::external_view_endpoints! {
         getSafePriceByRoundOffset => get_safe_price_by_round_offset
         getSafePriceByTimestampOffset => get_safe_price_by_timestamp_offset
         getSafePrice => get_safe_price
+        getGeometricSafePriceByDefaultOffset => get_geometric_safe_price_by_default_offset
+        getGeometricSafePriceByRoundOffset => get_geometric_safe_price_by_round_offset
+        getGeometricSafePriceByTimestampOffset => get_geometric_safe_price_by_timestamp_offset
+        getGeometricSafePrice => get_geometric_safe_price
         getPriceObservation => get_price_observation_view
+        getSafePrices => get_safe_prices
+        getLpTokensSafePrices => get_lp_tokens_safe_prices
+        getPriceObservationRounds => get_price_observation_rounds
+        getAverageReservesByRoundOffset => get_average_reserves_by_round_offset
+        getAverageReservesByTimestampOffset => get_average_reserves_by_timestamp_offset
+        getAverageReserves => get_average_reserves
+        getAverageLiquidityByRoundOffset => get_average_liquidity_by_round_offset
+        getAverageLiquidityByTimestampOffset => get_average_liquidity_by_timestamp_offset
+        getAverageLiquidity => get_average_liquidity
     )
 }
 
@@ -0,0 +1,309 @@
+multiversx_sc::imports!();
+multiversx_sc::derive_imports!();
+
+use pausable::State;
+
+use crate::{config, events, factory, multi_pair_swap};
+
+pub const MAX_SWAP_PATH_HOPS: usize = 3;
+pub const MAX_SWAP_PATH_INTERMEDIATE_TOKENS: usize = 5;
+
+static PAIR_STATE_STORAGE_KEY: &[u8] = b"state";
+static PAIR_LP_TOKEN_SUPPLY_STORAGE_KEY: &[u8] = b"lp_token_supply";
+
+#[derive(
+    TypeAbi,
+    TopEncode,
+    TopDecode,
+    NestedEncode,
+    NestedDecode,
+    ManagedVecItem,
+    Clone,
+    PartialEq,
+    Debug,
+)]
+pub struct SwapPathHop<M: ManagedTypeApi> {
+    pub pair_address: ManagedAddress<M>,
+    pub token_out: TokenIdentifier<M>,
+    pub amount_out: BigUint<M>,
+}
+
+/// A token reached while searching for a path, with the best amount found for it
+/// and the index of the node it was reached from.
+#[derive(ManagedVecItem)]
+pub struct SwapPathNode<M: ManagedTypeApi> {
+    pub token_id: TokenIdentifier<M>,
+    pub amount: BigUint<M>,
+    pub pair_address: ManagedAddress<M>,
+    pub previous_node_index: usize,
+}
+
+#[multiversx_sc::module]
+pub trait AutoRouteModule:
+    config::ConfigModule
+    + factory::FactoryModule
+    + token_send::TokenSendModule
+    + multi_pair_swap::MultiPairSwap
+    + events::EventsModule
+{
+    /// Swaps the payment through the path with the best output, found by `getBestSwapPath`.
+    /// Fails if the output is lower than `amount_out_min`.
+    #[payable("*")]
+    #[endpoint(swapAutoRoute)]
+    fn swap_auto_route(
+        &self,
+        token_out: TokenIdentifier,
+        amount_out_min: BigUint,
+        max_hops: usize,
+        intermediate_tokens: MultiValueEncoded<TokenIdentifier>,
+    ) -> EsdtTokenPayment {
+        require!(self.is_active(), "Not active");
+
+        let (token_id, nonce, amount) = self.call_value().single_esdt().into_tuple();
+        require!(nonce == 0, "Invalid nonce. Should be zero");
+
+        let swap_path = self.find_best_swap_path(
+            &token_id,
+            &amount,
+            &token_out,
+            max_hops,
+            intermediate_tokens,
+        );
+        require!(!swap_path.is_empty(), "No swap path found");
+
+        let last_hop_index = swap_path.len() - 1;
+        let mut last_payment = EsdtTokenPayment::new(token_id, nonce, amount);
+        for (hop_index, hop) in swap_path.iter().enumerate() {
+            // only the final output is checked against the caller's minimum
+            let hop_amount_out_min = if hop_index == last_hop_index {
+                amount_out_min.clone()
+            } else {
+                BigUint::from(1u64)
+            };
+
+            last_payment = self.actual_swap_fixed_input(
+                hop.pair_address,
+                last_payment.token_identifier,
+                last_payment.amount,
+                hop.token_out,
+                hop_amount_out_min,
+            );
+        }
+
+        let caller = self.blockchain().get_caller();
+        self.send().direct_esdt(
+            &caller,
+            &last_payment.token_identifier,
+            last_payment.token_nonce,
+            &last_payment.amount,
+        );
+
+        last_payment
+    }
+
+    /// Returns the expected output and the hops of the path with the best output
+    /// from `token_in` to `token_out`, using at most `max_hops` active pairs.
+    /// The intermediate tokens are picked from `intermediate_tokens`, or from the
+    /// common tokens for user pairs if none are given. Returns zero and no hops if there is no path.
+    #[view(getBestSwapPath)]
+    fn get_best_swap_path(
+        &self,
+        token_in: TokenIdentifier,
+        amount_in: BigUint,
+        token_out: TokenIdentifier,
+        max_hops: usize,
+        intermediate_tokens: MultiValueEncoded<TokenIdentifier>,
+    ) -> MultiValue2<BigUint, ManagedVec<SwapPathHop<Self::Api>>> {
+        let swap_path = self.find_best_swap_path(
+            &token_in,
+            &amount_in,
+            &token_out,
+            max_hops,
+            intermediate_tokens,
+        );
+        let amount_out = if swap_path.is_empty() {
+            BigUint::zero()
+        } else {
+            swap_path.get(swap_path.len() - 1).amount_out
+        };
+
+        (amount_out, swap_path).into()
+    }
+
+    /// Bounded breadth-first search, keeping the best amount for each token on each hop.
+    /// Only the pairs between the candidate tokens are looked up, so the cost does not grow
+    /// with the number of pairs deployed by the router.
+    fn find_best_swap_path(
+        &self,
+        token_in: &TokenIdentifier,
+        amount_in: &BigUint,
+        token_out: &TokenIdentifier,
+        max_hops: usize,
+        intermediate_tokens: MultiValueEncoded<TokenIdentifier>,
+    ) -> ManagedVec<SwapPathHop<Self::Api>> {
+        require!(
+            (1..=MAX_SWAP_PATH_HOPS).contains(&max_hops),
+            "Invalid max hops"
+        );
+        require!(token_in != token_out, "Invalid tokens");
+        require!(*amount_in > 0u64, "Invalid amount. Should not be zero");
+
+        let candidate_tokens =
+            self.get_swap_path_candidate_tokens(token_in, token_out, intermediate_tokens);
+
+        let mut nodes = ManagedVec::<Self::Api, SwapPathNode<Self::Api>>::new();
+        nodes.push(SwapPathNode {
+            token_id: token_in.clone(),
+            amount: amount_in.clone(),
+            pair_address: ManagedAddress::zero(),
+            previous_node_index: 0,
+        });
+
+        let mut opt_best_node_index = None;
+        let mut best_amount_out = BigUint::zero();
+        let mut hop_start_index = 0;
+        for hop in 0..max_hops {
+            let hop_end_index = nodes.len();
+            for node_index in hop_start_index..hop_end_index {
+                let node = nodes.get(node_index);
+                if &node.token_id == token_out {
+                    continue;
+                }
+
+                for candidate_token in candidate_tokens.iter() {
+                    let next_token_id = candidate_token.clone_value();
+                    if next_token_id == node.token_id
+                        || (&next_token_id != token_out && hop + 1 == max_hops)
+                    {
+                        continue;
+                    }
+
+                    let pair_address = self.get_pair(
+                        EgldOrEsdtTokenIdentifier::esdt(node.token_id.clone()),
+                        EgldOrEsdtTokenIdentifier::esdt(next_token_id.clone()),
+                    );
+                    if pair_address.is_zero() || !self.is_swappable_pair(&pair_address) {
+                        continue;
+                    }
+
+                    let amount_out = self.get_pair_amount_out(
+                        &pair_address,
+                        &node.token_id,
+                        &node.amount,
+                        &next_token_id,
+                    );
+                    if amount_out == 0u64 {
+                        continue;
+                    }
+
+                    let new_node = SwapPathNode {
+                        token_id: next_token_id,
+                        amount: amount_out,
+                        pair_address,
+                        previous_node_index: node_index,
+                    };
+                    let opt_existing_index = (hop_end_index..nodes.len())
+                        .find(|&index| nodes.get(index).token_id == new_node.token_id);
+                    match opt_existing_index {
+                        Some(existing_index) => {
+                            if new_node.amount > nodes.get(existing_index).amount {
+                                let _ = nodes.set(existing_index, &new_node);
+                            }
+                        }
+                        None => nodes.push(new_node),
+                    }
+                }
+            }
+
+            for node_index in hop_end_index..nodes.len() {
+                let node = nodes.get(node_index);
+                if &node.token_id == token_out && node.amount > best_amount_out {
+                    best_amount_out = node.amount;
+                    opt_best_node_index = Some(node_index);
+                }
+            }
+
+            hop_start_index = hop_end_index;
+        }
+
+        let mut swap_path = ManagedVec::new();
+        let mut node_index = match opt_best_node_index {
+            Some(best_node_index) => best_node_index,
+            None => return swap_path,
+        };
+
+        let mut reversed_path = ManagedVec::<Self::Api, SwapPathHop<Self::Api>>::new();
+        while node_index != 0 {
+            let node = nodes.get(node_index);
+            reversed_path.push(SwapPathHop {
+                pair_address: node.pair_address,
+                token_out: node.token_id,
+                amount_out: node.amount,
+            });
+            node_index = node.previous_node_index;
+        }
+        for hop_index in (0..reversed_path.len()).rev() {
+            swap_path.push(reversed_path.get(hop_index));
+        }
+
+        swap_path
+    }
+
+    /// The intermediate tokens, followed by `token_out`. Without given intermediate tokens,
+    /// the first MAX_SWAP_PATH_INTERMEDIATE_TOKENS common tokens are used.
+    fn get_swap_path_candidate_tokens(
+        &self,
+        token_in: &TokenIdentifier,
+        token_out: &TokenIdentifier,
+        intermediate_tokens: MultiValueEncoded<TokenIdentifier>,
+    ) -> ManagedVec<TokenIdentifier> {
+        let use_common_tokens = intermediate_tokens.is_empty();
+        let intermediate_tokens = if use_common_tokens {
+            self.common_tokens_for_user_pairs().iter().collect()
+        } else {
+            intermediate_tokens.to_vec()
+        };
+
+        let mut candidate_tokens = ManagedVec::new();
+        for token_id in intermediate_tokens.iter() {
+            if use_common_tokens && candidate_tokens.len() == MAX_SWAP_PATH_INTERMEDIATE_TOKENS {
+                break;
+            }
+
+            if &*token_id != token_in
+                && &*token_id != token_out
+                && !candidate_tokens.contains(&*token_id)
+            {
+                candidate_tokens.push(token_id.clone_value());
+            }
+        }
+        require!(
+            candidate_tokens.len() <= MAX_SWAP_PATH_INTERMEDIATE_TOKENS,
+            "Too many intermediate tokens"
+        );
+
+        candidate_tokens.push(token_out.clone());
+        candidate_tokens
+    }
+
+    /// Only active pairs with liquidity, which can be quoted, are used.
+    fn is_swappable_pair(&self, pair_address: &ManagedAddress) -> bool {
+        let state: State = self.read_storage_from_swap_pair(pair_address, PAIR_STATE_STORAGE_KEY);
+        let lp_token_supply: BigUint =
+            self.read_storage_from_swap_pair(pair_address, PAIR_LP_TOKEN_SUPPLY_STORAGE_KEY);
+
+        state == State::Active
+            && lp_token_supply > 0u64
+            && !self.has_before_swap_hooks(pair_address)
+    }
+
+    fn read_storage_from_swap_pair<T: TopDecode>(
+        &self,
+        pair_address: &ManagedAddress,
+        storage_key: &[u8],
+    ) -> T {
+        let key_buffer = ManagedBuffer::new_from_bytes(storage_key);
+        self.storage_raw()
+            .read_from_address(pair_address, key_buffer)
+    }
+}
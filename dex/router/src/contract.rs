@@ -3,11 +3,15 @@
 multiversx_sc::imports!();
 multiversx_sc::derive_imports!();
 
+pub mod auto_route;
 pub mod config;
 pub mod enable_swap_by_user;
 mod events;
 pub mod factory;
+pub mod liquidity_migration;
 pub mod multi_pair_swap;
+pub mod swap_and_lock;
+pub mod zap;
 
 use factory::PairTokens;
 use pair::config::ProxyTrait as _;
@@ -31,6 +35,10 @@ pub trait Router:
     + multi_pair_swap::MultiPairSwap
     + token_send::TokenSendModule
     + enable_swap_by_user::EnableSwapByUserModule
+    + liquidity_migration::LiquidityMigrationModule
+    + auto_route::AutoRouteModule
+    + swap_and_lock::SwapAndLockModule
+    + zap::ZapModule
 {
     #[init]
     fn init(&self, pair_template_address_opt: OptionalValue<ManagedAddress>) {
@@ -82,8 +90,8 @@ pub trait Router:
     #[endpoint(createPair)]
     fn create_pair_endpoint(
         &self,
-        first_token_id: TokenIdentifier,
-        second_token_id: TokenIdentifier,
+        first_token_id: EgldOrEsdtTokenIdentifier,
+        second_token_id: EgldOrEsdtTokenIdentifier,
         initial_liquidity_adder: ManagedAddress,
         opt_fee_percents: OptionalValue<MultiValue2<u64, u64>>,
         mut admins: MultiValueEncoded<ManagedAddress>,
@@ -101,12 +109,12 @@ pub trait Router:
 
         require!(first_token_id != second_token_id, "Identical tokens");
         require!(
-            first_token_id.is_valid_esdt_identifier(),
-            "First Token ID is not a valid esdt token ID"
+            first_token_id.is_valid(),
+            "First Token ID is not a valid token ID"
         );
         require!(
-            second_token_id.is_valid_esdt_identifier(),
-            "Second Token ID is not a valid esdt token ID"
+            second_token_id.is_valid(),
+            "Second Token ID is not a valid token ID"
         );
         let pair_address = self.get_pair(first_token_id.clone(), second_token_id.clone());
         require!(pair_address.is_zero(), "Pair already exists");
@@ -157,8 +165,8 @@ pub trait Router:
     #[endpoint(upgradePair)]
     fn upgrade_pair_endpoint(
         &self,
-        first_token_id: TokenIdentifier,
-        second_token_id: TokenIdentifier,
+        first_token_id: EgldOrEsdtTokenIdentifier,
+        second_token_id: EgldOrEsdtTokenIdentifier,
         initial_liquidity_adder: ManagedAddress,
         total_fee_percent_requested: u64,
         special_fee_percent_requested: u64,
@@ -167,12 +175,12 @@ pub trait Router:
 
         require!(first_token_id != second_token_id, "Identical tokens");
         require!(
-            first_token_id.is_valid_esdt_identifier(),
-            "First Token ID is not a valid esdt token ID"
+            first_token_id.is_valid(),
+            "First Token ID is not a valid token ID"
         );
         require!(
-            second_token_id.is_valid_esdt_identifier(),
-            "Second Token ID is not a valid esdt token ID"
+            second_token_id.is_valid(),
+            "Second Token ID is not a valid token ID"
         );
         let pair_address = self.get_pair(first_token_id.clone(), second_token_id.clone());
         require!(!pair_address.is_zero(), "Pair does not exists");
@@ -282,19 +290,19 @@ pub trait Router:
     #[endpoint(removePair)]
     fn remove_pair(
         &self,
-        first_token_id: TokenIdentifier,
-        second_token_id: TokenIdentifier,
+        first_token_id: EgldOrEsdtTokenIdentifier,
+        second_token_id: EgldOrEsdtTokenIdentifier,
     ) -> ManagedAddress {
         require!(self.is_active(), "Not active");
 
         require!(first_token_id != second_token_id, "Identical tokens");
         require!(
-            first_token_id.is_valid_esdt_identifier(),
-            "First Token ID is not a valid esdt token ID"
+            first_token_id.is_valid(),
+            "First Token ID is not a valid token ID"
         );
         require!(
-            second_token_id.is_valid_esdt_identifier(),
-            "Second Token ID is not a valid esdt token ID"
+            second_token_id.is_valid(),
+            "Second Token ID is not a valid token ID"
         );
         let mut pair_address = self.get_pair(first_token_id.clone(), second_token_id.clone());
         require!(!pair_address.is_zero(), "Pair does not exists");
@@ -142,8 +142,8 @@ pub trait EnableSwapByUserModule:
 
         self.emit_user_swaps_enabled_event(
             caller,
-            lp_token_safe_price_result.first_token_id,
-            lp_token_safe_price_result.second_token_id,
+            EgldOrEsdtTokenIdentifier::esdt(lp_token_safe_price_result.first_token_id),
+            EgldOrEsdtTokenIdentifier::esdt(lp_token_safe_price_result.second_token_id),
             pair_address,
         );
     }
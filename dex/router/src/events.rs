@@ -4,8 +4,8 @@ multiversx_sc::derive_imports!();
 #[derive(TypeAbi, TopEncode)]
 pub struct CreatePairEvent<M: ManagedTypeApi> {
     caller: ManagedAddress<M>,
-    first_token_id: TokenIdentifier<M>,
-    second_token_id: TokenIdentifier<M>,
+    first_token_id: EgldOrEsdtTokenIdentifier<M>,
+    second_token_id: EgldOrEsdtTokenIdentifier<M>,
     total_fee_percent: u64,
     special_fee_percent: u64,
     pair_address: ManagedAddress<M>,
@@ -17,18 +17,29 @@ pub struct CreatePairEvent<M: ManagedTypeApi> {
 #[derive(TypeAbi, TopEncode)]
 pub struct UserPairSwapEnabledEvent<M: ManagedTypeApi> {
     caller: ManagedAddress<M>,
-    first_token_id: TokenIdentifier<M>,
-    second_token_id: TokenIdentifier<M>,
+    first_token_id: EgldOrEsdtTokenIdentifier<M>,
+    second_token_id: EgldOrEsdtTokenIdentifier<M>,
     pair_address: ManagedAddress<M>,
 }
 
+#[derive(TypeAbi, TopEncode)]
+pub struct SplitSwapEvent<M: ManagedTypeApi> {
+    caller: ManagedAddress<M>,
+    payment_in: EsdtTokenPayment<M>,
+    payment_out: EsdtTokenPayment<M>,
+    route_payments_out: ManagedVec<M, EsdtTokenPayment<M>>,
+    block: u64,
+    epoch: u64,
+    timestamp: u64,
+}
+
 #[multiversx_sc::module]
 pub trait EventsModule {
     fn emit_create_pair_event(
         self,
         caller: ManagedAddress,
-        first_token_id: TokenIdentifier,
-        second_token_id: TokenIdentifier,
+        first_token_id: EgldOrEsdtTokenIdentifier,
+        second_token_id: EgldOrEsdtTokenIdentifier,
         total_fee_percent: u64,
         special_fee_percent: u64,
         pair_address: ManagedAddress,
@@ -56,8 +67,8 @@ pub trait EventsModule {
     fn emit_user_swaps_enabled_event(
         &self,
         caller: ManagedAddress,
-        first_token_id: TokenIdentifier,
-        second_token_id: TokenIdentifier,
+        first_token_id: EgldOrEsdtTokenIdentifier,
+        second_token_id: EgldOrEsdtTokenIdentifier,
         pair_address: ManagedAddress,
     ) {
         let epoch = self.blockchain().get_block_epoch();
@@ -75,11 +86,36 @@ pub trait EventsModule {
         )
     }
 
+    fn emit_split_swap_event(
+        &self,
+        caller: ManagedAddress,
+        payment_in: EsdtTokenPayment,
+        payment_out: EsdtTokenPayment,
+        route_payments_out: ManagedVec<EsdtTokenPayment>,
+    ) {
+        let epoch = self.blockchain().get_block_epoch();
+        self.split_swap_event(
+            payment_in.token_identifier.clone(),
+            payment_out.token_identifier.clone(),
+            caller.clone(),
+            epoch,
+            SplitSwapEvent {
+                caller,
+                payment_in,
+                payment_out,
+                route_payments_out,
+                block: self.blockchain().get_block_nonce(),
+                epoch,
+                timestamp: self.blockchain().get_block_timestamp(),
+            },
+        )
+    }
+
     #[event("create_pair")]
     fn create_pair_event(
         self,
-        #[indexed] first_token_id: TokenIdentifier,
-        #[indexed] second_token_id: TokenIdentifier,
+        #[indexed] first_token_id: EgldOrEsdtTokenIdentifier,
+        #[indexed] second_token_id: EgldOrEsdtTokenIdentifier,
         #[indexed] caller: ManagedAddress,
         #[indexed] epoch: u64,
         swap_event: CreatePairEvent<Self::Api>,
@@ -88,10 +124,20 @@ pub trait EventsModule {
     #[event("pairSwapEnabled")]
     fn pair_swap_enabled_event(
         &self,
-        #[indexed] first_token_id: TokenIdentifier,
-        #[indexed] second_token_id: TokenIdentifier,
+        #[indexed] first_token_id: EgldOrEsdtTokenIdentifier,
+        #[indexed] second_token_id: EgldOrEsdtTokenIdentifier,
         #[indexed] caller: ManagedAddress,
         #[indexed] epoch: u64,
         swap_enabled_event: UserPairSwapEnabledEvent<Self::Api>,
     );
+
+    #[event("split_swap")]
+    fn split_swap_event(
+        &self,
+        #[indexed] token_in: TokenIdentifier,
+        #[indexed] token_out: TokenIdentifier,
+        #[indexed] caller: ManagedAddress,
+        #[indexed] epoch: u64,
+        split_swap_event: SplitSwapEvent<Self::Api>,
+    );
 }
@@ -7,14 +7,14 @@ const TEMPORARY_OWNER_PERIOD_BLOCKS: u64 = 50;
 
 #[derive(TopEncode, TopDecode, NestedEncode, NestedDecode, PartialEq, TypeAbi)]
 pub struct PairTokens<M: ManagedTypeApi> {
-    pub first_token_id: TokenIdentifier<M>,
-    pub second_token_id: TokenIdentifier<M>,
+    pub first_token_id: EgldOrEsdtTokenIdentifier<M>,
+    pub second_token_id: EgldOrEsdtTokenIdentifier<M>,
 }
 
 #[derive(ManagedVecItem, TopEncode, TopDecode, PartialEq, TypeAbi)]
 pub struct PairContractMetadata<M: ManagedTypeApi> {
-    first_token_id: TokenIdentifier<M>,
-    second_token_id: TokenIdentifier<M>,
+    first_token_id: EgldOrEsdtTokenIdentifier<M>,
+    second_token_id: EgldOrEsdtTokenIdentifier<M>,
     address: ManagedAddress<M>,
 }
 
@@ -34,8 +34,8 @@ pub trait FactoryModule: config::ConfigModule {
 
     fn create_pair(
         &self,
-        first_token_id: &TokenIdentifier,
-        second_token_id: &TokenIdentifier,
+        first_token_id: &EgldOrEsdtTokenIdentifier,
+        second_token_id: &EgldOrEsdtTokenIdentifier,
         owner: &ManagedAddress,
         total_fee_percent: u64,
         special_fee_percent: u64,
@@ -91,8 +91,8 @@ pub trait FactoryModule: config::ConfigModule {
     fn upgrade_pair(
         &self,
         pair_address: ManagedAddress,
-        first_token_id: &TokenIdentifier,
-        second_token_id: &TokenIdentifier,
+        first_token_id: &EgldOrEsdtTokenIdentifier,
+        second_token_id: &EgldOrEsdtTokenIdentifier,
         owner: &ManagedAddress,
         _initial_liquidity_adder: &ManagedAddress,
         total_fee_percent: u64,
@@ -151,8 +151,8 @@ pub trait FactoryModule: config::ConfigModule {
     #[view(getPair)]
     fn get_pair(
         &self,
-        first_token_id: TokenIdentifier,
-        second_token_id: TokenIdentifier,
+        first_token_id: EgldOrEsdtTokenIdentifier,
+        second_token_id: EgldOrEsdtTokenIdentifier,
     ) -> ManagedAddress {
         let mut address = self
             .pair_map()
@@ -0,0 +1,79 @@
+multiversx_sc::imports!();
+
+use pair::pair_actions::migration::ProxyTrait as _;
+
+use crate::config;
+
+static PAIR_FIRST_TOKEN_ID_STORAGE_KEY: &[u8] = b"first_token_id";
+static PAIR_SECOND_TOKEN_ID_STORAGE_KEY: &[u8] = b"second_token_id";
+
+#[multiversx_sc::module]
+pub trait LiquidityMigrationModule: config::ConfigModule + crate::factory::FactoryModule {
+    /// Allows the LP tokens of the source pair to be migrated to the target pair,
+    /// through the target pair's `migrateLiquidity` endpoint.
+    /// The target must be a pair of this router, for the same tokens as the source pair.
+    #[only_owner]
+    #[endpoint(setLiquidityMigrationPath)]
+    fn set_liquidity_migration_path(
+        &self,
+        source_pair_address: ManagedAddress,
+        target_pair_address: ManagedAddress,
+    ) {
+        require!(self.is_active(), "Not active");
+        require!(
+            source_pair_address != target_pair_address,
+            "Source and target pairs must be different"
+        );
+        require!(
+            self.blockchain().is_smart_contract(&source_pair_address),
+            "Invalid source pair"
+        );
+        self.check_is_pair_sc(&target_pair_address);
+
+        let target_pair_tokens = self.get_pair_tokens(target_pair_address.clone());
+        let source_first_token_id: EgldOrEsdtTokenIdentifier = self
+            .read_storage_from_source_pair(&source_pair_address, PAIR_FIRST_TOKEN_ID_STORAGE_KEY);
+        let source_second_token_id: EgldOrEsdtTokenIdentifier = self
+            .read_storage_from_source_pair(&source_pair_address, PAIR_SECOND_TOKEN_ID_STORAGE_KEY);
+        let same_order = source_first_token_id == target_pair_tokens.first_token_id
+            && source_second_token_id == target_pair_tokens.second_token_id;
+        let reverse_order = source_first_token_id == target_pair_tokens.second_token_id
+            && source_second_token_id == target_pair_tokens.first_token_id;
+        require!(
+            same_order || reverse_order,
+            "The pairs have different tokens"
+        );
+
+        let _: IgnoreValue = self
+            .migration_pair_proxy(source_pair_address.clone())
+            .set_migration_target(target_pair_address.clone())
+            .execute_on_dest_context();
+        let _: IgnoreValue = self
+            .migration_pair_proxy(target_pair_address.clone())
+            .set_migration_source(source_pair_address.clone())
+            .execute_on_dest_context();
+
+        self.liquidity_migration_target(&source_pair_address)
+            .set(&target_pair_address);
+    }
+
+    fn read_storage_from_source_pair<T: TopDecode>(
+        &self,
+        pair_address: &ManagedAddress,
+        storage_key: &[u8],
+    ) -> T {
+        let key_buffer = ManagedBuffer::new_from_bytes(storage_key);
+        self.storage_raw()
+            .read_from_address(pair_address, key_buffer)
+    }
+
+    #[proxy]
+    fn migration_pair_proxy(&self, to: ManagedAddress) -> pair::Proxy<Self::Api>;
+
+    #[view(getLiquidityMigrationTarget)]
+    #[storage_mapper("liquidity_migration_target")]
+    fn liquidity_migration_target(
+        &self,
+        source_pair_address: &ManagedAddress,
+    ) -> SingleValueMapper<ManagedAddress>;
+}
@@ -2,24 +2,88 @@ multiversx_sc::imports!();
 multiversx_sc::derive_imports!();
 
 use pair::pair_actions::swap::ProxyTrait as _;
+use pair::pair_actions::views::ProxyTrait as _;
 
-use crate::config;
+use pair::config::{CurveType, MAX_PERCENTAGE};
+use pair::errors::{ERROR_DEADLINE_EXPIRED, ERROR_NOT_ENOUGH_RESERVE, ERROR_ZERO_AMOUNT};
+use pair::hooks::HookType;
+
+use crate::{config, events};
 
 use super::factory;
 
-type SwapOperationType<M> =
+pub type SwapOperationType<M> =
     MultiValue4<ManagedAddress<M>, ManagedBuffer<M>, TokenIdentifier<M>, BigUint<M>>;
+type SplitSwapRouteType<M> = MultiValue2<u64, ManagedVec<M, SwapRouteHop<M>>>;
 
 pub const SWAP_TOKENS_FIXED_INPUT_FUNC_NAME: &[u8] = b"swapTokensFixedInput";
 pub const SWAP_TOKENS_FIXED_OUTPUT_FUNC_NAME: &[u8] = b"swapTokensFixedOutput";
+pub const MAX_SPLIT_SWAP_ROUTES: usize = 5;
+
+static PAIR_RESERVE_STORAGE_KEY: &[u8] = b"reserve";
+static PAIR_TOTAL_FEE_PERCENT_STORAGE_KEY: &[u8] = b"total_fee_percent";
+static PAIR_CURVE_TYPE_STORAGE_KEY: &[u8] = b"curve_type";
+static PAIR_DYNAMIC_FEE_CONFIG_STORAGE_KEY: &[u8] = b"dynamic_fee_config";
+static PAIR_HOOKS_STORAGE_KEY: &[u8] = b"hooks";
+static VEC_MAPPER_LEN_STORAGE_KEY_SUFFIX: &[u8] = b".len";
+
+#[derive(TypeAbi, TopEncode, TopDecode, NestedEncode, NestedDecode, ManagedVecItem, Clone)]
+pub struct SwapRouteHop<M: ManagedTypeApi> {
+    pub pair_address: ManagedAddress<M>,
+    pub token_wanted: TokenIdentifier<M>,
+}
+
+/// The residuum is the part of the input left after a fixed output swap.
+#[derive(
+    TypeAbi,
+    TopEncode,
+    TopDecode,
+    NestedEncode,
+    NestedDecode,
+    ManagedVecItem,
+    Clone,
+    PartialEq,
+    Debug,
+)]
+pub struct SwapQuoteHop<M: ManagedTypeApi> {
+    pub pair_address: ManagedAddress<M>,
+    pub token_in: TokenIdentifier<M>,
+    pub amount_in: BigUint<M>,
+    pub token_out: TokenIdentifier<M>,
+    pub amount_out: BigUint<M>,
+    pub residuum: BigUint<M>,
+}
 
 #[multiversx_sc::module]
 pub trait MultiPairSwap:
-    config::ConfigModule + factory::FactoryModule + token_send::TokenSendModule
+    config::ConfigModule + factory::FactoryModule + token_send::TokenSendModule + events::EventsModule
 {
     #[payable("*")]
     #[endpoint(multiPairSwap)]
-    fn multi_pair_swap(&self, swap_operations: MultiValueEncoded<SwapOperationType<Self::Api>>) -> ManagedVec<EsdtTokenPayment> {
+    fn multi_pair_swap(
+        &self,
+        swap_operations: MultiValueEncoded<SwapOperationType<Self::Api>>,
+    ) -> ManagedVec<EsdtTokenPayment> {
+        self.multi_pair_swap_common(swap_operations)
+    }
+
+    /// Same as `multiPairSwap`, failing once the block timestamp is past `deadline`,
+    /// the same as the `opt_deadline` of the pair swaps.
+    #[payable("*")]
+    #[endpoint(multiPairSwapWithDeadline)]
+    fn multi_pair_swap_with_deadline(
+        &self,
+        deadline: u64,
+        swap_operations: MultiValueEncoded<SwapOperationType<Self::Api>>,
+    ) -> ManagedVec<EsdtTokenPayment> {
+        self.require_deadline_not_expired(deadline);
+        self.multi_pair_swap_common(swap_operations)
+    }
+
+    fn multi_pair_swap_common(
+        &self,
+        swap_operations: MultiValueEncoded<SwapOperationType<Self::Api>>,
+    ) -> ManagedVec<EsdtTokenPayment> {
         require!(self.is_active(), "Not active");
 
         let (token_id, nonce, amount) = self.call_value().single_esdt().into_tuple();
@@ -30,13 +94,37 @@ pub trait MultiPairSwap:
             "Invalid swap operations chain. Should not be empty"
         );
 
+        let caller = self.blockchain().get_caller();
+        let (last_payment, mut payments) = self.perform_swap_operations(
+            EsdtTokenPayment::new(token_id, nonce, amount),
+            swap_operations,
+        );
+
+        payments.push(last_payment);
+        self.send().direct_multi(&caller, &payments);
+
+        payments
+    }
+
+    fn require_deadline_not_expired(&self, deadline: u64) {
+        require!(
+            self.blockchain().get_block_timestamp() <= deadline,
+            ERROR_DEADLINE_EXPIRED
+        );
+    }
+
+    /// Executes the swap operations starting from `payment`.
+    /// Returns the final payment and the residuums of the fixed output swaps.
+    fn perform_swap_operations(
+        &self,
+        payment: EsdtTokenPayment,
+        swap_operations: MultiValueEncoded<SwapOperationType<Self::Api>>,
+    ) -> (EsdtTokenPayment, ManagedVec<EsdtTokenPayment>) {
         let swap_fixed_input_endpoint = ManagedBuffer::from(SWAP_TOKENS_FIXED_INPUT_FUNC_NAME);
         let swap_fixed_output_endpoint = ManagedBuffer::from(SWAP_TOKENS_FIXED_OUTPUT_FUNC_NAME);
 
-        let caller = self.blockchain().get_caller();
-        let mut payments = ManagedVec::new();
-        let mut last_payment = EsdtTokenPayment::new(token_id, nonce, amount);
-
+        let mut residuums = ManagedVec::new();
+        let mut last_payment = payment;
         for entry in swap_operations.into_iter() {
             let (pair_address, function, token_wanted, amount_wanted) = entry.into_tuple();
             self.check_is_pair_sc(&pair_address);
@@ -59,16 +147,419 @@ pub trait MultiPairSwap:
                 );
 
                 last_payment = payment;
-                payments.push(residuum);
+                residuums.push(residuum);
             } else {
                 sc_panic!("Invalid function to call");
             }
         }
 
-        payments.push(last_payment);
-        self.send().direct_multi(&caller, &payments);
+        (last_payment, residuums)
+    }
 
-        payments
+    /// Splits the payment between several routes to the same token, proportionally to
+    /// the route weights, to reduce the price impact of large swaps.
+    /// Each route is a list of fixed input swaps. Only the total output is checked
+    /// against `amount_out_min`. Returns the output of each route.
+    #[payable("*")]
+    #[endpoint(multiPairSwapSplit)]
+    fn multi_pair_swap_split(
+        &self,
+        amount_out_min: BigUint,
+        routes: MultiValueEncoded<SplitSwapRouteType<Self::Api>>,
+    ) -> ManagedVec<EsdtTokenPayment> {
+        self.multi_pair_swap_split_common(amount_out_min, routes)
+    }
+
+    /// Same as `multiPairSwapSplit`, failing once the block timestamp is past `deadline`.
+    #[payable("*")]
+    #[endpoint(multiPairSwapSplitWithDeadline)]
+    fn multi_pair_swap_split_with_deadline(
+        &self,
+        amount_out_min: BigUint,
+        deadline: u64,
+        routes: MultiValueEncoded<SplitSwapRouteType<Self::Api>>,
+    ) -> ManagedVec<EsdtTokenPayment> {
+        self.require_deadline_not_expired(deadline);
+        self.multi_pair_swap_split_common(amount_out_min, routes)
+    }
+
+    fn multi_pair_swap_split_common(
+        &self,
+        amount_out_min: BigUint,
+        routes: MultiValueEncoded<SplitSwapRouteType<Self::Api>>,
+    ) -> ManagedVec<EsdtTokenPayment> {
+        require!(self.is_active(), "Not active");
+
+        let (token_id, nonce, amount) = self.call_value().single_esdt().into_tuple();
+        require!(nonce == 0, "Invalid nonce. Should be zero");
+        require!(amount > 0u64, "Invalid amount. Should not be zero");
+
+        let mut routes_count = 0;
+        let mut total_weight = 0u64;
+        let mut opt_token_out: Option<TokenIdentifier> = None;
+        for route in routes.clone().into_iter() {
+            let (weight, hops) = route.into_tuple();
+            require!(weight > 0, "Invalid route weight");
+            require!(!hops.is_empty(), "Invalid route. Should not be empty");
+
+            let route_token_out = hops.get(hops.len() - 1).token_wanted;
+            match &opt_token_out {
+                Some(token_out) => require!(
+                    token_out == &route_token_out,
+                    "All routes must have the same output token"
+                ),
+                None => opt_token_out = Some(route_token_out),
+            }
+            total_weight = match total_weight.checked_add(weight) {
+                Some(new_total_weight) => new_total_weight,
+                None => sc_panic!("Invalid route weight"),
+            };
+            routes_count += 1;
+        }
+        require!(
+            routes_count > 0 && routes_count <= MAX_SPLIT_SWAP_ROUTES,
+            "Invalid number of routes"
+        );
+        let token_out = opt_token_out.unwrap();
+
+        let last_route_index = routes_count - 1;
+        let mut remaining_amount = amount.clone();
+        let mut total_amount_out = BigUint::zero();
+        let mut route_payments = ManagedVec::new();
+        for (route_index, route) in routes.into_iter().enumerate() {
+            let (weight, hops) = route.into_tuple();
+            // the last route also gets the rounding remainder
+            let route_amount = if route_index == last_route_index {
+                remaining_amount.clone()
+            } else {
+                &amount * weight / total_weight
+            };
+            require!(
+                route_amount > 0u64,
+                "Invalid route amount. Should not be zero"
+            );
+            remaining_amount -= &route_amount;
+
+            let mut last_payment = EsdtTokenPayment::new(token_id.clone(), 0, route_amount);
+            for hop in hops.iter() {
+                self.check_is_pair_sc(&hop.pair_address);
+
+                last_payment = self.actual_swap_fixed_input(
+                    hop.pair_address,
+                    last_payment.token_identifier,
+                    last_payment.amount,
+                    hop.token_wanted,
+                    BigUint::from(1u64),
+                );
+            }
+
+            total_amount_out += &last_payment.amount;
+            route_payments.push(last_payment);
+        }
+
+        require!(total_amount_out >= amount_out_min, "Slippage exceeded");
+
+        let caller = self.blockchain().get_caller();
+        self.send()
+            .direct_esdt(&caller, &token_out, 0, &total_amount_out);
+
+        self.emit_split_swap_event(
+            caller,
+            EsdtTokenPayment::new(token_id, 0, amount),
+            EsdtTokenPayment::new(token_out, 0, total_amount_out),
+            route_payments.clone(),
+        );
+
+        route_payments
+    }
+
+    /// Simulates `multiPairSwap` for a payment of `amount_in` of `token_in`,
+    /// with the same results as the `getAmountOut` and `getAmountIn` views of the pairs.
+    /// Returns the final output and the amounts of each operation.
+    /// The reserves are not updated between operations, so the result is not exact
+    /// for a chain using the same pair more than once.
+    #[view(getMultiPairSwapQuote)]
+    fn get_multi_pair_swap_quote(
+        &self,
+        token_in: TokenIdentifier,
+        amount_in: BigUint,
+        swap_operations: MultiValueEncoded<SwapOperationType<Self::Api>>,
+    ) -> MultiValue2<BigUint, ManagedVec<SwapQuoteHop<Self::Api>>> {
+        require!(amount_in > 0u64, "Invalid amount. Should not be zero");
+        require!(
+            !swap_operations.is_empty(),
+            "Invalid swap operations chain. Should not be empty"
+        );
+
+        let swap_fixed_input_endpoint = ManagedBuffer::from(SWAP_TOKENS_FIXED_INPUT_FUNC_NAME);
+        let swap_fixed_output_endpoint = ManagedBuffer::from(SWAP_TOKENS_FIXED_OUTPUT_FUNC_NAME);
+
+        let mut quote_hops = ManagedVec::new();
+        let mut last_token_id = token_in;
+        let mut last_amount = amount_in;
+        for entry in swap_operations.into_iter() {
+            let (pair_address, function, token_wanted, amount_wanted) = entry.into_tuple();
+            let other_token_id = self.get_other_pair_token(&pair_address, &token_wanted);
+            require!(
+                other_token_id == last_token_id,
+                "Invalid swap operations chain"
+            );
+
+            let quote_hop = if function == swap_fixed_input_endpoint {
+                let amount_out = self.get_pair_amount_out(
+                    &pair_address,
+                    &last_token_id,
+                    &last_amount,
+                    &token_wanted,
+                );
+                require!(amount_out >= amount_wanted, "Slippage exceeded");
+
+                SwapQuoteHop {
+                    pair_address,
+                    token_in: last_token_id,
+                    amount_in: last_amount,
+                    token_out: token_wanted,
+                    amount_out,
+                    residuum: BigUint::zero(),
+                }
+            } else if function == swap_fixed_output_endpoint {
+                let amount_in_needed = self.get_pair_amount_in(
+                    &pair_address,
+                    &last_token_id,
+                    &token_wanted,
+                    &amount_wanted,
+                );
+                require!(amount_in_needed <= last_amount, "Slippage exceeded");
+
+                SwapQuoteHop {
+                    pair_address,
+                    token_in: last_token_id,
+                    residuum: &last_amount - &amount_in_needed,
+                    amount_in: amount_in_needed,
+                    token_out: token_wanted,
+                    amount_out: amount_wanted,
+                }
+            } else {
+                sc_panic!("Invalid function to call");
+            };
+
+            last_token_id = quote_hop.token_out.clone();
+            last_amount = quote_hop.amount_out.clone();
+            quote_hops.push(quote_hop);
+        }
+
+        (last_amount, quote_hops).into()
+    }
+
+    /// Computes the input needed to receive exactly `amount_out` at the end of the path,
+    /// each hop being a pair address and the token wanted from it.
+    /// Returns the input amount and the amounts of each hop, which can be used
+    /// as fixed output operations for `multiPairSwap`.
+    #[view(getMultiPairSwapAmountIn)]
+    fn get_multi_pair_swap_amount_in(
+        &self,
+        amount_out: BigUint,
+        path: MultiValueEncoded<MultiValue2<ManagedAddress, TokenIdentifier>>,
+    ) -> MultiValue2<BigUint, ManagedVec<SwapQuoteHop<Self::Api>>> {
+        require!(amount_out > 0u64, "Invalid amount. Should not be zero");
+
+        let mut hops = ManagedVec::<Self::Api, SwapRouteHop<Self::Api>>::new();
+        for entry in path.into_iter() {
+            let (pair_address, token_wanted) = entry.into_tuple();
+            hops.push(SwapRouteHop {
+                pair_address,
+                token_wanted,
+            });
+        }
+        require!(!hops.is_empty(), "Invalid swap path. Should not be empty");
+
+        let mut reversed_quote_hops = ManagedVec::<Self::Api, SwapQuoteHop<Self::Api>>::new();
+        let mut amount_wanted = amount_out;
+        for hop_index in (0..hops.len()).rev() {
+            let hop = hops.get(hop_index);
+            let token_in = self.get_other_pair_token(&hop.pair_address, &hop.token_wanted);
+            if hop_index > 0 {
+                require!(
+                    hops.get(hop_index - 1).token_wanted == token_in,
+                    "Invalid swap path"
+                );
+            }
+
+            let amount_in = self.get_pair_amount_in(
+                &hop.pair_address,
+                &token_in,
+                &hop.token_wanted,
+                &amount_wanted,
+            );
+
+            reversed_quote_hops.push(SwapQuoteHop {
+                pair_address: hop.pair_address,
+                token_in,
+                amount_in: amount_in.clone(),
+                token_out: hop.token_wanted,
+                amount_out: amount_wanted,
+                residuum: BigUint::zero(),
+            });
+            amount_wanted = amount_in;
+        }
+
+        let mut quote_hops = ManagedVec::new();
+        for hop_index in (0..reversed_quote_hops.len()).rev() {
+            quote_hops.push(reversed_quote_hops.get(hop_index));
+        }
+
+        (amount_wanted, quote_hops).into()
+    }
+
+    /// Same as the pair's `getAmountOut` view. Only plain constant product pairs, with
+    /// a fixed fee, are quoted from their storage, the others through the view.
+    /// Pairs with before swap hooks cannot be quoted, as the hooks may change the fee.
+    fn get_pair_amount_out(
+        &self,
+        pair_address: &ManagedAddress,
+        token_in: &TokenIdentifier,
+        amount_in: &BigUint,
+        token_out: &TokenIdentifier,
+    ) -> BigUint {
+        self.require_quotable_pair(pair_address);
+        if !self.can_quote_from_pair_storage(pair_address) {
+            return self
+                .pair_contract_proxy(pair_address.clone())
+                .get_amount_out_view(
+                    token_in.clone(),
+                    amount_in.clone(),
+                    OptionalValue::<ManagedAddress>::None,
+                )
+                .execute_on_dest_context();
+        }
+
+        require!(*amount_in > 0u64, ERROR_ZERO_AMOUNT);
+        let reserve_in = self.read_pair_reserve(pair_address, token_in);
+        let reserve_out = self.read_pair_reserve(pair_address, token_out);
+        let total_fee_percent: u64 =
+            self.read_storage_from_quoted_pair(pair_address, PAIR_TOTAL_FEE_PERCENT_STORAGE_KEY);
+
+        let amount_in_with_fee = amount_in * (MAX_PERCENTAGE - total_fee_percent);
+        let numerator = &amount_in_with_fee * &reserve_out;
+        let denominator = (reserve_in * MAX_PERCENTAGE) + amount_in_with_fee;
+        let amount_out = numerator / denominator;
+        require!(reserve_out > amount_out, ERROR_NOT_ENOUGH_RESERVE);
+
+        amount_out
+    }
+
+    /// Same as the pair's `getAmountIn` view, with the same limits as `get_pair_amount_out`.
+    fn get_pair_amount_in(
+        &self,
+        pair_address: &ManagedAddress,
+        token_in: &TokenIdentifier,
+        token_wanted: &TokenIdentifier,
+        amount_wanted: &BigUint,
+    ) -> BigUint {
+        self.require_quotable_pair(pair_address);
+        if !self.can_quote_from_pair_storage(pair_address) {
+            return self
+                .pair_contract_proxy(pair_address.clone())
+                .get_amount_in_view(
+                    token_wanted.clone(),
+                    amount_wanted.clone(),
+                    OptionalValue::<ManagedAddress>::None,
+                )
+                .execute_on_dest_context();
+        }
+
+        require!(*amount_wanted > 0u64, ERROR_ZERO_AMOUNT);
+        let reserve_in = self.read_pair_reserve(pair_address, token_in);
+        let reserve_out = self.read_pair_reserve(pair_address, token_wanted);
+        require!(reserve_out > *amount_wanted, ERROR_NOT_ENOUGH_RESERVE);
+        let total_fee_percent: u64 =
+            self.read_storage_from_quoted_pair(pair_address, PAIR_TOTAL_FEE_PERCENT_STORAGE_KEY);
+
+        let numerator = reserve_in * amount_wanted * MAX_PERCENTAGE;
+        let denominator = (reserve_out - amount_wanted) * (MAX_PERCENTAGE - total_fee_percent);
+
+        (numerator / denominator) + 1u64
+    }
+
+    /// The StableSwap curve and the dynamic fee depend on the amplification ramp
+    /// and on the price observations, which are left to the pair's views.
+    fn can_quote_from_pair_storage(&self, pair_address: &ManagedAddress) -> bool {
+        let curve_type: CurveType =
+            self.read_storage_from_quoted_pair(pair_address, PAIR_CURVE_TYPE_STORAGE_KEY);
+        let dynamic_fee_config: ManagedBuffer =
+            self.read_storage_from_quoted_pair(pair_address, PAIR_DYNAMIC_FEE_CONFIG_STORAGE_KEY);
+
+        curve_type == CurveType::ConstantProduct && dynamic_fee_config.is_empty()
+    }
+
+    fn require_quotable_pair(&self, pair_address: &ManagedAddress) {
+        require!(
+            !self.has_before_swap_hooks(pair_address),
+            "Cannot quote a pair with before swap hooks"
+        );
+    }
+
+    fn has_before_swap_hooks(&self, pair_address: &ManagedAddress) -> bool {
+        let mut key_buffer = ManagedBuffer::new_from_bytes(PAIR_HOOKS_STORAGE_KEY);
+        HookType::BeforeSwap
+            .dep_encode(&mut key_buffer)
+            .unwrap_or_else(|err| sc_panic!(err.message_str()));
+        key_buffer.append_bytes(VEC_MAPPER_LEN_STORAGE_KEY_SUFFIX);
+
+        let hooks_count: usize = self
+            .storage_raw()
+            .read_from_address(pair_address, key_buffer);
+        hooks_count > 0
+    }
+
+    fn read_pair_reserve(
+        &self,
+        pair_address: &ManagedAddress,
+        token_id: &TokenIdentifier,
+    ) -> BigUint {
+        let mut key_buffer = ManagedBuffer::new_from_bytes(PAIR_RESERVE_STORAGE_KEY);
+        let token_id_buffer = token_id.as_managed_buffer();
+        key_buffer.append_u32_be(token_id_buffer.len() as u32);
+        key_buffer.append(token_id_buffer);
+
+        self.storage_raw()
+            .read_from_address(pair_address, key_buffer)
+    }
+
+    fn read_storage_from_quoted_pair<T: TopDecode>(
+        &self,
+        pair_address: &ManagedAddress,
+        storage_key: &[u8],
+    ) -> T {
+        let key_buffer = ManagedBuffer::new_from_bytes(storage_key);
+        self.storage_raw()
+            .read_from_address(pair_address, key_buffer)
+    }
+
+    /// Returns the token of the pair which is swapped for `token_id`.
+    fn get_other_pair_token(
+        &self,
+        pair_address: &ManagedAddress,
+        token_id: &TokenIdentifier,
+    ) -> TokenIdentifier {
+        let pair_tokens = match self.address_pair_map().get(pair_address) {
+            Some(pair_tokens) => pair_tokens,
+            None => sc_panic!("Not a pair SC"),
+        };
+
+        let other_token_id = if &pair_tokens.first_token_id == token_id {
+            pair_tokens.second_token_id
+        } else if &pair_tokens.second_token_id == token_id {
+            pair_tokens.first_token_id
+        } else {
+            sc_panic!("Invalid token wanted");
+        };
+
+        // the swap paths are sent as multi ESDT transfers, so they cannot go through EGLD
+        match other_token_id.into_esdt_option() {
+            Some(other_token_id) => other_token_id,
+            None => sc_panic!("Cannot swap through EGLD"),
+        }
     }
 
     fn actual_swap_fixed_input(
@@ -80,7 +571,7 @@ pub trait MultiPairSwap:
         amount_out_min: BigUint,
     ) -> EsdtTokenPayment<Self::Api> {
         self.pair_contract_proxy(pair_address)
-            .swap_tokens_fixed_input(token_out, amount_out_min)
+            .swap_tokens_fixed_input(token_out, amount_out_min, OptionalValue::<u64>::None)
             .with_esdt_transfer((token_in, 0, amount_in))
             .execute_on_dest_context()
     }
@@ -95,7 +586,7 @@ pub trait MultiPairSwap:
     ) -> (EsdtTokenPayment<Self::Api>, EsdtTokenPayment<Self::Api>) {
         let call_result: MultiValue2<EsdtTokenPayment<Self::Api>, EsdtTokenPayment<Self::Api>> =
             self.pair_contract_proxy(pair_address)
-                .swap_tokens_fixed_output(token_out, amount_out)
+                .swap_tokens_fixed_output(token_out, amount_out, OptionalValue::<u64>::None)
                 .with_esdt_transfer((token_in, 0, amount_in_max))
                 .execute_on_dest_context();
 
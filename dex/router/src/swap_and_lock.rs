@@ -0,0 +1,89 @@
+multiversx_sc::imports!();
+
+use energy_factory::ProxyTrait as _;
+
+use crate::multi_pair_swap::SwapOperationType;
+use crate::{config, events, factory, multi_pair_swap};
+
+static ENERGY_FACTORY_BASE_ASSET_STORAGE_KEY: &[u8] = b"baseAssetTokenId";
+
+#[multiversx_sc::module]
+pub trait SwapAndLockModule:
+    config::ConfigModule
+    + factory::FactoryModule
+    + token_send::TokenSendModule
+    + multi_pair_swap::MultiPairSwap
+    + events::EventsModule
+{
+    #[only_owner]
+    #[endpoint(setEnergyFactoryAddress)]
+    fn set_energy_factory_address(&self, sc_address: ManagedAddress) {
+        require!(
+            self.blockchain().is_smart_contract(&sc_address),
+            "Invalid SC Address"
+        );
+
+        self.energy_factory_address().set(&sc_address);
+    }
+
+    /// Performs the swap operations, like `multiPairSwap`, and locks the output
+    /// in the energy factory for `lock_epochs`, which must be one of its lock options.
+    /// The last operation must output the energy factory's base asset.
+    /// The locked tokens and the energy go to `destination`, while the residuums
+    /// of fixed output swaps are sent back to the caller.
+    /// Returns the locked tokens payment.
+    #[payable("*")]
+    #[endpoint(multiPairSwapAndLock)]
+    fn multi_pair_swap_and_lock(
+        &self,
+        lock_epochs: u64,
+        destination: ManagedAddress,
+        swap_operations: MultiValueEncoded<SwapOperationType<Self::Api>>,
+    ) -> EsdtTokenPayment {
+        require!(self.is_active(), "Not active");
+        require!(
+            !self.energy_factory_address().is_empty(),
+            "Energy factory address not set"
+        );
+
+        let (token_id, nonce, amount) = self.call_value().single_esdt().into_tuple();
+        require!(nonce == 0, "Invalid nonce. Should be zero");
+        require!(amount > 0u64, "Invalid amount. Should not be zero");
+        require!(
+            !swap_operations.is_empty(),
+            "Invalid swap operations chain. Should not be empty"
+        );
+
+        let energy_factory_address = self.energy_factory_address().get();
+        let base_asset_token_id: TokenIdentifier = self.storage_raw().read_from_address(
+            &energy_factory_address,
+            ManagedBuffer::new_from_bytes(ENERGY_FACTORY_BASE_ASSET_STORAGE_KEY),
+        );
+
+        let (swap_payment, residuums) = self.perform_swap_operations(
+            EsdtTokenPayment::new(token_id, nonce, amount),
+            swap_operations,
+        );
+        require!(
+            swap_payment.token_identifier == base_asset_token_id,
+            "Swap output must be the energy factory base asset"
+        );
+
+        let caller = self.blockchain().get_caller();
+        if !residuums.is_empty() {
+            self.send().direct_multi(&caller, &residuums);
+        }
+
+        self.energy_factory_proxy(energy_factory_address)
+            .lock_tokens_endpoint(lock_epochs, OptionalValue::Some(destination))
+            .with_esdt_transfer(swap_payment)
+            .execute_on_dest_context()
+    }
+
+    #[proxy]
+    fn energy_factory_proxy(&self, sc_address: ManagedAddress) -> energy_factory::Proxy<Self::Api>;
+
+    #[view(getEnergyFactoryAddress)]
+    #[storage_mapper("energyFactoryAddress")]
+    fn energy_factory_address(&self) -> SingleValueMapper<ManagedAddress>;
+}
@@ -0,0 +1,133 @@
+multiversx_sc::imports!();
+
+use pair::pair_actions::single_token_liq::ProxyTrait as _;
+
+use crate::multi_pair_swap::SwapOperationType;
+use crate::{config, events, factory, multi_pair_swap};
+
+type AddLiquidityResultType<M> =
+    MultiValue3<EsdtTokenPayment<M>, EsdtTokenPayment<M>, EsdtTokenPayment<M>>;
+pub type EnterFarmResultType<M> = MultiValue2<EsdtTokenPayment<M>, EsdtTokenPayment<M>>;
+
+mod farm_proxy {
+    multiversx_sc::imports!();
+    use super::EnterFarmResultType;
+
+    #[multiversx_sc::proxy]
+    pub trait FarmProxy {
+        #[payable("*")]
+        #[endpoint(enterFarm)]
+        fn enter_farm(
+            &self,
+            opt_orig_caller: OptionalValue<ManagedAddress>,
+        ) -> EnterFarmResultType<Self::Api>;
+    }
+}
+
+#[multiversx_sc::module]
+pub trait ZapModule:
+    config::ConfigModule
+    + factory::FactoryModule
+    + token_send::TokenSendModule
+    + multi_pair_swap::MultiPairSwap
+    + events::EventsModule
+{
+    /// Swaps the payment into one of the tokens of `pair_address`, through the given
+    /// swap operations, which can be empty if the payment already is a pool token.
+    /// The pool token is then added as liquidity through the pair's `addLiquiditySingleToken`,
+    /// and the LP tokens are used to enter the farm on behalf of the caller.
+    /// The router must be whitelisted in the farm.
+    /// Returns the farm tokens and the boosted rewards. The leftovers are sent back to the caller.
+    #[payable("*")]
+    #[endpoint(zapIntoFarm)]
+    fn zap_into_farm(
+        &self,
+        pair_address: ManagedAddress,
+        farm_address: ManagedAddress,
+        lp_token_amount_min: BigUint,
+        swap_operations: MultiValueEncoded<SwapOperationType<Self::Api>>,
+    ) -> EnterFarmResultType<Self::Api> {
+        require!(self.is_active(), "Not active");
+        require!(
+            self.blockchain().is_smart_contract(&farm_address),
+            "Invalid SC Address"
+        );
+
+        let pair_tokens = match self.address_pair_map().get(&pair_address) {
+            Some(pair_tokens) => pair_tokens,
+            None => sc_panic!("Not a pair SC"),
+        };
+
+        let (token_id, nonce, amount) = self.call_value().single_esdt().into_tuple();
+        require!(nonce == 0, "Invalid nonce. Should be zero");
+        require!(amount > 0u64, "Invalid amount. Should not be zero");
+
+        let (pool_payment, mut output_payments) = self.perform_swap_operations(
+            EsdtTokenPayment::new(token_id, nonce, amount),
+            swap_operations,
+        );
+        require!(
+            pair_tokens.first_token_id == pool_payment.token_identifier
+                || pair_tokens.second_token_id == pool_payment.token_identifier,
+            "Swap output must be one of the pool tokens"
+        );
+
+        // the leftovers of the pool tokens are sent back by the pair to the router
+        let first_token_balance_before =
+            self.get_zap_token_balance(&pair_tokens.first_token_id, &pool_payment);
+        let second_token_balance_before =
+            self.get_zap_token_balance(&pair_tokens.second_token_id, &pool_payment);
+
+        let add_liq_result: AddLiquidityResultType<Self::Api> = self
+            .pair_contract_proxy(pair_address)
+            .add_liquidity_single_token(lp_token_amount_min, OptionalValue::<u64>::None)
+            .with_esdt_transfer(pool_payment)
+            .execute_on_dest_context();
+        let (lp_payment, _, _) = add_liq_result.into_tuple();
+
+        let caller = self.blockchain().get_caller();
+        let enter_farm_result: EnterFarmResultType<Self::Api> = self
+            .farm_proxy(farm_address)
+            .enter_farm(OptionalValue::Some(caller.clone()))
+            .with_esdt_transfer(lp_payment)
+            .execute_on_dest_context();
+        let (farm_tokens, boosted_rewards) = enter_farm_result.into_tuple();
+
+        for (pool_token_id, balance_before) in [
+            (pair_tokens.first_token_id, first_token_balance_before),
+            (pair_tokens.second_token_id, second_token_balance_before),
+        ] {
+            let balance_after = self.blockchain().get_sc_balance(&pool_token_id, 0);
+            let leftover_amount = balance_after - balance_before;
+            match pool_token_id.into_esdt_option() {
+                Some(pool_token_id) => {
+                    output_payments.push(EsdtTokenPayment::new(pool_token_id, 0, leftover_amount))
+                }
+                // the EGLD cannot be part of the multi ESDT transfer
+                None => self.send().direct_non_zero_egld(&caller, &leftover_amount),
+            }
+        }
+        output_payments.push(farm_tokens.clone());
+        output_payments.push(boosted_rewards.clone());
+        self.send_multiple_tokens_if_not_zero(&caller, &output_payments);
+
+        (farm_tokens, boosted_rewards).into()
+    }
+
+    /// The router's balance of a pool token, without the part sent to the pair.
+    fn get_zap_token_balance(
+        &self,
+        token_id: &EgldOrEsdtTokenIdentifier,
+        pool_payment: &EsdtTokenPayment,
+    ) -> BigUint {
+        let balance = self.blockchain().get_sc_balance(token_id, 0);
+        if token_id == &pool_payment.token_identifier {
+            balance - &pool_payment.amount
+        } else {
+            balance
+        }
+    }
+
+    #[proxy]
+    fn farm_proxy(&self, sc_address: ManagedAddress) -> farm_proxy::Proxy<Self::Api>;
+}
@@ -2,8 +2,8 @@ use multiversx_sc::codec::multi_types::{MultiValue4, OptionalValue};
 use multiversx_sc::types::{Address, EsdtLocalRole, ManagedAddress, MultiValueEncoded};
 use multiversx_sc_scenario::whitebox_legacy::TxTokenTransfer;
 use multiversx_sc_scenario::{
-    managed_address, managed_biguint, managed_buffer, managed_token_id, rust_biguint,
-    whitebox_legacy::*, DebugApi,
+    managed_address, managed_biguint, managed_buffer, managed_token_id, managed_token_id_wrapped,
+    rust_biguint, whitebox_legacy::*, DebugApi,
 };
 
 pub const PAIR_WASM_PATH: &str = "pair/output/pair.wasm";
@@ -83,8 +83,8 @@ where
 
         blockchain_wrapper
             .execute_tx(&owner_addr, &mex_pair_wrapper, &rust_zero, |sc| {
-                let first_token_id = managed_token_id!(WEGLD_TOKEN_ID);
-                let second_token_id = managed_token_id!(MEX_TOKEN_ID);
+                let first_token_id = managed_token_id_wrapped!(WEGLD_TOKEN_ID);
+                let second_token_id = managed_token_id_wrapped!(MEX_TOKEN_ID);
                 let router_address = managed_address!(&owner_addr);
                 let router_owner_address = managed_address!(&owner_addr);
                 let total_fee_percent = 300u64;
@@ -110,8 +110,8 @@ where
 
         blockchain_wrapper
             .execute_tx(&owner_addr, &usdc_pair_wrapper, &rust_zero, |sc| {
-                let first_token_id = managed_token_id!(WEGLD_TOKEN_ID);
-                let second_token_id = managed_token_id!(USDC_TOKEN_ID);
+                let first_token_id = managed_token_id_wrapped!(WEGLD_TOKEN_ID);
+                let second_token_id = managed_token_id_wrapped!(USDC_TOKEN_ID);
                 let router_address = managed_address!(&owner_addr);
                 let router_owner_address = managed_address!(&owner_addr);
                 let total_fee_percent = 300u64;
@@ -141,15 +141,15 @@ where
 
                 sc.pair_map().insert(
                     PairTokens {
-                        first_token_id: managed_token_id!(WEGLD_TOKEN_ID),
-                        second_token_id: managed_token_id!(MEX_TOKEN_ID),
+                        first_token_id: managed_token_id_wrapped!(WEGLD_TOKEN_ID),
+                        second_token_id: managed_token_id_wrapped!(MEX_TOKEN_ID),
                     },
                     managed_address!(mex_pair_wrapper.address_ref()),
                 );
                 sc.pair_map().insert(
                     PairTokens {
-                        first_token_id: managed_token_id!(WEGLD_TOKEN_ID),
-                        second_token_id: managed_token_id!(USDC_TOKEN_ID),
+                        first_token_id: managed_token_id_wrapped!(WEGLD_TOKEN_ID),
+                        second_token_id: managed_token_id_wrapped!(USDC_TOKEN_ID),
                     },
                     managed_address!(usdc_pair_wrapper.address_ref()),
                 );
@@ -220,6 +220,7 @@ where
                     sc.add_liquidity(
                         managed_biguint!(ADD_LIQUIDITY_TOKENS),
                         managed_biguint!(ADD_LIQUIDITY_TOKENS),
+                        OptionalValue::None,
                     );
                 },
             )
@@ -247,6 +248,7 @@ where
                     sc.add_liquidity(
                         managed_biguint!(ADD_LIQUIDITY_TOKENS),
                         managed_biguint!(ADD_LIQUIDITY_TOKENS),
+                        OptionalValue::None,
                     );
                 },
             )
@@ -1,28 +1,45 @@
 #![allow(deprecated)]
 
 mod router_setup;
+use energy_factory::SimpleLockEnergy;
+use farm::Farm;
+use farm_token::FarmTokenModule;
 use multiversx_sc::{
-    codec::multi_types::OptionalValue,
+    codec::multi_types::{MultiValue4, OptionalValue},
     storage::mappers::StorageTokenWrapper,
     types::{
         Address, EgldOrEsdtTokenIdentifier, EsdtLocalRole, ManagedAddress, ManagedVec,
-        MultiValueEncoded,
+        MultiValueEncoded, TokenIdentifier,
     },
 };
+use multiversx_sc_modules::pause::PauseModule;
 use pair::{
-    config::ConfigModule as PairConfigModule, pair_actions::initial_liq::InitialLiquidityModule,
+    config::ConfigModule as PairConfigModule,
+    hooks::{HookType, HooksModule},
+    pair_actions::initial_liq::InitialLiquidityModule,
     Pair,
 };
 use pausable::{PausableModule, State};
 use router::{
-    config::ConfigModule, enable_swap_by_user::EnableSwapByUserModule, factory::PairTokens,
-    multi_pair_swap::SWAP_TOKENS_FIXED_INPUT_FUNC_NAME, Router,
+    auto_route::{AutoRouteModule, MAX_SWAP_PATH_HOPS, MAX_SWAP_PATH_INTERMEDIATE_TOKENS},
+    config::ConfigModule,
+    enable_swap_by_user::EnableSwapByUserModule,
+    factory::{FactoryModule, PairTokens},
+    multi_pair_swap::{
+        MultiPairSwap, SwapOperationType, SwapQuoteHop, SwapRouteHop,
+        SWAP_TOKENS_FIXED_INPUT_FUNC_NAME, SWAP_TOKENS_FIXED_OUTPUT_FUNC_NAME,
+    },
+    swap_and_lock::SwapAndLockModule,
+    zap::ZapModule,
+    Router,
 };
 use router_setup::*;
+use sc_whitelist_module::SCWhitelistModule;
 
 use multiversx_sc_scenario::{
-    managed_address, managed_biguint, managed_token_id, managed_token_id_wrapped, rust_biguint,
-    whitebox_legacy::BlockchainStateWrapper, whitebox_legacy::TxTokenTransfer, DebugApi,
+    managed_address, managed_biguint, managed_buffer, managed_token_id, managed_token_id_wrapped,
+    rust_biguint, whitebox_legacy::BlockchainStateWrapper, whitebox_legacy::TxTokenTransfer,
+    DebugApi,
 };
 use simple_lock::{
     locked_token::{LockedTokenAttributes, LockedTokenModule},
@@ -65,8 +82,8 @@ fn test_router_upgrade_pair() {
     // setup pair
     b_mock
         .execute_tx(&owner, &pair_template_wrapper, &rust_zero, |sc| {
-            let first_token_id = managed_token_id!(CUSTOM_TOKEN_ID);
-            let second_token_id = managed_token_id!(USDC_TOKEN_ID);
+            let first_token_id = managed_token_id_wrapped!(CUSTOM_TOKEN_ID);
+            let second_token_id = managed_token_id_wrapped!(USDC_TOKEN_ID);
             let router_address = managed_address!(&Address::zero());
             let router_owner_address = managed_address!(&owner);
 
@@ -97,8 +114,8 @@ fn test_router_upgrade_pair() {
 
     b_mock
         .execute_tx(&user, &router_wrapper, &rust_zero, |sc| {
-            let first_token_id = managed_token_id!(CUSTOM_TOKEN_ID);
-            let second_token_id = managed_token_id!(USDC_TOKEN_ID);
+            let first_token_id = managed_token_id_wrapped!(CUSTOM_TOKEN_ID);
+            let second_token_id = managed_token_id_wrapped!(USDC_TOKEN_ID);
             let _new_pair_address = sc.create_pair_endpoint(
                 first_token_id,
                 second_token_id,
@@ -111,8 +128,8 @@ fn test_router_upgrade_pair() {
 
     b_mock
         .execute_tx(&owner, &router_wrapper, &rust_zero, |sc| {
-            let first_token_id = managed_token_id!(CUSTOM_TOKEN_ID);
-            let second_token_id = managed_token_id!(USDC_TOKEN_ID);
+            let first_token_id = managed_token_id_wrapped!(CUSTOM_TOKEN_ID);
+            let second_token_id = managed_token_id_wrapped!(USDC_TOKEN_ID);
             sc.upgrade_pair_endpoint(
                 first_token_id,
                 second_token_id,
@@ -131,6 +148,107 @@ fn test_router_upgrade_pair() {
         .assert_ok();
 }
 
+#[test]
+fn test_router_create_egld_pair() {
+    let rust_zero = rust_biguint!(0u64);
+    let mut b_mock = BlockchainStateWrapper::new();
+    let owner = b_mock.create_user_account(&rust_zero);
+    let user = b_mock.create_user_account(&rust_zero);
+
+    let router_wrapper = b_mock.create_sc_account(
+        &rust_zero,
+        Some(&owner),
+        router::contract_obj,
+        ROUTER_WASM_PATH,
+    );
+    let pair_template_wrapper = b_mock.create_sc_account(
+        &rust_zero,
+        Some(router_wrapper.address_ref()),
+        pair::contract_obj,
+        PAIR_WASM_PATH,
+    );
+    b_mock
+        .execute_tx(&owner, &pair_template_wrapper, &rust_zero, |sc| {
+            sc.init(
+                managed_token_id_wrapped!(CUSTOM_TOKEN_ID),
+                managed_token_id_wrapped!(USDC_TOKEN_ID),
+                managed_address!(&Address::zero()),
+                managed_address!(&owner),
+                0,
+                0,
+                managed_address!(&user),
+                MultiValueEncoded::<DebugApi, ManagedAddress<DebugApi>>::new(),
+            );
+        })
+        .assert_ok();
+
+    let pair_wrapper =
+        b_mock.prepare_deploy_from_sc(router_wrapper.address_ref(), pair::contract_obj);
+
+    b_mock
+        .execute_tx(&owner, &router_wrapper, &rust_zero, |sc| {
+            sc.init(OptionalValue::Some(managed_address!(
+                pair_template_wrapper.address_ref()
+            )));
+            sc.set_pair_creation_enabled(true);
+        })
+        .assert_ok();
+
+    b_mock
+        .execute_tx(&user, &router_wrapper, &rust_zero, |sc| {
+            let new_pair_address = sc.create_pair_endpoint(
+                EgldOrEsdtTokenIdentifier::egld(),
+                managed_token_id_wrapped!(USDC_TOKEN_ID),
+                managed_address!(&user),
+                OptionalValue::None,
+                MultiValueEncoded::<DebugApi, ManagedAddress<DebugApi>>::new(),
+            );
+            assert_eq!(
+                new_pair_address,
+                managed_address!(pair_wrapper.address_ref())
+            );
+        })
+        .assert_ok();
+
+    b_mock
+        .execute_tx(&user, &router_wrapper, &rust_zero, |sc| {
+            let _ = sc.create_pair_endpoint(
+                managed_token_id_wrapped!(USDC_TOKEN_ID),
+                EgldOrEsdtTokenIdentifier::egld(),
+                managed_address!(&user),
+                OptionalValue::None,
+                MultiValueEncoded::<DebugApi, ManagedAddress<DebugApi>>::new(),
+            );
+        })
+        .assert_user_error("Pair already exists");
+
+    b_mock
+        .execute_query(&router_wrapper, |sc| {
+            let pair_address = managed_address!(pair_wrapper.address_ref());
+            assert_eq!(
+                sc.get_pair(
+                    managed_token_id_wrapped!(USDC_TOKEN_ID),
+                    EgldOrEsdtTokenIdentifier::egld()
+                ),
+                pair_address
+            );
+
+            let pair_tokens = sc.get_pair_tokens(pair_address);
+            assert!(pair_tokens.first_token_id.is_egld());
+            assert_eq!(
+                pair_tokens.second_token_id,
+                managed_token_id_wrapped!(USDC_TOKEN_ID)
+            );
+        })
+        .assert_ok();
+
+    b_mock
+        .execute_query(&pair_wrapper, |sc| {
+            assert!(sc.first_token_id().get().is_egld());
+        })
+        .assert_ok();
+}
+
 #[test]
 fn test_multi_pair_swap() {
     let mut router_setup = RouterSetup::new(router::contract_obj, pair::contract_obj);
@@ -188,6 +306,863 @@ fn test_multi_pair_swap() {
     );
 }
 
+#[test]
+fn test_multi_pair_swap_deadline() {
+    let mut router_setup = RouterSetup::new(router::contract_obj, pair::contract_obj);
+    router_setup.migrate_pair_map();
+
+    router_setup.add_liquidity();
+
+    router_setup.blockchain_wrapper.set_block_timestamp(100);
+
+    let mex_pair_address = router_setup.mex_pair_wrapper.address_ref().clone();
+    let usdc_pair_address = router_setup.usdc_pair_wrapper.address_ref().clone();
+
+    let swap_operations = || {
+        let mut swap_operations = MultiValueEncoded::<DebugApi, SwapOperationType<DebugApi>>::new();
+        swap_operations.push(
+            (
+                managed_address!(&mex_pair_address),
+                managed_buffer!(SWAP_TOKENS_FIXED_INPUT_FUNC_NAME),
+                managed_token_id!(WEGLD_TOKEN_ID),
+                managed_biguint!(1),
+            )
+                .into(),
+        );
+        swap_operations.push(
+            (
+                managed_address!(&usdc_pair_address),
+                managed_buffer!(SWAP_TOKENS_FIXED_INPUT_FUNC_NAME),
+                managed_token_id!(USDC_TOKEN_ID),
+                managed_biguint!(1),
+            )
+                .into(),
+        );
+        swap_operations
+    };
+
+    router_setup
+        .blockchain_wrapper
+        .execute_esdt_transfer(
+            &router_setup.user_address,
+            &router_setup.router_wrapper,
+            MEX_TOKEN_ID,
+            0,
+            &rust_biguint!(100_000),
+            |sc| {
+                sc.multi_pair_swap_with_deadline(99, swap_operations());
+            },
+        )
+        .assert_user_error("Deadline expired");
+
+    router_setup
+        .blockchain_wrapper
+        .execute_esdt_transfer(
+            &router_setup.user_address,
+            &router_setup.router_wrapper,
+            MEX_TOKEN_ID,
+            0,
+            &rust_biguint!(100_000),
+            |sc| {
+                sc.multi_pair_swap_with_deadline(100, swap_operations());
+            },
+        )
+        .assert_ok();
+
+    router_setup.blockchain_wrapper.check_esdt_balance(
+        &router_setup.user_address,
+        MEX_TOKEN_ID,
+        &rust_biguint!(4_999_900_000),
+    );
+    router_setup.blockchain_wrapper.check_esdt_balance(
+        &router_setup.user_address,
+        USDC_TOKEN_ID,
+        &rust_biguint!(5_000_082_909),
+    );
+}
+
+#[test]
+fn test_swap_auto_route() {
+    let mut router_setup = RouterSetup::new(router::contract_obj, pair::contract_obj);
+    router_setup.migrate_pair_map();
+    router_setup.add_liquidity();
+
+    let mex_pair_address = router_setup.mex_pair_wrapper.address_ref().clone();
+    let usdc_pair_address = router_setup.usdc_pair_wrapper.address_ref().clone();
+    let wegld_intermediate = || {
+        let mut intermediate_tokens = MultiValueEncoded::new();
+        intermediate_tokens.push(managed_token_id!(WEGLD_TOKEN_ID));
+        intermediate_tokens
+    };
+    router_setup
+        .blockchain_wrapper
+        .execute_query(&router_setup.router_wrapper, |sc| {
+            // MEX -> WEGLD -> USDC, same output as the manual route of the multi pair swap test
+            let (amount_out, swap_path) = sc
+                .get_best_swap_path(
+                    managed_token_id!(MEX_TOKEN_ID),
+                    managed_biguint!(100_000),
+                    managed_token_id!(USDC_TOKEN_ID),
+                    MAX_SWAP_PATH_HOPS,
+                    wegld_intermediate(),
+                )
+                .into_tuple();
+            assert_eq!(amount_out, managed_biguint!(82_909));
+            assert_eq!(swap_path.len(), 2);
+            assert_eq!(
+                swap_path.get(0).pair_address,
+                managed_address!(&mex_pair_address)
+            );
+            assert_eq!(
+                swap_path.get(0).token_out,
+                managed_token_id!(WEGLD_TOKEN_ID)
+            );
+            assert_eq!(
+                swap_path.get(1).pair_address,
+                managed_address!(&usdc_pair_address)
+            );
+            assert_eq!(swap_path.get(1).amount_out, managed_biguint!(82_909));
+
+            // without intermediate tokens, the common tokens are used, and WEGLD is not one of them
+            let (amount_out, swap_path) = sc
+                .get_best_swap_path(
+                    managed_token_id!(MEX_TOKEN_ID),
+                    managed_biguint!(100_000),
+                    managed_token_id!(USDC_TOKEN_ID),
+                    MAX_SWAP_PATH_HOPS,
+                    MultiValueEncoded::new(),
+                )
+                .into_tuple();
+            assert_eq!(amount_out, managed_biguint!(0));
+            assert!(swap_path.is_empty());
+
+            // No direct pair
+            let (_, swap_path) = sc
+                .get_best_swap_path(
+                    managed_token_id!(MEX_TOKEN_ID),
+                    managed_biguint!(100_000),
+                    managed_token_id!(USDC_TOKEN_ID),
+                    1,
+                    MultiValueEncoded::new(),
+                )
+                .into_tuple();
+            assert!(swap_path.is_empty());
+        })
+        .assert_ok();
+
+    // the number of intermediate tokens is capped, to bound the pairs visited
+    router_setup
+        .blockchain_wrapper
+        .execute_query(&router_setup.router_wrapper, |sc| {
+            let mut intermediate_tokens = MultiValueEncoded::new();
+            for index in 0..=MAX_SWAP_PATH_INTERMEDIATE_TOKENS {
+                intermediate_tokens.push(TokenIdentifier::from(
+                    format!("TOKEN{}-abcdef", index).as_bytes(),
+                ));
+            }
+            let _ = sc.get_best_swap_path(
+                managed_token_id!(MEX_TOKEN_ID),
+                managed_biguint!(100_000),
+                managed_token_id!(USDC_TOKEN_ID),
+                MAX_SWAP_PATH_HOPS,
+                intermediate_tokens,
+            );
+        })
+        .assert_user_error("Too many intermediate tokens");
+
+    // a longer list of common tokens is not an error, only the first ones are used
+    router_setup
+        .blockchain_wrapper
+        .execute_tx(
+            &router_setup.owner_address,
+            &router_setup.router_wrapper,
+            &rust_biguint!(0),
+            |sc| {
+                let mut common_tokens = MultiValueEncoded::new();
+                common_tokens.push(managed_token_id!(WEGLD_TOKEN_ID));
+                for index in 0..MAX_SWAP_PATH_INTERMEDIATE_TOKENS {
+                    common_tokens.push(TokenIdentifier::from(
+                        format!("TOKEN{}-abcdef", index).as_bytes(),
+                    ));
+                }
+                sc.add_common_tokens_for_user_pairs(common_tokens);
+            },
+        )
+        .assert_ok();
+    router_setup
+        .blockchain_wrapper
+        .execute_query(&router_setup.router_wrapper, |sc| {
+            let (amount_out, swap_path) = sc
+                .get_best_swap_path(
+                    managed_token_id!(MEX_TOKEN_ID),
+                    managed_biguint!(100_000),
+                    managed_token_id!(USDC_TOKEN_ID),
+                    MAX_SWAP_PATH_HOPS,
+                    MultiValueEncoded::new(),
+                )
+                .into_tuple();
+            assert_eq!(amount_out, managed_biguint!(82_909));
+            assert_eq!(swap_path.len(), 2);
+        })
+        .assert_ok();
+
+    // Min out guard
+    router_setup
+        .blockchain_wrapper
+        .execute_esdt_transfer(
+            &router_setup.user_address,
+            &router_setup.router_wrapper,
+            MEX_TOKEN_ID,
+            0,
+            &rust_biguint!(100_000),
+            |sc| {
+                let _ = sc.swap_auto_route(
+                    managed_token_id!(USDC_TOKEN_ID),
+                    managed_biguint!(82_910),
+                    MAX_SWAP_PATH_HOPS,
+                    wegld_intermediate(),
+                );
+            },
+        )
+        .assert_user_error("Slippage exceeded");
+
+    router_setup
+        .blockchain_wrapper
+        .execute_esdt_transfer(
+            &router_setup.user_address,
+            &router_setup.router_wrapper,
+            MEX_TOKEN_ID,
+            0,
+            &rust_biguint!(100_000),
+            |sc| {
+                let payment = sc.swap_auto_route(
+                    managed_token_id!(USDC_TOKEN_ID),
+                    managed_biguint!(82_909),
+                    MAX_SWAP_PATH_HOPS,
+                    wegld_intermediate(),
+                );
+                assert_eq!(payment.token_identifier, managed_token_id!(USDC_TOKEN_ID));
+                assert_eq!(payment.amount, managed_biguint!(82_909));
+            },
+        )
+        .assert_ok();
+
+    router_setup.blockchain_wrapper.check_esdt_balance(
+        &router_setup.user_address,
+        MEX_TOKEN_ID,
+        &rust_biguint!(4_999_900_000),
+    );
+    router_setup.blockchain_wrapper.check_esdt_balance(
+        &router_setup.user_address,
+        USDC_TOKEN_ID,
+        &rust_biguint!(5_000_082_909),
+    );
+}
+
+#[test]
+fn test_multi_pair_swap_split() {
+    let mut router_setup = RouterSetup::new(router::contract_obj, pair::contract_obj);
+    router_setup.migrate_pair_map();
+    router_setup.add_liquidity();
+
+    let mex_pair_address = router_setup.mex_pair_wrapper.address_ref().clone();
+    let usdc_pair_address = router_setup.usdc_pair_wrapper.address_ref().clone();
+    let split_swap = |router_setup: &mut RouterSetup<_, _>, amount_out_min: u64| {
+        router_setup.blockchain_wrapper.execute_esdt_transfer(
+            &router_setup.user_address,
+            &router_setup.router_wrapper,
+            MEX_TOKEN_ID,
+            0,
+            &rust_biguint!(100_000),
+            |sc: router::ContractObj<DebugApi>| {
+                let mut routes = MultiValueEncoded::new();
+                for weight in [1u64, 3] {
+                    let mut hops = ManagedVec::new();
+                    hops.push(SwapRouteHop {
+                        pair_address: managed_address!(&mex_pair_address),
+                        token_wanted: managed_token_id!(WEGLD_TOKEN_ID),
+                    });
+                    hops.push(SwapRouteHop {
+                        pair_address: managed_address!(&usdc_pair_address),
+                        token_wanted: managed_token_id!(USDC_TOKEN_ID),
+                    });
+                    routes.push((weight, hops).into());
+                }
+
+                let route_payments =
+                    sc.multi_pair_swap_split(managed_biguint!(amount_out_min), routes);
+                assert_eq!(route_payments.len(), 2);
+                assert_eq!(route_payments.get(0).amount, managed_biguint!(23_672));
+                assert_eq!(route_payments.get(1).amount, managed_biguint!(59_228));
+            },
+        )
+    };
+
+    // The min out is checked against the total output of the routes
+    split_swap(&mut router_setup, 82_901).assert_user_error("Slippage exceeded");
+    split_swap(&mut router_setup, 82_900).assert_ok();
+
+    router_setup.blockchain_wrapper.check_esdt_balance(
+        &router_setup.user_address,
+        MEX_TOKEN_ID,
+        &rust_biguint!(4_999_900_000),
+    );
+    router_setup.blockchain_wrapper.check_esdt_balance(
+        &router_setup.user_address,
+        USDC_TOKEN_ID,
+        &rust_biguint!(5_000_082_900),
+    );
+
+    // the sum of the weights cannot overflow
+    router_setup
+        .blockchain_wrapper
+        .execute_esdt_transfer(
+            &router_setup.user_address,
+            &router_setup.router_wrapper,
+            MEX_TOKEN_ID,
+            0,
+            &rust_biguint!(100_000),
+            |sc| {
+                let mut routes = MultiValueEncoded::new();
+                for weight in [u64::MAX, 1] {
+                    let mut hops = ManagedVec::new();
+                    hops.push(SwapRouteHop {
+                        pair_address: managed_address!(&mex_pair_address),
+                        token_wanted: managed_token_id!(WEGLD_TOKEN_ID),
+                    });
+                    routes.push((weight, hops).into());
+                }
+                sc.multi_pair_swap_split(managed_biguint!(1), routes);
+            },
+        )
+        .assert_user_error("Invalid route weight");
+
+    // the deadline is checked, and each route must get a part of the payment
+    router_setup.blockchain_wrapper.set_block_timestamp(100);
+    let split_swap_with_deadline =
+        |router_setup: &mut RouterSetup<_, _>, payment_amount: u64, deadline: u64| {
+            router_setup.blockchain_wrapper.execute_esdt_transfer(
+                &router_setup.user_address,
+                &router_setup.router_wrapper,
+                MEX_TOKEN_ID,
+                0,
+                &rust_biguint!(payment_amount),
+                |sc: router::ContractObj<DebugApi>| {
+                    let mut routes = MultiValueEncoded::new();
+                    for weight in [1u64, 3] {
+                        let mut hops = ManagedVec::<DebugApi, SwapRouteHop<DebugApi>>::new();
+                        hops.push(SwapRouteHop {
+                            pair_address: managed_address!(&mex_pair_address),
+                            token_wanted: managed_token_id!(WEGLD_TOKEN_ID),
+                        });
+                        routes.push((weight, hops).into());
+                    }
+                    sc.multi_pair_swap_split_with_deadline(managed_biguint!(1), deadline, routes);
+                },
+            )
+        };
+
+    split_swap_with_deadline(&mut router_setup, 100_000, 99).assert_user_error("Deadline expired");
+    split_swap_with_deadline(&mut router_setup, 3, 100)
+        .assert_user_error("Invalid route amount. Should not be zero");
+    split_swap_with_deadline(&mut router_setup, 100_000, 100).assert_ok();
+
+    router_setup.blockchain_wrapper.check_esdt_balance(
+        &router_setup.user_address,
+        MEX_TOKEN_ID,
+        &rust_biguint!(4_999_800_000),
+    );
+}
+
+#[test]
+fn test_multi_pair_swap_quote() {
+    let mut router_setup = RouterSetup::new(router::contract_obj, pair::contract_obj);
+    router_setup.migrate_pair_map();
+    router_setup.add_liquidity();
+
+    let mex_pair_address = router_setup.mex_pair_wrapper.address_ref().clone();
+    let usdc_pair_address = router_setup.usdc_pair_wrapper.address_ref().clone();
+    router_setup
+        .blockchain_wrapper
+        .execute_query(&router_setup.router_wrapper, |sc| {
+            // Same operations as the multi pair swap test
+            let mut swap_operations = MultiValueEncoded::new();
+            swap_operations.push(MultiValue4::from((
+                managed_address!(&mex_pair_address),
+                managed_buffer!(SWAP_TOKENS_FIXED_INPUT_FUNC_NAME),
+                managed_token_id!(WEGLD_TOKEN_ID),
+                managed_biguint!(1),
+            )));
+            swap_operations.push(MultiValue4::from((
+                managed_address!(&usdc_pair_address),
+                managed_buffer!(SWAP_TOKENS_FIXED_INPUT_FUNC_NAME),
+                managed_token_id!(USDC_TOKEN_ID),
+                managed_biguint!(1),
+            )));
+            let (amount_out, quote_hops) = sc
+                .get_multi_pair_swap_quote(
+                    managed_token_id!(MEX_TOKEN_ID),
+                    managed_biguint!(100_000),
+                    swap_operations,
+                )
+                .into_tuple();
+            assert_eq!(amount_out, managed_biguint!(82_909));
+            assert_eq!(quote_hops.len(), 2);
+            assert_eq!(quote_hops.get(0).amount_out, managed_biguint!(90_669));
+            assert_eq!(quote_hops.get(1).amount_in, managed_biguint!(90_669));
+
+            // Fixed output hop, the rest of the input is the residuum
+            let mut swap_operations = MultiValueEncoded::new();
+            swap_operations.push(MultiValue4::from((
+                managed_address!(&mex_pair_address),
+                managed_buffer!(SWAP_TOKENS_FIXED_INPUT_FUNC_NAME),
+                managed_token_id!(WEGLD_TOKEN_ID),
+                managed_biguint!(1),
+            )));
+            swap_operations.push(MultiValue4::from((
+                managed_address!(&usdc_pair_address),
+                managed_buffer!(SWAP_TOKENS_FIXED_OUTPUT_FUNC_NAME),
+                managed_token_id!(USDC_TOKEN_ID),
+                managed_biguint!(80_000),
+            )));
+            let (amount_out, quote_hops) = sc
+                .get_multi_pair_swap_quote(
+                    managed_token_id!(MEX_TOKEN_ID),
+                    managed_biguint!(100_000),
+                    swap_operations,
+                )
+                .into_tuple();
+            assert_eq!(amount_out, managed_biguint!(80_000));
+            assert_eq!(
+                quote_hops.get(1),
+                SwapQuoteHop {
+                    pair_address: managed_address!(&usdc_pair_address),
+                    token_in: managed_token_id!(WEGLD_TOKEN_ID),
+                    amount_in: managed_biguint!(87_211),
+                    token_out: managed_token_id!(USDC_TOKEN_ID),
+                    amount_out: managed_biguint!(80_000),
+                    residuum: managed_biguint!(3_458),
+                }
+            );
+
+            // Exact output, back-computed over the whole path
+            let mut path = MultiValueEncoded::new();
+            path.push(
+                (
+                    managed_address!(&mex_pair_address),
+                    managed_token_id!(WEGLD_TOKEN_ID),
+                )
+                    .into(),
+            );
+            path.push(
+                (
+                    managed_address!(&usdc_pair_address),
+                    managed_token_id!(USDC_TOKEN_ID),
+                )
+                    .into(),
+            );
+            let (amount_in, quote_hops) = sc
+                .get_multi_pair_swap_amount_in(managed_biguint!(82_909), path)
+                .into_tuple();
+            assert_eq!(amount_in, managed_biguint!(100_000));
+            assert_eq!(quote_hops.get(0).token_in, managed_token_id!(MEX_TOKEN_ID));
+            assert_eq!(quote_hops.get(0).amount_out, managed_biguint!(90_669));
+            assert_eq!(quote_hops.get(1).amount_in, managed_biguint!(90_669));
+        })
+        .assert_ok();
+
+    // the before swap hooks may change the fee, so such pairs cannot be quoted
+    router_setup
+        .blockchain_wrapper
+        .execute_tx(
+            &router_setup.owner_address,
+            &router_setup.usdc_pair_wrapper,
+            &rust_biguint!(0),
+            |sc| {
+                sc.hooks(HookType::BeforeSwap)
+                    .push(&managed_address!(&mex_pair_address));
+            },
+        )
+        .assert_ok();
+    router_setup
+        .blockchain_wrapper
+        .execute_query(&router_setup.router_wrapper, |sc| {
+            let mut swap_operations = MultiValueEncoded::new();
+            swap_operations.push(MultiValue4::from((
+                managed_address!(&usdc_pair_address),
+                managed_buffer!(SWAP_TOKENS_FIXED_INPUT_FUNC_NAME),
+                managed_token_id!(USDC_TOKEN_ID),
+                managed_biguint!(1),
+            )));
+            let _ = sc.get_multi_pair_swap_quote(
+                managed_token_id!(WEGLD_TOKEN_ID),
+                managed_biguint!(100_000),
+                swap_operations,
+            );
+        })
+        .assert_user_error("Cannot quote a pair with before swap hooks");
+
+    // and they are left out of the auto route search
+    router_setup
+        .blockchain_wrapper
+        .execute_query(&router_setup.router_wrapper, |sc| {
+            let mut intermediate_tokens = MultiValueEncoded::new();
+            intermediate_tokens.push(managed_token_id!(WEGLD_TOKEN_ID));
+            let (amount_out, swap_path) = sc
+                .get_best_swap_path(
+                    managed_token_id!(MEX_TOKEN_ID),
+                    managed_biguint!(100_000),
+                    managed_token_id!(USDC_TOKEN_ID),
+                    MAX_SWAP_PATH_HOPS,
+                    intermediate_tokens,
+                )
+                .into_tuple();
+            assert_eq!(amount_out, managed_biguint!(0));
+            assert!(swap_path.is_empty());
+        })
+        .assert_ok();
+}
+
+#[test]
+fn test_multi_pair_swap_and_lock() {
+    let mut router_setup = RouterSetup::new(router::contract_obj, pair::contract_obj);
+    router_setup.migrate_pair_map();
+    router_setup.add_liquidity();
+
+    let rust_zero = rust_biguint!(0u64);
+    let owner = router_setup.owner_address.clone();
+    let user = router_setup.user_address.clone();
+    let destination = router_setup
+        .blockchain_wrapper
+        .create_user_account(&rust_zero);
+    let lock_epochs = 360;
+    let energy_locked_token_id: &[u8] = b"XMEX-abcdef";
+
+    let energy_factory_wrapper = router_setup.blockchain_wrapper.create_sc_account(
+        &rust_zero,
+        Some(&owner),
+        energy_factory::contract_obj,
+        "energy factory path",
+    );
+    let router_address = router_setup.router_wrapper.address_ref().clone();
+    router_setup
+        .blockchain_wrapper
+        .execute_tx(&owner, &energy_factory_wrapper, &rust_zero, |sc| {
+            let mut lock_options = MultiValueEncoded::new();
+            lock_options.push((lock_epochs, 4_000u64).into());
+            sc.init(
+                managed_token_id!(MEX_TOKEN_ID),
+                managed_token_id!(b"LEGACY-abcdef"),
+                managed_address!(&router_address),
+                0,
+                lock_options,
+            );
+
+            sc.locked_token()
+                .set_token_id(managed_token_id!(energy_locked_token_id));
+            sc.set_paused(false);
+        })
+        .assert_ok();
+    router_setup.blockchain_wrapper.set_esdt_local_roles(
+        energy_factory_wrapper.address_ref(),
+        MEX_TOKEN_ID,
+        &[EsdtLocalRole::Mint, EsdtLocalRole::Burn],
+    );
+    router_setup.blockchain_wrapper.set_esdt_local_roles(
+        energy_factory_wrapper.address_ref(),
+        energy_locked_token_id,
+        &[
+            EsdtLocalRole::NftCreate,
+            EsdtLocalRole::NftAddQuantity,
+            EsdtLocalRole::NftBurn,
+            EsdtLocalRole::Transfer,
+        ],
+    );
+
+    router_setup
+        .blockchain_wrapper
+        .execute_tx(&owner, &router_setup.router_wrapper, &rust_zero, |sc| {
+            sc.set_energy_factory_address(managed_address!(energy_factory_wrapper.address_ref()));
+        })
+        .assert_ok();
+
+    let mex_pair_address = router_setup.mex_pair_wrapper.address_ref().clone();
+    let usdc_pair_address = router_setup.usdc_pair_wrapper.address_ref().clone();
+
+    // the swap must end in the base asset
+    router_setup
+        .blockchain_wrapper
+        .execute_esdt_transfer(
+            &user,
+            &router_setup.router_wrapper,
+            MEX_TOKEN_ID,
+            0,
+            &rust_biguint!(100_000),
+            |sc| {
+                let mut swap_operations = MultiValueEncoded::new();
+                swap_operations.push(MultiValue4::from((
+                    managed_address!(&mex_pair_address),
+                    managed_buffer!(SWAP_TOKENS_FIXED_INPUT_FUNC_NAME),
+                    managed_token_id!(WEGLD_TOKEN_ID),
+                    managed_biguint!(1),
+                )));
+                let _ = sc.multi_pair_swap_and_lock(
+                    lock_epochs,
+                    managed_address!(&destination),
+                    swap_operations,
+                );
+            },
+        )
+        .assert_user_error("Swap output must be the energy factory base asset");
+
+    // slippage is checked on the swap operations
+    router_setup
+        .blockchain_wrapper
+        .execute_esdt_transfer(
+            &user,
+            &router_setup.router_wrapper,
+            USDC_TOKEN_ID,
+            0,
+            &rust_biguint!(100_000),
+            |sc| {
+                let mut swap_operations = MultiValueEncoded::new();
+                swap_operations.push(MultiValue4::from((
+                    managed_address!(&usdc_pair_address),
+                    managed_buffer!(SWAP_TOKENS_FIXED_INPUT_FUNC_NAME),
+                    managed_token_id!(WEGLD_TOKEN_ID),
+                    managed_biguint!(1),
+                )));
+                swap_operations.push(MultiValue4::from((
+                    managed_address!(&mex_pair_address),
+                    managed_buffer!(SWAP_TOKENS_FIXED_INPUT_FUNC_NAME),
+                    managed_token_id!(MEX_TOKEN_ID),
+                    managed_biguint!(82_910),
+                )));
+                let _ = sc.multi_pair_swap_and_lock(
+                    lock_epochs,
+                    managed_address!(&destination),
+                    swap_operations,
+                );
+            },
+        )
+        .assert_user_error("Slippage exceeded");
+
+    router_setup
+        .blockchain_wrapper
+        .execute_esdt_transfer(
+            &user,
+            &router_setup.router_wrapper,
+            USDC_TOKEN_ID,
+            0,
+            &rust_biguint!(100_000),
+            |sc| {
+                let mut swap_operations = MultiValueEncoded::new();
+                swap_operations.push(MultiValue4::from((
+                    managed_address!(&usdc_pair_address),
+                    managed_buffer!(SWAP_TOKENS_FIXED_INPUT_FUNC_NAME),
+                    managed_token_id!(WEGLD_TOKEN_ID),
+                    managed_biguint!(1),
+                )));
+                swap_operations.push(MultiValue4::from((
+                    managed_address!(&mex_pair_address),
+                    managed_buffer!(SWAP_TOKENS_FIXED_INPUT_FUNC_NAME),
+                    managed_token_id!(MEX_TOKEN_ID),
+                    managed_biguint!(82_909),
+                )));
+                let locked_payment = sc.multi_pair_swap_and_lock(
+                    lock_epochs,
+                    managed_address!(&destination),
+                    swap_operations,
+                );
+                assert_eq!(
+                    locked_payment.token_identifier,
+                    managed_token_id!(energy_locked_token_id)
+                );
+                assert_eq!(locked_payment.amount, managed_biguint!(82_909));
+            },
+        )
+        .assert_ok();
+
+    router_setup.blockchain_wrapper.check_esdt_balance(
+        &user,
+        USDC_TOKEN_ID,
+        &rust_biguint!(4_999_900_000),
+    );
+
+    DebugApi::dummy();
+    router_setup.blockchain_wrapper.check_nft_balance(
+        &destination,
+        energy_locked_token_id,
+        1,
+        &rust_biguint!(82_909),
+        Some(&LockedTokenAttributes::<DebugApi> {
+            original_token_id: managed_token_id_wrapped!(MEX_TOKEN_ID),
+            original_token_nonce: 0,
+            unlock_epoch: lock_epochs,
+        }),
+    );
+}
+
+#[test]
+fn test_zap_into_farm() {
+    let mut router_setup = RouterSetup::new(router::contract_obj, pair::contract_obj);
+    router_setup.migrate_pair_map();
+    router_setup.add_liquidity();
+
+    let rust_zero = rust_biguint!(0u64);
+    let owner = router_setup.owner_address.clone();
+    let user = router_setup.user_address.clone();
+    let farm_token_id: &[u8] = b"FARM-abcdef";
+
+    let farm_wrapper = router_setup.blockchain_wrapper.create_sc_account(
+        &rust_zero,
+        Some(&owner),
+        farm::contract_obj,
+        "farm path",
+    );
+    let router_address = router_setup.router_wrapper.address_ref().clone();
+    router_setup
+        .blockchain_wrapper
+        .execute_tx(&owner, &farm_wrapper, &rust_zero, |sc| {
+            sc.init(
+                managed_token_id!(MEX_TOKEN_ID),
+                managed_token_id!(LPMEX_TOKEN_ID),
+                managed_biguint!(1_000_000_000_000),
+                ManagedAddress::zero(),
+                ManagedAddress::zero(),
+                MultiValueEncoded::new(),
+            );
+
+            sc.farm_token()
+                .set_token_id(managed_token_id!(farm_token_id));
+            sc.state().set(State::Active);
+            sc.add_sc_address_to_whitelist(managed_address!(&router_address));
+        })
+        .assert_ok();
+    router_setup.blockchain_wrapper.set_esdt_local_roles(
+        farm_wrapper.address_ref(),
+        farm_token_id,
+        &[
+            EsdtLocalRole::NftCreate,
+            EsdtLocalRole::NftAddQuantity,
+            EsdtLocalRole::NftBurn,
+        ],
+    );
+
+    let mex_pair_address = router_setup.mex_pair_wrapper.address_ref().clone();
+    let usdc_pair_address = router_setup.usdc_pair_wrapper.address_ref().clone();
+    let farm_address = farm_wrapper.address_ref().clone();
+
+    // the swap must end in one of the pool tokens
+    router_setup
+        .blockchain_wrapper
+        .execute_esdt_transfer(
+            &user,
+            &router_setup.router_wrapper,
+            WEGLD_TOKEN_ID,
+            0,
+            &rust_biguint!(100_000),
+            |sc| {
+                let mut swap_operations = MultiValueEncoded::new();
+                swap_operations.push(MultiValue4::from((
+                    managed_address!(&usdc_pair_address),
+                    managed_buffer!(SWAP_TOKENS_FIXED_INPUT_FUNC_NAME),
+                    managed_token_id!(USDC_TOKEN_ID),
+                    managed_biguint!(1),
+                )));
+                let _ = sc.zap_into_farm(
+                    managed_address!(&mex_pair_address),
+                    managed_address!(&farm_address),
+                    managed_biguint!(1),
+                    swap_operations,
+                );
+            },
+        )
+        .assert_user_error("Swap output must be one of the pool tokens");
+
+    // the LP tokens amount is checked against the minimum
+    router_setup
+        .blockchain_wrapper
+        .execute_esdt_transfer(
+            &user,
+            &router_setup.router_wrapper,
+            USDC_TOKEN_ID,
+            0,
+            &rust_biguint!(100_000),
+            |sc| {
+                let mut swap_operations = MultiValueEncoded::new();
+                swap_operations.push(MultiValue4::from((
+                    managed_address!(&usdc_pair_address),
+                    managed_buffer!(SWAP_TOKENS_FIXED_INPUT_FUNC_NAME),
+                    managed_token_id!(WEGLD_TOKEN_ID),
+                    managed_biguint!(1),
+                )));
+                let _ = sc.zap_into_farm(
+                    managed_address!(&mex_pair_address),
+                    managed_address!(&farm_address),
+                    managed_biguint!(100_000),
+                    swap_operations,
+                );
+            },
+        )
+        .assert_user_error("Slippage exceeded");
+
+    let mut farm_token_amount = 0u64;
+    router_setup
+        .blockchain_wrapper
+        .execute_esdt_transfer(
+            &user,
+            &router_setup.router_wrapper,
+            USDC_TOKEN_ID,
+            0,
+            &rust_biguint!(100_000),
+            |sc| {
+                let mut swap_operations = MultiValueEncoded::new();
+                swap_operations.push(MultiValue4::from((
+                    managed_address!(&usdc_pair_address),
+                    managed_buffer!(SWAP_TOKENS_FIXED_INPUT_FUNC_NAME),
+                    managed_token_id!(WEGLD_TOKEN_ID),
+                    managed_biguint!(1),
+                )));
+                let (farm_tokens, boosted_rewards) = sc
+                    .zap_into_farm(
+                        managed_address!(&mex_pair_address),
+                        managed_address!(&farm_address),
+                        managed_biguint!(1),
+                        swap_operations,
+                    )
+                    .into_tuple();
+                assert_eq!(
+                    farm_tokens.token_identifier,
+                    managed_token_id!(farm_token_id)
+                );
+                assert_eq!(boosted_rewards.amount, managed_biguint!(0));
+                farm_token_amount = farm_tokens.amount.to_u64().unwrap();
+            },
+        )
+        .assert_ok();
+
+    assert!(farm_token_amount > 0);
+    router_setup.blockchain_wrapper.check_esdt_balance(
+        &user,
+        USDC_TOKEN_ID,
+        &rust_biguint!(4_999_900_000),
+    );
+    assert_eq!(
+        router_setup
+            .blockchain_wrapper
+            .get_esdt_balance(&user, farm_token_id, 1),
+        rust_biguint!(farm_token_amount)
+    );
+
+    // the leftovers were sent back to the user
+    for token_id in [WEGLD_TOKEN_ID, MEX_TOKEN_ID, LPMEX_TOKEN_ID] {
+        router_setup
+            .blockchain_wrapper
+            .check_esdt_balance(&router_address, token_id, &rust_zero);
+    }
+}
+
 #[test]
 fn user_enable_pair_swaps_through_router_test() {
     let rust_zero = rust_biguint!(0u64);
@@ -249,8 +1224,8 @@ fn user_enable_pair_swaps_through_router_test() {
 
             sc.pair_map().insert(
                 PairTokens {
-                    first_token_id: managed_token_id!(CUSTOM_TOKEN_ID),
-                    second_token_id: managed_token_id!(USDC_TOKEN_ID),
+                    first_token_id: managed_token_id_wrapped!(CUSTOM_TOKEN_ID),
+                    second_token_id: managed_token_id_wrapped!(USDC_TOKEN_ID),
                 },
                 managed_address!(pair_wrapper.address_ref()),
             );
@@ -273,8 +1248,8 @@ fn user_enable_pair_swaps_through_router_test() {
     // setup pair
     b_mock
         .execute_tx(&owner, &pair_wrapper, &rust_zero, |sc| {
-            let first_token_id = managed_token_id!(CUSTOM_TOKEN_ID);
-            let second_token_id = managed_token_id!(USDC_TOKEN_ID);
+            let first_token_id = managed_token_id_wrapped!(CUSTOM_TOKEN_ID);
+            let second_token_id = managed_token_id_wrapped!(USDC_TOKEN_ID);
             let router_address = managed_address!(router_wrapper.address_ref());
             let router_owner_address = managed_address!(&owner);
 
@@ -436,8 +1411,8 @@ fn user_enable_pair_swaps_fail_test() {
 
             sc.pair_map().insert(
                 PairTokens {
-                    first_token_id: managed_token_id!(CUSTOM_TOKEN_ID),
-                    second_token_id: managed_token_id!(USDC_TOKEN_ID),
+                    first_token_id: managed_token_id_wrapped!(CUSTOM_TOKEN_ID),
+                    second_token_id: managed_token_id_wrapped!(USDC_TOKEN_ID),
                 },
                 managed_address!(pair_wrapper.address_ref()),
             );
@@ -460,8 +1435,8 @@ fn user_enable_pair_swaps_fail_test() {
     // setup pair
     b_mock
         .execute_tx(&owner, &pair_wrapper, &rust_zero, |sc| {
-            let first_token_id = managed_token_id!(CUSTOM_TOKEN_ID);
-            let second_token_id = managed_token_id!(USDC_TOKEN_ID);
+            let first_token_id = managed_token_id_wrapped!(CUSTOM_TOKEN_ID);
+            let second_token_id = managed_token_id_wrapped!(USDC_TOKEN_ID);
             let router_address = managed_address!(router_wrapper.address_ref());
             let router_owner_address = managed_address!(&owner);
 
@@ -5,9 +5,9 @@
 ////////////////////////////////////////////////////
 
 // Init:                                 1
-// Endpoints:                           33
+// Endpoints:                           46
 // Async Callback:                       1
-// Total number of exported functions:  35
+// Total number of exported functions:  48
 
 #![no_std]
 #![allow(internal_features)]
@@ -47,11 +47,24 @@ multiversx_sc_wasm_adapter::endpoints! {
         getPairTokens => get_pair_tokens
         clearPairTemporaryOwnerStorage => clear_pair_temporary_owner_storage
         multiPairSwap => multi_pair_swap
+        multiPairSwapWithDeadline => multi_pair_swap_with_deadline
+        multiPairSwapSplit => multi_pair_swap_split
+        multiPairSwapSplitWithDeadline => multi_pair_swap_split_with_deadline
+        getMultiPairSwapQuote => get_multi_pair_swap_quote
+        getMultiPairSwapAmountIn => get_multi_pair_swap_amount_in
         configEnableByUserParameters => config_enable_by_user_parameters
         addCommonTokensForUserPairs => add_common_tokens_for_user_pairs
         removeCommonTokensForUserPairs => remove_common_tokens_for_user_pairs
         setSwapEnabledByUser => set_swap_enabled_by_user
         getEnableSwapByUserConfig => try_get_config
+        setLiquidityMigrationPath => set_liquidity_migration_path
+        getLiquidityMigrationTarget => liquidity_migration_target
+        swapAutoRoute => swap_auto_route
+        getBestSwapPath => get_best_swap_path
+        setEnergyFactoryAddress => set_energy_factory_address
+        multiPairSwapAndLock => multi_pair_swap_and_lock
+        getEnergyFactoryAddress => energy_factory_address
+        zapIntoFarm => zap_into_farm
     )
 }
 
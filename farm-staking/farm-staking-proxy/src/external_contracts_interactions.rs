@@ -175,7 +175,11 @@ pub trait ExternalContractsInteractionsModule:
         let pair_address = self.pair_address().get();
         let pair_withdraw_result: RemoveLiquidityResultType<Self::Api> = self
             .pair_proxy_obj(pair_address)
-            .remove_liquidity(pair_first_token_min_amount, pair_second_token_min_amount)
+            .remove_liquidity(
+                pair_first_token_min_amount,
+                pair_second_token_min_amount,
+                OptionalValue::<u64>::None,
+            )
             .with_esdt_transfer(lp_tokens)
             .execute_on_dest_context();
         let (pair_first_token_payment, pair_second_token_payment) =
@@ -192,8 +196,8 @@ pub trait ExternalContractsInteractionsModule:
             };
 
         PairRemoveLiquidityResult {
-            staking_token_payment,
-            other_token_payment,
+            staking_token_payment: staking_token_payment.unwrap_esdt(),
+            other_token_payment: other_token_payment.unwrap_esdt(),
         }
     }
 
@@ -10,7 +10,8 @@ use multiversx_sc::types::{Address, EsdtLocalRole, ManagedAddress, MultiValueEnc
 use multiversx_sc_modules::pause::PauseModule;
 use multiversx_sc_scenario::whitebox_legacy::TxTokenTransfer;
 use multiversx_sc_scenario::{
-    managed_address, managed_biguint, managed_token_id, rust_biguint, whitebox_legacy::*, DebugApi,
+    managed_address, managed_biguint, managed_token_id, managed_token_id_wrapped, rust_biguint,
+    whitebox_legacy::*, DebugApi,
 };
 use pair::pair_actions::add_liq::AddLiquidityModule;
 use pair::pair_actions::remove_liq::RemoveLiquidityModule;
@@ -46,8 +47,8 @@ where
 
     b_mock
         .execute_tx(owner_addr, &pair_wrapper, &rust_zero, |sc| {
-            let first_token_id = managed_token_id!(WEGLD_TOKEN_ID);
-            let second_token_id = managed_token_id!(RIDE_TOKEN_ID);
+            let first_token_id = managed_token_id_wrapped!(WEGLD_TOKEN_ID);
+            let second_token_id = managed_token_id_wrapped!(RIDE_TOKEN_ID);
             let router_address = managed_address!(owner_addr);
             let router_owner_address = managed_address!(owner_addr);
             let total_fee_percent = 300u64;
@@ -198,6 +199,7 @@ fn add_liquidity<PairObjBuilder>(
             let MultiValue3 { 0: payments } = sc.add_liquidity(
                 managed_biguint!(first_token_min),
                 managed_biguint!(second_token_min),
+                OptionalValue::None,
             );
 
             assert_eq!(payments.0.token_identifier, managed_token_id!(LP_TOKEN_ID));
@@ -240,6 +242,7 @@ fn remove_liquidity<PairObjBuilder>(
                 sc.remove_liquidity(
                     managed_biguint!(lp_token_amount),
                     managed_biguint!(lp_token_amount),
+                    OptionalValue::None,
                 );
             },
         )
@@ -28,6 +28,7 @@ pub trait PairInteractionsModule {
         second_token_id: TokenIdentifier,
         second_token_amount_desired: BigUint,
         second_token_amount_min: BigUint,
+        opt_deadline: OptionalValue<u64>,
     ) -> AddLiquidityResultWrapper<Self::Api> {
         let first_payment =
             EsdtTokenPayment::new(first_token_id, 0, first_token_amount_desired.clone());
@@ -40,7 +41,11 @@ pub trait PairInteractionsModule {
 
         let raw_result: AddLiquidityResultType<Self::Api> = self
             .pair_contract_proxy(pair_address)
-            .add_liquidity(first_token_amount_min, second_token_amount_min)
+            .add_liquidity(
+                first_token_amount_min,
+                second_token_amount_min,
+                opt_deadline,
+            )
             .with_multi_token_transfer(all_token_payments)
             .execute_on_dest_context();
         let (lp_tokens_received, first_tokens_used, second_tokens_used) = raw_result.into_tuple();
@@ -49,12 +54,12 @@ pub trait PairInteractionsModule {
             &second_token_amount_desired - &second_tokens_used.amount;
 
         let first_token_leftover = EsdtTokenPayment::new(
-            first_tokens_used.token_identifier,
+            first_tokens_used.token_identifier.unwrap_esdt(),
             0,
             first_token_leftover_amount,
         );
         let second_token_leftover = EsdtTokenPayment::new(
-            second_tokens_used.token_identifier,
+            second_tokens_used.token_identifier.unwrap_esdt(),
             0,
             second_token_leftover_amount,
         );
@@ -73,17 +78,22 @@ pub trait PairInteractionsModule {
         lp_token_amount: BigUint,
         first_token_amount_min: BigUint,
         second_token_amount_min: BigUint,
+        opt_deadline: OptionalValue<u64>,
     ) -> RemoveLiqudityResultWrapper<Self::Api> {
         let raw_result: RemoveLiquidityResultType<Self::Api> = self
             .pair_contract_proxy(pair_address)
-            .remove_liquidity(first_token_amount_min, second_token_amount_min)
+            .remove_liquidity(
+                first_token_amount_min,
+                second_token_amount_min,
+                opt_deadline,
+            )
             .with_esdt_transfer((lp_token_id, 0, lp_token_amount))
             .execute_on_dest_context();
         let (first_token_received, second_token_received) = raw_result.into_tuple();
 
         RemoveLiqudityResultWrapper {
-            first_token_received,
-            second_token_received,
+            first_token_received: first_token_received.unwrap_esdt(),
+            second_token_received: second_token_received.unwrap_esdt(),
         }
     }
 
@@ -30,6 +30,7 @@ pub trait ProxyPairModule:
         pair_address: ManagedAddress,
         first_token_amount_min: BigUint,
         second_token_amount_min: BigUint,
+        opt_deadline: OptionalValue<u64>,
     ) -> MultiValueEncoded<EsdtTokenPayment> {
         self.require_is_intermediated_pair(&pair_address);
         self.require_wrapped_lp_token_id_not_empty();
@@ -57,6 +58,7 @@ pub trait ProxyPairModule:
             second_unlocked_token_id,
             second_payment.amount.clone(),
             second_token_amount_min,
+            opt_deadline,
         );
 
         let mut locked_token_used = input_token_refs.locked_token_ref.clone();
@@ -138,6 +140,7 @@ pub trait ProxyPairModule:
         pair_address: ManagedAddress,
         first_token_amount_min: BigUint,
         second_token_amount_min: BigUint,
+        opt_deadline: OptionalValue<u64>,
     ) -> MultiValueEncoded<EsdtTokenPayment> {
         self.require_is_intermediated_pair(&pair_address);
         self.require_wrapped_lp_token_id_not_empty();
@@ -149,6 +152,7 @@ pub trait ProxyPairModule:
             pair_address,
             first_token_amount_min,
             second_token_amount_min,
+            opt_deadline,
         );
         let caller = self.blockchain().get_caller();
         self.send_multiple_tokens_if_not_zero(&caller, &output_payments);
@@ -162,6 +166,7 @@ pub trait ProxyPairModule:
         pair_address: ManagedAddress,
         first_token_amount_min: BigUint,
         second_token_amount_min: BigUint,
+        opt_deadline: OptionalValue<u64>,
     ) -> ManagedVec<EsdtTokenPayment> {
         let wrapped_lp_mapper = self.wrapped_lp_token();
         wrapped_lp_mapper.require_same_token(&input_payment.token_identifier);
@@ -176,6 +181,7 @@ pub trait ProxyPairModule:
             attributes.lp_token_amount.clone(),
             first_token_amount_min,
             second_token_amount_min,
+            opt_deadline,
         );
         let received_token_refs = self.require_exactly_one_base_asset(
             &remove_liq_result.first_token_received,
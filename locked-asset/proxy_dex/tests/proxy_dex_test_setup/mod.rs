@@ -235,8 +235,8 @@ where
 
     b_mock
         .execute_tx(owner, &pair_wrapper, &rust_zero, |sc| {
-            let first_token_id = managed_token_id!(MEX_TOKEN_ID);
-            let second_token_id = managed_token_id!(WEGLD_TOKEN_ID);
+            let first_token_id = managed_token_id_wrapped!(MEX_TOKEN_ID);
+            let second_token_id = managed_token_id_wrapped!(WEGLD_TOKEN_ID);
             let router_address = managed_address!(owner);
             let router_owner_address = managed_address!(owner);
             let total_fee_percent = 300u64;
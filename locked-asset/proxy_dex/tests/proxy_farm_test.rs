@@ -320,6 +320,7 @@ fn farm_with_wrapped_lp_test() {
                 managed_address!(&pair_addr),
                 managed_biguint!(locked_token_amount.to_u64().unwrap()),
                 managed_biguint!(other_token_amount.to_u64().unwrap()),
+                OptionalValue::None,
             );
         })
         .assert_ok();
@@ -1317,6 +1318,7 @@ fn increase_proxy_farm_proxy_lp_energy() {
                 managed_address!(&pair_addr),
                 managed_biguint!(locked_token_amount.to_u64().unwrap() / 2),
                 managed_biguint!(other_token_amount.to_u64().unwrap() / 2),
+                OptionalValue::None,
             );
         })
         .assert_ok();
@@ -1329,6 +1331,7 @@ fn increase_proxy_farm_proxy_lp_energy() {
                 managed_address!(&pair_addr),
                 managed_biguint!(locked_token_amount.to_u64().unwrap() / 2),
                 managed_biguint!(other_token_amount.to_u64().unwrap() / 2),
+                OptionalValue::None,
             );
         })
         .assert_ok();
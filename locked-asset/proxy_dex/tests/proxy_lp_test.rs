@@ -67,6 +67,7 @@ fn add_remove_liquidity_proxy_test() {
                 managed_address!(&pair_addr),
                 managed_biguint!(locked_token_amount.to_u64().unwrap()),
                 managed_biguint!(other_token_amount.to_u64().unwrap()),
+                OptionalValue::None,
             );
         })
         .assert_ok();
@@ -162,6 +163,7 @@ fn add_remove_liquidity_proxy_test() {
                     managed_address!(&pair_addr),
                     managed_biguint!(1),
                     managed_biguint!(1),
+                    OptionalValue::None,
                 );
                 let output_vec = output_payments.to_vec();
 
@@ -265,6 +267,7 @@ fn tripple_add_liquidity_proxy_test() {
                 managed_address!(&pair_addr),
                 managed_biguint!(locked_token_amount1.to_u64().unwrap()),
                 managed_biguint!(other_token_amount.to_u64().unwrap()),
+                OptionalValue::None,
             );
         })
         .assert_ok();
@@ -348,6 +351,7 @@ fn tripple_add_liquidity_proxy_test() {
                 managed_address!(&pair_addr),
                 managed_biguint!(locked_token_amount1.to_u64().unwrap()),
                 managed_biguint!(other_token_amount.to_u64().unwrap()),
+                OptionalValue::None,
             );
         })
         .assert_ok();
@@ -432,6 +436,7 @@ fn tripple_add_liquidity_proxy_test() {
                 managed_address!(&pair_addr),
                 managed_biguint!(locked_token_amount1.to_u64().unwrap()),
                 managed_biguint!(other_token_amount.to_u64().unwrap()),
+                OptionalValue::None,
             );
         })
         .assert_ok();
@@ -531,6 +536,7 @@ fn wrapped_same_nonce_lp_token_merge_test() {
                 managed_address!(&pair_addr),
                 managed_biguint!(locked_token_amount.to_u64().unwrap()),
                 managed_biguint!(other_token_amount.to_u64().unwrap()),
+                OptionalValue::None,
             );
         })
         .assert_ok();
@@ -604,6 +610,7 @@ fn wrapped_same_nonce_lp_token_merge_test() {
                     managed_address!(&pair_addr),
                     managed_biguint!(1),
                     managed_biguint!(1),
+                    OptionalValue::None,
                 );
             },
         )
@@ -679,6 +686,7 @@ fn wrapped_different_nonce_lp_token_merge_test() {
                 managed_address!(&pair_addr),
                 managed_biguint!(locked_token_amount.to_u64().unwrap()),
                 managed_biguint!(other_token_amount.to_u64().unwrap()),
+                OptionalValue::None,
             );
 
             assert_eq!(output_lp_token.to_vec().get(0).token_nonce, 1);
@@ -712,6 +720,7 @@ fn wrapped_different_nonce_lp_token_merge_test() {
                 managed_address!(&pair_addr),
                 managed_biguint!(locked_token_amount.to_u64().unwrap()),
                 managed_biguint!(other_token_amount.to_u64().unwrap()),
+                OptionalValue::None,
             );
             assert_eq!(output_lp_token.to_vec().get(0).token_nonce, 2);
             assert_eq!(
@@ -780,6 +789,7 @@ fn wrapped_different_nonce_lp_token_merge_test() {
                     managed_address!(&pair_addr),
                     managed_biguint!(1),
                     managed_biguint!(1),
+                    OptionalValue::None,
                 );
             },
         )
@@ -836,6 +846,7 @@ fn increase_proxy_lp_token_energy() {
                 managed_address!(&pair_addr),
                 managed_biguint!(locked_token_amount.to_u64().unwrap()),
                 managed_biguint!(other_token_amount.to_u64().unwrap()),
+                OptionalValue::None,
             );
         })
         .assert_ok();
@@ -33,6 +33,7 @@ mod lp_proxy {
             &self,
             first_token_amount_min: BigUint,
             second_token_amount_min: BigUint,
+            opt_deadline: OptionalValue<u64>,
         ) -> AddLiquidityResultType<Self::Api>;
 
         #[payable("*")]
@@ -41,6 +42,7 @@ mod lp_proxy {
             &self,
             first_token_amount_min: BigUint,
             second_token_amount_min: BigUint,
+            opt_deadline: OptionalValue<u64>,
         ) -> RemoveLiquidityResultType<Self::Api>;
     }
 }
@@ -54,6 +56,7 @@ pub trait LpInteractionsModule {
         second_payment: &EsdtTokenPayment<Self::Api>,
         first_token_amount_min: BigUint,
         second_token_amount_min: BigUint,
+        opt_deadline: OptionalValue<u64>,
     ) -> AddLiquidityResultWrapper<Self::Api> {
         let mut lp_payments_in = ManagedVec::new();
         lp_payments_in.push(first_payment.clone());
@@ -61,7 +64,11 @@ pub trait LpInteractionsModule {
 
         let lp_payments_out: AddLiquidityResultType<Self::Api> = self
             .lp_proxy(lp_address)
-            .add_liquidity(first_token_amount_min, second_token_amount_min)
+            .add_liquidity(
+                first_token_amount_min,
+                second_token_amount_min,
+                opt_deadline,
+            )
             .with_multi_token_transfer(lp_payments_in)
             .execute_on_dest_context();
         let (lp_tokens, first_token_optimal_payment, second_token_optimal_payment) =
@@ -103,10 +110,15 @@ pub trait LpInteractionsModule {
         second_token_amount_min: BigUint,
         expected_first_token_id_out: &TokenIdentifier,
         expected_second_token_id_out: &TokenIdentifier,
+        opt_deadline: OptionalValue<u64>,
     ) -> RemoveLiquidityResultWrapper<Self::Api> {
         let lp_payments_out: RemoveLiquidityResultType<Self::Api> = self
             .lp_proxy(lp_address)
-            .remove_liquidity(first_token_amount_min, second_token_amount_min)
+            .remove_liquidity(
+                first_token_amount_min,
+                second_token_amount_min,
+                opt_deadline,
+            )
             .with_esdt_transfer((lp_token_id, 0, lp_token_amount))
             .execute_on_dest_context();
 
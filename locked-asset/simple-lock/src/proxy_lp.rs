@@ -135,6 +135,7 @@ pub trait ProxyLpModule:
         &self,
         first_token_amount_min: BigUint,
         second_token_amount_min: BigUint,
+        opt_deadline: OptionalValue<u64>,
     ) -> AddLiquidityThroughProxyResultType<Self::Api> {
         let [first_payment, second_payment] = self.call_value().multi_esdt();
         let (mut first_payment_unlocked_wrapper, mut second_payment_unlocked_wrapper) =
@@ -170,6 +171,7 @@ pub trait ProxyLpModule:
             ref_second_payment_unlocked,
             first_token_amount_min,
             second_token_amount_min,
+            opt_deadline,
         );
 
         let caller = self.blockchain().get_caller();
@@ -234,6 +236,7 @@ pub trait ProxyLpModule:
         &self,
         first_token_amount_min: BigUint,
         second_token_amount_min: BigUint,
+        opt_deadline: OptionalValue<u64>,
     ) -> RemoveLiquidityThroughProxyResultType<Self::Api> {
         let payment: EsdtTokenPayment<Self::Api> = self.call_value().single_esdt();
 
@@ -241,6 +244,7 @@ pub trait ProxyLpModule:
             payment,
             first_token_amount_min,
             second_token_amount_min,
+            opt_deadline,
         )
     }
 
@@ -249,6 +253,7 @@ pub trait ProxyLpModule:
         input_payment: EsdtTokenPayment,
         first_token_amount_min: BigUint,
         second_token_amount_min: BigUint,
+        opt_deadline: OptionalValue<u64>,
     ) -> RemoveLiquidityThroughProxyResultType<Self::Api> {
         let lp_proxy_token_mapper = self.lp_proxy_token();
         lp_proxy_token_mapper.require_same_token(&input_payment.token_identifier);
@@ -272,6 +277,7 @@ pub trait ProxyLpModule:
             second_token_amount_min,
             &lp_proxy_token_attributes.first_token_id,
             &lp_proxy_token_attributes.second_token_id,
+            opt_deadline,
         );
 
         let caller = self.blockchain().get_caller();
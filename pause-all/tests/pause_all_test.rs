@@ -3,7 +3,8 @@
 use farm::Farm;
 use multiversx_sc::types::{Address, ManagedAddress, MultiValueEncoded, OperationCompletionStatus};
 use multiversx_sc_scenario::{
-    managed_address, managed_biguint, managed_token_id, rust_biguint, whitebox_legacy::*, DebugApi,
+    managed_address, managed_biguint, managed_token_id, managed_token_id_wrapped, rust_biguint,
+    whitebox_legacy::*, DebugApi,
 };
 use pair::Pair;
 use pausable::{PausableModule, State};
@@ -67,8 +68,8 @@ fn pause_all_test() {
     b_mock
         .execute_tx(&owner_address, &pair_sc, &rust_zero, |sc| {
             sc.init(
-                managed_token_id!(FIRST_TOKEN_ID),
-                managed_token_id!(SECOND_TOKEN_ID),
+                managed_token_id_wrapped!(FIRST_TOKEN_ID),
+                managed_token_id_wrapped!(SECOND_TOKEN_ID),
                 managed_address!(&Address::zero()),
                 managed_address!(&owner_address),
                 TOTAL_FEE_PERCENT,